@@ -0,0 +1,56 @@
+//! Reusing an `InlineParser` across paragraphs: retained capacity versus
+//! shrinking after each parse versus a fresh parser per paragraph.
+//!
+//! Run with: `cargo bench --bench inline_reuse`
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use ferromark::InlineParser;
+
+const PARAGRAPH: &[u8] = b"Some *emphasis*, a [link](https://example.com), and `code`.";
+
+fn benchmark_inline_reuse(c: &mut Criterion) {
+    let large = "*a* [b](c) `d` ".repeat(50_000);
+    let mut group = c.benchmark_group("inline_reuse/small_after_large");
+    let mut events = Vec::new();
+
+    let mut retained = InlineParser::new();
+    retained.parse(large.as_bytes(), None, true, &mut events);
+    group.bench_function("retained", |b| {
+        b.iter(|| {
+            events.clear();
+            retained.parse(black_box(PARAGRAPH), None, true, &mut events);
+        });
+    });
+
+    let mut shrinking = InlineParser::new();
+    group.bench_function("shrink_to_fit_each", |b| {
+        b.iter(|| {
+            events.clear();
+            shrinking.parse(black_box(PARAGRAPH), None, true, &mut events);
+            shrinking.shrink_to_fit();
+        });
+    });
+
+    let mut bounded = InlineParser::new();
+    bounded.parse(large.as_bytes(), None, true, &mut events);
+    bounded.shrink_to(64);
+    group.bench_function("shrink_to_64_once", |b| {
+        b.iter(|| {
+            events.clear();
+            bounded.parse(black_box(PARAGRAPH), None, true, &mut events);
+        });
+    });
+
+    group.bench_function("fresh_parser", |b| {
+        b.iter(|| {
+            events.clear();
+            InlineParser::new().parse(black_box(PARAGRAPH), None, true, &mut events);
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_inline_reuse);
+criterion_main!(benches);
@@ -1,7 +1,7 @@
 use std::hint::black_box;
 
 use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
-use ferromark::{Options, RenderPolicy};
+use ferromark::{CalloutTitles, Options, RenderPolicy, Sanitizer, WwwScheme};
 
 const SHARED_SECTION: &str = r#"
 ## Release notes
@@ -42,6 +42,45 @@ fn all_extensions() -> Options {
         heading_ids: true,
         math: true,
         callouts: true,
+        untrusted_content: true,
+        wikilinks: true,
+        wikilink_url: None,
+        ordered_list_start_override: None,
+        emoji: true,
+        loose_list_continuation: false,
+        pretty: None,
+        callout_aria: true,
+        collapsible_callouts: true,
+        max_nesting_depth: None,
+        max_inline_marks: None,
+        custom_delimiters: &[],
+        code_info_meta: true,
+        task_list_classes: true,
+        allow_nested_links: false,
+        allow_block_html: true,
+        allow_inline_html: true,
+        table_cell_merge: false,
+        code_span_renderer: None,
+        image_src_transform: None,
+        numeric_footnote_ids: false,
+        callout_titles: CalloutTitles::ENGLISH,
+        callout_custom_titles: false,
+        compact_list_items: false,
+        external_links: None,
+        blockquote_cite_url: false,
+        strikethrough_single_tilde: false,
+        sanitizer: Sanitizer::None,
+        hard_wrap: false,
+        www_autolink_scheme: WwwScheme::Http,
+        task_list_interactive: false,
+        smart_punctuation: false,
+        xhtml: true,
+        inline_footnotes: false,
+        footnote_backref_label: "↩",
+        footnote_id_prefix: "user-content-",
+        footnote_section_title: None,
+        fenced_divs: false,
+        attributes: false,
     }
 }
 
@@ -0,0 +1,35 @@
+//! UTF-8 validation cost against rendering, for `to_html_from_bytes`.
+//!
+//! Run with and without the SIMD validator to compare:
+//! `cargo bench --bench utf8` and `cargo bench --bench utf8 --features simdutf8`
+
+use std::hint::black_box;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+
+const COMMONMARK_50K: &str = include_str!("fixtures/commonmark-50k.md");
+
+fn benchmark_utf8(c: &mut Criterion) {
+    let ascii = COMMONMARK_50K.replace(|ch: char| !ch.is_ascii(), "?");
+    let mixed = COMMONMARK_50K
+        .replace("the", "thé")
+        .replace("and", "und ✓ 日本");
+    for (name, input) in [("ascii_50k", ascii), ("mixed_50k", mixed)] {
+        let mut group = c.benchmark_group(format!("utf8/{name}"));
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        let bytes = input.as_bytes();
+        group.bench_function("std_from_utf8", |b| {
+            b.iter(|| std::str::from_utf8(black_box(bytes)).unwrap().len());
+        });
+        group.bench_function("std_then_to_html", |b| {
+            b.iter(|| ferromark::to_html(std::str::from_utf8(black_box(bytes)).unwrap()));
+        });
+        group.bench_function("to_html_from_bytes", |b| {
+            b.iter(|| ferromark::to_html_from_bytes(black_box(bytes)).unwrap());
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, benchmark_utf8);
+criterion_main!(benches);
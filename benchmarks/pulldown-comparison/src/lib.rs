@@ -5,7 +5,7 @@ mod corpus;
 mod metadata;
 mod model;
 
-use ferromark::{Options as FerromarkOptions, RenderPolicy};
+use ferromark::{CalloutTitles, Options as FerromarkOptions, RenderPolicy, Sanitizer, WwwScheme};
 use pulldown_cmark::{Options as PulldownOptions, Parser, html};
 
 pub use corpus::{Corpus, CorpusData};
@@ -48,6 +48,45 @@ pub fn ferromark_options(config: ParityConfig) -> FerromarkOptions {
         heading_ids: false,
         math,
         callouts,
+        untrusted_content: false,
+        wikilinks: false,
+        wikilink_url: None,
+        ordered_list_start_override: None,
+        emoji: false,
+        loose_list_continuation: false,
+        pretty: None,
+        callout_aria: false,
+        collapsible_callouts: false,
+        max_nesting_depth: None,
+        max_inline_marks: None,
+        custom_delimiters: &[],
+        code_info_meta: false,
+        task_list_classes: false,
+        allow_nested_links: false,
+        allow_block_html: true,
+        allow_inline_html: true,
+        table_cell_merge: false,
+        code_span_renderer: None,
+        image_src_transform: None,
+        numeric_footnote_ids: false,
+        callout_titles: CalloutTitles::ENGLISH,
+        callout_custom_titles: false,
+        compact_list_items: false,
+        external_links: None,
+        blockquote_cite_url: false,
+        strikethrough_single_tilde: false,
+        sanitizer: Sanitizer::None,
+        hard_wrap: false,
+        www_autolink_scheme: WwwScheme::Http,
+        task_list_interactive: false,
+        smart_punctuation: false,
+        xhtml: true,
+        inline_footnotes: false,
+        footnote_backref_label: "↩",
+        footnote_id_prefix: "user-content-",
+        footnote_section_title: None,
+        fenced_divs: false,
+        attributes: false,
     }
 }
 
@@ -0,0 +1,157 @@
+//! Differential test: ferromark against pulldown-cmark in the CommonMark lane.
+//!
+//! Every CommonMark spec example, every benchmark corpus, and the
+//! repository's own prose documents are rendered by both parsers. Output is
+//! compared after normalizing whitespace between tags and quote escaping, so
+//! only structural and textual differences count as divergences.
+//!
+//! List divergences for triage:
+//!
+//! ```bash
+//! cargo test --manifest-path benchmarks/pulldown-comparison/Cargo.toml \
+//!   --test differential -- --ignored --nocapture
+//! ```
+
+use ferromark_pulldown_comparison::{
+    Corpus, ParityConfig, render_ferromark_into, render_pulldown_into,
+};
+use serde::Deserialize;
+use std::path::Path;
+
+const SPEC_JSON: &str = include_str!("../../../tests/spec.json");
+
+#[derive(Deserialize)]
+struct SpecExample {
+    markdown: String,
+    example: u32,
+    section: String,
+}
+
+struct Divergence {
+    source: String,
+    input: String,
+    ferromark: String,
+    pulldown: String,
+}
+
+fn corpus() -> Vec<(String, String)> {
+    let examples: Vec<SpecExample> = serde_json::from_str(SPEC_JSON).expect("spec.json parses");
+    let mut documents: Vec<(String, String)> = examples
+        .into_iter()
+        .map(|example| {
+            (
+                format!("spec example {} ({})", example.example, example.section),
+                example.markdown,
+            )
+        })
+        .collect();
+    documents.extend(Corpus::ALL.iter().map(|corpus| {
+        (
+            corpus.as_str().to_owned(),
+            corpus.materialize().input().to_owned(),
+        )
+    }));
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let mut paths: Vec<_> = ["README.md", "CHANGELOG.md", "CONTRIBUTING.md"]
+        .iter()
+        .map(|name| root.join(name))
+        .collect();
+    if let Ok(entries) = std::fs::read_dir(root.join("docs/arch")) {
+        paths.extend(entries.filter_map(|entry| Some(entry.ok()?.path())));
+    }
+    paths.sort();
+    documents.extend(paths.into_iter().filter_map(|path| {
+        let content = std::fs::read_to_string(&path).ok()?;
+        let name = path
+            .strip_prefix(&root)
+            .unwrap_or(&path)
+            .display()
+            .to_string();
+        Some((name, content))
+    }));
+    documents
+}
+
+/// Drop whitespace that HTML rendering ignores (around tags, at line ends)
+/// and unify quote escaping: ferromark writes `&quot;` in text, pulldown-cmark
+/// a bare `"`.
+fn normalize(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    for line in html.lines() {
+        let line = line.trim_end();
+        if !line.is_empty() {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out.replace(">\n<", "><").replace("&quot;", "\"")
+}
+
+fn divergences() -> Vec<Divergence> {
+    let mut ferromark = Vec::new();
+    let mut pulldown = String::new();
+    corpus()
+        .into_iter()
+        .filter_map(|(source, input)| {
+            render_ferromark_into(&input, ParityConfig::CommonMark, &mut ferromark);
+            render_pulldown_into(&input, ParityConfig::CommonMark, &mut pulldown);
+            let ferromark = normalize(std::str::from_utf8(&ferromark).expect("UTF-8 output"));
+            let pulldown = normalize(&pulldown);
+            (ferromark != pulldown).then_some(Divergence {
+                source,
+                input,
+                ferromark,
+                pulldown,
+            })
+        })
+        .collect()
+}
+
+/// Context around the first differing byte, for inputs too large to print.
+fn first_difference(a: &str, b: &str) -> (String, String) {
+    let at = a
+        .bytes()
+        .zip(b.bytes())
+        .position(|(x, y)| x != y)
+        .unwrap_or(a.len().min(b.len()));
+    let window = |s: &str| {
+        let start = (at.saturating_sub(60)..=at)
+            .find(|&i| s.is_char_boundary(i))
+            .unwrap_or(0);
+        let end = (at + 60..s.len())
+            .find(|&i| s.is_char_boundary(i))
+            .unwrap_or(s.len());
+        s[start..end].to_owned()
+    };
+    (window(a), window(b))
+}
+
+#[test]
+fn matches_pulldown_cmark() {
+    let sources: Vec<String> = divergences().into_iter().map(|d| d.source).collect();
+    assert!(
+        sources.is_empty(),
+        "{} divergences from pulldown-cmark (run the ignored report to triage): {sources:?}",
+        sources.len()
+    );
+}
+
+#[test]
+#[ignore]
+fn divergence_report() {
+    let found = divergences();
+    for divergence in &found {
+        println!("== {}", divergence.source);
+        if divergence.input.len() <= 400 {
+            println!("input:     {:?}", divergence.input);
+            println!("ferromark: {:?}", divergence.ferromark);
+            println!("pulldown:  {:?}", divergence.pulldown);
+        } else {
+            let (ferromark, pulldown) =
+                first_difference(&divergence.ferromark, &divergence.pulldown);
+            println!("ferromark: {ferromark:?}");
+            println!("pulldown:  {pulldown:?}");
+        }
+    }
+    println!("Divergences: {}", found.len());
+}
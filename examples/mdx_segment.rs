@@ -57,6 +57,10 @@ cargo add ferromark
                 println!("[{i}] Expression");
                 println!("    {}", s.trim());
             }
+            ferromark::mdx::Segment::JsxInline(s) => {
+                println!("[{i}] JSX Inline");
+                println!("    {s}");
+            }
         }
         println!();
     }
@@ -0,0 +1,184 @@
+//! Attribute blocks: `{#id .class key="value"}` after headings and code
+//! fences, and `::: warning` or `::: {...}` on fenced divs.
+
+use smallvec::SmallVec;
+
+/// One attribute of an attribute block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Attribute<'a> {
+    /// `#id`.
+    Id(&'a [u8]),
+    /// `.class`, or a bare class name after a div fence.
+    Class(&'a [u8]),
+    /// `key=value`, `key="value"` or `key='value'`.
+    Pair(&'a [u8], &'a [u8]),
+}
+
+/// Parsed attributes; most blocks have only a few.
+pub(crate) type Attributes<'a> = SmallVec<[Attribute<'a>; 4]>;
+
+/// Parse a whitespace-separated attribute list in braces. `None` if `text`
+/// is not one.
+pub(crate) fn attribute_list(text: &[u8]) -> Option<Attributes<'_>> {
+    let mut rest = text.strip_prefix(b"{")?.strip_suffix(b"}")?;
+    let mut attributes = SmallVec::new();
+    loop {
+        rest = &rest[rest.iter().take_while(|&&b| is_space(b)).count()..];
+        let Some(&first) = rest.first() else {
+            return Some(attributes);
+        };
+        let (attribute, after) = match first {
+            b'#' | b'.' => {
+                let len = rest[1..]
+                    .iter()
+                    .take_while(|&&b| !is_space(b) && !matches!(b, b'"' | b'='))
+                    .count();
+                if len == 0 {
+                    return None;
+                }
+                let name = &rest[1..1 + len];
+                let attribute = if first == b'#' {
+                    Attribute::Id(name)
+                } else {
+                    Attribute::Class(name)
+                };
+                (attribute, &rest[1 + len..])
+            }
+            _ => {
+                let key_len = rest
+                    .iter()
+                    .take_while(|&&b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_'))
+                    .count();
+                if key_len == 0 || rest.get(key_len) != Some(&b'=') {
+                    return None;
+                }
+                let key = &rest[..key_len];
+                let value = &rest[key_len + 1..];
+                if let Some(&quote @ (b'"' | b'\'')) = value.first() {
+                    let quoted = &value[1..];
+                    let end = quoted.iter().position(|&b| b == quote)?;
+                    (Attribute::Pair(key, &quoted[..end]), &quoted[end + 1..])
+                } else {
+                    let len = value.iter().take_while(|&&b| !is_space(b)).count();
+                    (Attribute::Pair(key, &value[..len]), &value[len..])
+                }
+            }
+        };
+        if after.first().is_some_and(|&b| !is_space(b)) {
+            return None;
+        }
+        attributes.push(attribute);
+        rest = after;
+    }
+}
+
+/// Parse the text after an opening div fence: a single class name, or an
+/// attribute list. `None` if it is neither.
+pub(crate) fn div_attributes(text: &[u8]) -> Option<Attributes<'_>> {
+    if text.starts_with(b"{") {
+        return attribute_list(text);
+    }
+    if text.is_empty()
+        || text
+            .iter()
+            .any(|&b| is_space(b) || matches!(b, b'{' | b'}'))
+    {
+        return None;
+    }
+    let mut attributes = SmallVec::new();
+    attributes.push(Attribute::Class(text));
+    Some(attributes)
+}
+
+/// Start of an attribute list ending `text`, if there is one: its `{` is
+/// unescaped and starts `text` or follows whitespace.
+pub(crate) fn trailing_attributes(text: &[u8]) -> Option<usize> {
+    if !text.ends_with(b"}") {
+        return None;
+    }
+    // A quoted value may contain braces; the first `{` that starts a
+    // valid list from the right wins
+    text.iter()
+        .enumerate()
+        .rev()
+        .filter(|&(_, &b)| b == b'{')
+        .map(|(start, _)| start)
+        .find(|&start| {
+            let escapes = text[..start]
+                .iter()
+                .rev()
+                .take_while(|&&b| b == b'\\')
+                .count();
+            escapes % 2 == 0
+                && (start == 0 || is_space(text[start - 1]))
+                && attribute_list(&text[start..]).is_some()
+        })
+}
+
+/// The first `#id` of a parsed attribute list.
+pub(crate) fn attribute_id<'a>(attributes: &[Attribute<'a>]) -> Option<&'a [u8]> {
+    attributes.iter().find_map(|attribute| match attribute {
+        Attribute::Id(id) => Some(*id),
+        _ => None,
+    })
+}
+
+fn is_space(b: u8) -> bool {
+    matches!(b, b' ' | b'\t')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_class_names_and_attribute_lists() {
+        assert_eq!(
+            div_attributes(b"warning").as_deref(),
+            Some(&[Attribute::Class(b"warning")][..])
+        );
+        assert_eq!(
+            div_attributes(b"{#main .a .b title=\"Two words\" k=v}").as_deref(),
+            Some(
+                &[
+                    Attribute::Id(b"main"),
+                    Attribute::Class(b"a"),
+                    Attribute::Class(b"b"),
+                    Attribute::Pair(b"title", b"Two words"),
+                    Attribute::Pair(b"k", b"v"),
+                ][..]
+            )
+        );
+        assert_eq!(
+            attribute_list(b"{a='x \"y\"' b=\"it's\"}").as_deref(),
+            Some(
+                &[
+                    Attribute::Pair(b"a", b"x \"y\""),
+                    Attribute::Pair(b"b", b"it's")
+                ][..]
+            )
+        );
+        assert_eq!(div_attributes(b"{}").as_deref(), Some(&[][..]));
+        for invalid in [
+            &b"two words"[..],
+            b"{.a\"b\"}",
+            b"{k=\"open}",
+            b"{k='open}",
+            b"{plain}",
+            b"{#}",
+        ] {
+            assert_eq!(div_attributes(invalid), None, "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn finds_trailing_attribute_lists() {
+        assert_eq!(trailing_attributes(b"Title {#id .a}"), Some(6));
+        assert_eq!(trailing_attributes(b"{#id}"), Some(0));
+        assert_eq!(trailing_attributes(b"T {title=\"a {b}\"}"), Some(2));
+        assert_eq!(trailing_attributes(b"Title \\{#id}"), None);
+        assert_eq!(trailing_attributes(b"Title{#id}"), None);
+        assert_eq!(trailing_attributes(b"Title {not attributes}"), None);
+        assert_eq!(trailing_attributes(b"Title {#id} after"), None);
+    }
+}
@@ -29,7 +29,9 @@ impl CalloutType {
         }
     }
 
-    /// Display title for the callout.
+    /// Default English display title for the callout.
+    ///
+    /// See [`CalloutTitles`] to localize it.
     pub fn title(self) -> &'static str {
         match self {
             Self::Note => "Note",
@@ -39,6 +41,70 @@ impl CalloutType {
             Self::Caution => "Caution",
         }
     }
+
+    /// ARIA role for the callout container.
+    ///
+    /// Warnings are announced as `alert`; every other callout is a `note`.
+    pub fn aria_role(self) -> &'static str {
+        match self {
+            Self::Warning => "alert",
+            Self::Note | Self::Tip | Self::Important | Self::Caution => "note",
+        }
+    }
+}
+
+/// Display titles for each [`CalloutType`], e.g. to localize them.
+///
+/// An empty title omits the title element of non-collapsible callouts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CalloutTitles {
+    /// Title for `[!NOTE]`.
+    pub note: &'static str,
+    /// Title for `[!TIP]`.
+    pub tip: &'static str,
+    /// Title for `[!IMPORTANT]`.
+    pub important: &'static str,
+    /// Title for `[!WARNING]`.
+    pub warning: &'static str,
+    /// Title for `[!CAUTION]`.
+    pub caution: &'static str,
+}
+
+impl CalloutTitles {
+    /// GitHub's English titles, as returned by [`CalloutType::title`].
+    pub const ENGLISH: Self = Self {
+        note: "Note",
+        tip: "Tip",
+        important: "Important",
+        warning: "Warning",
+        caution: "Caution",
+    };
+
+    /// The title for `callout`.
+    pub fn get(self, callout: CalloutType) -> &'static str {
+        match callout {
+            CalloutType::Note => self.note,
+            CalloutType::Tip => self.tip,
+            CalloutType::Important => self.important,
+            CalloutType::Warning => self.warning,
+            CalloutType::Caution => self.caution,
+        }
+    }
+}
+
+impl Default for CalloutTitles {
+    fn default() -> Self {
+        Self::ENGLISH
+    }
+}
+
+/// Fold marker of a collapsible callout (`[!TYPE]-` or `[!TYPE]+`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CalloutFold {
+    /// `true` for `+` (initially expanded), `false` for `-` (collapsed).
+    pub expanded: bool,
+    /// Custom title after the fold marker, if any.
+    pub title: Option<Range>,
 }
 
 /// Column alignment for table cells.
@@ -86,6 +152,10 @@ pub enum BlockEvent {
         level: u8,
     },
 
+    /// Attribute list (`{#id .class key="value"}`) of the heading or fenced
+    /// code block that directly follows, with `attributes`.
+    Attributes(Range),
+
     /// Start of a code block.
     CodeBlockStart {
         /// Whether the block is fenced or indented.
@@ -98,10 +168,28 @@ pub enum BlockEvent {
     BlockQuoteStart {
         /// Callout type, if this blockquote starts with `[!TYPE]`.
         callout: Option<CalloutType>,
+        /// Fold marker, if this callout is collapsible.
+        fold: Option<CalloutFold>,
     },
+    /// Custom title after the marker of a non-collapsible callout, with
+    /// `callout_custom_titles`. Directly follows its `BlockQuoteStart`;
+    /// collapsible callout titles are in [`CalloutFold::title`] instead.
+    CalloutTitle(Range),
+    /// URL of a `[cite:URL]` line opening a plain blockquote, with
+    /// `blockquote_cite_url`. Directly follows its `BlockQuoteStart`.
+    BlockQuoteCite(Range),
     /// End of a blockquote.
     BlockQuoteEnd,
 
+    /// Start of a fenced div (`::: warning`), with `fenced_divs`.
+    DivStart {
+        /// Text after the opening fence: a class name, or an attribute
+        /// list in braces (`{#id .class key="value"}`).
+        class: Option<Range>,
+    },
+    /// End of a fenced div.
+    DivEnd,
+
     /// Start of a list.
     ListStart {
         /// List type (ordered or unordered).
@@ -168,6 +256,9 @@ pub enum BlockEvent {
     TableCellStart {
         /// Column alignment for this cell.
         alignment: Alignment,
+        /// Number of columns the cell spans; above 1 only with
+        /// `table_cell_merge`.
+        colspan: u16,
     },
     /// End of a table cell.
     TableCellEnd,
@@ -199,6 +290,34 @@ pub enum TaskState {
     Checked,
 }
 
+impl BlockEvent {
+    /// The source range this event carries, if any: text, code, and HTML
+    /// lines, a fenced info string, a callout title or cite URL, the class
+    /// of a fenced div, or an attribute list.
+    pub(crate) fn range_mut(&mut self) -> Option<&mut Range> {
+        match self {
+            BlockEvent::Text(range)
+            | BlockEvent::CodeBlockStart {
+                kind: CodeBlockKind::Fenced { info: Some(range) },
+            }
+            | BlockEvent::HtmlBlockText(range)
+            | BlockEvent::Code(range)
+            | BlockEvent::BlockQuoteStart {
+                fold:
+                    Some(CalloutFold {
+                        title: Some(range), ..
+                    }),
+                ..
+            }
+            | BlockEvent::CalloutTitle(range)
+            | BlockEvent::BlockQuoteCite(range)
+            | BlockEvent::Attributes(range)
+            | BlockEvent::DivStart { class: Some(range) } => Some(range),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,7 +325,7 @@ mod tests {
     #[test]
     fn test_event_size() {
         // Events should be reasonably small
-        assert!(std::mem::size_of::<BlockEvent>() <= 24);
+        assert!(core::mem::size_of::<BlockEvent>() <= 24);
     }
 
     #[test]
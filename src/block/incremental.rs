@@ -0,0 +1,217 @@
+//! Planning for incremental re-parsing (see [`BlockParser::reparse_range`]).
+//!
+//! Re-parsing restarts and stops at blank lines between top-level blocks.
+//! Such a cut is found from the previous events alone: it is the first blank
+//! line after the last source range of one block and before the first range
+//! of the next. A block whose first or last content has no range (a thematic
+//! break, an empty list item) leaves that side unknown, so no cut is taken
+//! there: the blank line could be part of the block. So does a fenced div,
+//! whose fence lines carry no range and may have blank lines inside them. A
+//! blank line must also end before the next block's first range, which can
+//! start after stripped indentation on a blank line of fenced code. Before
+//! the edit, the cut must also follow a block that cannot absorb later lines,
+//! which lists and indented code can. After the edit, the parser decides by
+//! checking its own state when it reaches a cut.
+//!
+//! [`BlockParser::reparse_range`]: super::BlockParser::reparse_range
+
+use super::event::{BlockEvent, CodeBlockKind};
+use super::span::{Boundary, boundary};
+use crate::Range;
+use alloc::vec::Vec;
+
+/// A restart point between two top-level blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Cut {
+    /// Start of the line after the blank line, in the edited input.
+    pub at: usize,
+    /// Index of the first previous event after the cut.
+    pub event: usize,
+}
+
+/// Where re-parsing starts and where it may stop.
+#[derive(Debug)]
+pub(super) struct Plan {
+    /// Last cut before the edit.
+    pub start: Cut,
+    /// Cuts after the edit, in input order.
+    pub ends: Vec<Cut>,
+    /// Offset change for ranges after the edit.
+    pub delta: isize,
+}
+
+/// The input before the edit, readable outside the edited bytes.
+struct PreviousInput<'a> {
+    input: &'a [u8],
+    edit_start: usize,
+    edit_end: usize,
+    new_len: usize,
+}
+
+impl PreviousInput<'_> {
+    /// Byte at `pos` of the previous input, `None` inside the edit or at EOF.
+    fn get(&self, pos: usize) -> Option<u8> {
+        if pos < self.edit_start {
+            self.input.get(pos).copied()
+        } else if pos >= self.edit_end {
+            self.input
+                .get(pos - self.edit_end + self.edit_start + self.new_len)
+                .copied()
+        } else {
+            None
+        }
+    }
+
+    /// Start of the line after the one holding `pos`. Like the parser, only
+    /// `\n` ends a line.
+    fn next_line(&self, mut pos: usize) -> Option<usize> {
+        while self.get(pos)? != b'\n' {
+            pos += 1;
+        }
+        Some(pos + 1)
+    }
+
+    /// Cut after the first blank line between `end` and `next_start`.
+    fn blank_line_cut(&self, end: usize, next_start: usize, footnotes: bool) -> Option<usize> {
+        let mut line = if end == 0 || self.get(end - 1)? == b'\n' {
+            end
+        } else {
+            self.next_line(end)?
+        };
+        while line < next_start {
+            let mut pos = line;
+            while matches!(self.get(pos)?, b' ' | b'\t') {
+                pos += 1;
+            }
+            if self.get(pos)? == b'\n' && pos < next_start {
+                // A footnote definition would carry on past the blank line
+                let defines_footnote = footnotes
+                    && (end..line)
+                        .any(|i| self.get(i) == Some(b'[') && self.get(i + 1) == Some(b'^'));
+                return (!defines_footnote).then_some(self.next_line(pos)?);
+            }
+            line = self.next_line(pos)?;
+        }
+        None
+    }
+}
+
+/// A top-level block of the previous events.
+struct TopBlock {
+    first_event: usize,
+    /// Start of the first source range, if the first content event carries
+    /// one.
+    start: Option<usize>,
+    /// End of the source ranges, if the last content event carries one.
+    end: Option<usize>,
+    /// Whether lines after a blank line can still join the block.
+    absorbs: bool,
+}
+
+fn top_level_blocks(events: &[BlockEvent]) -> Vec<TopBlock> {
+    let mut blocks: Vec<TopBlock> = Vec::new();
+    let mut depth = 0usize;
+    let mut max_end = 0;
+    let mut has_content = false;
+    let mut open_divs = 0usize;
+    for (i, event) in events.iter().enumerate() {
+        let kind = boundary(event);
+        if depth == 0 {
+            if kind == Boundary::End {
+                continue;
+            }
+            blocks.push(TopBlock {
+                first_event: i,
+                start: None,
+                end: None,
+                absorbs: matches!(
+                    event,
+                    BlockEvent::ListStart { .. }
+                        | BlockEvent::CodeBlockStart {
+                            kind: CodeBlockKind::Indented
+                        }
+                ),
+            });
+            max_end = 0;
+            has_content = false;
+        }
+        match kind {
+            Boundary::Start => depth += 1,
+            Boundary::End => depth -= 1,
+            Boundary::Leaf => {}
+        }
+        match event {
+            BlockEvent::DivStart { .. } => open_divs += 1,
+            BlockEvent::DivEnd => open_divs -= 1,
+            _ => {}
+        }
+        let Some(block) = blocks.last_mut() else {
+            continue;
+        };
+        let range = event.clone().range_mut().copied();
+        if kind != Boundary::Start && !has_content {
+            has_content = true;
+            block.start = range
+                .filter(|_| open_divs == 0)
+                .map(|range| range.start_usize());
+        }
+        match range {
+            Some(range) => {
+                max_end = max_end.max(range.end_usize());
+                block.end = Some(max_end);
+            }
+            None if kind != Boundary::End || matches!(event, BlockEvent::DivEnd) => {
+                block.end = None
+            }
+            None => {}
+        }
+    }
+    blocks
+}
+
+/// Find the cuts around an edit that replaced `edit` of the previous input
+/// with `new_len` bytes, now part of `input`.
+pub(super) fn plan(
+    input: &[u8],
+    events: &[BlockEvent],
+    edit: Range,
+    new_len: usize,
+    footnotes: bool,
+) -> Plan {
+    let previous = PreviousInput {
+        input,
+        edit_start: edit.start_usize(),
+        edit_end: edit.end_usize(),
+        new_len,
+    };
+    let mut start = Cut { at: 0, event: 0 };
+    let mut ends = Vec::new();
+    let blocks = top_level_blocks(events);
+    for pair in blocks.windows(2) {
+        let (block, next) = (&pair[0], &pair[1]);
+        let (Some(end), Some(next_start)) = (block.end, next.start) else {
+            continue;
+        };
+        let Some(at) = previous.blank_line_cut(end, next_start, footnotes) else {
+            continue;
+        };
+        if at <= previous.edit_start {
+            if !block.absorbs {
+                start = Cut {
+                    at,
+                    event: next.first_event,
+                };
+            }
+        } else if end >= previous.edit_end {
+            ends.push(Cut {
+                at: at - previous.edit_end + previous.edit_start + new_len,
+                event: next.first_event,
+            });
+        }
+    }
+    Plan {
+        start,
+        ends,
+        delta: new_len as isize - edit.len_usize() as isize,
+    }
+}
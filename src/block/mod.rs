@@ -6,18 +6,29 @@
 //! - Fenced code blocks
 //! - Blockquotes
 //! - Lists
+//! - Fenced divs
 //! - Paragraphs
 
+mod attributes;
 mod event;
+mod incremental;
 mod parser;
+mod span;
 
-pub use event::{Alignment, BlockEvent, CalloutType, CodeBlockKind, ListKind, TaskState};
-pub use parser::BlockParser;
+use alloc::vec::Vec;
+pub use event::{
+    Alignment, BlockEvent, CalloutFold, CalloutTitles, CalloutType, CodeBlockKind, ListKind,
+    TaskState,
+};
+pub use parser::{BlockEvents, BlockParser};
 
-/// Post-process events to fix up list tight status.
+pub(crate) use attributes::{Attribute, attribute_id, attribute_list, div_attributes};
+
+/// Copy each `ListEnd`'s tight status onto its matching `ListStart`.
 ///
-/// Since we emit `ListStart` before knowing if the list is tight,
-/// we need to patch the tight value using information from `ListEnd`.
+/// [`BlockParser`] already patches `ListStart` when a list closes, so its
+/// output needs no fixup. This remains for event sequences assembled or
+/// rewritten outside the parser.
 pub fn fixup_list_tight(events: &mut [BlockEvent]) {
     // First pass: collect (start_idx, tight) pairs
     let mut list_starts: Vec<usize> = Vec::new();
@@ -3,6 +3,7 @@
 use crate::Range;
 use crate::cursor::Cursor;
 use crate::limits;
+use alloc::{string::String, vec::Vec};
 use smallvec::SmallVec;
 
 // Parser branches establish these bounds through `peek`, `at`, `remaining`,
@@ -27,13 +28,18 @@ macro_rules! parser_cursor_advance {
     }};
 }
 
-use super::event::{Alignment, BlockEvent, CalloutType, CodeBlockKind, ListKind, TaskState};
+use super::attributes::{div_attributes, trailing_attributes};
+use super::event::{
+    Alignment, BlockEvent, CalloutFold, CalloutType, CodeBlockKind, ListKind, TaskState,
+};
+use super::incremental;
+use super::span::{self, LineMark};
 use crate::Options;
 use crate::footnote::{FootnoteStore, normalize_footnote_label};
 use crate::link_ref::{LinkRefDef, LinkRefStore, normalize_label_into};
 
 #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
-use std::arch::aarch64::*;
+use core::arch::aarch64::*;
 
 /// State for an open fenced code block.
 #[derive(Debug, Clone)]
@@ -77,6 +83,11 @@ enum ContainerType {
         /// Column where content starts (after `[^label]: `)
         content_indent: usize,
     },
+    /// Fenced div (`::: class`)
+    Div {
+        /// Number of colons in the opening fence
+        fence_len: usize,
+    },
 }
 
 /// An open container on the stack.
@@ -102,6 +113,11 @@ struct OpenList {
     blank_in_item: bool,
     /// Number of items so far.
     item_count: u32,
+    /// Source number of the most recent ordered item.
+    last_number: u32,
+    /// Index of this list's `ListStart` event, patched with the final
+    /// tightness when the list closes.
+    start_event: usize,
 }
 
 /// Block parser state.
@@ -162,6 +178,16 @@ pub struct BlockParser<'a> {
     footnote_event_start: Option<usize>,
     /// Pending label for the current footnote definition (normalized, original).
     pending_footnote_label: Option<(String, String)>,
+    /// Whether source spans are recorded (see [`Self::set_record_spans`]).
+    record_spans: bool,
+    /// Exact spans logged at emit sites, keyed by event index.
+    span_log: Vec<(usize, Range)>,
+    /// One mark per processed line, used for events without an exact span.
+    line_log: Vec<LineMark>,
+    /// End of the last non-blank content consumed so far.
+    content_end: usize,
+    /// Resolved spans from the last [`Self::parse`].
+    spans: Vec<Range>,
 }
 
 impl<'a> BlockParser<'a> {
@@ -199,15 +225,214 @@ impl<'a> BlockParser<'a> {
             footnote_store: FootnoteStore::new(),
             footnote_event_start: None,
             pending_footnote_label: None,
+            record_spans: false,
+            span_log: Vec::new(),
+            line_log: Vec::new(),
+            content_end: 0,
+            spans: Vec::new(),
+        }
+    }
+
+    /// Record the source span of every emitted event.
+    ///
+    /// Spans are off by default so the hot path stays unchanged. When
+    /// enabled, [`Self::spans`] returns one byte range per event pushed by
+    /// the next [`Self::parse`] call.
+    pub fn set_record_spans(&mut self, record: bool) {
+        self.record_spans = record;
+    }
+
+    /// Source spans for the events pushed by the last [`Self::parse`].
+    ///
+    /// `spans()[i]` is the byte range of the `i`-th pushed event: start and
+    /// end events carry the span of the whole construct, text events their
+    /// own range. Events of footnote definitions move into the footnote
+    /// store and have no spans. Empty unless [`Self::set_record_spans`] was
+    /// enabled.
+    pub fn spans(&self) -> &[Range] {
+        &self.spans
+    }
+
+    /// Log the exact span of the event about to be pushed.
+    #[inline]
+    fn record_span(&mut self, events: &[BlockEvent], start: usize, end: usize) {
+        if self.record_spans {
+            self.span_log
+                .push((events.len(), Range::from_usize(start, end)));
         }
     }
 
+    /// Offset just past the last non-whitespace byte in `floor..pos`.
+    fn content_end_before(&self, floor: usize, pos: usize) -> Option<usize> {
+        self.input[floor..pos]
+            .iter()
+            .rposition(|b| !matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+            .map(|i| floor + i + 1)
+    }
+
+    /// Log where the next line starts and which event it emits first.
+    #[inline]
+    fn mark_line(&mut self, events: &[BlockEvent]) {
+        self.line_log.push(LineMark {
+            first_event: events.len(),
+            line_start: self.cursor.offset(),
+            content_end: self.content_end,
+        });
+    }
+
+    /// Maximum container nesting depth; deeper markers stay literal text.
+    #[inline]
+    fn max_nesting_depth(&self) -> usize {
+        self.options
+            .max_nesting_depth
+            .unwrap_or(limits::MAX_BLOCK_NESTING)
+    }
+
     /// Parse all blocks and collect events.
     pub fn parse(&mut self, events: &mut Vec<BlockEvent>) {
+        let base = events.len();
+        self.spans.clear();
+        self.span_log.clear();
+        self.line_log.clear();
         while !self.cursor.is_eof() {
-            self.parse_line(events);
+            if self.record_spans {
+                let line_start = self.cursor.offset();
+                self.mark_line(events);
+                self.parse_line(events);
+                if let Some(end) = self.content_end_before(line_start, self.cursor.offset()) {
+                    self.content_end = end;
+                }
+            } else {
+                self.parse_line(events);
+            }
+        }
+        if self.record_spans {
+            self.mark_line(events);
         }
 
+        self.finish(events);
+
+        if self.record_spans {
+            for entry in &mut self.span_log {
+                entry.0 -= base;
+            }
+            for mark in &mut self.line_log {
+                mark.first_event = mark.first_event.saturating_sub(base);
+            }
+            span::resolve_spans(
+                &events[base..],
+                &self.span_log,
+                &self.line_log,
+                &mut self.spans,
+            );
+        }
+    }
+
+    /// Parse blocks lazily, yielding the events [`Self::parse`] would push,
+    /// in the same order.
+    ///
+    /// Lines are parsed as events are pulled. A list's events are held back
+    /// until the list ends, because its `ListStart` carries the tightness
+    /// that only the whole list decides; a footnote definition's events are
+    /// held back until it ends and then move into the footnote store. Other
+    /// blocks are yielded once they close, so memory is bounded by the
+    /// largest top-level list rather than the document. Spans are not
+    /// recorded.
+    ///
+    /// ```
+    /// use ferromark::{BlockEvent, BlockParser};
+    ///
+    /// let mut parser = BlockParser::new(b"# Title\n\n- a\n\n- b\n");
+    /// let mut events = parser.events();
+    /// assert!(matches!(events.next(), Some(BlockEvent::HeadingStart { level: 1 })));
+    /// let list = events.find(|event| matches!(event, BlockEvent::ListStart { .. }));
+    /// assert!(matches!(list, Some(BlockEvent::ListStart { tight: false, .. })));
+    /// ```
+    pub fn events(&mut self) -> BlockEvents<'_, 'a> {
+        self.spans.clear();
+        BlockEvents {
+            parser: self,
+            events: Vec::new(),
+            next: 0,
+            finished: false,
+        }
+    }
+
+    /// Re-parse the edited input, reusing the events of the previous parse.
+    ///
+    /// `prev_events` are the events [`Self::parse`] pushed for the input
+    /// before the edit, and `edit` is the byte range of that input which was
+    /// replaced by `new_len` bytes. The parser must be fresh and hold the
+    /// edited input. Only the top-level blocks around the edit are parsed
+    /// again, from the blank line before them to the first blank line after
+    /// which parsing is back at the top level; the events outside are reused,
+    /// with ranges after the edit shifted.
+    ///
+    /// The result matches what [`Self::parse`] pushes for the edited input.
+    /// Link reference and footnote definitions are only collected for the
+    /// re-parsed lines, and no spans are recorded.
+    pub fn reparse_range(
+        &mut self,
+        prev_events: &[BlockEvent],
+        edit: Range,
+        new_len: usize,
+    ) -> Vec<BlockEvent> {
+        let plan = incremental::plan(
+            self.input,
+            prev_events,
+            edit,
+            new_len,
+            self.options.footnotes,
+        );
+        let mut events = prev_events[..plan.start.event].to_vec();
+        self.cursor = Cursor::new_at(self.input, plan.start.at);
+
+        let mut tail = None;
+        for cut in &plan.ends {
+            while self.cursor.offset() < cut.at && !self.cursor.is_eof() {
+                self.parse_line(&mut events);
+            }
+            if self.cursor.offset() == cut.at && self.at_top_level() {
+                tail = Some(cut.event);
+                break;
+            }
+        }
+        if tail.is_none() {
+            while !self.cursor.is_eof() {
+                self.parse_line(&mut events);
+            }
+        }
+        self.finish(&mut events);
+
+        if let Some(tail) = tail {
+            events.extend(prev_events[tail..].iter().map(|event| {
+                let mut event = event.clone();
+                if let Some(range) = event.range_mut() {
+                    *range = Range::from_usize(
+                        range.start_usize().wrapping_add_signed(plan.delta),
+                        range.end_usize().wrapping_add_signed(plan.delta),
+                    );
+                }
+                event
+            }));
+        }
+        events
+    }
+
+    /// Whether nothing is open, so the next line starts a top-level block
+    /// regardless of the lines before.
+    fn at_top_level(&self) -> bool {
+        self.container_stack.is_empty()
+            && self.open_lists.is_empty()
+            && !self.in_paragraph
+            && !self.in_table
+            && !self.in_indented_code
+            && self.fence_state.is_none()
+            && self.html_block.is_none()
+    }
+
+    /// Close everything still open at the end of the input.
+    fn finish(&mut self, events: &mut Vec<BlockEvent>) {
         // Close any open table at end of input
         self.close_table(events);
 
@@ -235,16 +460,17 @@ impl<'a> BlockParser<'a> {
 
         // Close all open containers
         self.close_all_containers(events);
+        self.close_orphaned_lists(events);
     }
 
     /// Take the collected link reference definitions.
     pub fn take_link_refs(&mut self) -> LinkRefStore {
-        std::mem::take(&mut self.link_refs)
+        core::mem::take(&mut self.link_refs)
     }
 
     /// Take the collected footnote definitions.
     pub fn take_footnote_store(&mut self) -> FootnoteStore {
-        std::mem::take(&mut self.footnote_store)
+        core::mem::take(&mut self.footnote_store)
     }
 
     /// Parse a single line.
@@ -334,7 +560,7 @@ impl<'a> BlockParser<'a> {
         let matched_containers = self.match_containers(events);
 
         // If we're inside an HTML block, handle it after container matching.
-        if self.options.allow_html && self.html_block.is_some() {
+        if self.options.block_html() && self.html_block.is_some() {
             if matched_containers < self.container_stack.len() {
                 // Containers didn't match, close the HTML block
                 self.html_block = None;
@@ -377,6 +603,11 @@ impl<'a> BlockParser<'a> {
             }
             self.close_table(events);
             self.close_paragraph(events);
+            // Blank lines match every list item, so anything left unmatched sits
+            // inside a blockquote whose marker is missing (`>` under `>> a`)
+            while self.container_stack.len() > matched_containers {
+                self.close_top_container(events);
+            }
             // Container markers were present, so don't close blockquotes
             self.handle_blank_line_containers(events, false);
             return;
@@ -386,6 +617,7 @@ impl<'a> BlockParser<'a> {
         if matched_containers < self.container_stack.len() {
             // If we're in an indented code block and containers don't match, close it
             if self.in_indented_code {
+                self.pending_code_blanks.clear();
                 self.in_indented_code = false;
                 events.push(BlockEvent::CodeBlockEnd);
             }
@@ -457,7 +689,7 @@ impl<'a> BlockParser<'a> {
         // If we're in a table, check for continuation or termination
         if self.in_table {
             let first = self.cursor.peek_or_zero();
-            if first == 0 || first == b'\n' {
+            if self.cursor.is_eof() || first == b'\n' {
                 if first == b'\n' {
                     parser_cursor_bump!(self.cursor);
                 }
@@ -487,17 +719,8 @@ impl<'a> BlockParser<'a> {
                     let drain_count = consumed.min(self.paragraph_lines.len());
                     self.paragraph_lines.drain(0..drain_count);
                 }
-                if self.paragraph_lines.is_empty() {
-                    // No paragraph content left after stripping definitions; not a setext heading.
-                    // Treat this line as normal paragraph content.
-                    let line_start = self.cursor.offset();
-                    self.parse_paragraph_line(line_start, events);
-                    return;
-                } else {
-                    // Skip to end of line
-                    while !self.cursor.is_eof() && !self.cursor.at(b'\n') {
-                        parser_cursor_bump!(self.cursor);
-                    }
+                if !self.paragraph_lines.is_empty() {
+                    self.skip_to_line_end();
                     if !self.cursor.is_eof() {
                         parser_cursor_bump!(self.cursor);
                     }
@@ -505,6 +728,9 @@ impl<'a> BlockParser<'a> {
                     self.close_paragraph_as_setext_heading(level, events);
                     return;
                 }
+                // Only definitions preceded the underline, so there is no heading text.
+                // Fall through like cmark: `---` can still be a thematic break and `===`
+                // becomes ordinary paragraph text.
             }
         }
 
@@ -532,14 +758,15 @@ impl<'a> BlockParser<'a> {
                 if !self.paragraph_lines.is_empty() {
                     let last_para_line = self.paragraph_lines.last().unwrap();
                     let header_line = last_para_line.slice(self.input);
-                    let header_cells = Self::split_table_cells(header_line);
+                    let mut header_cells = Self::split_table_cells(header_line);
+                    let merged = self.take_merge_marker(header_line, &mut header_cells);
 
-                    if header_cells.len() == alignments.len() {
+                    if Self::header_fits(header_cells.len(), merged, alignments.len()) {
                         self.cursor = Cursor::new_at(self.input, line_end);
                         if !self.cursor.is_eof() && self.cursor.at(b'\n') {
                             parser_cursor_bump!(self.cursor);
                         }
-                        self.start_table(header_cells, alignments, events);
+                        self.start_table(header_cells, merged, alignments, events);
                         return;
                     }
                 }
@@ -556,7 +783,7 @@ impl<'a> BlockParser<'a> {
         }
 
         // Check for new container starts (blockquote, list)
-        if indent < 4 && self.container_stack.len() < limits::MAX_BLOCK_NESTING {
+        if indent < 4 && self.container_stack.len() < self.max_nesting_depth() {
             // Check for blockquote
             if self.try_blockquote(events) {
                 // Recursively parse the rest of the line
@@ -606,7 +833,7 @@ impl<'a> BlockParser<'a> {
             self.line_indent_bytes = indent_bytes;
 
             let first = self.cursor.peek_or_zero();
-            if first == 0 || first == b'\n' {
+            if self.cursor.is_eof() || first == b'\n' {
                 if !self.cursor.is_eof() && self.cursor.at(b'\n') {
                     parser_cursor_bump!(self.cursor);
                 }
@@ -622,7 +849,9 @@ impl<'a> BlockParser<'a> {
             // When tables are enabled and we're in a paragraph, bail on lines
             // that could be delimiter rows (starting with ':' or '-')
             // '-' is already caught by is_simple_line_start above.
-            if self.options.tables && self.in_paragraph && first == b':' {
+            if (self.options.tables && self.in_paragraph || self.options.fenced_divs)
+                && first == b':'
+            {
                 self.cursor = Cursor::new_at(self.input, line_start);
                 return consumed_any;
             }
@@ -797,7 +1026,7 @@ impl<'a> BlockParser<'a> {
         let first = self.cursor.peek_or_zero();
 
         // Check for blank line (can happen after container markers)
-        if first == 0 || first == b'\n' {
+        if self.cursor.is_eof() || first == b'\n' {
             if first == b'\n' {
                 parser_cursor_bump!(self.cursor);
             }
@@ -825,20 +1054,19 @@ impl<'a> BlockParser<'a> {
             if is_simple_line_start(first)
                 && !(self.options.tables && (first == b'|' || (first == b':' && self.in_paragraph)))
                 && !(self.options.footnotes && first == b'[')
+                && !(self.options.fenced_divs && first == b':')
             {
                 let line_start = self.cursor.offset();
                 self.parse_paragraph_line(line_start, events);
                 return;
             }
 
-            // Check for setext heading underline (when in a paragraph)
+            // Check for setext heading underline (when in a paragraph with heading text)
             // Must check BEFORE thematic break since `---` can be either
-            if self.in_paragraph && matches!(first, b'=' | b'-') {
+            if self.in_paragraph && !self.paragraph_lines.is_empty() && matches!(first, b'=' | b'-')
+            {
                 if let Some(level) = self.is_setext_underline_after_indent() {
-                    // Skip to end of line
-                    while !self.cursor.is_eof() && !self.cursor.at(b'\n') {
-                        parser_cursor_bump!(self.cursor);
-                    }
+                    self.skip_to_line_end();
                     if !self.cursor.is_eof() {
                         parser_cursor_bump!(self.cursor);
                     }
@@ -848,6 +1076,11 @@ impl<'a> BlockParser<'a> {
                 }
             }
 
+            // Check for a fenced div fence (`::: class` or a closing `:::`)
+            if self.options.fenced_divs && first == b':' && self.try_fenced_div(events) {
+                return;
+            }
+
             // Check for GFM table delimiter row (when in a paragraph)
             // Must check BEFORE thematic break since `---` lines can be delimiter rows
             // Quick guard: delimiter rows start with |, -, or :
@@ -871,9 +1104,10 @@ impl<'a> BlockParser<'a> {
                     if !self.paragraph_lines.is_empty() {
                         let last_para_line = self.paragraph_lines.last().unwrap();
                         let header_line = last_para_line.slice(self.input);
-                        let header_cells = Self::split_table_cells(header_line);
+                        let mut header_cells = Self::split_table_cells(header_line);
+                        let merged = self.take_merge_marker(header_line, &mut header_cells);
 
-                        if header_cells.len() == alignments.len() {
+                        if Self::header_fits(header_cells.len(), merged, alignments.len()) {
                             // We have a table! Convert the paragraph.
                             // Skip the delimiter row
                             self.cursor = Cursor::new_at(self.input, line_end);
@@ -881,7 +1115,7 @@ impl<'a> BlockParser<'a> {
                                 parser_cursor_bump!(self.cursor);
                             }
 
-                            self.start_table(header_cells, alignments, events);
+                            self.start_table(header_cells, merged, alignments, events);
                             return;
                         }
                     }
@@ -910,7 +1144,7 @@ impl<'a> BlockParser<'a> {
             }
 
             // Check for nested containers (blockquote, list)
-            if self.container_stack.len() < limits::MAX_BLOCK_NESTING {
+            if self.container_stack.len() < self.max_nesting_depth() {
                 // Check for blockquote
                 if first == b'>' && self.try_blockquote(events) {
                     // Recursively parse the rest of the line
@@ -926,7 +1160,9 @@ impl<'a> BlockParser<'a> {
             }
 
             // Check for HTML block
-            if self.options.allow_html && first == b'<' && self.try_html_block_start(indent, events)
+            if self.options.block_html()
+                && first == b'<'
+                && self.try_html_block_start(indent, events)
             {
                 return;
             }
@@ -1029,6 +1265,17 @@ impl<'a> BlockParser<'a> {
                                 deepest_list_match = Some(list_index - 1);
                             }
                             matched += 1;
+                        } else if self.options.loose_list_continuation
+                            && self.continues_ordered_list(i, kind, cols, save_pos)
+                        {
+                            // Heuristic continuation: keep the interrupting paragraph
+                            // inside this item (cursor is already past the whitespace)
+                            let list_index = self.container_stack[..=i]
+                                .iter()
+                                .filter(|c| matches!(c.typ, ContainerType::ListItem { .. }))
+                                .count();
+                            deepest_list_match = Some(list_index - 1);
+                            matched += 1;
                         } else {
                             // Not enough indent - check if it's a new list item of same type
                             // (cursor is already past the whitespace)
@@ -1078,6 +1325,9 @@ impl<'a> BlockParser<'a> {
                         }
                     }
                 }
+                // Divs have no line marker; a closing fence is found with the
+                // line's content
+                ContainerType::Div { .. } => matched += 1,
             }
         }
 
@@ -1099,6 +1349,90 @@ impl<'a> BlockParser<'a> {
         matched
     }
 
+    /// Check whether an under-indented line should stay in ordered list item `index`
+    /// under [`Options::loose_list_continuation`](crate::Options::loose_list_continuation).
+    ///
+    /// The line must be a lone paragraph after a blank line, followed (after more
+    /// blank lines) by the next sequentially numbered item with the same delimiter.
+    /// The cursor must be past the line's indentation.
+    fn continues_ordered_list(
+        &self,
+        index: usize,
+        kind: ListKind,
+        indent: usize,
+        line_start: usize,
+    ) -> bool {
+        let ListKind::Ordered { delimiter, .. } = kind else {
+            return false;
+        };
+        if indent >= 4 || !self.container_stack[index].has_content || self.would_start_block(indent)
+        {
+            return false;
+        }
+
+        // The previous line must be blank
+        let input = self.input;
+        if line_start == 0 || input[line_start - 1] != b'\n' {
+            return false;
+        }
+        let prev_start = input[..line_start - 1]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |p| p + 1);
+        if !Self::is_blank_remaining_line(&input[prev_start..line_start]) {
+            return false;
+        }
+
+        let list_index = self.container_stack[..=index]
+            .iter()
+            .filter(|c| matches!(c.typ, ContainerType::ListItem { .. }))
+            .count();
+        let Some(expected) = self
+            .open_lists
+            .get(list_index - 1)
+            .and_then(|list| list.last_number.checked_add(1))
+        else {
+            return false;
+        };
+
+        // Skip the paragraph lines, then at least one blank line
+        let mut pos = self.cursor.offset();
+        let mut seen_blank = false;
+        loop {
+            let line_end = memchr::memchr(b'\n', &input[pos..]).map_or(input.len(), |p| pos + p);
+            if Self::is_blank_remaining_line(&input[pos..line_end]) {
+                seen_blank = true;
+            } else if seen_blank {
+                break;
+            }
+            if line_end >= input.len() {
+                return false;
+            }
+            pos = line_end + 1;
+        }
+
+        // The next block must be the following item of the same list
+        let spaces = input[pos..].iter().take_while(|&&b| b == b' ').count();
+        if spaces >= 4 {
+            return false;
+        }
+        let digits_start = pos + spaces;
+        let digits = input[digits_start..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if digits == 0 || digits > limits::MAX_LIST_MARKER_DIGITS {
+            return false;
+        }
+        let number = input[digits_start..digits_start + digits]
+            .iter()
+            .fold(0u32, |n, &b| n * 10 + u32::from(b - b'0'));
+        let after = digits_start + digits;
+        number == expected
+            && input.get(after) == Some(&delimiter)
+            && matches!(input.get(after + 1), None | Some(b' ' | b'\t' | b'\n'))
+    }
+
     /// Return whether the remaining source contains only horizontal whitespace
     /// before its next newline or end of input.
     #[inline]
@@ -1211,16 +1545,7 @@ impl<'a> BlockParser<'a> {
             // ATX heading - only at indent < 4
             b'#' => indent < 4,
             // Fenced code block - only at indent < 4
-            b'`' | b'~' => {
-                indent < 4
-                    && self
-                        .cursor
-                        .remaining_slice()
-                        .iter()
-                        .take_while(|&&c| c == b)
-                        .count()
-                        >= 3
-            }
+            b'`' | b'~' => indent < 4 && self.cursor.starts_with(&[b; 3]),
             // Blockquote - only at indent < 4
             b'>' => indent < 4,
             // Unordered list marker or thematic break - only at indent < 4
@@ -1263,8 +1588,12 @@ impl<'a> BlockParser<'a> {
             }
             // HTML block (only types that can interrupt paragraphs) - only at indent < 4
             b'<' => {
-                self.options.allow_html && indent < 4 && self.peek_html_block_start(true).is_some()
+                self.options.block_html()
+                    && indent < 4
+                    && self.peek_html_block_start(true).is_some()
             }
+            // Fenced div fence - only at indent < 4
+            b':' => self.options.fenced_divs && indent < 4 && self.peek_div_fence().is_some(),
             // Note: We don't check for setext underlines (= or plain line of -) here because
             // setext underlines can't interrupt lazy continuation. They only work when the
             // paragraph is at the same container level as the underline.
@@ -1304,6 +1633,7 @@ impl<'a> BlockParser<'a> {
                         // Just close the item, not the list
                         self.container_stack.pop();
                         self.close_paragraph(events);
+                        self.close_nested_lists(events);
                         events.push(BlockEvent::ListItemEnd);
                         // Don't pop from open_lists
                         continue;
@@ -1323,14 +1653,16 @@ impl<'a> BlockParser<'a> {
         events: &mut Vec<BlockEvent>,
         close_blockquotes: bool,
     ) {
-        // A blank line (without > marker) closes blockquotes
+        // A blank line (without > marker) closes the outermost blockquote and
+        // everything inside it; list items around it match blank lines
         if close_blockquotes {
-            // Close all blockquote containers from the top
-            while let Some(container) = self.container_stack.last() {
-                if container.typ == ContainerType::BlockQuote {
+            if let Some(first) = self
+                .container_stack
+                .iter()
+                .position(|c| c.typ == ContainerType::BlockQuote)
+            {
+                while self.container_stack.len() > first {
                     self.close_top_container(events);
-                } else {
-                    break;
                 }
             }
         }
@@ -1389,6 +1721,7 @@ impl<'a> BlockParser<'a> {
             return false;
         }
 
+        let marker_start = self.cursor.offset();
         parser_cursor_bump!(self.cursor); // consume >
         self.current_col += 1;
 
@@ -1398,14 +1731,23 @@ impl<'a> BlockParser<'a> {
         self.skip_indent_max(1);
 
         // Try to detect callout marker [!TYPE] on first line
-        let callout = if self.options.callouts {
+        let (callout, fold, title) = if self.options.callouts {
             self.try_callout_type()
+                .map_or((None, None, None), |(callout, fold, title)| {
+                    (Some(callout), fold, title)
+                })
+        } else {
+            (None, None, None)
+        };
+        let cite = if callout.is_none() && self.options.blockquote_cite_url {
+            self.try_blockquote_cite()
         } else {
             None
         };
 
         // Close paragraph if any
         self.close_paragraph(events);
+        self.close_orphaned_lists(events);
 
         // Mark the current container as having content (before pushing new container)
         self.mark_container_has_content();
@@ -1416,14 +1758,56 @@ impl<'a> BlockParser<'a> {
             has_content: false,
         });
 
-        events.push(BlockEvent::BlockQuoteStart { callout });
+        self.record_span(events, marker_start, marker_start);
+        events.push(BlockEvent::BlockQuoteStart { callout, fold });
+        if let Some(title) = title {
+            self.record_span(events, title.start_usize(), title.end_usize());
+            events.push(BlockEvent::CalloutTitle(title));
+        }
+        if let Some(cite) = cite {
+            self.record_span(events, cite.start_usize(), cite.end_usize());
+            events.push(BlockEvent::BlockQuoteCite(cite));
+        }
         true
     }
 
+    /// Try to detect a `[cite:URL]` line at the current cursor position.
+    /// If found, consumes it up to the line ending and returns the URL.
+    /// If not found, cursor is unchanged.
+    fn try_blockquote_cite(&mut self) -> Option<Range> {
+        let rest = self.cursor.remaining_slice();
+        if rest.len() < 6 || !rest[..6].eq_ignore_ascii_case(b"[cite:") {
+            return None;
+        }
+        let url_len = rest[6..]
+            .iter()
+            .position(|&b| matches!(b, b']' | b' ' | b'\t' | b'\n' | b'\r' | b'<' | b'>'))?;
+        if url_len == 0 || rest[6 + url_len] != b']' {
+            return None;
+        }
+        let line_len = rest
+            .iter()
+            .position(|&b| b == b'\n' || b == b'\r')
+            .unwrap_or(rest.len());
+        if !rest[7 + url_len..line_len]
+            .iter()
+            .all(|&b| b == b' ' || b == b'\t')
+        {
+            return None;
+        }
+
+        let url_start = self.cursor.offset() + 6;
+        // Leave the line ending, like a callout marker
+        self.cursor = Cursor::new_at(self.input, self.cursor.offset() + line_len);
+        Some(Range::from_usize(url_start, url_start + url_len))
+    }
+
     /// Try to detect a callout type marker `[!TYPE]` at the current cursor position.
-    /// If found, consumes the marker and trailing whitespace/newline. Returns the type.
+    /// If found, consumes the marker and trailing whitespace/newline. Returns the type
+    /// and, with `collapsible_callouts`, the `-`/`+` fold marker and its title.
+    /// With `callout_custom_titles`, text after a plain marker is the returned title.
     /// If not found, cursor is unchanged.
-    fn try_callout_type(&mut self) -> Option<CalloutType> {
+    fn try_callout_type(&mut self) -> Option<(CalloutType, Option<CalloutFold>, Option<Range>)> {
         let saved_offset = self.cursor.offset();
 
         // Check for `[!`
@@ -1457,10 +1841,32 @@ impl<'a> BlockParser<'a> {
         }
         parser_cursor_bump!(self.cursor);
 
-        // After `]`, only optional whitespace allowed before newline/EOF
+        // Optional fold marker: `-` collapsed, `+` expanded
+        let expanded = match self.cursor.peek() {
+            Some(b'-') if self.options.collapsible_callouts => Some(false),
+            Some(b'+') if self.options.collapsible_callouts => Some(true),
+            _ => None,
+        };
+        if expanded.is_some() {
+            parser_cursor_bump!(self.cursor);
+        }
+
+        // After `]`, only optional whitespace allowed before newline/EOF,
+        // except for the title of a collapsible or custom-titled callout
+        let at_title = self.cursor.peek().is_some_and(|b| b == b' ' || b == b'\t');
         while self.cursor.peek().is_some_and(|b| b == b' ' || b == b'\t') {
             parser_cursor_bump!(self.cursor);
         }
+        let title_start = self.cursor.offset();
+        if expanded.is_some() || (at_title && self.options.callout_custom_titles) {
+            while self.cursor.peek().is_some_and(|b| b != b'\n' && b != b'\r') {
+                parser_cursor_bump!(self.cursor);
+            }
+        }
+        let mut title_end = self.cursor.offset();
+        while title_end > title_start && matches!(self.input[title_end - 1], b' ' | b'\t') {
+            title_end -= 1;
+        }
         if !self.cursor.is_eof() && !self.cursor.at(b'\n') && !self.cursor.at(b'\r') {
             // Non-whitespace after `]` — not a callout
             self.cursor = Cursor::new_at(self.input, saved_offset);
@@ -1482,10 +1888,14 @@ impl<'a> BlockParser<'a> {
             }
         };
 
+        let title = (title_end > title_start).then(|| Range::from_usize(title_start, title_end));
+        let fold = expanded.map(|expanded| CalloutFold { expanded, title });
+        let title = title.filter(|_| fold.is_none());
+
         // Don't consume the trailing newline — let parse_line_content handle
         // it as a blank line so the next line is processed through match_containers.
 
-        Some(callout_type)
+        Some((callout_type, fold, title))
     }
 
     /// Try to start a list item.
@@ -1506,7 +1916,13 @@ impl<'a> BlockParser<'a> {
 
             // Absolute content_indent = spaces before marker + marker width + spaces after marker
             let absolute_content_indent = pre_marker_indent + relative_content_indent;
-            self.start_list_item(ListKind::Unordered, marker, absolute_content_indent, events);
+            self.start_list_item(
+                ListKind::Unordered,
+                marker,
+                absolute_content_indent,
+                start_offset,
+                events,
+            );
             return true;
         }
 
@@ -1529,6 +1945,7 @@ impl<'a> BlockParser<'a> {
                 },
                 delimiter,
                 absolute_content_indent,
+                start_offset,
                 events,
             );
             return true;
@@ -1695,6 +2112,7 @@ impl<'a> BlockParser<'a> {
         kind: ListKind,
         marker: u8,
         content_indent: usize,
+        marker_start: usize,
         events: &mut Vec<BlockEvent>,
     ) {
         // Close paragraph if any
@@ -1719,6 +2137,11 @@ impl<'a> BlockParser<'a> {
         let inside_list_item = list_item_count > 0;
 
         if !continuing_list {
+            // A waiting list of another kind ends here
+            if has_waiting_list {
+                self.close_orphaned_lists(events);
+            }
+
             // Close any existing list items from incompatible lists
             // (but not if we're nesting inside a matched item)
             if !inside_list_item {
@@ -1731,7 +2154,9 @@ impl<'a> BlockParser<'a> {
                 }
             }
 
-            // Start new list (tight will be determined later)
+            // Start new list (tight is patched in when the list closes)
+            self.record_span(events, marker_start, marker_start);
+            let start_event = events.len();
             events.push(BlockEvent::ListStart { kind, tight: true });
             self.open_lists.push(OpenList {
                 kind,
@@ -1739,6 +2164,8 @@ impl<'a> BlockParser<'a> {
                 tight: true,
                 blank_in_item: false,
                 item_count: 0,
+                last_number: 0,
+                start_event,
             });
         }
         // Note: if continuing_list is true, the previous item was already
@@ -1765,6 +2192,9 @@ impl<'a> BlockParser<'a> {
             }
             open_list.item_count += 1;
             open_list.blank_in_item = false;
+            if let ListKind::Ordered { start, .. } = kind {
+                open_list.last_number = start;
+            }
         }
 
         // Check for task list checkbox (only if enabled)
@@ -1774,6 +2204,7 @@ impl<'a> BlockParser<'a> {
             TaskState::None
         };
 
+        self.record_span(events, marker_start, marker_start);
         events.push(BlockEvent::ListItemStart { task });
     }
 
@@ -1806,8 +2237,8 @@ impl<'a> BlockParser<'a> {
             return TaskState::None;
         }
 
-        // Must be followed by space
-        if self.cursor.peek_ahead(3) != Some(b' ') {
+        // Must be followed by a space or tab
+        if !matches!(self.cursor.peek_ahead(3), Some(b' ' | b'\t')) {
             return TaskState::None;
         }
 
@@ -1843,29 +2274,47 @@ impl<'a> BlockParser<'a> {
                     events.push(BlockEvent::BlockQuoteEnd);
                 }
                 ContainerType::ListItem { kind, .. } => {
+                    let remaining_items = self.close_nested_lists(events);
                     events.push(BlockEvent::ListItemEnd);
 
-                    // Count remaining ListItem containers
-                    let remaining_items = self
-                        .container_stack
-                        .iter()
-                        .filter(|c| matches!(c.typ, ContainerType::ListItem { .. }))
-                        .count();
-
                     // Close lists until open_lists count matches remaining items
                     // This properly handles nested lists: each nesting level has one
                     // ListItem container and one open list
                     while self.open_lists.len() > remaining_items {
-                        let tight = self.open_lists.last().is_none_or(|l| l.tight);
-                        events.push(BlockEvent::ListEnd { kind, tight });
-                        self.open_lists.pop();
+                        if let Some(open_list) = self.open_lists.pop() {
+                            Self::close_list(events, &open_list, kind);
+                        }
                     }
                 }
                 ContainerType::FootnoteDefinition { .. } => {
                     self.close_footnote_definition(events);
                 }
+                ContainerType::Div { .. } => {
+                    events.push(BlockEvent::DivEnd);
+                }
+            }
+        }
+    }
+
+    /// Close lists nested in a just-popped list item, left open when a blank
+    /// line closed their empty last item. Returns the number of list items
+    /// still open.
+    fn close_nested_lists(&mut self, events: &mut Vec<BlockEvent>) -> usize {
+        let remaining_items = self
+            .container_stack
+            .iter()
+            .filter(|c| matches!(c.typ, ContainerType::ListItem { .. }))
+            .count();
+        while self.open_lists.len() > remaining_items + 1 {
+            if let Some(open_list) = self.open_lists.pop() {
+                Self::close_list(events, &open_list, open_list.kind);
+                // The blank line now separates items of the enclosing list
+                if let Some(parent) = self.open_lists.last_mut() {
+                    parent.blank_in_item |= open_list.blank_in_item;
+                }
             }
         }
+        remaining_items
     }
 
     /// Close all containers.
@@ -1908,7 +2357,7 @@ impl<'a> BlockParser<'a> {
     /// Try to parse a thematic break.
     /// Returns true if successful.
     fn try_thematic_break(&mut self, events: &mut Vec<BlockEvent>) -> bool {
-        let _start_pos = self.cursor.offset();
+        let start_pos = self.cursor.offset();
 
         // Must start with -, *, or _
         let marker = match self.cursor.peek() {
@@ -1941,16 +2390,22 @@ impl<'a> BlockParser<'a> {
 
         // Consume the line
         self.cursor = temp_cursor;
+        let line_end = self.cursor.offset();
         if !self.cursor.is_eof() && self.cursor.at(b'\n') {
             parser_cursor_bump!(self.cursor);
         }
 
         // Close any open paragraph
         self.close_paragraph(events);
+        self.close_orphaned_lists(events);
 
         // Mark the current container as having content
         self.mark_container_has_content();
 
+        let end = self
+            .content_end_before(start_pos, line_end)
+            .unwrap_or(line_end);
+        self.record_span(events, start_pos, end);
         events.push(BlockEvent::ThematicBreak);
         true
     }
@@ -1963,7 +2418,7 @@ impl<'a> BlockParser<'a> {
             return false;
         }
 
-        let _start_pos = self.cursor.offset();
+        let start_pos = self.cursor.offset();
 
         // Count # characters (1-6)
         let mut level = 0u8;
@@ -2000,8 +2455,12 @@ impl<'a> BlockParser<'a> {
             None => content_start + temp_cursor.remaining(),
         };
 
+        // An attribute list ends the line, after any closing #s
+        let attributes = self.trailing_attributes(content_start, line_end);
+        let text_end = attributes.map_or(line_end, |range| range.start_usize());
+
         // Trim trailing # and spaces from content
-        let content_end = self.trim_heading_end(content_start, line_end);
+        let content_end = self.trim_heading_end(content_start, text_end);
 
         // Update cursor to end of line
         self.cursor = Cursor::new_at(self.input, line_end);
@@ -2011,11 +2470,20 @@ impl<'a> BlockParser<'a> {
 
         // Close any open paragraph
         self.close_paragraph(events);
+        self.close_orphaned_lists(events);
 
         // Mark the current container as having content
         self.mark_container_has_content();
 
         // Emit heading events
+        let end = self
+            .content_end_before(start_pos, line_end)
+            .unwrap_or(line_end);
+        if let Some(range) = attributes {
+            self.record_span(events, range.start_usize(), range.end_usize());
+            events.push(BlockEvent::Attributes(range));
+        }
+        self.record_span(events, start_pos, end);
         events.push(BlockEvent::HeadingStart { level });
 
         if content_end > content_start {
@@ -2025,6 +2493,7 @@ impl<'a> BlockParser<'a> {
             )));
         }
 
+        self.record_span(events, start_pos, end);
         events.push(BlockEvent::HeadingEnd { level });
 
         true
@@ -2064,6 +2533,22 @@ impl<'a> BlockParser<'a> {
 
         pos
     }
+
+    /// Attribute list at the end of `start..end`, ignoring trailing
+    /// whitespace, with the `attributes` option.
+    fn trailing_attributes(&self, start: usize, end: usize) -> Option<Range> {
+        if !self.options.attributes {
+            return None;
+        }
+        let end = start
+            + self.input[start..end]
+                .iter()
+                .rposition(|b| !matches!(b, b' ' | b'\t'))
+                .map_or(0, |i| i + 1);
+        let offset = trailing_attributes(&self.input[start..end])?;
+        Some(Range::from_usize(start + offset, end))
+    }
+
     /// Check if the current position (after indent has been skipped) is a setext underline.
     /// Returns Some(level) where level is 1 for '=' and 2 for '-', or None.
     /// Unlike peek_setext_underline, this assumes indent has already been consumed.
@@ -2109,6 +2594,28 @@ impl<'a> BlockParser<'a> {
         // Mark the current container as having content
         self.mark_container_has_content();
 
+        // The cursor is past the underline
+        let start = self.paragraph_lines[0].start as usize;
+        let end = self
+            .content_end_before(start, self.cursor.offset())
+            .unwrap_or(start);
+
+        // An attribute list ends the last line, after some text
+        let last = self.paragraph_lines.len() - 1;
+        let last_line = self.paragraph_lines[last];
+        let attributes = self
+            .trailing_attributes(last_line.start_usize(), last_line.end_usize())
+            .filter(|range| {
+                self.input[last_line.start_usize()..range.start_usize()]
+                    .iter()
+                    .any(|b| !matches!(b, b' ' | b'\t'))
+            });
+        if let Some(range) = attributes {
+            self.paragraph_lines[last].end = range.start;
+            self.record_span(events, range.start_usize(), range.end_usize());
+            events.push(BlockEvent::Attributes(range));
+        }
+        self.record_span(events, start, end);
         events.push(BlockEvent::HeadingStart { level });
 
         // Emit text ranges for each line with soft breaks between
@@ -2132,12 +2639,15 @@ impl<'a> BlockParser<'a> {
             events.push(BlockEvent::Text(range));
         }
 
+        self.record_span(events, start, end);
         events.push(BlockEvent::HeadingEnd { level });
     }
 
     /// Try to parse a fenced code block opening.
     /// Returns true if successful.
     fn try_code_fence(&mut self, indent: usize, events: &mut Vec<BlockEvent>) -> bool {
+        let fence_start = self.cursor.offset();
+
         // Must start with ` or ~
         let fence_char = match self.cursor.peek() {
             Some(b'`') | Some(b'~') => self.cursor.peek().unwrap(),
@@ -2178,8 +2688,11 @@ impl<'a> BlockParser<'a> {
             }
         }
 
+        // An attribute list ends the info string
+        let attributes = self.trailing_attributes(info_content_start, line_end);
+
         // Trim trailing whitespace from info string
-        let mut info_end = line_end;
+        let mut info_end = attributes.map_or(line_end, |range| range.start_usize());
         while info_end > info_content_start
             && (self.input[info_end - 1] == b' ' || self.input[info_end - 1] == b'\t')
         {
@@ -2194,6 +2707,7 @@ impl<'a> BlockParser<'a> {
 
         // Close any open paragraph
         self.close_paragraph(events);
+        self.close_orphaned_lists(events);
 
         // Store fence state
         self.fence_state = Some(FenceState {
@@ -2211,6 +2725,11 @@ impl<'a> BlockParser<'a> {
         } else {
             None
         };
+        if let Some(range) = attributes {
+            self.record_span(events, range.start_usize(), range.end_usize());
+            events.push(BlockEvent::Attributes(range));
+        }
+        self.record_span(events, fence_start, fence_start);
         events.push(BlockEvent::CodeBlockStart {
             kind: CodeBlockKind::Fenced { info },
         });
@@ -2218,11 +2737,103 @@ impl<'a> BlockParser<'a> {
         true
     }
 
+    /// Check for a fenced div fence at the cursor without consuming input:
+    /// three or more colons, then optionally a class name or attribute list
+    /// and more colons. Returns the fence length, the class and the line end.
+    fn peek_div_fence(&self) -> Option<(usize, Option<Range>, usize)> {
+        let start = self.cursor.offset();
+        let rest = self.cursor.remaining_slice();
+        let line = &rest[..self.cursor.find_newline().unwrap_or(rest.len())];
+        let fence_len = line.iter().take_while(|&&b| b == b':').count();
+        if fence_len < 3 {
+            return None;
+        }
+
+        let is_space = |b: &u8| matches!(b, b' ' | b'\t');
+        let info_start = fence_len + line[fence_len..].iter().take_while(|b| is_space(b)).count();
+        // Not before `info_start` when nothing but whitespace follows the colons
+        let mut info_end =
+            (line.len() - line.iter().rev().take_while(|b| is_space(b)).count()).max(info_start);
+        // Optional closing colons after the class
+        let colons = line[info_start..info_end]
+            .iter()
+            .rev()
+            .take_while(|&&b| b == b':')
+            .count();
+        if colons > 0
+            && (info_end - colons == info_start
+                || matches!(line[info_end - colons - 1], b' ' | b'\t' | b'}'))
+        {
+            info_end -= colons;
+            info_end -= line[info_start..info_end]
+                .iter()
+                .rev()
+                .take_while(|b| is_space(b))
+                .count();
+        }
+
+        let class = if info_end > info_start {
+            div_attributes(&line[info_start..info_end])?;
+            Some(Range::from_usize(start + info_start, start + info_end))
+        } else {
+            None
+        };
+        Some((fence_len, class, start + line.len()))
+    }
+
+    /// Try to open or close a fenced div at the cursor.
+    ///
+    /// A bare fence closes the innermost div when it is at least as long as
+    /// that div's opening fence and only list items are open inside the
+    /// div. Otherwise it opens a div without a class.
+    fn try_fenced_div(&mut self, events: &mut Vec<BlockEvent>) -> bool {
+        let Some((fence_len, class, line_end)) = self.peek_div_fence() else {
+            return false;
+        };
+        let fence_start = self.cursor.offset();
+        let innermost_div = self
+            .container_stack
+            .iter()
+            .rposition(|c| !matches!(c.typ, ContainerType::ListItem { .. }))
+            .filter(|&i| {
+                matches!(self.container_stack[i].typ,
+                    ContainerType::Div { fence_len: open_len } if fence_len >= open_len)
+            });
+        let closes = class.is_none() && innermost_div.is_some();
+        if !closes && self.container_stack.len() >= self.max_nesting_depth() {
+            return false;
+        }
+
+        self.cursor = Cursor::new_at(self.input, line_end);
+        if !self.cursor.is_eof() && self.cursor.at(b'\n') {
+            parser_cursor_bump!(self.cursor);
+        }
+        if let (true, Some(div)) = (closes, innermost_div) {
+            while self.container_stack.len() > div {
+                self.close_top_container(events);
+            }
+            return true;
+        }
+
+        self.close_table(events);
+        self.close_paragraph(events);
+        self.close_orphaned_lists(events);
+        self.mark_container_has_content();
+        self.container_stack.push(Container {
+            typ: ContainerType::Div { fence_len },
+            has_content: false,
+        });
+        self.record_span(events, fence_start, fence_start);
+        events.push(BlockEvent::DivStart { class });
+        true
+    }
+
     /// Start an indented code block.
     /// `indent_cols` is the number of columns of indentation measured.
     fn start_indented_code(&mut self, indent_cols: usize, events: &mut Vec<BlockEvent>) {
         // Close any open paragraph first
         self.close_paragraph(events);
+        self.close_orphaned_lists(events);
 
         // Mark the current container as having content
         self.mark_container_has_content();
@@ -2231,6 +2842,8 @@ impl<'a> BlockParser<'a> {
         self.in_indented_code = true;
         // Store the excess columns (indent_cols - 4) to prepend as spaces
         self.indented_code_extra_spaces = indent_cols.saturating_sub(4);
+        let indent_start = self.cursor.offset().saturating_sub(self.line_indent_bytes);
+        self.record_span(events, indent_start, indent_start);
         events.push(BlockEvent::CodeBlockStart {
             kind: CodeBlockKind::Indented,
         });
@@ -2281,8 +2894,9 @@ impl<'a> BlockParser<'a> {
         self.mark_container_has_content();
 
         self.html_block = Some(kind);
-        self.pending_html_indent_start =
-            Some(self.cursor.offset().saturating_sub(self.line_indent_bytes));
+        let indent_start = self.cursor.offset().saturating_sub(self.line_indent_bytes);
+        self.pending_html_indent_start = Some(indent_start);
+        self.record_span(events, indent_start, indent_start);
         events.push(BlockEvent::HtmlBlockStart);
 
         // Consume the current line as HTML block content
@@ -2338,6 +2952,10 @@ impl<'a> BlockParser<'a> {
         let line = &self.input[content_start..line_end];
         if self.html_block_ends(kind, line) {
             self.html_block = None;
+            let end = self
+                .content_end_before(content_start, line_end)
+                .unwrap_or(content_start);
+            self.record_span(events, end, end);
             events.push(BlockEvent::HtmlBlockEnd);
         }
     }
@@ -2360,22 +2978,22 @@ impl<'a> BlockParser<'a> {
         }
 
         // Type 2: <!--
-        if line.starts_with(b"<!--") {
+        if self.cursor.starts_with(b"<!--") {
             return Some(HtmlBlockKind::Type2);
         }
 
         // Type 3: <?
-        if line.starts_with(b"<?") {
+        if self.cursor.starts_with(b"<?") {
             return Some(HtmlBlockKind::Type3);
         }
 
         // Type 4: <![CDATA[
-        if line.starts_with(b"<![CDATA[") {
+        if self.cursor.starts_with(b"<![CDATA[") {
             return Some(HtmlBlockKind::Type4);
         }
 
         // Type 5: <! + letter
-        if line.starts_with(b"<!") && line.len() > 2 && line[2].is_ascii_alphabetic() {
+        if matches!(self.cursor.peek_slice(3), [b'<', b'!', c] if c.is_ascii_alphabetic()) {
             return Some(HtmlBlockKind::Type5);
         }
 
@@ -2711,12 +3329,23 @@ impl<'a> BlockParser<'a> {
     }
     /// Find end of current line (position of \n or EOF).
     fn find_line_end(&mut self) -> usize {
-        while !self.cursor.is_eof() && !self.cursor.at(b'\n') {
-            parser_cursor_bump!(self.cursor);
-        }
+        self.skip_to_line_end();
         self.cursor.offset()
     }
 
+    /// Move the cursor to the next `\n` (or EOF) with a vectorized search.
+    ///
+    /// `current_col` and `partial_tab_cols` are not updated, so only call this
+    /// where the rest of the line is consumed without column tracking.
+    #[inline]
+    fn skip_to_line_end(&mut self) {
+        let distance = self
+            .cursor
+            .find_newline()
+            .unwrap_or_else(|| self.cursor.remaining());
+        parser_cursor_advance!(self.cursor, distance);
+    }
+
     /// Parse a fenced code line after container matching.
     /// Called when we're inside a fenced code block and containers matched.
     /// The cursor is at the content position (past container indent).
@@ -2759,12 +3388,16 @@ impl<'a> BlockParser<'a> {
                 temp_cursor.skip_whitespace();
                 if temp_cursor.is_eof() || temp_cursor.at(b'\n') {
                     // Valid closing fence
+                    let fence_end = self
+                        .content_end_before(content_pos, temp_cursor.offset())
+                        .unwrap_or(content_pos);
                     self.cursor = temp_cursor;
                     if !self.cursor.is_eof() && self.cursor.at(b'\n') {
                         parser_cursor_bump!(self.cursor);
                     }
 
                     self.fence_state = None;
+                    self.record_span(events, fence_end, fence_end);
                     events.push(BlockEvent::CodeBlockEnd);
                     return;
                 }
@@ -2861,9 +3494,17 @@ impl<'a> BlockParser<'a> {
         if has_leading_pipe {
             pos += 1;
         }
+        // The pipe is escaped by an odd run of backslashes; `\\|` ends the
+        // cell with an escaped backslash
         let has_trailing_pipe = line_end > pos
             && line[line_end - 1] == b'|'
-            && !(line_end >= 2 && line[line_end - 2] == b'\\');
+            && line[pos..line_end - 1]
+                .iter()
+                .rev()
+                .take_while(|&&b| b == b'\\')
+                .count()
+                % 2
+                == 0;
         let scan_end = if has_trailing_pipe {
             line_end - 1
         } else {
@@ -3037,12 +3678,7 @@ impl<'a> BlockParser<'a> {
             b'>' => true, // Blockquote
             b'`' | b'~' => {
                 // Fenced code block (need at least 3)
-                self.cursor
-                    .remaining_slice()
-                    .iter()
-                    .take_while(|&&c| c == first)
-                    .count()
-                    >= 3
+                self.cursor.starts_with(&[first; 3])
             }
             b'-' | b'*' | b'_' => {
                 // Thematic break check
@@ -3050,16 +3686,55 @@ impl<'a> BlockParser<'a> {
             }
             b'<' => {
                 // HTML block
-                self.options.allow_html && self.peek_html_block_start(false).is_some()
+                self.options.block_html() && self.peek_html_block_start(false).is_some()
+            }
+            b':' => {
+                // Fenced div fence
+                self.options.fenced_divs && self.peek_div_fence().is_some()
             }
             _ => false,
         }
     }
 
+    /// With `table_cell_merge`, remove a trailing `||` merge marker (the
+    /// empty cell between two adjacent closing pipes) from `cells`. Returns
+    /// whether it was present; the row's last cell then spans the remaining
+    /// columns.
+    fn take_merge_marker(&self, line: &[u8], cells: &mut SmallVec<[(usize, usize); 8]>) -> bool {
+        if !self.options.table_cell_merge || cells.len() < 2 {
+            return false;
+        }
+        let (s, e) = cells[cells.len() - 1];
+        let marker = s == e && line[s - 1] == b'|' && line.get(s) == Some(&b'|');
+        if marker {
+            cells.pop();
+        }
+        marker
+    }
+
+    /// GFM requires one header cell per column; a merged header may have
+    /// fewer, since its last cell spans the rest.
+    #[inline]
+    fn header_fits(cells: usize, merged: bool, columns: usize) -> bool {
+        cells == columns || (merged && cells < columns)
+    }
+
+    /// Column span of cell `i` in a row of `cells` cells (after removing the
+    /// merge marker) in a table with `columns` columns.
+    #[inline]
+    fn cell_colspan(i: usize, cells: usize, merged: bool, columns: usize) -> u16 {
+        if merged && i + 1 == cells {
+            (columns - i) as u16
+        } else {
+            1
+        }
+    }
+
     /// Start a table from the last paragraph line (header) and delimiter row.
     fn start_table(
         &mut self,
         header_cells: SmallVec<[(usize, usize); 8]>,
+        merged: bool,
         alignments: SmallVec<[Alignment; 8]>,
         events: &mut Vec<BlockEvent>,
     ) {
@@ -3071,6 +3746,9 @@ impl<'a> BlockParser<'a> {
         if !self.paragraph_lines.is_empty() {
             // Emit those lines as a paragraph
             self.mark_container_has_content();
+            let start = self.paragraph_lines[0].start as usize;
+            let end = self.paragraph_lines[self.paragraph_lines.len() - 1].end as usize;
+            self.record_span(events, start, end);
             events.push(BlockEvent::ParagraphStart);
             for (i, range) in self.paragraph_lines.drain(..).enumerate() {
                 if i > 0 {
@@ -3078,6 +3756,7 @@ impl<'a> BlockParser<'a> {
                 }
                 events.push(BlockEvent::Text(range));
             }
+            self.record_span(events, start, end);
             events.push(BlockEvent::ParagraphEnd);
         }
         self.in_paragraph = false;
@@ -3085,26 +3764,38 @@ impl<'a> BlockParser<'a> {
         // Now emit the table structure
         self.mark_container_has_content();
 
-        events.push(BlockEvent::TableStart);
-        events.push(BlockEvent::TableHeadStart);
-        events.push(BlockEvent::TableRowStart);
-
         let header_base = header_range.start as usize;
-
-        for (i, &alignment) in alignments.iter().enumerate() {
-            events.push(BlockEvent::TableCellStart { alignment });
-            if i < header_cells.len() {
-                let (s, e) = header_cells[i];
-                if e > s {
-                    events.push(BlockEvent::Text(Range::from_usize(
-                        header_base + s,
-                        header_base + e,
-                    )));
-                }
+        let header_end = header_range.end as usize;
+        for event in [
+            BlockEvent::TableStart,
+            BlockEvent::TableHeadStart,
+            BlockEvent::TableRowStart,
+        ] {
+            self.record_span(events, header_base, header_base);
+            events.push(event);
+        }
+
+        let columns = alignments.len();
+        let mut i = 0;
+        while i < columns {
+            let alignment = alignments[i];
+            let colspan = Self::cell_colspan(i, header_cells.len(), merged, columns);
+            let (s, e) = header_cells
+                .get(i)
+                .map_or((header_end, header_end), |&(s, e)| {
+                    (header_base + s, header_base + e)
+                });
+            self.record_span(events, s, e);
+            events.push(BlockEvent::TableCellStart { alignment, colspan });
+            if e > s {
+                events.push(BlockEvent::Text(Range::from_usize(s, e)));
             }
+            self.record_span(events, s, e);
             events.push(BlockEvent::TableCellEnd);
+            i += usize::from(colspan);
         }
 
+        self.record_span(events, header_end, header_end);
         events.push(BlockEvent::TableRowEnd);
         events.push(BlockEvent::TableHeadEnd);
 
@@ -3146,32 +3837,41 @@ impl<'a> BlockParser<'a> {
         };
 
         let line = &self.input[line_start..line_end];
-        let cells = Self::split_table_cells(line);
+        let mut cells = Self::split_table_cells(line);
+        let merged = self.take_merge_marker(line, &mut cells);
         let col_count = self.table_alignments.len();
 
         if !self.table_has_body {
             self.table_has_body = true;
+            self.record_span(events, line_start, line_start);
             events.push(BlockEvent::TableBodyStart);
         }
 
+        self.record_span(events, line_start, line_start);
         events.push(BlockEvent::TableRowStart);
 
-        for i in 0..col_count {
+        let row_end = self
+            .content_end_before(line_start, line_end)
+            .unwrap_or(line_start);
+        let mut i = 0;
+        while i < col_count {
             let alignment = self.table_alignments[i];
-            events.push(BlockEvent::TableCellStart { alignment });
-            if i < cells.len() {
-                let (s, e) = cells[i];
-                if e > s {
-                    events.push(BlockEvent::Text(Range::from_usize(
-                        line_start + s,
-                        line_start + e,
-                    )));
-                }
+            let colspan = Self::cell_colspan(i, cells.len(), merged, col_count);
+            // Missing cells are empty and sit at the end of the row
+            let (s, e) = cells.get(i).map_or((row_end, row_end), |&(s, e)| {
+                (line_start + s, line_start + e)
+            });
+            self.record_span(events, s, e);
+            events.push(BlockEvent::TableCellStart { alignment, colspan });
+            if e > s {
+                events.push(BlockEvent::Text(Range::from_usize(s, e)));
             }
-            // If i >= cells.len(), emit an empty cell (no Text event)
+            self.record_span(events, s, e);
             events.push(BlockEvent::TableCellEnd);
+            i += usize::from(colspan);
         }
 
+        self.record_span(events, row_end, row_end);
         events.push(BlockEvent::TableRowEnd);
     }
 
@@ -3201,6 +3901,9 @@ impl<'a> BlockParser<'a> {
         // Mark the current container as having content
         self.mark_container_has_content();
 
+        let start = self.paragraph_lines[0].start as usize;
+        let end = self.paragraph_lines[self.paragraph_lines.len() - 1].end as usize;
+        self.record_span(events, start, end);
         events.push(BlockEvent::ParagraphStart);
 
         // Emit text ranges for each line with soft breaks between
@@ -3212,16 +3915,36 @@ impl<'a> BlockParser<'a> {
             events.push(BlockEvent::Text(range));
         }
 
+        self.record_span(events, start, end);
         events.push(BlockEvent::ParagraphEnd);
     }
 
     /// Close the current footnote definition, draining captured events into the store.
     fn close_footnote_definition(&mut self, events: &mut Vec<BlockEvent>) {
         if let Some(start) = self.footnote_event_start.take() {
+            // A list can outlive its last item (`[^1]: -` followed by a blank
+            // line); it ends with the definition, not in the document body
+            while self
+                .open_lists
+                .last()
+                .is_some_and(|open_list| open_list.start_event >= start)
+            {
+                if let Some(open_list) = self.open_lists.pop() {
+                    Self::close_list(events, &open_list, open_list.kind);
+                }
+            }
+
             // Find the footnote container to get the label
             // We need to find the label from the footnote container that was just popped.
             // We stored it when we opened the definition — extract from the drained events.
             let footnote_events: Vec<BlockEvent> = events.drain(start..).collect();
+            if self.record_spans {
+                // Drained events leave no spans; later lines emit from `start` again
+                self.span_log.retain(|&(index, _)| index < start);
+                for mark in &mut self.line_log {
+                    mark.first_event = mark.first_event.min(start);
+                }
+            }
 
             // The label was stored as a Text event right at the start marker position.
             // Actually, we need to store the label separately. Let's use a field.
@@ -3264,8 +3987,9 @@ impl<'a> BlockParser<'a> {
         }
         let label_end = self.cursor.offset();
 
-        if label_end == label_start {
-            // Empty label
+        // The caret counts towards the label length
+        if label_end == label_start || label_end - label_start >= limits::MAX_REF_LABEL_LEN {
+            // Empty or over-long label
             self.cursor = Cursor::new_at(self.input, save_pos);
             self.partial_tab_cols = save_partial;
             self.current_col = save_col;
@@ -3312,6 +4036,7 @@ impl<'a> BlockParser<'a> {
         // Close current paragraph and containers that don't match
         self.close_table(events);
         self.close_paragraph(events);
+        self.close_orphaned_lists(events);
 
         // Store the label for when we close the definition
         self.pending_footnote_label = Some((normalized, label));
@@ -3386,7 +4111,7 @@ impl<'a> BlockParser<'a> {
             self.link_ref_parse_buf
                 .extend_from_slice(range.slice(self.input));
         }
-        let para_buf = std::mem::take(&mut self.link_ref_parse_buf);
+        let para_buf = core::mem::take(&mut self.link_ref_parse_buf);
         let consumed_lines = self.extract_link_ref_defs_from_bytes(para_buf.as_slice());
         self.link_ref_parse_buf = para_buf;
         consumed_lines
@@ -3416,8 +4141,9 @@ impl<'a> BlockParser<'a> {
                     title: def.title.map(|r| r.slice(para).to_vec()),
                 };
                 self.link_refs
-                    .insert(std::mem::take(&mut self.link_ref_label_buf), link_def);
+                    .insert(core::mem::take(&mut self.link_ref_label_buf), link_def);
             } else {
+                self.link_refs.record_duplicate(&self.link_ref_label_buf);
                 self.link_ref_label_buf.clear();
             }
 
@@ -3482,10 +4208,77 @@ impl<'a> BlockParser<'a> {
         // Close lists that have no corresponding item
         while self.open_lists.len() > active_items {
             if let Some(open_list) = self.open_lists.pop() {
-                events.push(BlockEvent::ListEnd {
-                    kind: open_list.kind,
-                    tight: open_list.tight,
-                });
+                Self::close_list(events, &open_list, open_list.kind);
+            }
+        }
+    }
+
+    /// Emit `ListEnd` and give the list's `ListStart` its final tightness, so
+    /// consumers see the right value without a second pass over the events.
+    fn close_list(events: &mut Vec<BlockEvent>, open_list: &OpenList, kind: ListKind) {
+        let tight = open_list.tight;
+        if let Some(BlockEvent::ListStart { tight: start, .. }) =
+            events.get_mut(open_list.start_event)
+        {
+            *start = tight;
+        }
+        events.push(BlockEvent::ListEnd { kind, tight });
+    }
+}
+
+/// Iterator over block events, returned by [`BlockParser::events`].
+pub struct BlockEvents<'p, 'a> {
+    parser: &'p mut BlockParser<'a>,
+    /// Events parsed so far; those before `next` were yielded.
+    events: Vec<BlockEvent>,
+    next: usize,
+    finished: bool,
+}
+
+impl BlockEvents<'_, '_> {
+    /// End of the events that later lines can no longer change.
+    fn ready(&self) -> usize {
+        if self.finished {
+            return self.events.len();
+        }
+        let list = self.parser.open_lists.first().map(|list| list.start_event);
+        let footnote = self.parser.footnote_event_start;
+        [list, footnote]
+            .into_iter()
+            .flatten()
+            .fold(self.events.len(), usize::min)
+    }
+}
+
+impl Iterator for BlockEvents<'_, '_> {
+    type Item = BlockEvent;
+
+    fn next(&mut self) -> Option<BlockEvent> {
+        loop {
+            if self.next < self.ready() {
+                let event =
+                    core::mem::replace(&mut self.events[self.next], BlockEvent::ThematicBreak);
+                self.next += 1;
+                return Some(event);
+            }
+            if self.finished {
+                return None;
+            }
+            // Nothing refers to buffered events unless a list or footnote
+            // definition is open
+            if self.next == self.events.len()
+                && self.parser.open_lists.is_empty()
+                && self.parser.footnote_event_start.is_none()
+            {
+                self.events.clear();
+                self.parser.span_log.clear();
+                self.next = 0;
+            }
+            if self.parser.cursor.is_eof() {
+                self.parser.finish(&mut self.events);
+                self.finished = true;
+            } else {
+                self.parser.parse_line(&mut self.events);
             }
         }
     }
@@ -3516,6 +4309,9 @@ fn parse_link_ref_def(input: &[u8], start: usize) -> Option<(ParsedLinkRefDef, u
     // Parse label
     let label_start = i;
     while i < len {
+        if i - label_start > limits::MAX_REF_LABEL_LEN {
+            return None;
+        }
         match input[i] {
             b'\\' => {
                 if i + 1 < len {
@@ -3791,7 +4587,7 @@ mod tests {
 
     fn get_text<'a>(input: &'a str, event: &BlockEvent) -> &'a str {
         match event {
-            BlockEvent::Text(range) => std::str::from_utf8(range.slice(input.as_bytes())).unwrap(),
+            BlockEvent::Text(range) => core::str::from_utf8(range.slice(input.as_bytes())).unwrap(),
             _ => panic!("Expected Text event"),
         }
     }
@@ -4018,7 +4814,7 @@ mod tests {
 
     fn get_code<'a>(input: &'a str, event: &BlockEvent) -> &'a str {
         match event {
-            BlockEvent::Code(range) => std::str::from_utf8(range.slice(input.as_bytes())).unwrap(),
+            BlockEvent::Code(range) => core::str::from_utf8(range.slice(input.as_bytes())).unwrap(),
             _ => panic!("Expected Code event"),
         }
     }
@@ -4029,7 +4825,7 @@ mod tests {
                 kind: CodeBlockKind::Fenced { info },
             } => info
                 .as_ref()
-                .map(|r| std::str::from_utf8(r.slice(input.as_bytes())).unwrap()),
+                .map(|r| core::str::from_utf8(r.slice(input.as_bytes())).unwrap()),
             _ => panic!("Expected CodeBlockStart event"),
         }
     }
@@ -4189,7 +4985,13 @@ mod tests {
         let input = "> quote";
         let events = parse(input);
 
-        assert_eq!(events[0], BlockEvent::BlockQuoteStart { callout: None });
+        assert_eq!(
+            events[0],
+            BlockEvent::BlockQuoteStart {
+                callout: None,
+                fold: None,
+            }
+        );
         assert_eq!(events[1], BlockEvent::ParagraphStart);
         assert_eq!(get_text(input, &events[2]), "quote");
         assert_eq!(events[3], BlockEvent::ParagraphEnd);
@@ -4201,7 +5003,13 @@ mod tests {
         let input = "> line1\n> line2";
         let events = parse(input);
 
-        assert_eq!(events[0], BlockEvent::BlockQuoteStart { callout: None });
+        assert_eq!(
+            events[0],
+            BlockEvent::BlockQuoteStart {
+                callout: None,
+                fold: None,
+            }
+        );
         assert_eq!(events[1], BlockEvent::ParagraphStart);
         assert_eq!(get_text(input, &events[2]), "line1");
         assert_eq!(events[3], BlockEvent::SoftBreak);
@@ -4214,7 +5022,13 @@ mod tests {
         let events = parse(input);
 
         // > without space is still valid
-        assert_eq!(events[0], BlockEvent::BlockQuoteStart { callout: None });
+        assert_eq!(
+            events[0],
+            BlockEvent::BlockQuoteStart {
+                callout: None,
+                fold: None,
+            }
+        );
     }
 
     #[test]
@@ -4222,8 +5036,20 @@ mod tests {
         let input = "> > nested";
         let events = parse(input);
 
-        assert_eq!(events[0], BlockEvent::BlockQuoteStart { callout: None });
-        assert_eq!(events[1], BlockEvent::BlockQuoteStart { callout: None });
+        assert_eq!(
+            events[0],
+            BlockEvent::BlockQuoteStart {
+                callout: None,
+                fold: None,
+            }
+        );
+        assert_eq!(
+            events[1],
+            BlockEvent::BlockQuoteStart {
+                callout: None,
+                fold: None,
+            }
+        );
         assert!(matches!(events[2], BlockEvent::ParagraphStart));
     }
 
@@ -4261,7 +5087,10 @@ mod tests {
         assert_eq!(
             events,
             vec![
-                BlockEvent::BlockQuoteStart { callout: None },
+                BlockEvent::BlockQuoteStart {
+                    callout: None,
+                    fold: None,
+                },
                 BlockEvent::ParagraphStart,
                 BlockEvent::Text(Range::new(2, 11)),
                 BlockEvent::SoftBreak,
@@ -4294,6 +5123,104 @@ mod tests {
         assert!(matches!(events[8], BlockEvent::ListEnd { .. }));
     }
 
+    #[test]
+    fn test_setext_underline_is_not_lazy_in_blockquote() {
+        // cmark: `===` only continues the paragraph lazily; it cannot turn
+        // the blockquote paragraph into a heading.
+        let input = "> text\n===";
+        let events = parse(input);
+
+        assert_eq!(
+            events,
+            vec![
+                BlockEvent::BlockQuoteStart {
+                    callout: None,
+                    fold: None,
+                },
+                BlockEvent::ParagraphStart,
+                BlockEvent::Text(Range::new(2, 6)),
+                BlockEvent::SoftBreak,
+                BlockEvent::Text(Range::new(7, 10)),
+                BlockEvent::ParagraphEnd,
+                BlockEvent::BlockQuoteEnd,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_start_carries_final_tightness() {
+        let events = parse("- a\n\n- b\n  - c\n  - d\n");
+        let starts: Vec<bool> = events
+            .iter()
+            .filter_map(|event| match event {
+                BlockEvent::ListStart { tight, .. } => Some(*tight),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(starts, [false, true]);
+
+        let mut patched = events.clone();
+        crate::block::fixup_list_tight(&mut patched);
+        assert_eq!(patched, events);
+    }
+
+    #[test]
+    fn test_line_end_skip_keeps_next_line_columns() {
+        // The underline's trailing tabs are skipped without column tracking;
+        // the next line must still see its tab as a full indent.
+        let input = "Title\n===\t \t\n\tcode\n";
+        let events = parse(input);
+
+        assert!(matches!(
+            events[0],
+            BlockEvent::HeadingStart { level: 1, .. }
+        ));
+        assert_eq!(events[1], BlockEvent::Text(Range::new(0, 5)));
+        assert_eq!(events[2], BlockEvent::HeadingEnd { level: 1 });
+        assert!(matches!(events[3], BlockEvent::CodeBlockStart { .. }));
+        assert_eq!(events[4], BlockEvent::Code(Range::new(14, 19)));
+        assert_eq!(events[5], BlockEvent::CodeBlockEnd);
+    }
+
+    #[test]
+    fn test_setext_dash_underline_after_blockquote_is_thematic_break() {
+        let events = parse("> text\n---");
+
+        assert_eq!(
+            events,
+            vec![
+                BlockEvent::BlockQuoteStart {
+                    callout: None,
+                    fold: None,
+                },
+                BlockEvent::ParagraphStart,
+                BlockEvent::Text(Range::new(2, 6)),
+                BlockEvent::ParagraphEnd,
+                BlockEvent::BlockQuoteEnd,
+                BlockEvent::ThematicBreak,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_setext_underline_is_not_lazy_in_list_item() {
+        let input = "- text\n===";
+        let events = parse(input);
+
+        assert!(matches!(events[0], BlockEvent::ListStart { .. }));
+        assert!(matches!(events[1], BlockEvent::ListItemStart { .. }));
+        assert_eq!(events[2], BlockEvent::ParagraphStart);
+        assert_eq!(get_text(input, &events[3]), "text");
+        assert_eq!(events[4], BlockEvent::SoftBreak);
+        assert_eq!(get_text(input, &events[5]), "===");
+        assert_eq!(events[6], BlockEvent::ParagraphEnd);
+        assert!(
+            !events
+                .iter()
+                .any(|e| matches!(e, BlockEvent::HeadingStart { .. }))
+        );
+    }
+
     // List tests
 
     #[test]
@@ -4453,6 +5380,41 @@ mod tests {
         assert!(has_list_end);
     }
 
+    #[test]
+    fn test_list_after_blank_closed_empty_item_stays_balanced() {
+        let kinds = |input: &str| -> Vec<&str> {
+            parse(input)
+                .iter()
+                .filter_map(|event| match event {
+                    BlockEvent::ListStart { .. } => Some("list"),
+                    BlockEvent::ListEnd { .. } => Some("/list"),
+                    BlockEvent::ListItemStart { .. } => Some("item"),
+                    BlockEvent::ListItemEnd => Some("/item"),
+                    _ => None,
+                })
+                .collect()
+        };
+        // A list of another kind starts after the empty item's list
+        assert_eq!(
+            kinds("-\n\n+ x\n"),
+            [
+                "list", "item", "/item", "/list", "list", "item", "/item", "/list"
+            ]
+        );
+        // A nested list ends inside its item
+        let nested = [
+            "list", "item", "list", "item", "/item", "/list", "/item", "item", "/item", "/list",
+        ];
+        assert_eq!(kinds("2. -\n\n3. a\n"), nested);
+        assert_eq!(kinds("- - \n\n- b\n"), nested);
+        assert_eq!(
+            kinds("1. 2.\n\nx\n"),
+            [
+                "list", "item", "list", "item", "/item", "/list", "/item", "/list"
+            ]
+        );
+    }
+
     #[test]
     fn blank_remaining_line_stops_at_first_non_whitespace_byte() {
         assert!(BlockParser::is_blank_remaining_line(b""));
@@ -4467,7 +5429,59 @@ mod tests {
         let input = "> - item";
         let events = parse(input);
 
-        assert_eq!(events[0], BlockEvent::BlockQuoteStart { callout: None });
+        assert_eq!(
+            events[0],
+            BlockEvent::BlockQuoteStart {
+                callout: None,
+                fold: None,
+            }
+        );
         assert!(matches!(events[1], BlockEvent::ListStart { .. }));
     }
+
+    #[test]
+    fn test_fenced_div_events() {
+        let options = Options {
+            fenced_divs: true,
+            ..Options::default()
+        };
+        let input = "::: note
+text
+:::
+";
+        let events = parse_with_options(input, options);
+        let BlockEvent::DivStart { class: Some(class) } = events[0] else {
+            panic!("expected a div with a class: {events:?}");
+        };
+        assert_eq!(class.slice(input.as_bytes()), b"note");
+        assert_eq!(events[1], BlockEvent::ParagraphStart);
+        assert_eq!(events[3], BlockEvent::ParagraphEnd);
+        assert_eq!(events[4], BlockEvent::DivEnd);
+        assert_eq!(events.len(), 5);
+    }
+
+    #[test]
+    fn test_attribute_events() {
+        let options = Options {
+            attributes: true,
+            ..Options::default()
+        };
+        let input = "# Title {#a}\n\n```rust {.b}\n```\n";
+        let events = parse_with_options(input, options);
+        let slice = |event: &BlockEvent| match event {
+            BlockEvent::Attributes(range) | BlockEvent::Text(range) => {
+                range.slice(input.as_bytes())
+            }
+            BlockEvent::CodeBlockStart {
+                kind: CodeBlockKind::Fenced { info: Some(range) },
+            } => range.slice(input.as_bytes()),
+            _ => panic!("expected a range: {event:?}"),
+        };
+        assert_eq!(slice(&events[0]), b"{#a}");
+        assert_eq!(events[1], BlockEvent::HeadingStart { level: 1 });
+        assert_eq!(slice(&events[2]), b"Title");
+        assert_eq!(slice(&events[4]), b"{.b}");
+        assert_eq!(slice(&events[5]), b"rust");
+        assert_eq!(events.len(), 7);
+    }
 }
@@ -0,0 +1,164 @@
+//! Source spans for block events (see [`BlockParser::set_record_spans`]).
+//!
+//! The parser logs two things while it runs: the exact start (or end) of
+//! events whose position is known where they are emitted, and, for every
+//! line, the index of the first event emitted while processing it. Events
+//! without an exact position fall back to the line data: a start begins at
+//! the start of its line, and an end closes after the last non-blank line
+//! consumed before it. A final pass gives each start/end pair the combined
+//! span of the construct.
+//!
+//! [`BlockParser::set_record_spans`]: super::BlockParser::set_record_spans
+
+use super::event::BlockEvent;
+use crate::Range;
+use alloc::vec::Vec;
+
+/// Line boundary recorded before the parser processes a line.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct LineMark {
+    /// Index of the first event emitted for this line.
+    pub first_event: usize,
+    /// Byte offset where the line starts.
+    pub line_start: usize,
+    /// End of the last non-blank line consumed before this one.
+    pub content_end: usize,
+}
+
+/// Whether an event opens or closes a construct.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) enum Boundary {
+    Start,
+    End,
+    Leaf,
+}
+
+pub(super) fn boundary(event: &BlockEvent) -> Boundary {
+    match event {
+        BlockEvent::ParagraphStart
+        | BlockEvent::HeadingStart { .. }
+        | BlockEvent::CodeBlockStart { .. }
+        | BlockEvent::BlockQuoteStart { .. }
+        | BlockEvent::DivStart { .. }
+        | BlockEvent::ListStart { .. }
+        | BlockEvent::ListItemStart { .. }
+        | BlockEvent::HtmlBlockStart
+        | BlockEvent::TableStart
+        | BlockEvent::TableHeadStart
+        | BlockEvent::TableBodyStart
+        | BlockEvent::TableRowStart
+        | BlockEvent::TableCellStart { .. } => Boundary::Start,
+        BlockEvent::ParagraphEnd
+        | BlockEvent::HeadingEnd { .. }
+        | BlockEvent::CodeBlockEnd
+        | BlockEvent::BlockQuoteEnd
+        | BlockEvent::DivEnd
+        | BlockEvent::ListEnd { .. }
+        | BlockEvent::ListItemEnd
+        | BlockEvent::HtmlBlockEnd
+        | BlockEvent::TableEnd
+        | BlockEvent::TableHeadEnd
+        | BlockEvent::TableBodyEnd
+        | BlockEvent::TableRowEnd
+        | BlockEvent::TableCellEnd => Boundary::End,
+        BlockEvent::ThematicBreak
+        | BlockEvent::HtmlBlockText(_)
+        | BlockEvent::SoftBreak
+        | BlockEvent::Text(_)
+        | BlockEvent::Code(_)
+        | BlockEvent::CalloutTitle(_)
+        | BlockEvent::BlockQuoteCite(_)
+        | BlockEvent::Attributes(_)
+        | BlockEvent::VirtualSpaces(_) => Boundary::Leaf,
+    }
+}
+
+/// Build one span per event into `spans`.
+///
+/// `exact` holds `(event index, span)` pairs in event order; `lines` holds
+/// one mark per processed line, also in event order.
+pub(super) fn resolve_spans(
+    events: &[BlockEvent],
+    exact: &[(usize, Range)],
+    lines: &[LineMark],
+    spans: &mut Vec<Range>,
+) {
+    spans.clear();
+    spans.reserve(events.len());
+
+    let mut exact_idx = 0usize;
+    let mut line_idx = 0usize;
+    for (i, event) in events.iter().enumerate() {
+        while line_idx + 1 < lines.len() && lines[line_idx + 1].first_event <= i {
+            line_idx += 1;
+        }
+        while exact_idx < exact.len() && exact[exact_idx].0 < i {
+            exact_idx += 1;
+        }
+        let exact_span = exact
+            .get(exact_idx)
+            .filter(|(index, _)| *index == i)
+            .map(|(_, span)| *span);
+        let line = lines.get(line_idx).copied();
+
+        let span = match (event, exact_span, line) {
+            (
+                BlockEvent::Text(range)
+                | BlockEvent::Code(range)
+                | BlockEvent::HtmlBlockText(range),
+                _,
+                _,
+            ) => *range,
+            (_, Some(span), _) => span,
+            (_, None, Some(line)) if boundary(event) == Boundary::Start => {
+                Range::empty_at(line.line_start as u32)
+            }
+            (_, None, Some(line)) if boundary(event) == Boundary::End => {
+                Range::empty_at(line.content_end as u32)
+            }
+            _ => Range::empty_at(spans.last().map_or(0, |span| span.end)),
+        };
+        spans.push(span);
+    }
+
+    // Breaks and virtual spaces sit between their neighbors
+    for i in 0..events.len() {
+        match events[i] {
+            BlockEvent::SoftBreak => {
+                let start = if i > 0 { spans[i - 1].end } else { 0 };
+                let end = match events.get(i + 1) {
+                    Some(BlockEvent::Text(range)) => range.start.max(start),
+                    _ => start,
+                };
+                spans[i] = Range::new(start, end);
+            }
+            BlockEvent::VirtualSpaces(_) => {
+                let at = spans.get(i + 1).map_or(spans[i].start, |next| next.start);
+                spans[i] = Range::empty_at(at);
+            }
+            _ => {}
+        }
+    }
+
+    // Give each start/end pair the span of the whole construct
+    let mut open: Vec<usize> = Vec::new();
+    for i in 0..events.len() {
+        match boundary(&events[i]) {
+            Boundary::Start => open.push(i),
+            Boundary::End => {
+                let Some(start_idx) = open.pop() else {
+                    continue;
+                };
+                let start = spans[start_idx].start;
+                let mut end = spans[i].end.max(spans[start_idx].end).max(start);
+                if i > start_idx + 1 {
+                    end = end.max(spans[i - 1].end);
+                }
+                let span = Range::new(start, end);
+                spans[start_idx] = span;
+                spans[i] = span;
+            }
+            Boundary::Leaf => {}
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Least-recently-used cache of rendered HTML.
+
+use crate::{Options, to_html_with_options};
+use alloc::{string::String, vec::Vec};
+use core::hash::BuildHasher;
+use hashbrown::HashMap;
+use rustc_hash::FxBuildHasher as FastHashBuilder;
+
+const NIL: usize = usize::MAX;
+
+struct Entry {
+    key: u64,
+    input: String,
+    options: Options,
+    html: String,
+    /// Neighbor toward the most recently used entry.
+    prev: usize,
+    /// Neighbor toward the least recently used entry.
+    next: usize,
+}
+
+/// Renderer that caches HTML for repeated `(input, options)` pairs.
+///
+/// Entries are keyed by a hash of the input and the options. A hit is
+/// confirmed against the stored input and options, so a hash collision
+/// re-renders instead of returning the wrong HTML. Once `capacity` entries
+/// are cached, the least recently used one is evicted.
+///
+/// ```
+/// use ferromark::{CachedRenderer, Options};
+///
+/// let mut renderer = CachedRenderer::new(64);
+/// let first = renderer.render("*shared*", &Options::default()).to_owned();
+/// assert_eq!(renderer.render("*shared*", &Options::default()), first);
+/// assert_eq!((renderer.hits(), renderer.misses()), (1, 1));
+/// ```
+pub struct CachedRenderer {
+    capacity: usize,
+    entries: Vec<Entry>,
+    by_key: HashMap<u64, usize, FastHashBuilder>,
+    /// Most recently used entry.
+    head: usize,
+    /// Least recently used entry, evicted first.
+    tail: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl CachedRenderer {
+    /// Create a cache holding up to `capacity` rendered documents. A capacity
+    /// of zero is treated as one.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            entries: Vec::new(),
+            by_key: HashMap::with_capacity_and_hasher(capacity, FastHashBuilder),
+            head: NIL,
+            tail: NIL,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Render `input` with `options`, reusing cached HTML when the same pair
+    /// was rendered before.
+    pub fn render(&mut self, input: &str, options: &Options) -> &str {
+        let key = FastHashBuilder.hash_one((input, options));
+        let cached = self.by_key.get(&key).copied();
+        if let Some(index) = cached {
+            let entry = &self.entries[index];
+            if entry.input == input && entry.options == *options {
+                self.hits += 1;
+                self.touch(index);
+                return &self.entries[index].html;
+            }
+        }
+
+        self.misses += 1;
+        let html = to_html_with_options(input, options);
+        let index = match cached {
+            // Hash collision: the new pair takes over the slot
+            Some(index) => {
+                self.unlink(index);
+                index
+            }
+            None if self.entries.len() < self.capacity => {
+                self.entries.push(Entry {
+                    key,
+                    input: String::new(),
+                    options: *options,
+                    html: String::new(),
+                    prev: NIL,
+                    next: NIL,
+                });
+                self.entries.len() - 1
+            }
+            None => {
+                let index = self.tail;
+                self.unlink(index);
+                self.by_key.remove(&self.entries[index].key);
+                index
+            }
+        };
+
+        let entry = &mut self.entries[index];
+        entry.key = key;
+        entry.input.clear();
+        entry.input.push_str(input);
+        entry.options = *options;
+        entry.html = html;
+        self.by_key.insert(key, index);
+        self.push_front(index);
+        &self.entries[index].html
+    }
+
+    /// Number of renders answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of renders that had to parse the input.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Number of cached documents.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Maximum number of cached documents.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Drop every cached document. Hit and miss counters are kept.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.by_key.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+
+    /// Mark `index` as the most recently used entry.
+    fn touch(&mut self, index: usize) {
+        if self.head != index {
+            self.unlink(index);
+            self.push_front(index);
+        }
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let (prev, next) = (self.entries[index].prev, self.entries[index].next);
+        match prev {
+            NIL => self.head = next,
+            prev => self.entries[prev].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.entries[next].prev = prev,
+        }
+    }
+
+    fn push_front(&mut self, index: usize) {
+        self.entries[index].prev = NIL;
+        self.entries[index].next = self.head;
+        match self.head {
+            NIL => self.tail = index,
+            head => self.entries[head].prev = index,
+        }
+        self.head = index;
+    }
+}
+
+impl core::fmt::Debug for CachedRenderer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CachedRenderer")
+            .field("capacity", &self.capacity)
+            .field("len", &self.entries.len())
+            .field("hits", &self.hits)
+            .field("misses", &self.misses)
+            .finish()
+    }
+}
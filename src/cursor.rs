@@ -26,7 +26,7 @@ pub struct Cursor<'a> {
     ptr: *const u8,
     end: *const u8,
     base: *const u8,
-    _marker: std::marker::PhantomData<&'a [u8]>,
+    _marker: core::marker::PhantomData<&'a [u8]>,
 }
 
 impl<'a> Cursor<'a> {
@@ -39,7 +39,7 @@ impl<'a> Cursor<'a> {
             ptr,
             end,
             base: ptr,
-            _marker: std::marker::PhantomData,
+            _marker: core::marker::PhantomData,
         }
     }
 
@@ -62,7 +62,7 @@ impl<'a> Cursor<'a> {
             ptr,
             end,
             base,
-            _marker: std::marker::PhantomData,
+            _marker: core::marker::PhantomData,
         }
     }
 
@@ -131,6 +131,22 @@ impl<'a> Cursor<'a> {
         }
     }
 
+    /// Peek at up to n bytes from the current position.
+    ///
+    /// Returns fewer than n bytes near the end of input.
+    #[inline]
+    pub fn peek_slice(&self, n: usize) -> &'a [u8] {
+        let len = n.min(self.remaining());
+        // SAFETY: len <= remaining
+        unsafe { core::slice::from_raw_parts(self.ptr, len) }
+    }
+
+    /// Check whether the remaining input starts with `bytes`.
+    #[inline]
+    pub fn starts_with(&self, bytes: &[u8]) -> bool {
+        self.peek_slice(bytes.len()) == bytes
+    }
+
     /// Advance by n bytes.
     ///
     /// # Panics
@@ -261,12 +277,7 @@ impl<'a> Cursor<'a> {
     /// Consume a specific byte sequence if present.
     #[inline]
     pub fn eat_bytes(&mut self, bytes: &[u8]) -> bool {
-        if self.remaining() < bytes.len() {
-            return false;
-        }
-        // SAFETY: remaining >= bytes.len()
-        let slice = unsafe { std::slice::from_raw_parts(self.ptr, bytes.len()) };
-        if slice == bytes {
+        if self.starts_with(bytes) {
             // SAFETY: The remaining-length check above covers `bytes.len()`.
             unsafe { self.advance_unchecked(bytes.len()) };
             true
@@ -285,7 +296,7 @@ impl<'a> Cursor<'a> {
     #[inline]
     pub fn remaining_slice(&self) -> &'a [u8] {
         // SAFETY: ptr and end are valid pointers from the same allocation
-        unsafe { std::slice::from_raw_parts(self.ptr, self.remaining()) }
+        unsafe { core::slice::from_raw_parts(self.ptr, self.remaining()) }
     }
 
     /// Find the next occurrence of a byte using memchr.
@@ -323,8 +334,8 @@ impl<'a> Cursor<'a> {
     }
 }
 
-impl std::fmt::Debug for Cursor<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for Cursor<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Cursor")
             .field("offset", &self.offset())
             .field("remaining", &self.remaining())
@@ -364,6 +375,38 @@ mod tests {
         assert_eq!(cursor.peek_ahead(3), None);
     }
 
+    #[test]
+    fn test_cursor_peek_slice() {
+        let mut cursor = Cursor::new(b"abc");
+        assert_eq!(cursor.peek_slice(0), b"");
+        assert_eq!(cursor.peek_slice(2), b"ab");
+        assert_eq!(cursor.peek_slice(3), b"abc");
+        assert_eq!(cursor.peek_slice(10), b"abc");
+
+        cursor.advance(2);
+        assert_eq!(cursor.peek_slice(2), b"c");
+        cursor.advance(1);
+        assert_eq!(cursor.peek_slice(1), b"");
+        assert_eq!(cursor.offset(), 3);
+    }
+
+    #[test]
+    fn test_cursor_starts_with() {
+        let mut cursor = Cursor::new(b"<!-- x");
+        assert!(cursor.starts_with(b""));
+        assert!(cursor.starts_with(b"<!--"));
+        assert!(cursor.starts_with(b"<!-- x"));
+        assert!(!cursor.starts_with(b"<!-- x "));
+        assert!(!cursor.starts_with(b"<?"));
+
+        cursor.advance(5);
+        assert!(cursor.starts_with(b"x"));
+        assert!(!cursor.starts_with(b"xy"));
+        cursor.advance(1);
+        assert!(cursor.starts_with(b""));
+        assert!(!cursor.starts_with(b"x"));
+    }
+
     #[test]
     fn test_cursor_advance() {
         let mut cursor = Cursor::new(b"Hello");
@@ -2,7 +2,15 @@
 //!
 //! Fast-path optimized: scans for first escapable character,
 //! then bulk-copies segments between escapes.
+//!
+//! Text escapes `<`, `>`, `&` and `"` everywhere, matching the CommonMark
+//! reference output; attributes escape `'` as well.
+//!
+//! Custom renderers built on the event stream can use [`escape_html_text`],
+//! [`escape_html_attr`], [`url_encode`] and [`link_url_encode`] to write text,
+//! attributes and URLs exactly as [`HtmlWriter`](crate::HtmlWriter) does.
 
+use alloc::{string::String, vec::Vec};
 use memchr::{memchr, memchr2, memchr3};
 
 /// Characters that need escaping in HTML text content.
@@ -50,22 +58,13 @@ const ATTR_ESCAPE_TABLE: [bool; 256] = {
 /// ```
 #[inline]
 pub fn escape_text_into(out: &mut Vec<u8>, input: &[u8]) {
-    if input.is_empty() {
-        return;
-    }
-
-    let mut start = 0usize;
-    while let Some(rel) = first_text_escape(&input[start..]) {
-        let pos = start + rel;
-        if pos > start {
-            out.extend_from_slice(&input[start..pos]);
-        }
-        push_text_escape(out, input[pos]);
-        start = pos + 1;
-    }
-    if start < input.len() {
-        out.extend_from_slice(&input[start..]);
-    }
+    escape_split_into(
+        out,
+        input,
+        |rest| memchr3(b'<', b'>', b'&', rest),
+        |rest| memchr(b'"', rest),
+        push_text_escape,
+    );
 }
 
 /// Escape HTML text content, checking for quotes as well (for attribute context).
@@ -73,18 +72,53 @@ pub fn escape_text_into(out: &mut Vec<u8>, input: &[u8]) {
 /// This version handles all 5 escapable characters.
 #[inline]
 pub fn escape_full_into(out: &mut Vec<u8>, input: &[u8]) {
+    escape_split_into(
+        out,
+        input,
+        |rest| memchr3(b'<', b'>', b'&', rest),
+        |rest| memchr2(b'"', b'\'', rest),
+        push_attr_escape,
+    );
+}
+
+/// Copy `input` to `out`, escaping every byte found by either search.
+///
+/// Each search position is cached until it is consumed, so input that is
+/// dense in one class of escapable bytes and free of the other stays linear
+/// instead of rescanning the remainder for every escape.
+#[inline]
+fn escape_split_into(
+    out: &mut Vec<u8>,
+    input: &[u8],
+    find_a: impl Fn(&[u8]) -> Option<usize>,
+    find_b: impl Fn(&[u8]) -> Option<usize>,
+    push_escape: fn(&mut Vec<u8>, u8),
+) {
     if input.is_empty() {
         return;
     }
 
+    let mut next_a = find_a(input);
+    let mut next_b = find_b(input);
     let mut start = 0usize;
-    while let Some(rel) = first_attr_escape(&input[start..]) {
-        let pos = start + rel;
+    loop {
+        let pos = match (next_a, next_b) {
+            (Some(a), Some(b)) => a.min(b),
+            (Some(a), None) => a,
+            (None, Some(b)) => b,
+            (None, None) => break,
+        };
         if pos > start {
             out.extend_from_slice(&input[start..pos]);
         }
-        push_attr_escape(out, input[pos]);
+        push_escape(out, input[pos]);
         start = pos + 1;
+        let rest = &input[start..];
+        if next_a == Some(pos) {
+            next_a = find_a(rest).map(|i| start + i);
+        } else {
+            next_b = find_b(rest).map(|i| start + i);
+        }
     }
     if start < input.len() {
         out.extend_from_slice(&input[start..]);
@@ -120,30 +154,6 @@ pub fn needs_attr_escape(input: &[u8]) -> bool {
     input.iter().any(|&b| ATTR_ESCAPE_TABLE[b as usize])
 }
 
-#[inline]
-fn first_text_escape(input: &[u8]) -> Option<usize> {
-    let a = memchr3(b'<', b'>', b'&', input);
-    let b = memchr(b'"', input);
-    min_opt(a, b)
-}
-
-#[inline]
-fn first_attr_escape(input: &[u8]) -> Option<usize> {
-    let a = memchr3(b'<', b'>', b'&', input);
-    let b = memchr2(b'"', b'\'', input);
-    min_opt(a, b)
-}
-
-#[inline]
-fn min_opt(a: Option<usize>, b: Option<usize>) -> Option<usize> {
-    match (a, b) {
-        (Some(a), Some(b)) => Some(a.min(b)),
-        (Some(a), None) => Some(a),
-        (None, Some(b)) => Some(b),
-        (None, None) => None,
-    }
-}
-
 /// Escape and return as a new Vec.
 ///
 /// Prefer `escape_text_into` to reuse buffers.
@@ -163,63 +173,17 @@ pub fn escape_text_to_string(input: &str) -> String {
     unsafe { String::from_utf8_unchecked(escaped) }
 }
 
-/// URL percent-encode special characters, then HTML-escape for href attribute.
-/// This is specifically for autolink URLs per CommonMark spec.
-///
-/// Check if a character is ASCII punctuation (can be backslash-escaped in URLs)
-#[inline]
-fn is_ascii_punctuation(b: u8) -> bool {
-    matches!(
-        b,
-        b'!' | b'"'
-            | b'#'
-            | b'$'
-            | b'%'
-            | b'&'
-            | b'\''
-            | b'('
-            | b')'
-            | b'*'
-            | b'+'
-            | b','
-            | b'-'
-            | b'.'
-            | b'/'
-            | b':'
-            | b';'
-            | b'<'
-            | b'='
-            | b'>'
-            | b'?'
-            | b'@'
-            | b'['
-            | b'\\'
-            | b']'
-            | b'^'
-            | b'_'
-            | b'`'
-            | b'{'
-            | b'|'
-            | b'}'
-            | b'~'
-    )
-}
-
 /// Process a link URL: decode entities, handle backslash escapes, and percent-encode.
 /// This is used for link destinations in `[text](url)` syntax.
 #[inline]
 pub fn url_escape_link_destination(out: &mut Vec<u8>, input: &[u8]) {
-    if memchr(b'&', input).is_none() {
+    if memchr2(b'&', b'\\', input).is_none() {
         url_escape_link_destination_raw(out, input);
         return;
     }
 
-    // First decode HTML entities
-    let input_str = core::str::from_utf8(input).unwrap_or("");
-    let decoded = html_escape::decode_html_entities(input_str);
-    let decoded_bytes = decoded.as_bytes();
-
-    url_escape_link_destination_raw(out, decoded_bytes);
+    let decoded = crate::render::decode_link_text(input);
+    url_escape_link_destination_raw(out, decoded.as_bytes());
 }
 
 #[inline]
@@ -245,7 +209,8 @@ fn push_attr_escape(out: &mut Vec<u8>, b: u8) {
     }
 }
 
-/// Process a link URL without entity decoding (used after entities are already decoded).
+/// Percent-encode and HTML-escape a link URL whose escapes and entities are
+/// already resolved.
 #[inline]
 fn url_escape_link_destination_raw(out: &mut Vec<u8>, input: &[u8]) {
     const HEX: &[u8; 16] = b"0123456789ABCDEF";
@@ -262,29 +227,7 @@ fn url_escape_link_destination_raw(out: &mut Vec<u8>, input: &[u8]) {
         return;
     }
 
-    let mut pos = 0;
-    while pos < input.len() {
-        let b = input[pos];
-
-        // Handle backslash escapes: \X where X is ASCII punctuation
-        if b == b'\\' && pos + 1 < input.len() && is_ascii_punctuation(input[pos + 1]) {
-            // Skip the backslash, encode the escaped character
-            pos += 1;
-            let escaped = input[pos];
-            // The escaped character still needs HTML attribute escaping
-            match escaped {
-                b'<' => out.extend_from_slice(b"&lt;"),
-                b'>' => out.extend_from_slice(b"&gt;"),
-                b'&' => out.extend_from_slice(b"&amp;"),
-                b'"' => out.extend_from_slice(b"%22"),
-                b'\'' => out.extend_from_slice(b"&#39;"),
-                _ => out.push(escaped),
-            }
-            pos += 1;
-            continue;
-        }
-
-        // Handle characters that need encoding
+    for &b in input {
         match b {
             // Characters that need URL percent-encoding
             b'\\' => out.extend_from_slice(b"%5C"),
@@ -310,7 +253,6 @@ fn url_escape_link_destination_raw(out: &mut Vec<u8>, input: &[u8]) {
             // Everything else passes through
             _ => out.push(b),
         }
-        pos += 1;
     }
 }
 
@@ -351,6 +293,81 @@ pub fn url_encode_then_html_escape(out: &mut Vec<u8>, input: &[u8]) {
     }
 }
 
+/// Append `input` to `out` with `escape`, replacing invalid UTF-8 first.
+///
+/// Every escape function only replaces ASCII bytes (or percent-encodes whole
+/// multi-byte characters), so valid UTF-8 stays valid.
+fn escape_into_string(out: &mut String, input: &[u8], escape: fn(&mut Vec<u8>, &[u8])) {
+    let input = String::from_utf8_lossy(input);
+    // SAFETY: see above; `input` is valid UTF-8 and escaping keeps it so
+    escape(unsafe { out.as_mut_vec() }, input.as_bytes());
+}
+
+/// Escape HTML text content into a string, like
+/// [`HtmlWriter::write_escaped_text`](crate::HtmlWriter::write_escaped_text).
+///
+/// Invalid UTF-8 in `input` is replaced with U+FFFD.
+///
+/// # Example
+/// ```
+/// use ferromark::escape::escape_html_text;
+///
+/// let mut out = String::new();
+/// escape_html_text(b"a < \"b\" & c", &mut out);
+/// assert_eq!(out, "a &lt; &quot;b&quot; &amp; c");
+/// ```
+pub fn escape_html_text(input: &[u8], out: &mut String) {
+    escape_into_string(out, input, escape_text_into);
+}
+
+/// Escape an HTML attribute value into a string, like
+/// [`HtmlWriter::write_escaped_attr`](crate::HtmlWriter::write_escaped_attr).
+///
+/// Invalid UTF-8 in `input` is replaced with U+FFFD.
+///
+/// # Example
+/// ```
+/// use ferromark::escape::escape_html_attr;
+///
+/// let mut out = String::new();
+/// escape_html_attr(b"it's \"quoted\"", &mut out);
+/// assert_eq!(out, "it&#39;s &quot;quoted&quot;");
+/// ```
+pub fn escape_html_attr(input: &[u8], out: &mut String) {
+    escape_into_string(out, input, escape_full_into);
+}
+
+/// Percent-encode and HTML-escape an autolink URL into a string, like
+/// [`HtmlWriter::write_url_encoded`](crate::HtmlWriter::write_url_encoded).
+///
+/// # Example
+/// ```
+/// use ferromark::escape::url_encode;
+///
+/// let mut out = String::new();
+/// url_encode("https://example.com/a b?x=[1]&y=ä".as_bytes(), &mut out);
+/// assert_eq!(out, "https://example.com/a%20b?x=%5B1%5D&amp;y=%C3%A4");
+/// ```
+pub fn url_encode(input: &[u8], out: &mut String) {
+    escape_into_string(out, input, url_encode_then_html_escape);
+}
+
+/// Resolve backslash escapes and entities in a link destination, then
+/// percent-encode and HTML-escape it into a string, like
+/// [`HtmlWriter::write_link_url`](crate::HtmlWriter::write_link_url).
+///
+/// # Example
+/// ```
+/// use ferromark::escape::link_url_encode;
+///
+/// let mut out = String::new();
+/// link_url_encode(br#"/a\*b?x=1&amp;y="2""#, &mut out);
+/// assert_eq!(out, "/a*b?x=1&amp;y=%222%22");
+/// ```
+pub fn link_url_encode(input: &[u8], out: &mut String) {
+    escape_into_string(out, input, url_escape_link_destination);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -457,6 +474,17 @@ mod tests {
         assert_eq!(result, "&lt;script&gt;");
     }
 
+    #[test]
+    fn test_string_helpers_append_and_replace_invalid_utf8() {
+        let mut out = String::from("x=");
+        escape_html_attr(b"<\xff>", &mut out);
+        assert_eq!(out, "x=&lt;\u{fffd}&gt;");
+
+        out.clear();
+        url_encode(b"\xff", &mut out);
+        assert_eq!(out, "%EF%BF%BD");
+    }
+
     #[test]
     fn test_escape_unicode() {
         let mut out = Vec::new();
@@ -1,8 +1,9 @@
 //! Footnote definitions storage.
 
 use crate::block::BlockEvent;
+use alloc::{string::String, vec::Vec};
+use hashbrown::HashMap;
 use rustc_hash::FxBuildHasher as FastHashBuilder;
-use std::collections::HashMap;
 
 /// A footnote definition (stores captured block events).
 #[derive(Debug, Clone)]
@@ -50,6 +51,28 @@ impl FootnoteStore {
     pub fn len(&self) -> usize {
         self.defs.len()
     }
+
+    /// Add every definition from `other` whose label is new, passing each
+    /// captured event through `map`.
+    #[cfg(feature = "mdx")]
+    pub(crate) fn merge_first_wins(
+        &mut self,
+        other: Self,
+        mut map: impl FnMut(BlockEvent) -> BlockEvent,
+    ) {
+        let mut labels = vec![None; other.defs.len()];
+        for (label, index) in other.by_label {
+            labels[index] = Some(label);
+        }
+
+        for (index, definition) in other.defs.into_iter().enumerate() {
+            let normalized = labels[index]
+                .take()
+                .expect("every footnote definition must have a label");
+            let events = definition.events.into_iter().map(&mut map).collect();
+            self.insert(normalized, definition.label, events);
+        }
+    }
 }
 
 /// Normalize a footnote label: trim, lowercase ASCII.
@@ -15,7 +15,8 @@ pub fn resolve_code_spans(marks: &mut [Mark], text: &[u8], html_spans: &[(u32, u
     let len = marks.len();
 
     for i in 0..len {
-        if marks[i].ch != b'`' || marks[i].is_resolved() {
+        // Backticks inside an earlier span are content, not openers
+        if marks[i].ch != b'`' || marks[i].is_resolved() || marks[i].flags & flags::IN_CODE != 0 {
             continue;
         }
 
@@ -80,7 +81,7 @@ pub fn resolve_code_spans(marks: &mut [Mark], text: &[u8], html_spans: &[(u32, u
 }
 
 #[inline]
-fn pos_in_spans(pos: u32, spans: &[(u32, u32)]) -> bool {
+pub(super) fn pos_in_spans(pos: u32, spans: &[(u32, u32)]) -> bool {
     spans.iter().any(|&(start, end)| pos >= start && pos < end)
 }
 
@@ -88,7 +89,7 @@ fn pos_in_spans(pos: u32, spans: &[(u32, u32)]) -> bool {
 /// Returns iterator of (opener_pos, closer_end, content_start, content_end).
 pub fn extract_code_spans(marks: &[Mark]) -> impl Iterator<Item = CodeSpan> + '_ {
     let mut i = 0;
-    std::iter::from_fn(move || {
+    core::iter::from_fn(move || {
         while i < marks.len() {
             let mark = &marks[i];
             if mark.ch == b'`' && mark.is_resolved() {
@@ -162,6 +163,18 @@ mod tests {
         assert_eq!(spans.len(), 1);
     }
 
+    #[test]
+    fn test_backtick_content_does_not_open_span() {
+        let text = b"`` ` `` `a`";
+        let mut buffer = MarkBuffer::new();
+        collect_marks(text, &mut buffer);
+        resolve_code_spans(buffer.marks_mut(), text, &[]);
+
+        let spans: Vec<_> = extract_code_spans(buffer.marks()).collect();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[1].content_range(), (9, 10)); // "a"
+    }
+
     #[test]
     fn test_unmatched_backticks() {
         let text = b"hello `code`` world";
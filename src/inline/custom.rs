@@ -0,0 +1,204 @@
+//! User-registered inline delimiters (see [`Options::custom_delimiters`]).
+//!
+//! Custom delimiters are resolved last, directly on the text: every range
+//! already claimed by a built-in construct (code spans, links, HTML, emphasis
+//! and its extensions, ...) is excluded, so built-ins always win. A custom
+//! pair must nest cleanly with built-in pairs and with pairs of delimiters
+//! registered earlier.
+//!
+//! [`Options::custom_delimiters`]: crate::Options::custom_delimiters
+
+use alloc::vec::Vec;
+use memchr::memmem;
+
+/// An inline delimiter pair rendered as an HTML element.
+///
+/// `open` and `close` may be equal (`^text^`); such a delimiter only opens
+/// before non-whitespace and only closes after non-whitespace.
+///
+/// # Example
+/// ```
+/// use ferromark::{CustomDelimiter, Options, to_html_with_options};
+///
+/// const INSERT: &[CustomDelimiter] = &[CustomDelimiter { open: "++", close: "++", tag: "ins" }];
+/// let options = Options { custom_delimiters: INSERT, ..Options::default() };
+/// assert_eq!(to_html_with_options("++new++", &options), "<p><ins>new</ins></p>\n");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CustomDelimiter {
+    /// Opening delimiter.
+    pub open: &'static str,
+    /// Closing delimiter.
+    pub close: &'static str,
+    /// HTML element name written around the content, without angle brackets.
+    pub tag: &'static str,
+}
+
+/// A matched custom delimiter pair.
+#[derive(Debug, Clone, Copy)]
+pub struct CustomMatch {
+    /// Index of the delimiter in the registry.
+    pub index: u8,
+    pub opener_start: u32,
+    pub opener_end: u32,
+    pub closer_start: u32,
+    pub closer_end: u32,
+}
+
+/// Resolve custom delimiters in registry order.
+///
+/// `excluded` holds ranges no delimiter may overlap; `pairs` holds the
+/// `(start, end)` extent of built-in constructs a match must not cross.
+/// Both are extended with each accepted match.
+pub fn resolve_custom_delimiters_into(
+    text: &[u8],
+    delimiters: &[CustomDelimiter],
+    excluded: &mut Vec<(u32, u32)>,
+    pairs: &mut Vec<(u32, u32)>,
+    openers: &mut Vec<usize>,
+    matches: &mut Vec<CustomMatch>,
+) {
+    matches.clear();
+    for (index, delimiter) in delimiters.iter().take(usize::from(u8::MAX) + 1).enumerate() {
+        let open = delimiter.open.as_bytes();
+        let close = delimiter.close.as_bytes();
+        if open.is_empty() || close.is_empty() {
+            continue;
+        }
+        let symmetric = open == close;
+        let first_new = matches.len();
+        openers.clear();
+
+        let mut next_open = memmem::find(text, open);
+        let mut next_close = if symmetric {
+            None
+        } else {
+            memmem::find(text, close)
+        };
+        let mut pos = 0usize;
+
+        loop {
+            if next_open.is_some_and(|at| at < pos) {
+                next_open = memmem::find(&text[pos..], open).map(|i| pos + i);
+            }
+            if next_close.is_some_and(|at| at < pos) {
+                next_close = memmem::find(&text[pos..], close).map(|i| pos + i);
+            }
+            // A closer wins a tie only while something is open
+            let (at, is_close) = match (next_open, next_close) {
+                (Some(o), Some(c)) if c < o || (c == o && !openers.is_empty()) => (c, true),
+                (Some(o), _) => (o, false),
+                (None, Some(c)) => (c, true),
+                (None, None) => break,
+            };
+            let token_len = if is_close { close.len() } else { open.len() };
+            let end = at + token_len;
+            if overlaps(at, end, excluded) {
+                pos = at + 1;
+                continue;
+            }
+
+            let can_close = !openers.is_empty()
+                && (is_close || (symmetric && at > 0 && !text[at - 1].is_ascii_whitespace()));
+            let can_open = !is_close
+                && (!symmetric || text.get(end).is_some_and(|b| !b.is_ascii_whitespace()));
+
+            let opener = openers.last().copied().filter(|&start| {
+                can_close && start + open.len() < at && !crosses(start, at, pairs)
+            });
+            if let Some(start) = opener {
+                openers.pop();
+                matches.push(CustomMatch {
+                    index: index as u8,
+                    opener_start: start as u32,
+                    opener_end: (start + open.len()) as u32,
+                    closer_start: at as u32,
+                    closer_end: end as u32,
+                });
+                pos = end;
+            } else if can_open {
+                openers.push(at);
+                pos = end;
+            } else {
+                pos = at + 1;
+            }
+        }
+
+        for m in &matches[first_new..] {
+            excluded.push((m.opener_start, m.opener_end));
+            excluded.push((m.closer_start, m.closer_end));
+            pairs.push((m.opener_start, m.closer_end));
+        }
+    }
+}
+
+fn overlaps(start: usize, end: usize, ranges: &[(u32, u32)]) -> bool {
+    ranges
+        .iter()
+        .any(|&(s, e)| start < e as usize && end > s as usize)
+}
+
+/// Whether an opener at `open` and a closer at `close` straddle a boundary
+/// of any pair.
+fn crosses(open: usize, close: usize, pairs: &[(u32, u32)]) -> bool {
+    pairs.iter().any(|&(s, e)| {
+        let inside = |pos: usize| pos >= s as usize && pos < e as usize;
+        inside(open) != inside(close)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUP: CustomDelimiter = CustomDelimiter {
+        open: "^",
+        close: "^",
+        tag: "sup",
+    };
+
+    fn resolve(text: &str, delimiters: &[CustomDelimiter]) -> Vec<(u8, u32, u32)> {
+        let mut matches = Vec::new();
+        resolve_custom_delimiters_into(
+            text.as_bytes(),
+            delimiters,
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut matches,
+        );
+        matches
+            .iter()
+            .map(|m| (m.index, m.opener_start, m.closer_end))
+            .collect()
+    }
+
+    #[test]
+    fn symmetric_delimiters_need_flanking_content() {
+        assert_eq!(resolve("x^2^ and ^ 3^", &[SUP]), vec![(0, 1, 4)]);
+        assert_eq!(resolve("^^", &[SUP]), vec![]);
+    }
+
+    #[test]
+    fn asymmetric_delimiters_nest() {
+        let ins = CustomDelimiter {
+            open: "{+",
+            close: "+}",
+            tag: "ins",
+        };
+        assert_eq!(
+            resolve("{+a {+b+} c+}", &[ins]),
+            vec![(0, 4, 9), (0, 0, 13)]
+        );
+    }
+
+    #[test]
+    fn later_delimiters_cannot_cross_earlier_ones() {
+        let plus = CustomDelimiter {
+            open: "+",
+            close: "+",
+            tag: "ins",
+        };
+        assert_eq!(resolve("^a +b^ c+", &[SUP, plus]), vec![(0, 0, 6)]);
+    }
+}
@@ -0,0 +1,617 @@
+//! Emoji shortcode expansion (`:smile:`).
+//!
+//! Runs after inline resolution over the emitted text events, so code spans,
+//! autolinks, link destinations, and raw HTML never reach the scanner.
+
+use alloc::vec::Vec;
+use memchr::memchr;
+
+use super::InlineEvent;
+use crate::Range;
+
+/// Look up the Unicode emoji for a shortcode (without the colons).
+pub fn lookup(shortcode: &[u8]) -> Option<&'static str> {
+    SHORTCODES
+        .binary_search_by(|(name, _)| name.as_bytes().cmp(shortcode))
+        .ok()
+        .map(|index| SHORTCODES[index].1)
+}
+
+/// First shortcode (in table order) that expands to `glyph`.
+pub fn shortcode(glyph: &str) -> Option<&'static str> {
+    SHORTCODES
+        .iter()
+        .find(|(_, emoji)| *emoji == glyph)
+        .map(|(name, _)| *name)
+}
+
+/// Quick check for a possible `:shortcode:` pair.
+#[inline]
+pub fn has_emoji_candidates(text: &[u8]) -> bool {
+    memchr(b':', text).is_some_and(|first| memchr(b':', &text[first + 1..]).is_some())
+}
+
+/// Replace known shortcodes in the text events emitted since `new_events_start`.
+pub fn expand_emoji_text_events(
+    text: &[u8],
+    events: &mut Vec<InlineEvent>,
+    new_events_start: usize,
+    mut spans: Option<&mut Vec<Range>>,
+) {
+    let has_candidate = events[new_events_start..].iter().any(|event| {
+        matches!(event, InlineEvent::Text(range) if has_emoji_candidates(range.slice(text)))
+    });
+    if !has_candidate {
+        return;
+    }
+
+    let original_events = events.split_off(new_events_start);
+    let original_spans = spans.as_deref_mut().map(core::mem::take);
+    for (i, event) in original_events.into_iter().enumerate() {
+        match event {
+            InlineEvent::Text(range) => {
+                expand_emoji_text_range(text, range, events, spans.as_deref_mut())
+            }
+            event => {
+                events.push(event);
+                if let (Some(spans), Some(original)) = (spans.as_deref_mut(), &original_spans) {
+                    spans.push(original[i]);
+                }
+            }
+        }
+    }
+}
+
+fn expand_emoji_text_range(
+    text: &[u8],
+    range: Range,
+    events: &mut Vec<InlineEvent>,
+    mut spans: Option<&mut Vec<Range>>,
+) {
+    let end = range.end_usize();
+    let mut pos = range.start_usize();
+    let mut text_start = pos;
+
+    while let Some(offset) = memchr(b':', &text[pos..end]) {
+        let colon = pos + offset;
+
+        // Never treat the colon of a URL scheme (`http://...`) as a delimiter;
+        // skip the rest of the URL.
+        if text[colon + 1..end].starts_with(b"//") {
+            pos = text[colon..end]
+                .iter()
+                .position(|b| b.is_ascii_whitespace())
+                .map_or(end, |ws| colon + ws);
+            continue;
+        }
+
+        // The opening colon must not follow a word character (`10:30:00`, `a:b:`).
+        if colon > 0 && text[colon - 1].is_ascii_alphanumeric() {
+            pos = colon + 1;
+            continue;
+        }
+
+        let name_start = colon + 1;
+        let name_len = text[name_start..end]
+            .iter()
+            .take_while(|&&b| is_shortcode_byte(b))
+            .count();
+        let close = name_start + name_len;
+        if name_len == 0 || close >= end || text[close] != b':' {
+            pos = colon + 1;
+            continue;
+        }
+
+        match lookup(&text[name_start..close]) {
+            Some(glyph) => {
+                if text_start < colon {
+                    events.push(InlineEvent::Text(Range::from_usize(text_start, colon)));
+                    if let Some(spans) = spans.as_deref_mut() {
+                        spans.push(Range::from_usize(text_start, colon));
+                    }
+                }
+                events.push(InlineEvent::Emoji(glyph));
+                if let Some(spans) = spans.as_deref_mut() {
+                    spans.push(Range::from_usize(colon, close + 1));
+                }
+                pos = close + 1;
+                text_start = pos;
+            }
+            // Unknown shortcode: the closing colon may still open the next one.
+            None => pos = close,
+        }
+    }
+
+    if text_start < end {
+        events.push(InlineEvent::Text(Range::from_usize(text_start, end)));
+        if let Some(spans) = spans {
+            spans.push(Range::from_usize(text_start, end));
+        }
+    }
+}
+
+#[inline]
+fn is_shortcode_byte(b: u8) -> bool {
+    b.is_ascii_lowercase() || b.is_ascii_digit() || matches!(b, b'_' | b'+' | b'-')
+}
+
+/// Built-in shortcode table, sorted by name for binary search.
+static SHORTCODES: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("1234", "🔢"),
+    ("1st_place_medal", "🥇"),
+    ("abacus", "🧮"),
+    ("airplane", "✈️"),
+    ("alarm_clock", "⏰"),
+    ("alien", "👽"),
+    ("anchor", "⚓"),
+    ("anger", "💢"),
+    ("angry", "😠"),
+    ("apple", "🍎"),
+    ("arrow_down", "⬇️"),
+    ("arrow_left", "⬅️"),
+    ("arrow_right", "➡️"),
+    ("arrow_up", "⬆️"),
+    ("arrows_counterclockwise", "🔄"),
+    ("art", "🎨"),
+    ("avocado", "🥑"),
+    ("baby", "👶"),
+    ("balloon", "🎈"),
+    ("ballot_box_with_check", "☑️"),
+    ("banana", "🍌"),
+    ("bangbang", "‼️"),
+    ("bar_chart", "📊"),
+    ("basket", "🧺"),
+    ("basketball", "🏀"),
+    ("bathtub", "🛁"),
+    ("battery", "🔋"),
+    ("beach_umbrella", "🏖️"),
+    ("bear", "🐻"),
+    ("bed", "🛏️"),
+    ("bee", "🐝"),
+    ("beer", "🍺"),
+    ("beers", "🍻"),
+    ("bell", "🔔"),
+    ("bike", "🚲"),
+    ("bird", "🐦"),
+    ("birthday", "🎂"),
+    ("black_circle", "⚫"),
+    ("black_heart", "🖤"),
+    ("black_joker", "🃏"),
+    ("blue_heart", "💙"),
+    ("blush", "😊"),
+    ("bomb", "💣"),
+    ("book", "📖"),
+    ("bookmark", "🔖"),
+    ("books", "📚"),
+    ("boom", "💥"),
+    ("bow", "🙇"),
+    ("bow_and_arrow", "🏹"),
+    ("brain", "🧠"),
+    ("bread", "🍞"),
+    ("broken_heart", "💔"),
+    ("broom", "🧹"),
+    ("bug", "🐛"),
+    ("building_construction", "🏗️"),
+    ("bulb", "💡"),
+    ("burrito", "🌯"),
+    ("bus", "🚌"),
+    ("bust_in_silhouette", "👤"),
+    ("busts_in_silhouette", "👥"),
+    ("cactus", "🌵"),
+    ("cake", "🍰"),
+    ("calendar", "📆"),
+    ("camera", "📷"),
+    ("camping", "🏕️"),
+    ("candle", "🕯️"),
+    ("car", "🚗"),
+    ("card_index", "📇"),
+    ("cat", "🐱"),
+    ("champagne", "🍾"),
+    ("chart_with_downwards_trend", "📉"),
+    ("chart_with_upwards_trend", "📈"),
+    ("checkered_flag", "🏁"),
+    ("cherries", "🍒"),
+    ("cherry_blossom", "🌸"),
+    ("chess_pawn", "♟️"),
+    ("chicken", "🐔"),
+    ("city_sunset", "🌆"),
+    ("clap", "👏"),
+    ("clipboard", "📋"),
+    ("closed_lock_with_key", "🔐"),
+    ("cloud", "☁️"),
+    ("clown_face", "🤡"),
+    ("clubs", "♣️"),
+    ("cocktail", "🍸"),
+    ("coffee", "☕"),
+    ("collision", "💥"),
+    ("comet", "☄️"),
+    ("compass", "🧭"),
+    ("computer", "💻"),
+    ("confetti_ball", "🎊"),
+    ("confused", "😕"),
+    ("construction", "🚧"),
+    ("construction_worker", "👷"),
+    ("cookie", "🍪"),
+    ("cool", "🆒"),
+    ("copyright", "©️"),
+    ("couch_and_lamp", "🛋️"),
+    ("couple", "👫"),
+    ("cow", "🐮"),
+    ("crab", "🦀"),
+    ("credit_card", "💳"),
+    ("crescent_moon", "🌙"),
+    ("crossed_fingers", "🤞"),
+    ("crossed_swords", "⚔️"),
+    ("crown", "👑"),
+    ("cry", "😢"),
+    ("cyclone", "🌀"),
+    ("dagger", "🗡️"),
+    ("dancer", "💃"),
+    ("dart", "🎯"),
+    ("dash", "💨"),
+    ("date", "📅"),
+    ("deciduous_tree", "🌳"),
+    ("desert", "🏜️"),
+    ("desktop_computer", "🖥️"),
+    ("diamonds", "♦️"),
+    ("disappointed", "😞"),
+    ("dizzy", "💫"),
+    ("dna", "🧬"),
+    ("dog", "🐶"),
+    ("dollar", "💵"),
+    ("dolphin", "🐬"),
+    ("door", "🚪"),
+    ("doughnut", "🍩"),
+    ("dragon", "🐉"),
+    ("droplet", "💧"),
+    ("ear", "👂"),
+    ("earth_africa", "🌍"),
+    ("earth_americas", "🌎"),
+    ("eight_spoked_asterisk", "✳️"),
+    ("electric_plug", "🔌"),
+    ("email", "📧"),
+    ("envelope", "✉️"),
+    ("evergreen_tree", "🌲"),
+    ("exclamation", "❗"),
+    ("exploding_head", "🤯"),
+    ("expressionless", "😑"),
+    ("eye", "👁️"),
+    ("eyes", "👀"),
+    ("facepalm", "🤦"),
+    ("fallen_leaf", "🍂"),
+    ("family", "👪"),
+    ("file_folder", "📁"),
+    ("fire", "🔥"),
+    ("fire_extinguisher", "🧯"),
+    ("fish", "🐟"),
+    ("fist", "✊"),
+    ("flags", "🎏"),
+    ("flashlight", "🔦"),
+    ("flushed", "😳"),
+    ("flying_saucer", "🛸"),
+    ("fog", "🌫️"),
+    ("foggy", "🌁"),
+    ("football", "🏈"),
+    ("footprints", "👣"),
+    ("four_leaf_clover", "🍀"),
+    ("fox_face", "🦊"),
+    ("free", "🆓"),
+    ("fries", "🍟"),
+    ("frog", "🐸"),
+    ("fuelpump", "⛽"),
+    ("full_moon", "🌕"),
+    ("game_die", "🎲"),
+    ("gear", "⚙️"),
+    ("gem", "💎"),
+    ("ghost", "👻"),
+    ("gift", "🎁"),
+    ("globe_with_meridians", "🌐"),
+    ("grapes", "🍇"),
+    ("green_apple", "🍏"),
+    ("green_circle", "🟢"),
+    ("green_heart", "💚"),
+    ("grey_exclamation", "❕"),
+    ("grey_question", "❔"),
+    ("grin", "😁"),
+    ("grinning", "😀"),
+    ("guitar", "🎸"),
+    ("hamburger", "🍔"),
+    ("hammer", "🔨"),
+    ("hammer_and_wrench", "🛠️"),
+    ("hand", "✋"),
+    ("handshake", "🤝"),
+    ("hankey", "💩"),
+    ("headphones", "🎧"),
+    ("hear_no_evil", "🙉"),
+    ("heart", "❤️"),
+    ("heart_eyes", "😍"),
+    ("hearts", "♥️"),
+    ("heavy_check_mark", "✔️"),
+    ("heavy_exclamation_mark", "❗"),
+    ("heavy_minus_sign", "➖"),
+    ("heavy_multiplication_x", "✖️"),
+    ("heavy_plus_sign", "➕"),
+    ("helicopter", "🚁"),
+    ("herb", "🌿"),
+    ("honeybee", "🐝"),
+    ("hospital", "🏥"),
+    ("hotdog", "🌭"),
+    ("hourglass", "⌛"),
+    ("hourglass_flowing_sand", "⏳"),
+    ("house", "🏠"),
+    ("hugs", "🤗"),
+    ("inbox_tray", "📥"),
+    ("information_source", "ℹ️"),
+    ("innocent", "😇"),
+    ("interrobang", "⁉️"),
+    ("iphone", "📱"),
+    ("jigsaw", "🧩"),
+    ("joy", "😂"),
+    ("key", "🔑"),
+    ("keyboard", "⌨️"),
+    ("kissing_heart", "😘"),
+    ("koala", "🐨"),
+    ("label", "🏷️"),
+    ("large_blue_circle", "🔵"),
+    ("laughing", "😆"),
+    ("lemon", "🍋"),
+    ("link", "🔗"),
+    ("lion", "🦁"),
+    ("lips", "👄"),
+    ("lipstick", "💄"),
+    ("lock", "🔒"),
+    ("lock_with_ink_pen", "🔏"),
+    ("loudspeaker", "📢"),
+    ("mag", "🔍"),
+    ("mag_right", "🔎"),
+    ("mage", "🧙"),
+    ("mahjong", "🀄"),
+    ("mailbox", "📫"),
+    ("man", "👨"),
+    ("man_technologist", "👨‍💻"),
+    ("mask", "😷"),
+    ("medal_sports", "🏅"),
+    ("mega", "📣"),
+    ("memo", "📝"),
+    ("microphone", "🎤"),
+    ("microscope", "🔬"),
+    ("milky_way", "🌌"),
+    ("mirror", "🪞"),
+    ("moneybag", "💰"),
+    ("monkey", "🐒"),
+    ("moon", "🌔"),
+    ("motorcycle", "🏍️"),
+    ("mountain", "⛰️"),
+    ("mouse", "🐭"),
+    ("movie_camera", "🎥"),
+    ("muscle", "💪"),
+    ("musical_note", "🎵"),
+    ("nerd_face", "🤓"),
+    ("neutral_face", "😐"),
+    ("new", "🆕"),
+    ("new_moon", "🌑"),
+    ("night_with_stars", "🌃"),
+    ("ninja", "🥷"),
+    ("no_bell", "🔕"),
+    ("no_entry", "⛔"),
+    ("no_entry_sign", "🚫"),
+    ("nose", "👃"),
+    ("notes", "🎶"),
+    ("nut_and_bolt", "🔩"),
+    ("ocean", "🌊"),
+    ("octopus", "🐙"),
+    ("office", "🏢"),
+    ("ok", "🆗"),
+    ("ok_hand", "👌"),
+    ("older_man", "👴"),
+    ("older_woman", "👵"),
+    ("open_file_folder", "📂"),
+    ("orange_heart", "🧡"),
+    ("outbox_tray", "📤"),
+    ("package", "📦"),
+    ("page_facing_up", "📄"),
+    ("panda_face", "🐼"),
+    ("paperclip", "📎"),
+    ("partly_sunny", "⛅"),
+    ("partying_face", "🥳"),
+    ("peach", "🍑"),
+    ("pencil", "📝"),
+    ("pencil2", "✏️"),
+    ("penguin", "🐧"),
+    ("phone", "☎️"),
+    ("pig", "🐷"),
+    ("pill", "💊"),
+    ("pirate_flag", "🏴‍☠️"),
+    ("pizza", "🍕"),
+    ("pleading_face", "🥺"),
+    ("point_down", "👇"),
+    ("point_left", "👈"),
+    ("point_right", "👉"),
+    ("point_up", "☝️"),
+    ("poop", "💩"),
+    ("popcorn", "🍿"),
+    ("pray", "🙏"),
+    ("punch", "👊"),
+    ("purple_heart", "💜"),
+    ("pushpin", "📌"),
+    ("question", "❓"),
+    ("rabbit", "🐰"),
+    ("racing_car", "🏎️"),
+    ("rage", "😡"),
+    ("rainbow", "🌈"),
+    ("rainbow_flag", "🏳️‍🌈"),
+    ("raised_hand", "✋"),
+    ("raised_hands", "🙌"),
+    ("raising_hand", "🙋"),
+    ("ramen", "🍜"),
+    ("recycle", "♻️"),
+    ("red_circle", "🔴"),
+    ("registered", "®️"),
+    ("relaxed", "☺️"),
+    ("repeat", "🔁"),
+    ("ring", "💍"),
+    ("robot", "🤖"),
+    ("rocket", "🚀"),
+    ("rofl", "🤣"),
+    ("roll_eyes", "🙄"),
+    ("rose", "🌹"),
+    ("rotating_light", "🚨"),
+    ("runner", "🏃"),
+    ("running", "🏃"),
+    ("sailboat", "⛵"),
+    ("santa", "🎅"),
+    ("satellite", "📡"),
+    ("satisfied", "😆"),
+    ("school", "🏫"),
+    ("scissors", "✂️"),
+    ("scream", "😱"),
+    ("see_no_evil", "🙈"),
+    ("seedling", "🌱"),
+    ("shield", "🛡️"),
+    ("ship", "🚢"),
+    ("shooting_star", "🌠"),
+    ("shopping_cart", "🛒"),
+    ("shower", "🚿"),
+    ("shrug", "🤷"),
+    ("shushing_face", "🤫"),
+    ("skull", "💀"),
+    ("sleeping", "😴"),
+    ("sleepy", "😪"),
+    ("slightly_smiling_face", "🙂"),
+    ("smile", "😄"),
+    ("smiley", "😃"),
+    ("smirk", "😏"),
+    ("snail", "🐌"),
+    ("snake", "🐍"),
+    ("snowflake", "❄️"),
+    ("snowman", "⛄"),
+    ("soap", "🧼"),
+    ("sob", "😭"),
+    ("soccer", "⚽"),
+    ("sos", "🆘"),
+    ("spades", "♠️"),
+    ("sparkle", "❇️"),
+    ("sparkles", "✨"),
+    ("sparkling_heart", "💖"),
+    ("speak_no_evil", "🙊"),
+    ("speech_balloon", "💬"),
+    ("spiral_notepad", "🗒️"),
+    ("star", "⭐"),
+    ("star2", "🌟"),
+    ("star_struck", "🤩"),
+    ("stars", "🌠"),
+    ("stop_sign", "🛑"),
+    ("stopwatch", "⏱️"),
+    ("straight_ruler", "📏"),
+    ("strawberry", "🍓"),
+    ("stuck_out_tongue", "😛"),
+    ("stuck_out_tongue_winking_eye", "😜"),
+    ("sun_with_face", "🌞"),
+    ("sunflower", "🌻"),
+    ("sunglasses", "😎"),
+    ("sunny", "☀️"),
+    ("sunrise", "🌅"),
+    ("superhero", "🦸"),
+    ("surfer", "🏄"),
+    ("sushi", "🍣"),
+    ("sweat_drops", "💦"),
+    ("sweat_smile", "😅"),
+    ("swimmer", "🏊"),
+    ("syringe", "💉"),
+    ("taco", "🌮"),
+    ("tada", "🎉"),
+    ("taxi", "🚕"),
+    ("tea", "🍵"),
+    ("technologist", "🧑‍💻"),
+    ("teddy_bear", "🧸"),
+    ("telephone", "☎️"),
+    ("telescope", "🔭"),
+    ("tennis", "🎾"),
+    ("test_tube", "🧪"),
+    ("thinking", "🤔"),
+    ("thought_balloon", "💭"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("thunder_cloud_and_rain", "⛈️"),
+    ("tiger", "🐯"),
+    ("tm", "™️"),
+    ("toilet", "🚽"),
+    ("tomato", "🍅"),
+    ("tongue", "👅"),
+    ("tornado", "🌪️"),
+    ("tractor", "🚜"),
+    ("traffic_light", "🚥"),
+    ("train", "🚋"),
+    ("triangular_flag_on_post", "🚩"),
+    ("triangular_ruler", "📐"),
+    ("trophy", "🏆"),
+    ("tropical_drink", "🍹"),
+    ("truck", "🚚"),
+    ("tulip", "🌷"),
+    ("turtle", "🐢"),
+    ("tv", "📺"),
+    ("two_hearts", "💕"),
+    ("umbrella", "☔"),
+    ("unamused", "😒"),
+    ("unicorn", "🦄"),
+    ("unlock", "🔓"),
+    ("up", "🆙"),
+    ("upside_down_face", "🙃"),
+    ("v", "✌️"),
+    ("vampire", "🧛"),
+    ("vertical_traffic_light", "🚦"),
+    ("video_game", "🎮"),
+    ("volcano", "🌋"),
+    ("walking", "🚶"),
+    ("warning", "⚠️"),
+    ("wastebasket", "🗑️"),
+    ("watch", "⌚"),
+    ("watermelon", "🍉"),
+    ("wave", "👋"),
+    ("whale", "🐳"),
+    ("white_check_mark", "✅"),
+    ("white_circle", "⚪"),
+    ("white_flag", "🏳️"),
+    ("wind_face", "🌬️"),
+    ("window", "🪟"),
+    ("wine_glass", "🍷"),
+    ("wink", "😉"),
+    ("woman", "👩"),
+    ("woman_technologist", "👩‍💻"),
+    ("world_map", "🗺️"),
+    ("worried", "😟"),
+    ("wrench", "🔧"),
+    ("x", "❌"),
+    ("yawning_face", "🥱"),
+    ("yellow_circle", "🟡"),
+    ("yellow_heart", "💛"),
+    ("yum", "😋"),
+    ("zany_face", "🤪"),
+    ("zap", "⚡"),
+    ("zombie", "🧟"),
+    ("zzz", "💤"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcode_table_is_sorted_and_unique() {
+        assert!(
+            SHORTCODES
+                .windows(2)
+                .all(|pair| pair[0].0.as_bytes() < pair[1].0.as_bytes())
+        );
+    }
+
+    #[test]
+    fn lookup_known_and_unknown() {
+        assert_eq!(lookup(b"smile"), Some("\u{1F604}"));
+        assert_eq!(lookup(b"+1"), Some("\u{1F44D}"));
+        assert_eq!(lookup(b"not_an_emoji"), None);
+    }
+}
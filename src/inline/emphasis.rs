@@ -4,6 +4,7 @@
 //! match emphasis openers and closers according to CommonMark rules.
 
 use super::marks::{Mark, flags};
+use alloc::vec::Vec;
 
 /// Result of emphasis resolution for a mark pair.
 #[derive(Debug, Clone, Copy)]
@@ -35,6 +36,13 @@ impl EmphasisStacks {
         self.order = 0;
     }
 
+    pub fn shrink_to(&mut self, capacity: usize) {
+        self.clear();
+        for stack in &mut self.stacks {
+            stack.shrink_to(capacity);
+        }
+    }
+
     pub fn reserve_for_marks(&mut self, marks_len: usize) {
         let target_per_stack = (marks_len / 6).max(8);
         for stack in &mut self.stacks {
@@ -37,6 +37,11 @@ pub enum InlineEvent {
     HighlightStart,
     /// End of highlight/mark.
     HighlightEnd,
+    /// Start of a custom delimiter span; the index into
+    /// [`Options::custom_delimiters`](crate::Options::custom_delimiters).
+    CustomStart(u8),
+    /// End of a custom delimiter span.
+    CustomEnd(u8),
 
     /// Start of a link `[text](url)`.
     LinkStart {
@@ -84,6 +89,21 @@ pub enum InlineEvent {
         kind: crate::inline::links::AutolinkLiteralKind,
     },
 
+    /// Wikilink `[[target]]` or `[[target|label]]`.
+    WikiLink {
+        /// Page name passed to the URL transform.
+        target: Range,
+        /// Display text (the target when no label is given).
+        label: Range,
+    },
+
+    /// Emoji expanded from a `:shortcode:`.
+    Emoji(&'static str),
+
+    /// Typographic quote, dash or ellipsis replacing ASCII punctuation in
+    /// text, with `smart_punctuation`.
+    SmartPunctuation(&'static str),
+
     /// Raw inline HTML (not escaped or parsed).
     Html(Range),
 
@@ -102,6 +122,10 @@ pub enum InlineEvent {
         def_index: u32,
     },
 
+    /// Inline footnote `^[text]`: the range of the note text, which is
+    /// parsed as inline content where the footnotes are rendered.
+    InlineFootnote(Range),
+
     /// Inline math span (`$...$`).
     MathInline(Range),
 
@@ -132,6 +156,6 @@ mod tests {
     #[test]
     fn test_event_size() {
         // Events should be reasonably small (Link/Image have url + Option<Range>)
-        assert!(std::mem::size_of::<InlineEvent>() <= 32);
+        assert!(core::mem::size_of::<InlineEvent>() <= 32);
     }
 }
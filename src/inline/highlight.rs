@@ -4,6 +4,7 @@
 //! Uses the same flanking rules as `*` emphasis (already computed in mark collection).
 
 use super::marks::{Mark, flags};
+use alloc::vec::Vec;
 
 /// A matched highlight pair.
 #[derive(Debug, Clone, Copy)]
@@ -0,0 +1,92 @@
+//! Inline footnote resolution (`^[note text]`).
+//!
+//! Runs with wikilinks, before bracket links, so links and emphasis can
+//! neither reach into a note nor out of it. The note text is emitted as a
+//! single range and parsed on its own when the footnotes are rendered.
+
+use alloc::vec::Vec;
+
+/// A resolved inline footnote.
+#[derive(Debug, Clone, Copy)]
+pub struct InlineFootnote {
+    /// Position of the `^`.
+    pub start: u32,
+    /// Position after the closing `]`.
+    pub end: u32,
+    /// Start of the note text.
+    pub content_start: u32,
+    /// End of the note text.
+    pub content_end: u32,
+}
+
+/// Resolve `^[...]` notes.
+///
+/// The `]` is the one balancing the `[`, counting only brackets outside the
+/// `excluded` ranges (code spans, math, HTML, autolinks, wikilinks), so a
+/// note may contain links and other bracketed text. A note inside another
+/// note is part of its text, and a note with blank text stays literal.
+pub fn resolve_inline_footnotes_into(
+    text: &[u8],
+    open_brackets: &[(u32, bool)],
+    close_brackets: &[u32],
+    excluded: &[(u32, u32)],
+    out: &mut Vec<InlineFootnote>,
+) {
+    out.clear();
+    let is_excluded = |pos: u32| excluded.iter().any(|&(s, e)| pos >= s && pos < e);
+
+    let mut opens = open_brackets
+        .iter()
+        .map(|&(pos, _)| pos)
+        .filter(|&pos| !is_excluded(pos))
+        .peekable();
+    let mut stack: Vec<u32> = Vec::new();
+    for &close in close_brackets {
+        if is_excluded(close) {
+            continue;
+        }
+        while let Some(open) = opens.next_if(|&open| open < close) {
+            stack.push(open);
+        }
+        let Some(open) = stack.pop() else {
+            continue;
+        };
+        if !is_note_opener(text, open as usize) {
+            continue;
+        }
+        let content = &text[open as usize + 1..close as usize];
+        if content.iter().all(|b| matches!(b, b' ' | b'\t' | b'\n')) {
+            continue;
+        }
+        out.push(InlineFootnote {
+            start: open - 1,
+            end: close + 1,
+            content_start: open + 1,
+            content_end: close,
+        });
+    }
+
+    // Inner notes close first; keep the outermost ones
+    out.sort_unstable_by_key(|note| note.start);
+    let mut resume = 0;
+    out.retain(|note| {
+        let outermost = note.start >= resume;
+        if outermost {
+            resume = note.end;
+        }
+        outermost
+    });
+}
+
+/// Whether the `[` at `open` follows an unescaped `^`.
+fn is_note_opener(text: &[u8], open: usize) -> bool {
+    if open == 0 || text[open - 1] != b'^' {
+        return false;
+    }
+    let backslashes = text[..open - 1]
+        .iter()
+        .rev()
+        .take_while(|&&b| b == b'\\')
+        .count();
+    backslashes % 2 == 0
+}
@@ -7,6 +7,7 @@
 
 use crate::limits;
 use crate::link_ref::{LinkRefStore, normalize_label_into};
+use alloc::{string::String, vec::Vec};
 use memchr::memchr;
 
 /// A resolved link or image.
@@ -80,11 +81,16 @@ pub fn resolve_links(
         &mut formed_opens,
         &mut inactive_opens,
         &mut used_closes,
+        false,
     );
     out_links
 }
 
 /// Parse links from text, given bracket positions, reusing caller-owned buffers.
+///
+/// With `allow_nested`, a formed link leaves the brackets around it active,
+/// so an outer link may contain it.
+#[allow(clippy::too_many_arguments)]
 pub fn resolve_links_into(
     text: &[u8],
     open_brackets: &[(u32, bool)], // (position, is_image)
@@ -93,6 +99,7 @@ pub fn resolve_links_into(
     formed_opens: &mut Vec<bool>,
     inactive_opens: &mut Vec<bool>,
     used_closes: &mut Vec<bool>,
+    allow_nested: bool,
 ) {
     out_links.clear();
     // Track opens that have formed links (consumed with their close)
@@ -145,7 +152,7 @@ pub fn resolve_links_into(
                     // For links (not images), deactivate any outer LINK open brackets
                     // that would contain this link (links cannot contain links,
                     // but images CAN contain links)
-                    if !is_image {
+                    if !is_image && !allow_nested {
                         for (i, &(pos, outer_is_image)) in open_brackets.iter().enumerate() {
                             // Only deactivate outer LINK brackets, not image brackets
                             if pos < open_pos
@@ -191,6 +198,7 @@ pub fn resolve_reference_links_into(
     formed_opens: &mut Vec<bool>,
     used_closes: &mut Vec<bool>,
     occupied: &mut Vec<(u32, u32)>,
+    allow_nested: bool,
 ) {
     out_links.clear();
     label_buf.clear();
@@ -259,6 +267,9 @@ pub fn resolve_reference_links_into(
             end = (ref_close_pos + 1) as u32;
             ref_label = Some((ref_start, ref_end, ref_close_pos));
         }
+        if label_bytes.len() > limits::MAX_REF_LABEL_LEN {
+            continue;
+        }
 
         normalize_label_into(label_bytes, label_buf);
         if label_buf.is_empty() {
@@ -269,16 +280,17 @@ pub fn resolve_reference_links_into(
         };
 
         // Links cannot contain links (but can contain images)
-        if contains_link(occupied, open_pos, close_pos)
-            || contains_ref_link_candidate(
-                text,
-                open_brackets,
-                close_brackets,
-                defs,
-                open_pos,
-                close_pos,
-                &mut nested_label_buf,
-            )
+        if !allow_nested
+            && (contains_link(occupied, open_pos, close_pos)
+                || contains_ref_link_candidate(
+                    text,
+                    open_brackets,
+                    close_brackets,
+                    defs,
+                    open_pos,
+                    close_pos,
+                    &mut nested_label_buf,
+                ))
         {
             continue;
         }
@@ -343,6 +355,9 @@ fn contains_ref_link_candidate(
                 label_bytes = &text[ref_start..ref_end];
             }
         }
+        if label_bytes.len() > limits::MAX_REF_LABEL_LEN {
+            continue;
+        }
 
         normalize_label_into(label_bytes, label_buf);
         if label_buf.is_empty() {
@@ -449,6 +464,9 @@ fn parse_ref_label_immediate(text: &[u8], mut pos: usize) -> Option<(usize, usiz
     pos += 1;
 
     while pos < len {
+        if pos - label_start > limits::MAX_REF_LABEL_LEN {
+            return None;
+        }
         match text[pos] {
             b'\\' => {
                 if pos + 1 < len {
@@ -623,8 +641,8 @@ fn try_parse_autolink(text: &[u8], start: usize) -> Option<Autolink> {
     let content_start = start + 1;
     let mut pos = content_start;
 
-    // Find the closing '>'
-    while pos < len && text[pos] != b'>' && text[pos] != b' ' && text[pos] != b'\n' {
+    // Find the closing '>'; autolinks cannot contain spaces, newlines or '<'
+    while pos < len && !matches!(text[pos], b'>' | b' ' | b'\n' | b'<') {
         pos += 1;
     }
 
@@ -726,7 +744,8 @@ fn is_email_autolink(content: &[u8]) -> bool {
 /// Kind of autolink literal.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AutolinkLiteralKind {
-    /// `http://` or `https://` or `ftp://` URL.
+    /// `http://`, `https://`, or `ftp://` URL, or a `mailto:`/`xmpp:` address
+    /// (the href is the text as written).
     Url,
     /// `www.` link (needs `http://` prepended in href).
     Www,
@@ -1053,7 +1072,7 @@ fn try_email_autolink(text: &[u8], at_pos: usize) -> Option<AutolinkLiteral> {
 
     while domain_end < len {
         let b = text[domain_end];
-        if b.is_ascii_alphanumeric() || b == b'-' || b == b'.' {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.') {
             if b == b'.' {
                 dot_count += 1;
             }
@@ -1073,13 +1092,13 @@ fn try_email_autolink(text: &[u8], at_pos: usize) -> Option<AutolinkLiteral> {
         return None;
     }
 
-    // Domain must not end with hyphen or dot
-    let last = text[domain_end - 1];
-    if last == b'-' || last == b'.' {
-        // Trim trailing dots/hyphens
-        while domain_end > at_pos + 1 && matches!(text[domain_end - 1], b'-' | b'.') {
-            domain_end -= 1;
-        }
+    // A trailing dot is sentence punctuation; a trailing `-` or `_` rules
+    // out the whole address (GFM)
+    while domain_end > at_pos + 1 && text[domain_end - 1] == b'.' {
+        domain_end -= 1;
+    }
+    if matches!(text[domain_end - 1], b'-' | b'_') {
+        return None;
     }
 
     // Recount dots after trimming
@@ -1099,6 +1118,36 @@ fn try_email_autolink(text: &[u8], at_pos: usize) -> Option<AutolinkLiteral> {
         return None;
     }
 
+    // Extended email autolinks: `mailto:` and `xmpp:` are part of the link,
+    // and `xmpp:` may carry a `/resource`
+    let prefix = &text[..local_start];
+    if prefix.ends_with(b"mailto:") || prefix.ends_with(b"xmpp:") {
+        let scheme_len = if prefix.ends_with(b"mailto:") { 7 } else { 5 };
+        let start = local_start - scheme_len;
+        if !is_valid_autolink_preceding(text, start) {
+            return None;
+        }
+        let mut end = domain_end;
+        if scheme_len == 5 && text.get(end) == Some(&b'/') {
+            let resource_len = text[end + 1..]
+                .iter()
+                .take_while(|&&b| b.is_ascii_alphanumeric() || matches!(b, b'@' | b'.'))
+                .count();
+            let mut resource_end = end + 1 + resource_len;
+            while resource_end > end + 1 && text[resource_end - 1] == b'.' {
+                resource_end -= 1;
+            }
+            if resource_end > end + 1 {
+                end = resource_end;
+            }
+        }
+        return Some(AutolinkLiteral {
+            start: start as u32,
+            end: end as u32,
+            kind: AutolinkLiteralKind::Url,
+        });
+    }
+
     Some(AutolinkLiteral {
         start: local_start as u32,
         end: domain_end as u32,
@@ -1108,6 +1157,10 @@ fn try_email_autolink(text: &[u8], at_pos: usize) -> Option<AutolinkLiteral> {
 
 /// Trim trailing punctuation from an autolink per GFM rules.
 fn trim_autolink_trailing(text: &[u8], start: usize, mut end: usize) -> usize {
+    // Counted once so trimming a long run of `)` stays linear
+    let url = &text[start..end];
+    let open_count = url.iter().filter(|&&b| b == b'(').count();
+    let mut close_count = url.iter().filter(|&&b| b == b')').count();
     loop {
         if end <= start {
             return end;
@@ -1124,14 +1177,10 @@ fn trim_autolink_trailing(text: &[u8], start: usize, mut end: usize) -> usize {
         }
 
         // Rule 2: Parenthesis balancing
-        if last == b')' {
-            let url = &text[start..end];
-            let open_count = url.iter().filter(|&&b| b == b'(').count();
-            let close_count = url.iter().filter(|&&b| b == b')').count();
-            if close_count > open_count {
-                end -= 1;
-                continue;
-            }
+        if last == b')' && close_count > open_count {
+            close_count -= 1;
+            end -= 1;
+            continue;
         }
 
         // Rule 3: Entity check - if ends with `;` preceded by `&<alphanum>+`
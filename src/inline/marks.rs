@@ -6,6 +6,7 @@
 #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
 use super::simd;
 use crate::limits;
+use alloc::vec::Vec;
 use memchr::memchr3;
 
 /// Flags for mark state.
@@ -136,6 +137,7 @@ impl Mark {
 #[derive(Debug)]
 pub struct MarkBuffer {
     marks: Vec<Mark>,
+    limit: usize,
 }
 
 impl MarkBuffer {
@@ -143,19 +145,34 @@ impl MarkBuffer {
     pub fn new() -> Self {
         Self {
             marks: Vec::with_capacity(64),
+            limit: limits::MAX_INLINE_MARKS,
         }
     }
 
+    /// Set the maximum number of marks collected per inline run.
+    ///
+    /// Delimiters past the limit are dropped and render as literal text.
+    #[inline]
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
     /// Clear the buffer for reuse.
     #[inline]
     pub fn clear(&mut self) {
         self.marks.clear();
     }
 
+    /// Clear the buffer and release capacity above `capacity` marks.
+    pub fn shrink_to(&mut self, capacity: usize) {
+        self.marks.clear();
+        self.marks.shrink_to(capacity);
+    }
+
     /// Reserve capacity based on input length.
     #[inline]
     pub fn reserve_for_text(&mut self, text_len: usize) {
-        let target = (text_len / 8).clamp(8, limits::MAX_INLINE_MARKS);
+        let target = (text_len / 8).clamp(8, self.limit.max(8));
         if self.marks.capacity() < target {
             self.marks.reserve(target - self.marks.capacity());
         }
@@ -164,7 +181,7 @@ impl MarkBuffer {
     /// Add a mark if we haven't exceeded the limit.
     #[inline]
     pub fn push(&mut self, mark: Mark) {
-        if self.marks.len() < limits::MAX_INLINE_MARKS {
+        if self.marks.len() < self.limit {
             self.marks.push(mark);
         }
     }
@@ -253,9 +270,10 @@ fn collect_marks_impl<const HIGHLIGHT: bool, const SUPERSCRIPT: bool>(
 
     let mut pos = 0;
     let len = text.len();
+    let mut scan = SpecialScan::new();
 
     while pos < len {
-        let Some(next) = next_special::<HIGHLIGHT, SUPERSCRIPT>(text, pos) else {
+        let Some(next) = next_special::<HIGHLIGHT, SUPERSCRIPT>(text, pos, &mut scan) else {
             break;
         };
         pos = next;
@@ -349,6 +367,10 @@ fn collect_marks_impl<const HIGHLIGHT: bool, const SUPERSCRIPT: bool>(
                 if pos + 1 < len {
                     let next = text[pos + 1];
                     if is_escapable(next) || next == b'\n' {
+                        // `\(` and `\[` may open LaTeX-style math spans
+                        if next == b'(' || next == b'[' {
+                            summary.record(MarkSummary::MATH);
+                        }
                         // Regular escape or hard line break (backslash before newline)
                         buffer.push(Mark::new(
                             pos as u32,
@@ -463,10 +485,50 @@ fn collect_marks_impl<const HIGHLIGHT: bool, const SUPERSCRIPT: bool>(
     summary
 }
 
+/// Cached next occurrence of each memchr group used by [`next_special`].
+///
+/// Each group is only searched again once the scan has moved past its cached
+/// hit, so a run of adjacent specials does not rescan the rest of the text
+/// for every other group.
+struct SpecialScan {
+    next: [Option<usize>; 6],
+}
+
+impl SpecialScan {
+    const NONE: usize = usize::MAX;
+
+    fn new() -> Self {
+        Self { next: [None; 6] }
+    }
+
+    /// Next occurrence of group `group` at or after `start`.
+    #[inline]
+    fn find(
+        &mut self,
+        group: usize,
+        text: &[u8],
+        start: usize,
+        search: impl Fn(&[u8]) -> Option<usize>,
+    ) -> usize {
+        match self.next[group] {
+            Some(cached) if cached >= start => cached,
+            _ => {
+                let found = search(&text[start..]).map_or(Self::NONE, |i| start + i);
+                self.next[group] = Some(found);
+                found
+            }
+        }
+    }
+}
+
+/// Find the next special byte at or after `start`.
+///
+/// `start` must not decrease between calls sharing the same `scan`.
 #[inline]
 fn next_special<const HIGHLIGHT: bool, const SUPERSCRIPT: bool>(
     text: &[u8],
     start: usize,
+    scan: &mut SpecialScan,
 ) -> Option<usize> {
     let pos = {
         #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
@@ -484,33 +546,19 @@ fn next_special<const HIGHLIGHT: bool, const SUPERSCRIPT: bool>(
             start
         }
     };
-    let slice = &text[pos..];
-    let mut best = None;
 
-    if let Some(i) = memchr3(b'`', b'*', b'_', slice) {
-        best = Some(i);
-    }
-    if let Some(i) = memchr3(b'\\', b'\n', b'[', slice) {
-        best = Some(best.map_or(i, |b| b.min(i)));
-    }
-    if let Some(i) = memchr3(b']', b'<', b'~', slice) {
-        best = Some(best.map_or(i, |b| b.min(i)));
-    }
-    if let Some(i) = memchr::memchr(b'$', slice) {
-        best = Some(best.map_or(i, |b| b.min(i)));
-    }
+    let mut best = scan.find(0, text, pos, |s| memchr3(b'`', b'*', b'_', s));
+    best = best.min(scan.find(1, text, pos, |s| memchr3(b'\\', b'\n', b'[', s)));
+    best = best.min(scan.find(2, text, pos, |s| memchr3(b']', b'<', b'~', s)));
+    best = best.min(scan.find(3, text, pos, |s| memchr::memchr(b'$', s)));
     if SUPERSCRIPT {
-        if let Some(i) = memchr::memchr(b'^', slice) {
-            best = Some(best.map_or(i, |b| b.min(i)));
-        }
+        best = best.min(scan.find(4, text, pos, |s| memchr::memchr(b'^', s)));
     }
     if HIGHLIGHT {
-        if let Some(i) = memchr::memchr(b'=', slice) {
-            best = Some(best.map_or(i, |b| b.min(i)));
-        }
+        best = best.min(scan.find(5, text, pos, |s| memchr::memchr(b'=', s)));
     }
 
-    best.map(|i| pos + i)
+    (best != SpecialScan::NONE).then_some(best)
 }
 
 #[inline]
@@ -532,19 +580,10 @@ fn is_escaped(text: &[u8], pos: usize) -> bool {
 ///
 /// This version takes the full text and positions to properly handle Unicode.
 fn compute_emphasis_flags_with_context(ch: u8, text: &[u8], start: usize, end: usize) -> u8 {
-    let before_space = is_preceded_by_whitespace(text, start);
-    let after_space = is_followed_by_whitespace(text, end);
+    let (left_flanking, right_flanking) = flanking(text, start, end);
     let before_punct = is_preceded_by_punctuation(text, start);
     let after_punct = is_followed_by_punctuation(text, end);
 
-    // Left-flanking: not followed by whitespace, and either
-    // not followed by punctuation or preceded by whitespace/punctuation
-    let left_flanking = !after_space && (!after_punct || before_space || before_punct);
-
-    // Right-flanking: not preceded by whitespace, and either
-    // not preceded by punctuation or followed by whitespace/punctuation
-    let right_flanking = !before_space && (!before_punct || after_space || after_punct);
-
     let mut flags = 0;
 
     if ch == b'*' {
@@ -570,6 +609,69 @@ fn compute_emphasis_flags_with_context(ch: u8, text: &[u8], start: usize, end: u
     flags
 }
 
+/// Whether `content` can sit between sub- or superscript delimiters. As in
+/// Pandoc, whitespace ends the span unless it is a backslash-escaped space,
+/// and there must be something besides escaped spaces.
+pub(crate) fn is_script_content(content: &[u8]) -> bool {
+    let mut has_text = false;
+    let mut i = 0;
+    while i < content.len() {
+        match content[i] {
+            b' ' | b'\t' | b'\n' => return false,
+            b'\\' if content.get(i + 1) == Some(&b' ') => i += 2,
+            b'\\' if matches!(content.get(i + 1), Some(b'\t' | b'\n')) => return false,
+            b'\\' => {
+                has_text = true;
+                i += 2;
+            }
+            _ => {
+                has_text = true;
+                i += 1;
+            }
+        }
+    }
+    has_text
+}
+
+/// Positions of the backslashes escaping a space in `text[start..end]`, the
+/// content of a matched sub- or superscript.
+pub(crate) fn escaped_spaces(text: &[u8], start: u32, end: u32) -> impl Iterator<Item = u32> + '_ {
+    let mut pos = start as usize;
+    core::iter::from_fn(move || {
+        while pos + 1 < end as usize {
+            let at = pos;
+            if text[at] != b'\\' {
+                pos += 1;
+                continue;
+            }
+            pos += 2;
+            if text[at + 1] == b' ' {
+                return Some(at as u32);
+            }
+        }
+        None
+    })
+}
+
+/// Whether the delimiter run `text[start..end]` is left-flanking and
+/// right-flanking.
+pub(crate) fn flanking(text: &[u8], start: usize, end: usize) -> (bool, bool) {
+    let before_space = is_preceded_by_whitespace(text, start);
+    let after_space = is_followed_by_whitespace(text, end);
+    let before_punct = is_preceded_by_punctuation(text, start);
+    let after_punct = is_followed_by_punctuation(text, end);
+
+    // Left-flanking: not followed by whitespace, and either
+    // not followed by punctuation or preceded by whitespace/punctuation
+    let left_flanking = !after_space && (!after_punct || before_space || before_punct);
+
+    // Right-flanking: not preceded by whitespace, and either
+    // not preceded by punctuation or followed by whitespace/punctuation
+    let right_flanking = !before_space && (!before_punct || after_space || after_punct);
+
+    (left_flanking, right_flanking)
+}
+
 /// Check if position is preceded by Unicode whitespace (or start of text).
 #[inline]
 fn is_preceded_by_whitespace(text: &[u8], pos: usize) -> bool {
@@ -869,7 +971,7 @@ mod tests {
 
     #[test]
     fn test_mark_size() {
-        assert!(std::mem::size_of::<Mark>() <= 16);
+        assert!(core::mem::size_of::<Mark>() <= 16);
     }
 
     #[test]
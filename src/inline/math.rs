@@ -1,9 +1,12 @@
 //! Math span resolution.
 //!
 //! Follows code span pattern: `$` for inline math, `$$` for display math.
+//! The LaTeX delimiters `\(...\)` and `\[...\]` are recognized as well.
 //! Content inside is not parsed for inline markup.
 
+use super::code_span::pos_in_spans;
 use super::marks::{Mark, flags};
+use alloc::vec::Vec;
 
 /// A resolved math span.
 #[derive(Debug, Clone, Copy)]
@@ -16,7 +19,7 @@ pub struct MathSpan {
     pub closer_pos: u32,
     /// End of closing delimiter.
     pub closer_end: u32,
-    /// Whether this is display math ($$) vs inline ($).
+    /// Whether this is display math (`$$`, `\[`) vs inline (`$`, `\(`).
     pub is_display: bool,
 }
 
@@ -30,12 +33,29 @@ impl MathSpan {
 /// Resolve math spans in mark buffer.
 /// Similar to code span resolution: `$` matches `$`, `$$` matches `$$`.
 /// Marks everything between as IN_CODE to prevent further inline parsing.
-pub fn resolve_math_spans(marks: &mut [Mark], text: &[u8]) -> Vec<MathSpan> {
+/// Like code spans, math spans do not start inside `html_spans`.
+pub fn resolve_math_spans(
+    marks: &mut [Mark],
+    text: &[u8],
+    html_spans: &[(u32, u32)],
+) -> Vec<MathSpan> {
     let mut spans = Vec::new();
     let len = marks.len();
 
     for i in 0..len {
-        if marks[i].ch != b'$' || marks[i].is_resolved() || marks[i].flags & flags::IN_CODE != 0 {
+        if marks[i].is_resolved() || marks[i].flags & flags::IN_CODE != 0 {
+            continue;
+        }
+        if matches!(marks[i].ch, b'$' | b'\\') && pos_in_spans(marks[i].pos, html_spans) {
+            continue;
+        }
+        if marks[i].ch == b'\\' {
+            if let Some(span) = resolve_latex_span(marks, text, i) {
+                spans.push(span);
+            }
+            continue;
+        }
+        if marks[i].ch != b'$' {
             continue;
         }
 
@@ -52,6 +72,10 @@ pub fn resolve_math_spans(marks: &mut [Mark], text: &[u8]) -> Vec<MathSpan> {
 
         // Look for matching closer with same dollar sign count
         for j in (i + 1)..len {
+            // A code span between the delimiters takes precedence
+            if marks[j].ch == b'`' && marks[j].is_resolved() {
+                break;
+            }
             if marks[j].ch != b'$' || marks[j].is_resolved() {
                 continue;
             }
@@ -98,6 +122,46 @@ pub fn resolve_math_spans(marks: &mut [Mark], text: &[u8]) -> Vec<MathSpan> {
     spans
 }
 
+/// Match a `\(` or `\[` escape mark at `i` with the next `\)` or `\]`.
+///
+/// Escape marks cover the backslash and the escaped byte, so an escaped
+/// backslash (`\\(`) never produces an opener. Both delimiters are flagged
+/// IN_CODE so they are not emitted as escapes. A code span between the
+/// delimiters takes precedence and cancels the match.
+fn resolve_latex_span(marks: &mut [Mark], text: &[u8], i: usize) -> Option<MathSpan> {
+    let closer = match text[marks[i].pos as usize + 1] {
+        b'(' => b')',
+        b'[' => b']',
+        _ => return None,
+    };
+
+    for j in (i + 1)..marks.len() {
+        match marks[j].ch {
+            b'`' if marks[j].is_resolved() => return None,
+            b'\\'
+                if text[marks[j].pos as usize + 1] == closer
+                    && marks[j].flags & flags::IN_CODE == 0 =>
+            {
+                marks[i].resolve();
+                marks[j].resolve();
+                for mark in &mut marks[i..=j] {
+                    mark.flags |= flags::IN_CODE;
+                }
+                return Some(MathSpan {
+                    opener_pos: marks[i].pos,
+                    opener_end: marks[i].end,
+                    closer_pos: marks[j].pos,
+                    closer_end: marks[j].end,
+                    is_display: closer == b']',
+                });
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -108,7 +172,7 @@ mod tests {
         let text = b"hello $x^2$ world";
         let mut buffer = MarkBuffer::new();
         collect_marks(text, &mut buffer);
-        let spans = resolve_math_spans(buffer.marks_mut(), text);
+        let spans = resolve_math_spans(buffer.marks_mut(), text, &[]);
         assert_eq!(spans.len(), 1);
         assert!(!spans[0].is_display);
         let (start, end) = spans[0].content_range();
@@ -120,7 +184,7 @@ mod tests {
         let text = b"hello $$E=mc^2$$ world";
         let mut buffer = MarkBuffer::new();
         collect_marks(text, &mut buffer);
-        let spans = resolve_math_spans(buffer.marks_mut(), text);
+        let spans = resolve_math_spans(buffer.marks_mut(), text, &[]);
         assert_eq!(spans.len(), 1);
         assert!(spans[0].is_display);
         let (start, end) = spans[0].content_range();
@@ -132,7 +196,7 @@ mod tests {
         let text = b"hello $ world";
         let mut buffer = MarkBuffer::new();
         collect_marks(text, &mut buffer);
-        let spans = resolve_math_spans(buffer.marks_mut(), text);
+        let spans = resolve_math_spans(buffer.marks_mut(), text, &[]);
         assert_eq!(spans.len(), 0);
     }
 
@@ -141,7 +205,7 @@ mod tests {
         let text = b"hello \\$x\\$ world";
         let mut buffer = MarkBuffer::new();
         collect_marks(text, &mut buffer);
-        let spans = resolve_math_spans(buffer.marks_mut(), text);
+        let spans = resolve_math_spans(buffer.marks_mut(), text, &[]);
         assert_eq!(spans.len(), 0);
     }
 
@@ -150,7 +214,20 @@ mod tests {
         let text = b"$a$ and $b$";
         let mut buffer = MarkBuffer::new();
         collect_marks(text, &mut buffer);
-        let spans = resolve_math_spans(buffer.marks_mut(), text);
+        let spans = resolve_math_spans(buffer.marks_mut(), text, &[]);
         assert_eq!(spans.len(), 2);
     }
+
+    #[test]
+    fn test_latex_delimiters() {
+        let text = b"\\(a\\) and \\[b\\]";
+        let mut buffer = MarkBuffer::new();
+        collect_marks(text, &mut buffer);
+        let spans = resolve_math_spans(buffer.marks_mut(), text, &[]);
+        assert_eq!(spans.len(), 2);
+        assert!(!spans[0].is_display);
+        assert!(spans[1].is_display);
+        let (start, end) = spans[1].content_range();
+        assert_eq!(&text[start as usize..end as usize], b"b");
+    }
 }
@@ -6,39 +6,55 @@
 //! 3. Event Emission: Walk resolved marks and emit events
 
 mod code_span;
+mod custom;
+mod emoji;
 mod emphasis;
 pub mod event;
 mod highlight;
+mod inline_footnote;
 mod links;
 pub mod marks;
 mod math;
 mod simd;
+mod smart;
+mod span;
 mod strikethrough;
 mod subscript;
 mod superscript;
+mod wikilink;
 
+use alloc::{string::String, vec::Vec};
+pub use custom::CustomDelimiter;
 pub use event::InlineEvent;
 pub use links::AutolinkLiteralKind;
 
-use crate::Range;
+pub(crate) use emoji::shortcode;
+
 use crate::footnote::{FootnoteStore, normalize_footnote_label};
+use crate::limits;
 use crate::link_ref::LinkRefStore;
+use crate::{Options, Range};
 use code_span::{CodeSpan, extract_code_spans, resolve_code_spans};
+use custom::{CustomMatch, resolve_custom_delimiters_into};
+use emoji::expand_emoji_text_events;
 use emphasis::{EmphasisMatch, EmphasisStacks, resolve_emphasis_with_stacks_into};
 use highlight::{HighlightMatch, resolve_highlight_into};
+use inline_footnote::{InlineFootnote, resolve_inline_footnotes_into};
 use links::{
     Autolink, AutolinkLiteral, Link, RefLink, find_autolink_literals_into, find_autolinks_into,
     resolve_links_into, resolve_reference_links_into,
 };
 use marks::{
     Mark, MarkBuffer, MarkSummary, collect_marks, collect_marks_highlight,
-    collect_marks_highlight_superscript, collect_marks_superscript, flags,
+    collect_marks_highlight_superscript, collect_marks_superscript, escaped_spaces, flags,
 };
 use math::{MathSpan, resolve_math_spans};
 use memchr::memchr;
+use smart::smarten_text_events;
 use strikethrough::{StrikethroughMatch, resolve_strikethrough_into};
 use subscript::{SubscriptMatch, resolve_subscript_into};
 use superscript::{SuperscriptMatch, resolve_superscript_into};
+use wikilink::{WikiLink, resolve_wikilinks_into};
 
 /// Inline parser state.
 pub struct InlineParser {
@@ -77,6 +93,30 @@ pub struct InlineParser {
     html_autolink_ranges: Vec<(usize, usize)>,
     footnote_refs: Vec<FootnoteRef>,
     math_spans: Vec<MathSpan>,
+    wikilinks: Vec<WikiLink>,
+    inline_footnotes: Vec<InlineFootnote>,
+    /// Ranges where wikilink and inline footnote brackets do not count.
+    bracket_excluded: Vec<(u32, u32)>,
+    custom_delimiters: &'static [CustomDelimiter],
+    custom_excluded: Vec<(u32, u32)>,
+    custom_pairs: Vec<(u32, u32)>,
+    custom_openers: Vec<usize>,
+    custom_matches: Vec<CustomMatch>,
+    allow_nested_links: bool,
+    /// Whether `~text~` is strikethrough as well as `~~text~~`.
+    strikethrough_single_tilde: bool,
+    /// Whether text gets typographic quotes, dashes and ellipses.
+    smart_punctuation: bool,
+    /// Whether `^[note]` forms an inline footnote when footnotes are on.
+    parse_inline_footnotes: bool,
+    /// Whether `[[Page]]` forms a wikilink.
+    parse_wikilinks: bool,
+    /// Whether `:shortcode:` in text becomes an emoji.
+    expand_emoji: bool,
+    #[cfg(feature = "mdx")]
+    mdx_text: bool,
+    record_spans: bool,
+    spans: Vec<Range>,
 }
 
 impl InlineParser {
@@ -117,9 +157,193 @@ impl InlineParser {
             html_autolink_ranges: Vec::with_capacity(8),
             footnote_refs: Vec::new(),
             math_spans: Vec::new(),
+            wikilinks: Vec::new(),
+            inline_footnotes: Vec::new(),
+            bracket_excluded: Vec::new(),
+            custom_delimiters: &[],
+            custom_excluded: Vec::new(),
+            custom_pairs: Vec::new(),
+            custom_openers: Vec::new(),
+            custom_matches: Vec::new(),
+            allow_nested_links: false,
+            strikethrough_single_tilde: false,
+            smart_punctuation: false,
+            parse_inline_footnotes: false,
+            parse_wikilinks: false,
+            expand_emoji: false,
+            #[cfg(feature = "mdx")]
+            mdx_text: false,
+            record_spans: false,
+            spans: Vec::new(),
         }
     }
 
+    /// Clear the internal buffers and release capacity above `capacity`
+    /// entries each.
+    ///
+    /// Buffers grow to fit the largest text parsed and keep that capacity,
+    /// which avoids allocation churn when a parser is reused. After an
+    /// unusually large document, call this to return to a bounded footprint;
+    /// the next parse regrows whatever it needs. Shrinking after every parse
+    /// trades memory for reallocations.
+    pub fn shrink_to(&mut self, capacity: usize) {
+        macro_rules! shrink {
+            ($($field:ident),* $(,)?) => {$(
+                self.$field.clear();
+                self.$field.shrink_to(capacity);
+            )*};
+        }
+        shrink!(
+            mark_buffer,
+            open_brackets,
+            close_brackets,
+            autolinks,
+            html_spans,
+            html_ranges,
+            link_dest_ranges,
+            autolink_ranges,
+            code_spans,
+            link_boundaries,
+            resolved_links,
+            link_formed_opens,
+            link_inactive_opens,
+            link_used_closes,
+            ref_links,
+            ref_label_buf,
+            ref_formed_opens,
+            ref_used_closes,
+            ref_occupied,
+            autolink_literals,
+            emphasis_stacks,
+            emphasis_matches,
+            strikethrough_matches,
+            subscript_matches,
+            superscript_matches,
+            highlight_matches,
+            al_code_span_ranges,
+            al_link_ranges,
+            emit_points,
+            emit_suppress_ranges,
+            html_code_ranges,
+            html_autolink_ranges,
+            footnote_refs,
+            math_spans,
+            wikilinks,
+            inline_footnotes,
+            bracket_excluded,
+            custom_excluded,
+            custom_pairs,
+            custom_openers,
+            custom_matches,
+            spans,
+        );
+    }
+
+    /// Clear the internal buffers and release all of their capacity.
+    ///
+    /// See [`Self::shrink_to`] for the tradeoff.
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    /// Override the maximum number of inline delimiter marks
+    /// ([`limits::MAX_INLINE_MARKS`](crate::limits::MAX_INLINE_MARKS) by default).
+    ///
+    /// Delimiters past the limit are treated as literal text.
+    pub fn set_mark_limit(&mut self, limit: usize) {
+        self.mark_buffer.set_limit(limit);
+    }
+
+    /// Register custom inline delimiters, resolved after every built-in
+    /// construct. See [`Options::custom_delimiters`](crate::Options::custom_delimiters).
+    pub fn set_custom_delimiters(&mut self, delimiters: &'static [CustomDelimiter]) {
+        self.custom_delimiters = delimiters;
+    }
+
+    /// Let links contain other links instead of deactivating the outer one.
+    /// See [`Options::allow_nested_links`](crate::Options::allow_nested_links).
+    pub fn set_allow_nested_links(&mut self, allow: bool) {
+        self.allow_nested_links = allow;
+    }
+
+    /// Let single-tilde runs form strikethrough as well as double ones.
+    /// See [`Options::strikethrough_single_tilde`](crate::Options::strikethrough_single_tilde).
+    pub fn set_strikethrough_single_tilde(&mut self, on: bool) {
+        self.strikethrough_single_tilde = on;
+    }
+
+    /// Replace straight quotes, `--`, `---` and `...` in text with
+    /// typographic punctuation.
+    /// See [`Options::smart_punctuation`](crate::Options::smart_punctuation).
+    pub fn set_smart_punctuation(&mut self, on: bool) {
+        self.smart_punctuation = on;
+    }
+
+    /// Resolve inline footnotes (`^[note]`) when parsing with a footnote
+    /// store. See [`Options::inline_footnotes`](crate::Options::inline_footnotes).
+    pub fn set_inline_footnotes(&mut self, on: bool) {
+        self.parse_inline_footnotes = on;
+    }
+
+    /// Resolve `[[Page]]` and `[[Page|label]]` wikilinks.
+    /// See [`Options::wikilinks`](crate::Options::wikilinks).
+    pub fn set_wikilinks(&mut self, on: bool) {
+        self.parse_wikilinks = on;
+    }
+
+    /// Replace `:shortcode:` emoji shortcodes in text with the emoji.
+    /// See [`Options::emoji`](crate::Options::emoji).
+    pub fn set_emoji(&mut self, on: bool) {
+        self.expand_emoji = on;
+    }
+
+    /// Apply the parser settings of `options`: the mark limit, custom
+    /// delimiters and the inline extensions that are not parse arguments.
+    pub(crate) fn configure(&mut self, options: &Options) {
+        if let Some(limit) = options.max_inline_marks {
+            self.set_mark_limit(limit);
+        }
+        self.set_custom_delimiters(options.custom_delimiters);
+        self.set_allow_nested_links(options.allow_nested_links);
+        self.set_strikethrough_single_tilde(options.strikethrough_single_tilde);
+        self.set_smart_punctuation(options.smart_punctuation);
+        self.set_inline_footnotes(options.inline_footnotes);
+        self.set_wikilinks(options.wikilinks);
+        self.set_emoji(options.emoji);
+    }
+
+    /// Split inline MDX expressions and JSX tags out of text on every parse,
+    /// like [`Self::parse_mdx`], while keeping the caller's other inline
+    /// options. Inline HTML is disabled while this is on.
+    #[cfg(feature = "mdx")]
+    pub(crate) fn set_mdx_text(&mut self, on: bool) {
+        self.mdx_text = on;
+    }
+
+    #[cfg(feature = "mdx")]
+    pub(crate) fn mdx_text(&self) -> bool {
+        self.mdx_text
+    }
+
+    /// Record the source span of every emitted event.
+    ///
+    /// Off by default. When enabled, [`Self::spans`] returns one byte range
+    /// per event pushed by the next parse call, relative to its `text`.
+    pub fn set_record_spans(&mut self, record: bool) {
+        self.record_spans = record;
+    }
+
+    /// Source spans for the events pushed by the last parse call.
+    ///
+    /// `spans()[i]` belongs to the `i`-th pushed event: start and end events
+    /// carry the span of the whole construct (`*a*`, `[a](b)`), events with
+    /// a range their own range, and the rest the source they replace (an
+    /// escape, a shortcode, a line break). Empty unless
+    /// [`Self::set_record_spans`] was enabled.
+    pub fn spans(&self) -> &[Range] {
+        &self.spans
+    }
+
     /// Parse inline content and emit events.
     pub fn parse(
         &mut self,
@@ -153,7 +377,8 @@ impl InlineParser {
         self.parse_with_options(
             text, link_refs, false, true, false, false, false, true, false, None, events,
         );
-        split_mdx_text_events(text, events, new_events_start);
+        let spans = self.record_spans.then_some(&mut self.spans);
+        split_mdx_text_events(text, events, new_events_start, spans);
     }
 
     /// Parse inline content with configurable inline extensions.
@@ -171,6 +396,59 @@ impl InlineParser {
         math: bool,
         footnote_store: Option<&FootnoteStore>,
         events: &mut Vec<InlineEvent>,
+    ) {
+        let new_events_start = events.len();
+        self.spans.clear();
+        #[cfg(feature = "mdx")]
+        let allow_html = allow_html && !self.mdx_text;
+        self.parse_events(
+            text,
+            link_refs,
+            allow_html,
+            strikethrough,
+            highlight,
+            superscript,
+            subscript,
+            autolink_literals,
+            math,
+            self.parse_wikilinks,
+            footnote_store,
+            events,
+        );
+        if self.record_spans {
+            span::pair_spans(&events[new_events_start..], &mut self.spans);
+        }
+        // Shortcodes are expanded in emitted text only, after every other construct
+        if self.expand_emoji {
+            let spans = self.record_spans.then_some(&mut self.spans);
+            expand_emoji_text_events(text, events, new_events_start, spans);
+        }
+        #[cfg(feature = "mdx")]
+        if self.mdx_text {
+            let spans = self.record_spans.then_some(&mut self.spans);
+            split_mdx_text_events(text, events, new_events_start, spans);
+        }
+        if self.smart_punctuation {
+            let spans = self.record_spans.then_some(&mut self.spans);
+            smarten_text_events(text, events, new_events_start, spans);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn parse_events(
+        &mut self,
+        text: &[u8],
+        link_refs: Option<&LinkRefStore>,
+        allow_html: bool,
+        strikethrough: bool,
+        highlight: bool,
+        superscript: bool,
+        subscript: bool,
+        autolink_literals: bool,
+        math: bool,
+        wikilinks: bool,
+        footnote_store: Option<&FootnoteStore>,
+        events: &mut Vec<InlineEvent>,
     ) {
         #[cfg(feature = "profiling")]
         let event_start = events.len();
@@ -186,10 +464,17 @@ impl InlineParser {
 
         // Check for potential autolink literal triggers when enabled
         let may_have_autolinks = autolink_literals && has_autolink_candidates(text);
+        let may_have_custom = self
+            .custom_delimiters
+            .iter()
+            .any(|d| !d.open.is_empty() && memchr::memmem::find(text, d.open.as_bytes()).is_some());
 
-        if !has_specials && !may_have_autolinks {
+        if !has_specials && !may_have_autolinks && !may_have_custom {
             if !text.is_empty() {
                 events.push(InlineEvent::Text(Range::from_usize(0, text.len())));
+                if self.record_spans {
+                    self.spans.push(Range::from_usize(0, text.len()));
+                }
             }
             #[cfg(feature = "profiling")]
             crate::profiling::record_inline_parse(
@@ -221,10 +506,13 @@ impl InlineParser {
             MarkSummary::default()
         };
 
-        if self.mark_buffer.is_empty() && !may_have_autolinks {
+        if self.mark_buffer.is_empty() && !may_have_autolinks && !may_have_custom {
             // No special characters and no autolink candidates, emit as plain text
             if !text.is_empty() {
                 events.push(InlineEvent::Text(Range::from_usize(0, text.len())));
+                if self.record_spans {
+                    self.spans.push(Range::from_usize(0, text.len()));
+                }
             }
             #[cfg(feature = "profiling")]
             crate::profiling::record_inline_parse(
@@ -279,7 +567,8 @@ impl InlineParser {
 
         // Math spans (after code spans, before links; gated on math option)
         if math && summary.has_math() {
-            self.math_spans = resolve_math_spans(self.mark_buffer.marks_mut(), text);
+            self.math_spans =
+                resolve_math_spans(self.mark_buffer.marks_mut(), text, &self.html_ranges);
         } else {
             self.math_spans.clear();
         }
@@ -291,6 +580,17 @@ impl InlineParser {
                 .iter()
                 .any(|cs| al.start >= cs.opener_pos && al.start < cs.closer_end)
         });
+        // Math spans hide tags and autolinks the same way
+        if !self.math_spans.is_empty() {
+            let math_spans = &self.math_spans;
+            let in_math = |pos: u32| {
+                math_spans
+                    .iter()
+                    .any(|ms| pos >= ms.opener_pos && pos < ms.closer_end)
+            };
+            self.html_spans.retain(|span| !in_math(span.start));
+            self.autolinks.retain(|al| !in_math(al.start));
+        }
 
         // Fourth: collect bracket positions and detect inline delimiter candidates in one pass
         Self::collect_brackets(
@@ -298,7 +598,7 @@ impl InlineParser {
             &mut self.open_brackets,
             &mut self.close_brackets,
         );
-        let has_brackets = !self.open_brackets.is_empty() && !self.close_brackets.is_empty();
+        let mut has_brackets = !self.open_brackets.is_empty() && !self.close_brackets.is_empty();
         self.open_brackets
             .retain(|&(pos, _)| !pos_in_spans(pos, &self.html_spans));
         // Filter out close brackets that are inside autolinks - they can't close links
@@ -309,6 +609,72 @@ impl InlineParser {
                 .any(|al| pos > al.start && pos < al.end)
                 && !pos_in_spans(pos, &self.html_spans)
         });
+
+        // Wikilinks and inline footnotes claim their brackets before bracket
+        // links see them
+        self.wikilinks.clear();
+        self.inline_footnotes.clear();
+        let inline_footnotes = self.parse_inline_footnotes && footnote_store.is_some();
+        if (wikilinks || inline_footnotes) && has_brackets {
+            self.bracket_excluded.clear();
+            self.bracket_excluded.extend(
+                self.code_spans
+                    .iter()
+                    .map(|cs| (cs.opener_pos, cs.closer_end)),
+            );
+            self.bracket_excluded.extend(
+                self.math_spans
+                    .iter()
+                    .map(|ms| (ms.opener_pos, ms.closer_end)),
+            );
+            self.bracket_excluded
+                .extend(self.html_spans.iter().map(|span| (span.start, span.end)));
+            self.bracket_excluded
+                .extend(self.autolinks.iter().map(|al| (al.start, al.end)));
+        }
+        if wikilinks && has_brackets {
+            resolve_wikilinks_into(
+                text,
+                &self.open_brackets,
+                &self.close_brackets,
+                &self.bracket_excluded,
+                &mut self.wikilinks,
+            );
+            if !self.wikilinks.is_empty() {
+                let wikilinks = &self.wikilinks;
+                let in_wikilink =
+                    |pos: u32| wikilinks.iter().any(|wl| pos >= wl.start && pos < wl.end);
+                self.open_brackets.retain(|&(pos, _)| !in_wikilink(pos));
+                self.close_brackets.retain(|&pos| !in_wikilink(pos));
+                has_brackets = !self.open_brackets.is_empty() && !self.close_brackets.is_empty();
+            }
+        }
+        if inline_footnotes && has_brackets {
+            resolve_inline_footnotes_into(
+                text,
+                &self.open_brackets,
+                &self.close_brackets,
+                &self.bracket_excluded,
+                &mut self.inline_footnotes,
+            );
+            if !self.inline_footnotes.is_empty() {
+                let notes = &self.inline_footnotes;
+                let in_note = |pos: u32| notes.iter().any(|n| pos >= n.start && pos < n.end);
+                self.open_brackets.retain(|&(pos, _)| !in_note(pos));
+                self.close_brackets.retain(|&pos| !in_note(pos));
+                has_brackets = !self.open_brackets.is_empty() && !self.close_brackets.is_empty();
+                // The `^` belongs to the note, not to a superscript
+                for mark in self.mark_buffer.marks_mut() {
+                    if mark.ch == b'^'
+                        && notes
+                            .iter()
+                            .any(|n| n.start >= mark.pos && n.start < mark.end)
+                    {
+                        mark.resolve();
+                    }
+                }
+            }
+        }
         let has_inline_link_candidate = has_brackets && has_inline_link_opener(text);
         if has_inline_link_candidate {
             resolve_links_into(
@@ -319,11 +685,11 @@ impl InlineParser {
                 &mut self.link_formed_opens,
                 &mut self.link_inactive_opens,
                 &mut self.link_used_closes,
+                self.allow_nested_links,
             );
         } else {
             self.resolved_links.clear();
         }
-        let resolved_links = &self.resolved_links;
 
         if has_brackets {
             if let Some(defs) = link_refs.filter(|defs| !defs.is_empty()) {
@@ -331,21 +697,49 @@ impl InlineParser {
                     text,
                     &self.open_brackets,
                     &self.close_brackets,
-                    resolved_links,
+                    &self.resolved_links,
                     defs,
                     &mut self.ref_links,
                     &mut self.ref_label_buf,
                     &mut self.ref_formed_opens,
                     &mut self.ref_used_closes,
                     &mut self.ref_occupied,
+                    self.allow_nested_links,
                 );
+                // Like cmark, a reference link inside the text of an inline
+                // link deactivates the outer link; its brackets become text
+                let links_before = self.resolved_links.len();
+                let ref_links = &self.ref_links;
+                if !self.allow_nested_links {
+                    self.resolved_links
+                        .retain(|link| link.is_image || !contains_ref_link(ref_links, link));
+                }
+                if self.resolved_links.len() != links_before {
+                    resolve_reference_links_into(
+                        text,
+                        &self.open_brackets,
+                        &self.close_brackets,
+                        &self.resolved_links,
+                        defs,
+                        &mut self.ref_links,
+                        &mut self.ref_label_buf,
+                        &mut self.ref_formed_opens,
+                        &mut self.ref_used_closes,
+                        &mut self.ref_occupied,
+                        false,
+                    );
+                }
+                for link in &self.ref_links {
+                    defs.mark_used(link.def_index);
+                }
             } else {
                 self.ref_links.clear();
             }
-            filter_html_spans_in_link_destinations(&mut self.html_spans, resolved_links);
+            filter_html_spans_in_link_destinations(&mut self.html_spans, &self.resolved_links);
         } else {
             self.ref_links.clear();
         }
+        let resolved_links = &self.resolved_links;
         let resolved_ref_links = &self.ref_links;
 
         self.link_dest_ranges.clear();
@@ -383,7 +777,9 @@ impl InlineParser {
                 + resolved_ref_links.len()
                 + self.autolinks.len()
                 + self.html_spans.len()
-                + self.footnote_refs.len(),
+                + self.footnote_refs.len()
+                + self.wikilinks.len()
+                + self.inline_footnotes.len(),
         );
         self.link_boundaries
             .extend(resolved_links.iter().map(|l| (l.start, l.text_end)));
@@ -402,6 +798,14 @@ impl InlineParser {
         for fref in &self.footnote_refs {
             self.link_boundaries.push((fref.start, fref.end));
         }
+        // Also include wikilinks, whose labels are plain text
+        for wl in &self.wikilinks {
+            self.link_boundaries.push((wl.start, wl.end));
+        }
+        // And inline footnotes, whose text is parsed separately
+        for note in &self.inline_footnotes {
+            self.link_boundaries.push((note.start, note.end));
+        }
         let emphasis_matches = if summary.has_emphasis() {
             resolve_emphasis_with_stacks_into(
                 self.mark_buffer.marks_mut(),
@@ -420,6 +824,7 @@ impl InlineParser {
             resolve_strikethrough_into(
                 self.mark_buffer.marks_mut(),
                 &self.link_boundaries,
+                self.strikethrough_single_tilde,
                 &mut self.strikethrough_matches,
             );
         } else {
@@ -478,6 +883,11 @@ impl InlineParser {
                     .iter()
                     .map(|cs| (cs.opener_pos, cs.closer_end)),
             );
+            self.al_code_span_ranges.extend(
+                self.math_spans
+                    .iter()
+                    .map(|ms| (ms.opener_pos, ms.closer_end)),
+            );
             // Build link ranges (inline links + ref links) (reuse Vec)
             self.al_link_ranges.clear();
             self.al_link_ranges
@@ -488,6 +898,12 @@ impl InlineParser {
             for link in resolved_ref_links {
                 self.al_link_ranges.push((link.start, link.end));
             }
+            for wl in &self.wikilinks {
+                self.al_link_ranges.push((wl.start, wl.end));
+            }
+            for note in &self.inline_footnotes {
+                self.al_link_ranges.push((note.start, note.end));
+            }
             find_autolink_literals_into(
                 text,
                 &self.al_code_span_ranges,
@@ -500,6 +916,77 @@ impl InlineParser {
             self.autolink_literals.clear();
         }
 
+        // Twelfth: custom delimiters, on whatever the built-ins left as text
+        self.custom_matches.clear();
+        if may_have_custom {
+            // Built-ins win: exclude everything they claimed
+            let excluded = &mut self.custom_excluded;
+            let pairs = &mut self.custom_pairs;
+            excluded.clear();
+            pairs.clear();
+
+            excluded.extend(
+                self.code_spans
+                    .iter()
+                    .map(|cs| (cs.opener_pos, cs.closer_end)),
+            );
+            excluded.extend(
+                self.math_spans
+                    .iter()
+                    .map(|ms| (ms.opener_pos, ms.closer_end)),
+            );
+            excluded.extend(self.html_ranges.iter().copied());
+            excluded.extend(self.autolink_ranges.iter().copied());
+            excluded.extend(self.link_dest_ranges.iter().copied());
+            excluded.extend(self.autolink_literals.iter().map(|al| (al.start, al.end)));
+            excluded.extend(self.footnote_refs.iter().map(|f| (f.start, f.end)));
+            excluded.extend(self.wikilinks.iter().map(|wl| (wl.start, wl.end)));
+            excluded.extend(self.inline_footnotes.iter().map(|n| (n.start, n.end)));
+            excluded.extend(
+                self.mark_buffer
+                    .marks()
+                    .iter()
+                    .filter(|m| m.ch == b'\\' && m.flags & flags::POTENTIAL_OPENER != 0)
+                    .map(|m| (m.pos, m.end)),
+            );
+
+            for link in resolved_links {
+                let opener_len = if link.is_image { 2 } else { 1 };
+                excluded.push((link.start, link.start + opener_len));
+                excluded.push((link.text_end, link.end));
+                pairs.push((link.start, link.end));
+            }
+            for link in resolved_ref_links {
+                let opener_len = if link.is_image { 2 } else { 1 };
+                excluded.push((link.start, link.start + opener_len));
+                excluded.push((link.text_end, link.end));
+                pairs.push((link.start, link.end));
+            }
+
+            macro_rules! claim {
+                ($matches:expr) => {
+                    for m in $matches {
+                        excluded.push((m.opener_start, m.opener_end));
+                        excluded.push((m.closer_start, m.closer_end));
+                        pairs.push((m.opener_start, m.closer_end));
+                    }
+                };
+            }
+            claim!(emphasis_matches);
+            claim!(strikethrough_matches);
+            claim!(subscript_matches);
+            claim!(superscript_matches);
+            claim!(highlight_matches);
+            resolve_custom_delimiters_into(
+                text,
+                self.custom_delimiters,
+                &mut self.custom_excluded,
+                &mut self.custom_pairs,
+                &mut self.custom_openers,
+                &mut self.custom_matches,
+            );
+        }
+
         // Phase 3: Emit events
         let marks = self.mark_buffer.marks();
         Self::emit_events(
@@ -512,6 +999,7 @@ impl InlineParser {
             subscript_matches,
             superscript_matches,
             highlight_matches,
+            &self.custom_matches,
             &self.autolink_literals,
             resolved_links,
             resolved_ref_links,
@@ -521,9 +1009,12 @@ impl InlineParser {
             &self.autolink_ranges,
             &self.html_ranges,
             &self.footnote_refs,
+            &self.wikilinks,
+            &self.inline_footnotes,
             &mut self.emit_points,
             &mut self.emit_suppress_ranges,
             events,
+            self.record_spans.then_some(&mut self.spans),
         );
         #[cfg(feature = "profiling")]
         crate::profiling::record_inline_parse(
@@ -590,8 +1081,9 @@ impl InlineParser {
                 }
             }
 
-            if label_end == label_start {
-                continue; // Empty label
+            // The caret counts towards the label length
+            if label_end == label_start || label_end - caret_pos > limits::MAX_REF_LABEL_LEN {
+                continue; // Empty or over-long label
             }
 
             // Must be followed by `]`
@@ -662,6 +1154,7 @@ impl InlineParser {
         subscript_matches: &[SubscriptMatch],
         superscript_matches: &[SuperscriptMatch],
         highlight_matches: &[HighlightMatch],
+        custom_matches: &[CustomMatch],
         autolink_literals: &[AutolinkLiteral],
         resolved_links: &[Link],
         resolved_ref_links: &[RefLink],
@@ -671,9 +1164,12 @@ impl InlineParser {
         autolink_ranges: &[(u32, u32)],
         html_ranges: &[(u32, u32)],
         footnote_refs: &[FootnoteRef],
+        wikilinks: &[WikiLink],
+        inline_footnotes: &[InlineFootnote],
         emit_points: &mut Vec<EmitPoint>,
         suppress_ranges: &mut Vec<(u32, u32)>,
         events: &mut Vec<InlineEvent>,
+        mut spans: Option<&mut Vec<Range>>,
     ) {
         let mut pos = 0u32;
         let text_len = text.len() as u32;
@@ -695,7 +1191,10 @@ impl InlineParser {
             + (strikethrough_matches.len() * 2)
             + (subscript_matches.len() * 2)
             + (superscript_matches.len() * 2)
-            + (highlight_matches.len() * 2);
+            + (highlight_matches.len() * 2)
+            + (custom_matches.len() * 2)
+            + wikilinks.len()
+            + inline_footnotes.len();
         emit_points.clear();
         emit_points.reserve(estimated_events.max(8));
         events.reserve(estimated_events.max(8) + 4);
@@ -932,6 +1431,13 @@ impl InlineParser {
                 kind: EmitKind::SubscriptEnd,
                 end: m.closer_end,
             });
+            for pos in escaped_spaces(text, m.opener_end, m.closer_start) {
+                emit_points.push(EmitPoint {
+                    pos,
+                    kind: EmitKind::Escape(b' '),
+                    end: pos + 2,
+                });
+            }
         }
 
         // Add superscript events
@@ -946,6 +1452,13 @@ impl InlineParser {
                 kind: EmitKind::SuperscriptEnd,
                 end: m.closer_end,
             });
+            for pos in escaped_spaces(text, m.opener_end, m.closer_start) {
+                emit_points.push(EmitPoint {
+                    pos,
+                    kind: EmitKind::Escape(b' '),
+                    end: pos + 2,
+                });
+            }
         }
 
         // Add highlight events
@@ -962,6 +1475,20 @@ impl InlineParser {
             });
         }
 
+        // Add custom delimiter events
+        for m in custom_matches {
+            emit_points.push(EmitPoint {
+                pos: m.opener_start,
+                kind: EmitKind::CustomStart(m.index),
+                end: m.opener_end,
+            });
+            emit_points.push(EmitPoint {
+                pos: m.closer_start,
+                kind: EmitKind::CustomEnd(m.index),
+                end: m.closer_end,
+            });
+        }
+
         // Add footnote reference events
         for fref in footnote_refs {
             emit_points.push(EmitPoint {
@@ -973,6 +1500,32 @@ impl InlineParser {
             });
         }
 
+        // Add wikilink events
+        for wl in wikilinks {
+            emit_points.push(EmitPoint {
+                pos: wl.start,
+                kind: EmitKind::WikiLink {
+                    target_start: wl.target_start,
+                    target_end: wl.target_end,
+                    label_start: wl.label_start,
+                    label_end: wl.label_end,
+                },
+                end: wl.end,
+            });
+        }
+
+        // Add inline footnote events
+        for note in inline_footnotes {
+            emit_points.push(EmitPoint {
+                pos: note.start,
+                kind: EmitKind::InlineFootnote {
+                    content_start: note.content_start,
+                    content_end: note.content_end,
+                },
+                end: note.end,
+            });
+        }
+
         // Add backslash escapes and hard breaks
         // Note: Hard breaks inside code spans should not be processed
         for mark in marks {
@@ -1050,20 +1603,28 @@ impl InlineParser {
                         | EmitKind::SubscriptEnd
                         | EmitKind::SuperscriptEnd
                         | EmitKind::HighlightEnd
+                        | EmitKind::CustomEnd(_)
                         | EmitKind::LinkEnd
                         | EmitKind::ImageEnd
                 ),
             )
         });
 
-        // Build ranges to suppress (reference labels after link text)
+        // Build ranges to suppress (reference labels after link text, wikilink
+        // bodies, inline footnote text)
         suppress_ranges.clear();
-        suppress_ranges.reserve(resolved_ref_links.len());
+        suppress_ranges
+            .reserve(resolved_ref_links.len() + wikilinks.len() + inline_footnotes.len());
         for link in resolved_ref_links {
             if link.end > link.text_end {
                 suppress_ranges.push((link.text_end, link.end));
             }
         }
+        if !wikilinks.is_empty() || !inline_footnotes.is_empty() {
+            suppress_ranges.extend(wikilinks.iter().map(|wl| (wl.start, wl.end)));
+            suppress_ranges.extend(inline_footnotes.iter().map(|n| (n.start, n.end)));
+            suppress_ranges.sort_unstable();
+        }
 
         // Emit events in order
         let mut skip_until = 0u32;
@@ -1083,6 +1644,7 @@ impl InlineParser {
                     continue;
                 }
             }
+            let emitted = events.len();
             // Emit text before this point
             if point.pos > pos && point.pos > skip_until {
                 let text_start = pos.max(skip_until);
@@ -1183,6 +1745,15 @@ impl InlineParser {
                     events.push(InlineEvent::HighlightEnd);
                     skip_until = point.end;
                 }
+                EmitKind::CustomStart(index) => {
+                    events.push(InlineEvent::CustomStart(index));
+                    pos = point.end;
+                    skip_until = point.end;
+                }
+                EmitKind::CustomEnd(index) => {
+                    events.push(InlineEvent::CustomEnd(index));
+                    skip_until = point.end;
+                }
                 EmitKind::Escape(ch) => {
                     events.push(InlineEvent::EscapedChar(ch));
                     skip_until = point.end;
@@ -1279,6 +1850,28 @@ impl InlineParser {
                     events.push(InlineEvent::FootnoteRef { def_index });
                     skip_until = point.end;
                 }
+                EmitKind::WikiLink {
+                    target_start,
+                    target_end,
+                    label_start,
+                    label_end,
+                } => {
+                    events.push(InlineEvent::WikiLink {
+                        target: Range::from_usize(target_start as usize, target_end as usize),
+                        label: Range::from_usize(label_start as usize, label_end as usize),
+                    });
+                    skip_until = point.end;
+                }
+                EmitKind::InlineFootnote {
+                    content_start,
+                    content_end,
+                } => {
+                    events.push(InlineEvent::InlineFootnote(Range::from_usize(
+                        content_start as usize,
+                        content_end as usize,
+                    )));
+                    skip_until = point.end;
+                }
                 EmitKind::MathInline {
                     content_start,
                     content_end,
@@ -1301,6 +1894,16 @@ impl InlineParser {
                 }
             }
 
+            if let Some(spans) = spans.as_deref_mut() {
+                let point_end = match point.kind {
+                    EmitKind::AutolinkLiteral { end, .. } | EmitKind::HtmlRaw { end } => end,
+                    _ => point.end,
+                };
+                spans.extend(events[emitted..].iter().map(|event| match event {
+                    InlineEvent::Text(range) | InlineEvent::Code(range) => *range,
+                    _ => Range::new(point.pos, point_end),
+                }));
+            }
             pos = pos.max(point.end);
         }
 
@@ -1312,6 +1915,9 @@ impl InlineParser {
                     start as usize,
                     text_len as usize,
                 )));
+                if let Some(spans) = spans {
+                    spans.push(Range::new(start, text_len));
+                }
             }
         }
     }
@@ -1446,6 +2052,18 @@ fn has_inline_link_opener(input: &[u8]) -> bool {
     false
 }
 
+/// Whether a non-image reference link lies inside the text of `link`.
+///
+/// `ref_links` is sorted by start, so the scan stops at the end of the
+/// link text.
+fn contains_ref_link(ref_links: &[RefLink], link: &Link) -> bool {
+    let first = ref_links.partition_point(|r| r.start <= link.start);
+    ref_links[first..]
+        .iter()
+        .take_while(|r| r.start < link.text_end)
+        .any(|r| !r.is_image && r.end <= link.text_end)
+}
+
 impl Default for InlineParser {
     fn default() -> Self {
         Self::new()
@@ -1453,13 +2071,38 @@ impl Default for InlineParser {
 }
 
 #[cfg(feature = "mdx")]
-fn split_mdx_text_events(text: &[u8], events: &mut Vec<InlineEvent>, new_events_start: usize) {
+fn split_mdx_text_events(
+    text: &[u8],
+    events: &mut Vec<InlineEvent>,
+    new_events_start: usize,
+    mut spans: Option<&mut Vec<Range>>,
+) {
     let original_events = events.split_off(new_events_start);
+    let original_spans = spans.as_deref_mut().map(core::mem::take);
 
-    for event in original_events {
+    for (i, event) in original_events.into_iter().enumerate() {
         match event {
-            InlineEvent::Text(range) => split_mdx_text_range(text, range, events),
-            event => events.push(event),
+            InlineEvent::Text(range) => {
+                let split_start = events.len();
+                split_mdx_text_range(text, range, events);
+                // Every split event carries its own source range
+                if let Some(spans) = spans.as_deref_mut() {
+                    spans.extend(events[split_start..].iter().map(|event| match event {
+                        InlineEvent::Text(range)
+                        | InlineEvent::MdxExpression(range)
+                        | InlineEvent::MdxJsxOpen(range)
+                        | InlineEvent::MdxJsxClose(range)
+                        | InlineEvent::MdxJsxSelfClose(range) => *range,
+                        _ => range,
+                    }));
+                }
+            }
+            event => {
+                events.push(event);
+                if let (Some(spans), Some(original)) = (spans.as_deref_mut(), &original_spans) {
+                    spans.push(original[i]);
+                }
+            }
         }
     }
 }
@@ -1545,6 +2188,8 @@ enum EmitKind {
     SuperscriptEnd,
     HighlightStart,
     HighlightEnd,
+    CustomStart(u8), // delimiter index
+    CustomEnd(u8),
     Escape(u8),
     HardBreak,
     SoftBreak,
@@ -1586,6 +2231,10 @@ enum EmitKind {
     FootnoteRef {
         def_index: u32,
     },
+    InlineFootnote {
+        content_start: u32,
+        content_end: u32,
+    },
     MathInline {
         content_start: u32,
         content_end: u32,
@@ -1594,6 +2243,12 @@ enum EmitKind {
         content_start: u32,
         content_end: u32,
     },
+    WikiLink {
+        target_start: u32,
+        target_end: u32,
+        label_start: u32,
+        label_end: u32,
+    },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -1733,6 +2388,19 @@ fn is_escaped(text: &[u8], pos: usize) -> bool {
     backslashes % 2 == 1
 }
 
+/// HTML void elements, which have no content and never take an end tag.
+pub const VOID_ELEMENTS: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Whether `name` is an HTML void element (ASCII case-insensitive).
+pub fn is_void_element(name: &[u8]) -> bool {
+    VOID_ELEMENTS
+        .iter()
+        .any(|void| void.as_bytes().eq_ignore_ascii_case(name))
+}
+
 fn parse_inline_html(text: &[u8], start: usize) -> Option<usize> {
     if text.get(start) != Some(&b'<') {
         return None;
@@ -1948,6 +2616,26 @@ mod tests {
         events
     }
 
+    #[test]
+    fn test_shrink_to_releases_peak_capacity() {
+        let mut parser = InlineParser::new();
+        let mut events = Vec::new();
+        let large = "*a* [b](c) `d` ".repeat(2000);
+        parser.parse(large.as_bytes(), None, true, &mut events);
+        assert!(parser.emit_points.capacity() > 1000);
+
+        parser.shrink_to(16);
+        assert!(parser.emit_points.capacity() <= 16);
+        assert!(parser.mark_buffer.marks().is_empty());
+
+        // The parser stays usable and regrows on demand
+        events.clear();
+        parser.parse(b"*x*", None, true, &mut events);
+        assert_eq!(events, parse_inline("*x*"));
+        parser.shrink_to_fit();
+        assert_eq!(parser.emit_points.capacity(), 0);
+    }
+
     #[test]
     fn test_plain_text() {
         let events = parse_inline("hello world");
@@ -2010,7 +2698,7 @@ mod tests {
         // Double backticks should contain single backticks
         for event in &events {
             if let InlineEvent::Code(range) = event {
-                let content = std::str::from_utf8(range.slice(input.as_bytes())).unwrap();
+                let content = core::str::from_utf8(range.slice(input.as_bytes())).unwrap();
                 assert!(content.contains('`'));
             }
         }
@@ -2054,6 +2742,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_void_elements() {
+        for name in VOID_ELEMENTS {
+            assert!(is_void_element(name.as_bytes()), "{name}");
+        }
+        for name in [b"BR".as_slice(), b"Img", b"wbr"] {
+            assert!(is_void_element(name));
+        }
+        for name in [
+            b"a".as_slice(),
+            b"p",
+            b"div",
+            b"span",
+            b"script",
+            b"textarea",
+            b"brr",
+            b"b",
+            b"",
+        ] {
+            assert!(!is_void_element(name));
+        }
+    }
+
     #[test]
     fn test_html_tag_with_newline_in_attributes() {
         let input = "<a foo=\"bar\" bam = 'baz <em>\"</em>'\n_boolean zoop:33=zoop:33 />";
@@ -1,7 +1,7 @@
 //! SIMD helpers for inline parsing (AArch64 NEON).
 
 #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
-use std::arch::aarch64::*;
+use core::arch::aarch64::*;
 
 #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
 #[inline]
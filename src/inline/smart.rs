@@ -0,0 +1,214 @@
+//! Smart punctuation: curly quotes, dashes and ellipses.
+//!
+//! Runs after inline resolution over the emitted text events, like emoji
+//! expansion, so code spans, autolinks, raw HTML and escaped characters keep
+//! their ASCII punctuation. Quotes are paired the way cmark pairs them:
+//! flanking decides whether a quote can open or close, a closer matches the
+//! nearest open quote of its kind, and an unmatched `'` is an apostrophe.
+
+use alloc::vec::Vec;
+use memchr::{memchr, memchr3};
+
+use super::InlineEvent;
+use super::marks::flanking;
+use crate::Range;
+
+const LEFT_SINGLE: &str = "\u{2018}";
+const RIGHT_SINGLE: &str = "\u{2019}";
+const LEFT_DOUBLE: &str = "\u{201C}";
+const RIGHT_DOUBLE: &str = "\u{201D}";
+const EN_DASH: &str = "\u{2013}";
+const EM_DASH: &str = "\u{2014}";
+const ELLIPSIS: &str = "\u{2026}";
+
+/// Quick check for punctuation that may be replaced.
+#[inline]
+fn has_smart_candidates(text: &[u8]) -> bool {
+    memchr3(b'"', b'\'', b'-', text).is_some() || memchr(b'.', text).is_some()
+}
+
+/// Replace punctuation in the text events emitted since `new_events_start`.
+pub fn smarten_text_events(
+    text: &[u8],
+    events: &mut Vec<InlineEvent>,
+    new_events_start: usize,
+    mut spans: Option<&mut Vec<Range>>,
+) {
+    let has_candidate = events[new_events_start..].iter().any(|event| {
+        matches!(event, InlineEvent::Text(range) if has_smart_candidates(range.slice(text)))
+    });
+    if !has_candidate {
+        return;
+    }
+
+    let quotes = pair_quotes(text, &events[new_events_start..]);
+    let mut quotes = quotes.iter();
+    let original_events = events.split_off(new_events_start);
+    let original_spans = spans.as_deref_mut().map(core::mem::take);
+    for (i, event) in original_events.into_iter().enumerate() {
+        match event {
+            InlineEvent::Text(range) => {
+                smarten_text_range(text, range, &mut quotes, events, spans.as_deref_mut())
+            }
+            event => {
+                events.push(event);
+                if let (Some(spans), Some(original)) = (spans.as_deref_mut(), &original_spans) {
+                    spans.push(original[i]);
+                }
+            }
+        }
+    }
+}
+
+/// The replacement of every quote in the text events, in order.
+fn pair_quotes(text: &[u8], events: &[InlineEvent]) -> Vec<&'static str> {
+    let mut quotes = Vec::new();
+    // Quotes that may still open: index into `quotes` and the quote byte
+    let mut openers: Vec<(usize, u8)> = Vec::new();
+    for event in events {
+        let InlineEvent::Text(range) = event else {
+            continue;
+        };
+        for pos in range.start_usize()..range.end_usize() {
+            let quote = text[pos];
+            if quote != b'"' && quote != b'\'' {
+                continue;
+            }
+            let (left_flanking, right_flanking) = flanking(text, pos, pos + 1);
+            let can_open = left_flanking
+                && !right_flanking
+                && !(pos > 0 && matches!(text[pos - 1], b']' | b')'));
+            let can_close = right_flanking;
+            let (left, right) = if quote == b'"' {
+                (LEFT_DOUBLE, RIGHT_DOUBLE)
+            } else {
+                (LEFT_SINGLE, RIGHT_SINGLE)
+            };
+
+            let opener = if can_close {
+                openers.iter().rposition(|&(_, open)| open == quote)
+            } else {
+                None
+            };
+            if let Some(position) = opener {
+                quotes[openers[position].0] = left;
+                openers.truncate(position);
+                quotes.push(right);
+                continue;
+            }
+            if can_open {
+                openers.push((quotes.len(), quote));
+            }
+            // Unmatched: an apostrophe, or the side a double quote can take
+            quotes.push(if quote == b'"' && !can_close {
+                left
+            } else {
+                right
+            });
+        }
+    }
+    quotes
+}
+
+fn smarten_text_range(
+    text: &[u8],
+    range: Range,
+    quotes: &mut core::slice::Iter<'_, &'static str>,
+    events: &mut Vec<InlineEvent>,
+    mut spans: Option<&mut Vec<Range>>,
+) {
+    let end = range.end_usize();
+    let mut pos = range.start_usize();
+    let mut text_start = pos;
+
+    while pos < end {
+        let byte = text[pos];
+        let len = match byte {
+            b'"' | b'\'' => 1,
+            b'-' => text[pos..end].iter().take_while(|&&b| b == b'-').count(),
+            b'.' if text[pos..end].starts_with(b"...") => 3,
+            _ => 0,
+        };
+        // A single hyphen stays
+        if len == 0 || (byte == b'-' && len == 1) {
+            pos += len.max(1);
+            continue;
+        }
+
+        if text_start < pos {
+            let range = Range::from_usize(text_start, pos);
+            push_event(
+                InlineEvent::Text(range),
+                range,
+                events,
+                spans.as_deref_mut(),
+            );
+        }
+        match byte {
+            b'-' => {
+                let (em, en) = dash_counts(len);
+                let mut start = pos;
+                for (count, width, dash) in [(em, 3, EM_DASH), (en, 2, EN_DASH)] {
+                    for _ in 0..count {
+                        push_event(
+                            InlineEvent::SmartPunctuation(dash),
+                            Range::from_usize(start, start + width),
+                            events,
+                            spans.as_deref_mut(),
+                        );
+                        start += width;
+                    }
+                }
+            }
+            b'.' => push_event(
+                InlineEvent::SmartPunctuation(ELLIPSIS),
+                Range::from_usize(pos, pos + len),
+                events,
+                spans.as_deref_mut(),
+            ),
+            _ => {
+                let quote = quotes.next().expect("every quote in text events is paired");
+                push_event(
+                    InlineEvent::SmartPunctuation(quote),
+                    Range::from_usize(pos, pos + len),
+                    events,
+                    spans.as_deref_mut(),
+                );
+            }
+        }
+        pos += len;
+        text_start = pos;
+    }
+
+    if text_start < end {
+        let range = Range::from_usize(text_start, end);
+        push_event(InlineEvent::Text(range), range, events, spans);
+    }
+}
+
+fn push_event(
+    event: InlineEvent,
+    span: Range,
+    events: &mut Vec<InlineEvent>,
+    spans: Option<&mut Vec<Range>>,
+) {
+    events.push(event);
+    if let Some(spans) = spans {
+        spans.push(span);
+    }
+}
+
+/// Em and en dashes for a run of `len` hyphens, as cmark counts them: all
+/// em dashes when `len` is a multiple of three, otherwise all en dashes when
+/// it is even, otherwise em dashes followed by one or two en dashes.
+fn dash_counts(len: usize) -> (usize, usize) {
+    if len % 3 == 0 {
+        (len / 3, 0)
+    } else if len % 2 == 0 {
+        (0, len / 2)
+    } else if len % 3 == 2 {
+        ((len - 2) / 3, 1)
+    } else {
+        ((len - 4) / 3, 2)
+    }
+}
@@ -0,0 +1,59 @@
+//! Source spans for inline events (see [`InlineParser::set_record_spans`]).
+//!
+//! The emitter records the source of each event as it goes: the delimiter
+//! run for emphasis, the `[` or `](dest)` of a link, its own range for text.
+//! Pairing then widens every start/end event to the whole construct.
+//!
+//! [`InlineParser::set_record_spans`]: super::InlineParser::set_record_spans
+
+use super::event::InlineEvent;
+use crate::Range;
+use alloc::vec::Vec;
+
+/// Whether an event opens (`Some(true)`) or closes (`Some(false)`) a construct.
+fn opens(event: &InlineEvent) -> Option<bool> {
+    match event {
+        InlineEvent::EmphasisStart
+        | InlineEvent::StrongStart
+        | InlineEvent::StrikethroughStart
+        | InlineEvent::SubscriptStart
+        | InlineEvent::SuperscriptStart
+        | InlineEvent::HighlightStart
+        | InlineEvent::CustomStart(_)
+        | InlineEvent::LinkStart { .. }
+        | InlineEvent::LinkStartRef { .. }
+        | InlineEvent::ImageStart { .. }
+        | InlineEvent::ImageStartRef { .. } => Some(true),
+        InlineEvent::EmphasisEnd
+        | InlineEvent::StrongEnd
+        | InlineEvent::StrikethroughEnd
+        | InlineEvent::SubscriptEnd
+        | InlineEvent::SuperscriptEnd
+        | InlineEvent::HighlightEnd
+        | InlineEvent::CustomEnd(_)
+        | InlineEvent::LinkEnd
+        | InlineEvent::ImageEnd => Some(false),
+        _ => None,
+    }
+}
+
+/// Give each start/end pair the span from the opener's start to the
+/// closer's end.
+pub(super) fn pair_spans(events: &[InlineEvent], spans: &mut [Range]) {
+    debug_assert_eq!(events.len(), spans.len());
+    let mut open: Vec<usize> = Vec::new();
+    for (i, event) in events.iter().enumerate() {
+        match opens(event) {
+            Some(true) => open.push(i),
+            Some(false) => {
+                let Some(start_idx) = open.pop() else {
+                    continue;
+                };
+                let span = Range::new(spans[start_idx].start, spans[i].end);
+                spans[start_idx] = span;
+                spans[i] = span;
+            }
+            None => {}
+        }
+    }
+}
@@ -1,9 +1,11 @@
-//! Strikethrough resolution (`~~text~~`).
+//! Strikethrough resolution (`~~text~~`, optionally `~text~`).
 //!
-//! Matches double-tilde runs as opener/closer pairs.
+//! Matches double-tilde runs, and single-tilde runs when enabled, as
+//! opener/closer pairs of the same length.
 //! Uses same flanking rules as `*` emphasis (already computed in mark collection).
 
 use super::marks::{Mark, flags};
+use alloc::vec::Vec;
 
 /// A matched strikethrough pair.
 #[derive(Debug, Clone, Copy)]
@@ -15,10 +17,11 @@ pub struct StrikethroughMatch {
 }
 
 /// Resolve strikethrough marks. Matches double-tilde opener/closer runs greedily
-/// (innermost first, left to right).
+/// (innermost first, left to right), and single-tilde runs with `single_tilde`.
 pub fn resolve_strikethrough_into(
     marks: &mut [Mark],
     link_boundaries: &[(u32, u32)],
+    single_tilde: bool,
     matches: &mut Vec<StrikethroughMatch>,
 ) {
     matches.clear();
@@ -33,7 +36,7 @@ pub fn resolve_strikethrough_into(
         }
 
         let run_len = mark.len();
-        if run_len != 2 {
+        if run_len != 2 && !(single_tilde && run_len == 1) {
             continue;
         }
 
@@ -2,8 +2,10 @@
 //!
 //! Matches single-caret runs as opener/closer pairs.
 //! Uses the same flanking rules as `*` emphasis (already computed in mark collection).
+//! Whitespace inside ends the span unless escaped (`^a\ b^`), as in Pandoc.
 
-use super::marks::{Mark, flags};
+use super::marks::{Mark, flags, is_script_content};
+use alloc::vec::Vec;
 
 /// A matched superscript pair.
 #[derive(Debug, Clone, Copy)]
@@ -51,8 +53,7 @@ pub fn resolve_superscript_into(
                     continue;
                 }
 
-                let content = &text[opener.end as usize..mark.pos as usize];
-                if content.is_empty() || content.iter().all(|b| matches!(b, b' ' | b'\t' | b'\n')) {
+                if !is_script_content(&text[opener.end as usize..mark.pos as usize]) {
                     continue;
                 }
 
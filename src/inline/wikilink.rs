@@ -0,0 +1,108 @@
+//! Wikilink resolution (`[[Page]]` and `[[Page|Label]]`).
+//!
+//! Runs after code spans and before bracket links, so the two `[` of a
+//! wikilink are consumed as a pair and never misread as link openers.
+
+use alloc::vec::Vec;
+
+/// A resolved wikilink.
+#[derive(Debug, Clone, Copy)]
+pub struct WikiLink {
+    /// Position of the first `[`.
+    pub start: u32,
+    /// Position after the closing `]]`.
+    pub end: u32,
+    /// Start of the page target.
+    pub target_start: u32,
+    /// End of the page target.
+    pub target_end: u32,
+    /// Start of the display label (the target when no `|` is present).
+    pub label_start: u32,
+    /// End of the display label.
+    pub label_end: u32,
+}
+
+/// Resolve `[[...]]` pairs on a single line.
+///
+/// Both opening brackets must be adjacent and unescaped, and both closing
+/// brackets must appear in `close_brackets` (so escaped `]` and brackets in
+/// code or raw HTML are ignored). Content may not contain brackets or overlap
+/// any of the `excluded` ranges (code spans, math, HTML, autolinks).
+pub fn resolve_wikilinks_into(
+    text: &[u8],
+    open_brackets: &[(u32, bool)],
+    close_brackets: &[u32],
+    excluded: &[(u32, u32)],
+    out: &mut Vec<WikiLink>,
+) {
+    out.clear();
+
+    let mut resume = 0u32;
+    for pair in open_brackets.windows(2) {
+        let (first, first_is_image) = pair[0];
+        let (second, second_is_image) = pair[1];
+        if first < resume || first_is_image || second_is_image || second != first + 1 {
+            continue;
+        }
+
+        let content_start = (second + 1) as usize;
+        let Some(offset) = text[content_start..]
+            .iter()
+            .position(|&b| matches!(b, b'[' | b']' | b'\n'))
+        else {
+            continue;
+        };
+        let close = content_start + offset;
+        if text[close] != b']' || text.get(close + 1) != Some(&b']') {
+            continue;
+        }
+        let close = close as u32;
+        if close_brackets.binary_search(&close).is_err()
+            || close_brackets.binary_search(&(close + 1)).is_err()
+        {
+            continue;
+        }
+
+        let end = close + 2;
+        if excluded.iter().any(|&(s, e)| s < end && e > first) {
+            continue;
+        }
+
+        let content_start = content_start as u32;
+        let pipe = text[content_start as usize..close as usize]
+            .iter()
+            .position(|&b| b == b'|')
+            .map(|p| content_start + p as u32);
+        let (target_start, target_end) = trim(text, content_start, pipe.unwrap_or(close));
+        if target_start == target_end {
+            continue;
+        }
+        let (label_start, label_end) = match pipe {
+            Some(pipe) => match trim(text, pipe + 1, close) {
+                (s, e) if s < e => (s, e),
+                _ => (target_start, target_end),
+            },
+            None => (target_start, target_end),
+        };
+
+        out.push(WikiLink {
+            start: first,
+            end,
+            target_start,
+            target_end,
+            label_start,
+            label_end,
+        });
+        resume = end;
+    }
+}
+
+fn trim(text: &[u8], mut start: u32, mut end: u32) -> (u32, u32) {
+    while start < end && matches!(text[start as usize], b' ' | b'\t') {
+        start += 1;
+    }
+    while end > start && matches!(text[end as usize - 1], b' ' | b'\t') {
+        end -= 1;
+    }
+    (start, end)
+}
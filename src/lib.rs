@@ -10,38 +10,72 @@
 //! - Minimal allocations: ranges into input buffer
 //!
 //! # Future Optimizations
-//! - `simdutf` / `simdutf8`: SIMD-accelerated UTF-8 validation for input
 //! - NEON intrinsics for ARM: inline marker scanning
 //! - Loop unrolling in hot paths (4x unroll like md4c)
+//!
+//! # UTF-8 input
+//! [`to_html_from_bytes`] validates byte buffers; the optional `simdutf8`
+//! feature makes that validation SIMD-accelerated.
+//!
+//! # `no_std`
+//! The default `std` feature can be disabled to build for `no_std` targets
+//! with an allocator; everything except [`to_html_writer`] and the
+//! `profiling` feature needs only `core` and `alloc`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 pub mod block;
+mod cache;
 pub mod cursor;
 pub mod escape;
 pub mod footnote;
 pub mod inline;
 pub mod limits;
 pub mod link_ref;
+mod markdown;
 #[cfg(feature = "mdx")]
 pub mod mdx;
+mod options_builder;
 #[cfg(feature = "profiling")]
 #[doc(hidden)]
 pub mod profiling;
 pub mod range;
 pub mod render;
+mod renderer;
+mod sanitize;
+pub mod toc;
 
 // Re-export primary types
-pub use block::{Alignment, BlockEvent, BlockParser, CalloutType, CodeBlockKind, fixup_list_tight};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+pub use block::{
+    Alignment, BlockEvent, BlockEvents, BlockParser, CalloutFold, CalloutTitles, CalloutType,
+    CodeBlockKind, fixup_list_tight,
+};
+pub use cache::CachedRenderer;
 pub use footnote::FootnoteStore;
-pub use inline::{InlineEvent, InlineParser};
+pub use inline::{CustomDelimiter, InlineEvent, InlineParser};
 pub use link_ref::{LinkRefDef, LinkRefStore};
+pub use markdown::to_markdown;
+pub use options_builder::OptionsBuilder;
 pub use range::Range;
-pub use render::HtmlWriter;
+pub use render::{HtmlWriter, PrettyConfig};
+pub use renderer::{Renderer, render_with};
+pub use toc::{TocEntry, table_of_contents, table_of_contents_flat};
 
 /// A complete fenced code block passed to a custom renderer.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FencedCodeBlock<'a> {
     /// Decoded first word of the CommonMark info string, when present.
     pub language: Option<&'a str>,
+    /// Decoded rest of the info string after the language word, when present.
+    pub meta: Option<&'a str>,
     /// Raw code content before HTML escaping.
     pub code: &'a str,
 }
@@ -85,8 +119,70 @@ pub trait FencedCodeRenderer {
     fn render(&mut self, block: FencedCodeBlock<'_>) -> Option<TrustedHtml>;
 }
 
+/// Syntax-highlighting callback for fenced code blocks.
+///
+/// Receives the decoded language (if any) and the full code text, and returns
+/// highlighted HTML for the inside of `<code>`, or `None` for the default
+/// escaped output.
+pub type HighlightFn<'f> = dyn Fn(Option<&str>, &str) -> Option<String> + 'f;
+
+/// [`FencedCodeRenderer`] that wraps a [`HighlightFn`] in the usual
+/// `<pre><code class="language-...">` markup.
+///
+/// The callback's HTML is written verbatim, so it must escape the code text
+/// itself (as syntect and tree-sitter HTML emitters do); see [`TrustedHtml`].
+///
+/// # Example
+/// ```
+/// use ferromark::{Highlighter, Options, to_html_with_renderer};
+///
+/// let highlight = |lang: Option<&str>, code: &str| {
+///     (lang == Some("sh")).then(|| format!("<span class=\"cmd\">{}</span>", code.trim_end()))
+/// };
+/// let html = to_html_with_renderer(
+///     "```sh\nls\n```",
+///     &Options::default(),
+///     &mut Highlighter::new(&highlight),
+/// );
+/// assert_eq!(
+///     html,
+///     "<pre><code class=\"language-sh\"><span class=\"cmd\">ls</span></code></pre>\n"
+/// );
+/// ```
+pub struct Highlighter<'f> {
+    highlight: &'f HighlightFn<'f>,
+}
+
+impl<'f> Highlighter<'f> {
+    /// Wrap a highlighting callback.
+    pub fn new(highlight: &'f HighlightFn<'f>) -> Self {
+        Self { highlight }
+    }
+}
+
+impl FencedCodeRenderer for Highlighter<'_> {
+    fn render(&mut self, block: FencedCodeBlock<'_>) -> Option<TrustedHtml> {
+        let highlighted = (self.highlight)(block.language, block.code)?;
+        let mut writer = HtmlWriter::with_capacity(highlighted.len() + 48);
+        match block.language {
+            Some(language) => {
+                writer.write_str("<pre><code class=\"language-");
+                writer.write_escaped_attr(language.as_bytes());
+                writer.write_str("\">");
+            }
+            None => writer.write_str("<pre><code>"),
+        }
+        writer.write_string(&highlighted);
+        writer.code_block_end();
+        let html = writer
+            .into_string()
+            .expect("highlighter output and language are UTF-8");
+        Some(TrustedHtml::from_trusted(html))
+    }
+}
+
 /// Trust boundary applied while rendering links, images, and raw HTML.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum RenderPolicy {
     /// Escape all raw HTML and allow only browser-safe URL schemes.
     #[default]
@@ -95,12 +191,66 @@ pub enum RenderPolicy {
     Trusted,
 }
 
+/// Filter for raw HTML spans and blocks under [`RenderPolicy::Trusted`].
+///
+/// [`RenderPolicy::Untrusted`] escapes all raw HTML, so no filter applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Sanitizer {
+    /// Write raw HTML as is, unless `disallowed_raw_html` is set.
+    #[default]
+    None,
+    /// Escape the opening `<` of the GFM disallowed tags (`<script>`,
+    /// `<iframe>`, ...), like [`Options::disallowed_raw_html`].
+    GitHubBlacklist,
+    /// Keep only the elements, attributes and URL protocols GitHub allows
+    /// (`github-markup`'s sanitization filter). Other tags are dropped while
+    /// their content stays, except for `<script>`, whose content goes too;
+    /// comments are dropped.
+    ///
+    /// ```
+    /// use ferromark::{Options, RenderPolicy, Sanitizer, to_html_with_options};
+    ///
+    /// let options = Options {
+    ///     render_policy: RenderPolicy::Trusted,
+    ///     sanitizer: Sanitizer::GitHubAllowlist,
+    ///     ..Options::default()
+    /// };
+    /// assert_eq!(
+    ///     to_html_with_options("<b class=\"x\" title=\"t\">bold</b> <u>u</u>", &options),
+    ///     "<p><b title=\"t\">bold</b> u</p>\n"
+    /// );
+    /// ```
+    GitHubAllowlist,
+}
+
+/// Scheme prepended to `www.` autolink literals, which have none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WwwScheme {
+    /// `http://`, as GFM specifies.
+    #[default]
+    Http,
+    /// `https://`.
+    Https,
+}
+
+impl WwwScheme {
+    /// The scheme with its `://` separator.
+    pub const fn prefix(self) -> &'static str {
+        match self {
+            Self::Http => "http://",
+            Self::Https => "https://",
+        }
+    }
+}
+
 /// Parsing/rendering options.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Options {
     /// Select the output trust boundary. Defaults to [`RenderPolicy::Untrusted`].
     pub render_policy: RenderPolicy,
     /// Parse raw inline and block HTML. Untrusted rendering still escapes it.
+    /// [`Options::allow_block_html`] and [`Options::allow_inline_html`]
+    /// narrow this to one kind; both default to `true`.
     pub allow_html: bool,
     /// Resolve link reference definitions and reference-style links.
     pub allow_link_refs: bool,
@@ -121,7 +271,8 @@ pub struct Options {
     /// Enable the GFM disallowed raw HTML extension in trusted mode.
     ///
     /// This is not an HTML sanitizer. [`RenderPolicy::Untrusted`] escapes all
-    /// raw HTML regardless of this setting.
+    /// raw HTML regardless of this setting. Same as
+    /// [`Sanitizer::GitHubBlacklist`], which [`Options::sanitizer`] overrides.
     pub disallowed_raw_html: bool,
     /// Enable footnotes extension (`[^label]` references and `[^label]:` definitions).
     pub footnotes: bool,
@@ -129,13 +280,583 @@ pub struct Options {
     pub front_matter: bool,
     /// Generate GitHub-compatible heading IDs (`<h1 id="slug">`).
     pub heading_ids: bool,
-    /// Enable math spans (`$inline$` and `$$display$$`).
+    /// Enable math spans (`$inline$`, `$$display$$`, `\(inline\)`, `\[display\]`).
+    /// A paragraph holding a single display span renders as a
+    /// `<div class="math display">` block.
     pub math: bool,
     /// Enable GitHub-style callouts/admonitions (`> [!NOTE]`, `> [!WARNING]`, etc.).
     pub callouts: bool,
+    /// Mark links and images as user-generated content.
+    ///
+    /// Adds `rel="ugc nofollow"` to links and autolinks and
+    /// `referrerpolicy="no-referrer"` to images. This complements
+    /// [`RenderPolicy::Untrusted`] URL filtering; it does not replace it.
+    pub untrusted_content: bool,
+    /// Enable wikilinks (`[[Page]]` and `[[Page|Label]]`).
+    pub wikilinks: bool,
+    /// Map a wikilink target to its `href`. `None` uses
+    /// [`wikilink_percent_encoded`]; [`wikilink_slug`] slugifies the page name
+    /// instead.
+    ///
+    /// The produced URL still passes through the [`RenderPolicy`] URL filter.
+    pub wikilink_url: Option<WikiLinkUrl>,
+    /// Force the `start` number of top-level ordered lists.
+    ///
+    /// Useful when a document is split and a list continues across parts.
+    /// Lists nested inside another list keep their source numbering.
+    pub ordered_list_start_override: Option<u32>,
+    /// Expand GitHub-style emoji shortcodes (`:smile:`) to Unicode emoji.
+    ///
+    /// Unknown shortcodes are left as written.
+    pub emoji: bool,
+    /// Continue an ordered list across a single interrupting paragraph.
+    ///
+    /// With this heuristic, `1. a\n\ntext\n\n2. b` stays one list and the
+    /// paragraph joins item 1. It only applies when the next item's number
+    /// follows the previous item's; otherwise CommonMark behavior applies.
+    pub loose_list_continuation: bool,
+    /// Indent nested block elements for human-readable output.
+    ///
+    /// `None` keeps the compact default output. Inline whitespace is never
+    /// changed; see [`PrettyConfig`].
+    pub pretty: Option<PrettyConfig>,
+    /// Add an ARIA `role` to callout containers (`note`, or `alert` for
+    /// warnings). Only applies when `callouts` is enabled.
+    pub callout_aria: bool,
+    /// Render `> [!TYPE]-` and `> [!TYPE]+` callouts as collapsible
+    /// `<details>` elements (collapsed and expanded), with any text after the
+    /// marker as the `<summary>` title. Only applies when `callouts` is enabled.
+    pub collapsible_callouts: bool,
+    /// Override [`limits::MAX_BLOCK_NESTING`] for block containers
+    /// (blockquotes, list items, footnote definitions).
+    ///
+    /// Container markers past the limit are treated as literal text. Each
+    /// nesting level uses parser stack, so keep large values for trusted input.
+    pub max_nesting_depth: Option<usize>,
+    /// Override [`limits::MAX_INLINE_MARKS`], the number of inline delimiters
+    /// (emphasis, brackets, backticks, ...) collected per paragraph.
+    ///
+    /// Delimiters past the limit are treated as literal text.
+    pub max_inline_marks: Option<usize>,
+    /// Additional inline delimiters rendered as HTML elements.
+    ///
+    /// They are resolved after every built-in construct, which wins on any
+    /// overlap, and earlier entries take precedence over later ones. Only the
+    /// first 256 entries are used.
+    pub custom_delimiters: &'static [CustomDelimiter],
+    /// Expose the fenced-code info string after the language word as a
+    /// `data-meta` attribute on `<code>` (` ```rust title="main.rs" `).
+    pub code_info_meta: bool,
+    /// Add GitHub's task list classes: `contains-task-list` on lists with a
+    /// task item, `task-list-item` on those items, and
+    /// `task-list-item-checkbox` on their checkboxes. Only applies when
+    /// `task_lists` is enabled.
+    pub task_list_classes: bool,
+    /// Let a link contain other links, rendering nested `<a>` elements.
+    ///
+    /// CommonMark deactivates the outer link instead. Nested anchors are
+    /// invalid HTML, so only enable this when a custom renderer or client
+    /// script handles them (for example, a card with an overlay link).
+    pub allow_nested_links: bool,
+    /// Parse raw HTML blocks when [`Options::allow_html`] is also set.
+    /// Disabled, `<div>` lines start ordinary paragraphs instead.
+    pub allow_block_html: bool,
+    /// Parse raw inline HTML when [`Options::allow_html`] is also set.
+    /// Disabled, inline tags, comments, and declarations render as text.
+    pub allow_inline_html: bool,
+    /// Let a table row that ends in an empty `||` span its last cell across
+    /// the remaining columns (`| a ||` in a three-column table renders one
+    /// cell with `colspan="3"`). Rows that simply have fewer cells are still
+    /// padded with empty cells, as in GFM.
+    pub table_cell_merge: bool,
+    /// Render code spans with a callback instead of `<code>`. See
+    /// [`CodeSpanRenderer`].
+    pub code_span_renderer: Option<CodeSpanRenderer>,
+    /// Rewrite image sources and add `srcset`/`sizes`. See
+    /// [`ImageSrcTransform`].
+    pub image_src_transform: Option<ImageSrcTransform>,
+    /// Use the footnote's sequential number instead of its label in anchor
+    /// ids and hrefs (`user-content-fn-1`, `user-content-fnref-1`).
+    pub numeric_footnote_ids: bool,
+    /// Callout titles per type, e.g. localized. Defaults to
+    /// [`CalloutTitles::ENGLISH`]. Only applies when `callouts` is enabled.
+    pub callout_titles: CalloutTitles,
+    /// Use text after a `> [!TYPE]` marker as the callout title
+    /// (`> [!NOTE] Read this first`). Without it, such a blockquote is not a
+    /// callout, as on GitHub. Only applies when `callouts` is enabled.
+    pub callout_custom_titles: bool,
+    /// Write consecutive list items without whitespace between them
+    /// (`</li><li>`). Only the separator between items changes.
+    pub compact_list_items: bool,
+    /// Add `rel` and `target` attributes to links that leave the site. See
+    /// [`ExternalLinks`].
+    pub external_links: Option<ExternalLinks>,
+    /// Read a `[cite:URL]` line opening a blockquote as its `cite` attribute
+    /// (`<blockquote cite="URL">`). The URL passes through the
+    /// [`RenderPolicy`] URL filter; an unsafe one drops the attribute.
+    pub blockquote_cite_url: bool,
+    /// Let single-tilde runs form strikethrough too (`~text~`), as some GFM
+    /// renderers do. Opener and closer must still have the same length.
+    /// Only applies when `strikethrough` is enabled, and takes `~text~`
+    /// before `subscript` does.
+    pub strikethrough_single_tilde: bool,
+    /// Filter raw HTML under [`RenderPolicy::Trusted`]. See [`Sanitizer`].
+    /// [`Sanitizer::None`] leaves it to `disallowed_raw_html`.
+    pub sanitizer: Sanitizer,
+    /// Render soft line breaks in paragraphs and headings as `<br />`, as
+    /// GitHub does in comments. Code and raw HTML keep their newlines.
+    pub hard_wrap: bool,
+    /// Scheme for the `href` of `www.` autolink literals. The link text
+    /// stays as written.
+    pub www_autolink_scheme: WwwScheme,
+    /// Leave task list checkboxes enabled, without the `disabled`
+    /// attribute, for pages that let readers tick them. Only applies when
+    /// `task_lists` is enabled.
+    pub task_list_interactive: bool,
+    /// Write typographic punctuation in text: curly quotes for `"` and `'`,
+    /// an en dash for `--`, an em dash for `---` and an ellipsis for `...`.
+    /// Code, autolinks, raw HTML and escaped characters stay as written.
+    pub smart_punctuation: bool,
+    /// Close void elements XHTML-style (`<hr />`, `<br />`, `<img ... />`
+    /// and task list `<input ... />`). `false` writes HTML5 `<hr>`. Raw
+    /// HTML is left as written.
+    pub xhtml: bool,
+    /// Turn inline footnotes (`^[note text]`) into numbered footnotes,
+    /// listed with the others in order of reference. Only applies when
+    /// `footnotes` is enabled. Notes cannot contain other notes and are not
+    /// recognized in footnote definitions.
+    pub inline_footnotes: bool,
+    /// Text of the backref links after each footnote. Defaults to `↩`.
+    pub footnote_backref_label: &'static str,
+    /// Prefix of footnote anchor ids and hrefs, before `fn-`/`fnref-`.
+    /// Defaults to GitHub's `user-content-`.
+    pub footnote_id_prefix: &'static str,
+    /// Title of an `<h2>` heading the footnote section starts with, if any.
+    pub footnote_section_title: Option<&'static str>,
+    /// Parse Pandoc-style fenced divs: `::: warning` opens a
+    /// `<div class="warning">` that a bare `:::` line closes. Attribute
+    /// lists (`::: {#id .class key=value}`) set the id and classes; other
+    /// keys become `data-` attributes.
+    pub fenced_divs: bool,
+    /// Parse attribute lists (`{#id .class key=value}`) at the end of ATX
+    /// and setext headings and fenced code info strings, as in Pandoc. The
+    /// id replaces the generated heading id; other keys become `data-`
+    /// attributes. On code blocks the classes join the language class on
+    /// `<code>`, and the id and `data-` attributes go on `<pre>`.
+    pub attributes: bool,
+}
+
+/// Custom wikilink URL transform that writes the `href` for a target into `url`.
+#[derive(Clone, Copy)]
+pub struct WikiLinkUrl(pub fn(target: &str, url: &mut String));
+
+impl core::fmt::Debug for WikiLinkUrl {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("WikiLinkUrl(..)")
+    }
+}
+
+impl PartialEq for WikiLinkUrl {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::fn_addr_eq(self.0, other.0)
+    }
+}
+
+impl Eq for WikiLinkUrl {}
+
+impl core::hash::Hash for WikiLinkUrl {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (self.0 as *const ()).hash(state);
+    }
+}
+
+/// Custom code span rendering.
+///
+/// The callback receives the span content after CommonMark normalization
+/// (line endings turned into spaces, one surrounding space stripped) and
+/// returns the HTML to write in place of `<code>...</code>`, or `None` for
+/// the default. The returned HTML is written verbatim under every
+/// [`RenderPolicy`], so escape any content it echoes. Code spans in image alt
+/// text stay plain text.
+///
+/// ```
+/// use ferromark::{CodeSpanRenderer, Options, to_html_with_options};
+///
+/// fn kbd(content: &str) -> Option<String> {
+///     let key = content.strip_prefix("kbd:")?;
+///     key.chars()
+///         .all(char::is_alphanumeric)
+///         .then(|| format!("<kbd>{key}</kbd>"))
+/// }
+///
+/// let options = Options {
+///     code_span_renderer: Some(CodeSpanRenderer(kbd)),
+///     ..Options::default()
+/// };
+/// assert_eq!(
+///     to_html_with_options("`kbd:Esc` or `q`", &options),
+///     "<p><kbd>Esc</kbd> or <code>q</code></p>\n"
+/// );
+/// ```
+#[derive(Clone, Copy)]
+pub struct CodeSpanRenderer(pub fn(content: &str) -> Option<String>);
+
+impl core::fmt::Debug for CodeSpanRenderer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("CodeSpanRenderer(..)")
+    }
+}
+
+impl PartialEq for CodeSpanRenderer {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::fn_addr_eq(self.0, other.0)
+    }
+}
+
+impl Eq for CodeSpanRenderer {}
+
+impl core::hash::Hash for CodeSpanRenderer {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (self.0 as *const ()).hash(state);
+    }
+}
+
+/// Attributes produced by an [`ImageSrcTransform`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImageAttrs {
+    /// Value for `src`.
+    pub src: String,
+    /// Value for `srcset`, if any.
+    pub srcset: Option<String>,
+    /// Value for `sizes`, if any.
+    pub sizes: Option<String>,
+}
+
+/// Image source transform, for CDN rewriting and responsive images.
+///
+/// The callback receives the image destination with backslash escapes and
+/// entities resolved, exactly as written otherwise: relative paths stay
+/// relative. It runs for inline and reference images alike; `data:` URIs
+/// bypass it. The returned `src` still passes through the [`RenderPolicy`]
+/// URL filter; `srcset` and `sizes` are only attribute-escaped.
+///
+/// ```
+/// use ferromark::{ImageAttrs, ImageSrcTransform, Options, to_html_with_options};
+///
+/// fn cdn(src: &str) -> ImageAttrs {
+///     let src = format!("https://cdn.example/{}", src.trim_start_matches('/'));
+///     ImageAttrs {
+///         srcset: Some(format!("{src}?w=640 640w, {src}?w=1280 1280w")),
+///         sizes: Some("100vw".into()),
+///         src,
+///     }
+/// }
+///
+/// let options = Options {
+///     image_src_transform: Some(ImageSrcTransform(cdn)),
+///     ..Options::default()
+/// };
+/// assert_eq!(
+///     to_html_with_options("![Map](/map.png)", &options),
+///     "<p><img src=\"https://cdn.example/map.png\" \
+///      srcset=\"https://cdn.example/map.png?w=640 640w, https://cdn.example/map.png?w=1280 1280w\" \
+///      sizes=\"100vw\" alt=\"Map\" /></p>\n"
+/// );
+/// ```
+#[derive(Clone, Copy)]
+pub struct ImageSrcTransform(pub fn(src: &str) -> ImageAttrs);
+
+impl core::fmt::Debug for ImageSrcTransform {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("ImageSrcTransform(..)")
+    }
+}
+
+impl PartialEq for ImageSrcTransform {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::fn_addr_eq(self.0, other.0)
+    }
+}
+
+impl Eq for ImageSrcTransform {}
+
+impl core::hash::Hash for ImageSrcTransform {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (self.0 as *const ()).hash(state);
+    }
+}
+
+/// Attributes for external links: absolute `http`/`https` URLs and
+/// protocol-relative `//host` URLs whose host differs from
+/// [`ExternalLinks::base_host`].
+///
+/// Relative links, fragments, and other schemes such as `mailto:` are left
+/// untouched. The `rel` value joins `ugc nofollow` when
+/// [`Options::untrusted_content`] is also set.
+///
+/// ```
+/// use ferromark::{ExternalLinks, Options, to_html_with_options};
+///
+/// let options = Options {
+///     external_links: Some(ExternalLinks {
+///         base_host: Some("example.com"),
+///         ..ExternalLinks::NEW_TAB
+///     }),
+///     ..Options::default()
+/// };
+/// assert_eq!(
+///     to_html_with_options("[Home](https://example.com/) [Docs](https://docs.rs/)", &options),
+///     "<p><a href=\"https://example.com/\">Home</a> <a href=\"https://docs.rs/\" \
+///      rel=\"noopener noreferrer\" target=\"_blank\">Docs</a></p>\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExternalLinks {
+    /// Value for `rel`, if any.
+    pub rel: Option<&'static str>,
+    /// Value for `target`, if any.
+    pub target: Option<&'static str>,
+    /// Host of the site itself, compared case-insensitively and without a
+    /// port. `None` treats every `http`/`https` or `//host` link as external.
+    pub base_host: Option<&'static str>,
+    /// Custom check for `http`/`https` and `//host` URLs, used instead of
+    /// [`ExternalLinks::base_host`]. Relative links, fragments and other
+    /// schemes stay internal without calling it.
+    pub predicate: Option<ExternalLinkPredicate>,
+}
+
+impl ExternalLinks {
+    /// Open external links in a new tab without exposing the opener or
+    /// referrer.
+    pub const NEW_TAB: Self = Self {
+        rel: Some("noopener noreferrer"),
+        target: Some("_blank"),
+        base_host: None,
+        predicate: None,
+    };
+
+    /// Whether `url` is an absolute `http`/`https` URL, or a
+    /// protocol-relative `//host` URL, to another host.
+    pub fn is_external(&self, url: &[u8]) -> bool {
+        let Some(host) = url_authority(url).and_then(url_host) else {
+            return false;
+        };
+        match self.predicate {
+            Some(predicate) => core::str::from_utf8(url).is_ok_and(predicate.0),
+            None => self.is_external_host(host),
+        }
+    }
+
+    /// [`ExternalLinks::is_external`] for a `www.` autolink literal, which
+    /// gets `scheme` prepended in the `href`.
+    fn is_external_www(&self, url: &[u8], scheme: &str) -> bool {
+        let Some(host) = url_host(url) else {
+            return false;
+        };
+        match self.predicate {
+            Some(predicate) => core::str::from_utf8(url)
+                .is_ok_and(|url| (predicate.0)(&alloc::format!("{scheme}{url}"))),
+            None => self.is_external_host(host),
+        }
+    }
+
+    fn is_external_host(&self, host: &[u8]) -> bool {
+        self.base_host
+            .is_none_or(|base| !host.eq_ignore_ascii_case(base.as_bytes()))
+    }
+}
+
+/// Custom check for [`ExternalLinks::predicate`]: receives an absolute
+/// `http`/`https` or protocol-relative `//host` link destination and returns
+/// whether it is external.
+///
+/// ```
+/// use ferromark::{ExternalLinkPredicate, ExternalLinks, Options, to_html_with_options};
+///
+/// fn off_site(url: &str) -> bool {
+///     !url.contains(".example.com/") && !url.contains("//example.com/")
+/// }
+///
+/// let options = Options {
+///     external_links: Some(ExternalLinks {
+///         predicate: Some(ExternalLinkPredicate(off_site)),
+///         ..ExternalLinks::NEW_TAB
+///     }),
+///     ..Options::default()
+/// };
+/// assert_eq!(
+///     to_html_with_options("[Blog](https://blog.example.com/) [#](#top)", &options),
+///     "<p><a href=\"https://blog.example.com/\">Blog</a> <a href=\"#top\">#</a></p>\n"
+/// );
+/// ```
+#[derive(Clone, Copy)]
+pub struct ExternalLinkPredicate(pub fn(url: &str) -> bool);
+
+impl core::fmt::Debug for ExternalLinkPredicate {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("ExternalLinkPredicate(..)")
+    }
+}
+
+impl PartialEq for ExternalLinkPredicate {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::fn_addr_eq(self.0, other.0)
+    }
+}
+
+impl Eq for ExternalLinkPredicate {}
+
+impl core::hash::Hash for ExternalLinkPredicate {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (self.0 as *const ()).hash(state);
+    }
+}
+
+/// The authority and rest of `url` after `http://`, `https://` or a
+/// protocol-relative `//`. Browsers treat backslashes and further slashes
+/// there as part of the separator, so `/\host` and `https:///host` count too.
+fn url_authority(url: &[u8]) -> Option<&[u8]> {
+    let rest = [&b"http:"[..], b"https:"]
+        .iter()
+        .find_map(|scheme| {
+            url.get(..scheme.len())
+                .filter(|prefix| prefix.eq_ignore_ascii_case(scheme))
+                .map(|_| &url[scheme.len()..])
+        })
+        .unwrap_or(url);
+    let slashes = rest
+        .iter()
+        .take_while(|&&b| matches!(b, b'/' | b'\\'))
+        .count();
+    (slashes >= 2).then(|| &rest[slashes..])
+}
+
+/// Host of a URL authority (`user@host:port/path`), without userinfo or port.
+fn url_host(authority: &[u8]) -> Option<&[u8]> {
+    let end = authority
+        .iter()
+        .position(|&b| matches!(b, b'/' | b'?' | b'#' | b'\\'))
+        .unwrap_or(authority.len());
+    let authority = &authority[..end];
+    let host = match authority.iter().rposition(|&b| b == b'@') {
+        Some(at) => &authority[at + 1..],
+        None => authority,
+    };
+    let host = match host.iter().rposition(|&b| b == b':') {
+        Some(colon)
+            if host[colon + 1..].iter().all(u8::is_ascii_digit) && !host.ends_with(b"]") =>
+        {
+            &host[..colon]
+        }
+        _ => host,
+    };
+    (!host.is_empty()).then_some(host)
+}
+
+/// Wikilink URL transform that slugifies the page name.
+///
+/// Uses the same slug rules as heading ids, and keeps a `#section` suffix as a
+/// fragment, so `[[Page Name#Some Section]]` links to `page-name#some-section`.
+///
+/// ```
+/// use ferromark::{Options, WikiLinkUrl, to_html_with_options, wikilink_slug};
+///
+/// let options = Options {
+///     wikilinks: true,
+///     wikilink_url: Some(WikiLinkUrl(wikilink_slug)),
+///     ..Options::default()
+/// };
+/// assert_eq!(
+///     to_html_with_options("[[Page Name#Some Section]]", &options),
+///     "<p><a href=\"page-name#some-section\">Page Name#Some Section</a></p>\n"
+/// );
+/// ```
+pub fn wikilink_slug(target: &str, url: &mut String) {
+    let (page, section) = match target.split_once('#') {
+        Some((page, section)) => (page, Some(section)),
+        None => (target, None),
+    };
+    let mut slug = Vec::with_capacity(target.len());
+    generate_slug_into(page.as_bytes(), &mut slug);
+    url.push_str(&String::from_utf8_lossy(&slug));
+    if let Some(section) = section {
+        generate_slug_into(section.as_bytes(), &mut slug);
+        url.push('#');
+        url.push_str(&String::from_utf8_lossy(&slug));
+    }
+}
+
+/// Default wikilink URL transform: keep the page name and percent-encode it,
+/// so `[[Page Name]]` links to `Page%20Name`.
+///
+/// Letters, digits, `-._~` and `/` are kept; a `#section` suffix stays a
+/// fragment. Everything else, including `:`, is encoded, so a target never
+/// becomes a URL with a scheme.
+///
+/// ```
+/// use ferromark::{Options, to_html_with_options};
+///
+/// let options = Options {
+///     wikilinks: true,
+///     ..Options::default()
+/// };
+/// assert_eq!(
+///     to_html_with_options("[[Page Name]] [[Notes/Día 1#Top]]", &options),
+///     "<p><a href=\"Page%20Name\">Page Name</a> \
+///      <a href=\"Notes/D%C3%ADa%201#Top\">Notes/Día 1#Top</a></p>\n"
+/// );
+/// ```
+pub fn wikilink_percent_encoded(target: &str, url: &mut String) {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+    let (page, section) = match target.split_once('#') {
+        Some((page, section)) => (page, Some(section)),
+        None => (target, None),
+    };
+    let encode = |part: &str, url: &mut String| {
+        for &b in part.as_bytes() {
+            if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~' | b'/') {
+                url.push(b as char);
+            } else {
+                url.push('%');
+                url.push(HEX[(b >> 4) as usize] as char);
+                url.push(HEX[(b & 0xF) as usize] as char);
+            }
+        }
+    };
+    encode(page, url);
+    if let Some(section) = section {
+        url.push('#');
+        encode(section, url);
+    }
 }
 
 impl Options {
+    /// Start an [`OptionsBuilder`] from the default options.
+    pub fn builder() -> OptionsBuilder {
+        OptionsBuilder::default()
+    }
+
+    /// Whether raw HTML blocks are parsed.
+    #[inline]
+    pub(crate) const fn block_html(&self) -> bool {
+        self.allow_html && self.allow_block_html
+    }
+
+    /// Whether raw inline HTML is parsed.
+    #[inline]
+    pub(crate) const fn inline_html(&self) -> bool {
+        self.allow_html && self.allow_inline_html
+    }
+
+    /// The raw HTML filter in effect, counting `disallowed_raw_html`.
+    #[inline]
+    pub(crate) const fn raw_html_sanitizer(&self) -> Sanitizer {
+        match self.sanitizer {
+            Sanitizer::None if self.disallowed_raw_html => Sanitizer::GitHubBlacklist,
+            sanitizer => sanitizer,
+        }
+    }
+
     /// Return the smallest supported Markdown syntax surface.
     ///
     /// Ordinary paragraphs, headings, emphasis, code, links, images, lists,
@@ -160,6 +881,45 @@ impl Options {
             heading_ids: false,
             math: false,
             callouts: false,
+            untrusted_content: false,
+            wikilinks: false,
+            wikilink_url: None,
+            ordered_list_start_override: None,
+            emoji: false,
+            loose_list_continuation: false,
+            pretty: None,
+            callout_aria: false,
+            collapsible_callouts: false,
+            max_nesting_depth: None,
+            max_inline_marks: None,
+            custom_delimiters: &[],
+            code_info_meta: false,
+            task_list_classes: false,
+            allow_nested_links: false,
+            allow_block_html: true,
+            allow_inline_html: true,
+            table_cell_merge: false,
+            code_span_renderer: None,
+            image_src_transform: None,
+            numeric_footnote_ids: false,
+            callout_titles: CalloutTitles::ENGLISH,
+            callout_custom_titles: false,
+            compact_list_items: false,
+            external_links: None,
+            blockquote_cite_url: false,
+            strikethrough_single_tilde: false,
+            sanitizer: Sanitizer::None,
+            hard_wrap: false,
+            www_autolink_scheme: WwwScheme::Http,
+            task_list_interactive: false,
+            smart_punctuation: false,
+            xhtml: true,
+            inline_footnotes: false,
+            footnote_backref_label: "↩",
+            footnote_id_prefix: "user-content-",
+            footnote_section_title: None,
+            fenced_divs: false,
+            attributes: false,
         }
     }
 
@@ -187,6 +947,45 @@ impl Options {
             heading_ids: false,
             math: false,
             callouts: false,
+            untrusted_content: false,
+            wikilinks: false,
+            wikilink_url: None,
+            ordered_list_start_override: None,
+            emoji: false,
+            loose_list_continuation: false,
+            pretty: None,
+            callout_aria: false,
+            collapsible_callouts: false,
+            max_nesting_depth: None,
+            max_inline_marks: None,
+            custom_delimiters: &[],
+            code_info_meta: false,
+            task_list_classes: false,
+            allow_nested_links: false,
+            allow_block_html: true,
+            allow_inline_html: true,
+            table_cell_merge: false,
+            code_span_renderer: None,
+            image_src_transform: None,
+            numeric_footnote_ids: false,
+            callout_titles: CalloutTitles::ENGLISH,
+            callout_custom_titles: false,
+            compact_list_items: false,
+            external_links: None,
+            blockquote_cite_url: false,
+            strikethrough_single_tilde: false,
+            sanitizer: Sanitizer::None,
+            hard_wrap: false,
+            www_autolink_scheme: WwwScheme::Http,
+            task_list_interactive: false,
+            smart_punctuation: false,
+            xhtml: true,
+            inline_footnotes: false,
+            footnote_backref_label: "↩",
+            footnote_id_prefix: "user-content-",
+            footnote_section_title: None,
+            fenced_divs: false,
+            attributes: false,
         }
     }
 
@@ -214,6 +1013,45 @@ impl Options {
             heading_ids: false,
             math: false,
             callouts: false,
+            untrusted_content: false,
+            wikilinks: false,
+            wikilink_url: None,
+            ordered_list_start_override: None,
+            emoji: false,
+            loose_list_continuation: false,
+            pretty: None,
+            callout_aria: false,
+            collapsible_callouts: false,
+            max_nesting_depth: None,
+            max_inline_marks: None,
+            custom_delimiters: &[],
+            code_info_meta: false,
+            task_list_classes: false,
+            allow_nested_links: false,
+            allow_block_html: true,
+            allow_inline_html: true,
+            table_cell_merge: false,
+            code_span_renderer: None,
+            image_src_transform: None,
+            numeric_footnote_ids: false,
+            callout_titles: CalloutTitles::ENGLISH,
+            callout_custom_titles: false,
+            compact_list_items: false,
+            external_links: None,
+            blockquote_cite_url: false,
+            strikethrough_single_tilde: false,
+            sanitizer: Sanitizer::None,
+            hard_wrap: false,
+            www_autolink_scheme: WwwScheme::Http,
+            task_list_interactive: false,
+            smart_punctuation: false,
+            xhtml: true,
+            inline_footnotes: false,
+            footnote_backref_label: "↩",
+            footnote_id_prefix: "user-content-",
+            footnote_section_title: None,
+            fenced_divs: false,
+            attributes: false,
         }
     }
 }
@@ -237,6 +1075,45 @@ impl Default for Options {
             heading_ids: true,
             math: false,
             callouts: true,
+            untrusted_content: false,
+            wikilinks: false,
+            wikilink_url: None,
+            ordered_list_start_override: None,
+            emoji: false,
+            loose_list_continuation: false,
+            pretty: None,
+            callout_aria: false,
+            collapsible_callouts: false,
+            max_nesting_depth: None,
+            max_inline_marks: None,
+            custom_delimiters: &[],
+            code_info_meta: false,
+            task_list_classes: false,
+            allow_nested_links: false,
+            allow_block_html: true,
+            allow_inline_html: true,
+            table_cell_merge: false,
+            code_span_renderer: None,
+            image_src_transform: None,
+            numeric_footnote_ids: false,
+            callout_titles: CalloutTitles::ENGLISH,
+            callout_custom_titles: false,
+            compact_list_items: false,
+            external_links: None,
+            blockquote_cite_url: false,
+            strikethrough_single_tilde: false,
+            sanitizer: Sanitizer::None,
+            hard_wrap: false,
+            www_autolink_scheme: WwwScheme::Http,
+            task_list_interactive: false,
+            smart_punctuation: false,
+            xhtml: true,
+            inline_footnotes: false,
+            footnote_backref_label: "↩",
+            footnote_id_prefix: "user-content-",
+            footnote_section_title: None,
+            fenced_divs: false,
+            attributes: false,
         }
     }
 }
@@ -247,6 +1124,136 @@ pub struct ParseResult<'a> {
     pub html: String,
     /// Raw front matter content (between delimiters), if detected.
     pub front_matter: Option<&'a str>,
+    /// Syntax of `front_matter`, from its delimiter.
+    pub front_matter_format: Option<FrontMatterFormat>,
+}
+
+#[cfg(feature = "front-matter-parse")]
+impl ParseResult<'_> {
+    /// Parse the front matter as YAML or TOML, following
+    /// `front_matter_format`. `None` without front matter.
+    ///
+    /// ```
+    /// use ferromark::FrontMatter;
+    ///
+    /// let result = ferromark::parse("+++\ntitle = \"Hello\"\n+++\n# Content");
+    /// let Some(Ok(FrontMatter::Toml(value))) = result.parsed_front_matter() else {
+    ///     panic!("expected TOML front matter");
+    /// };
+    /// assert_eq!(value["title"].as_str(), Some("Hello"));
+    /// ```
+    pub fn parsed_front_matter(&self) -> Option<Result<FrontMatter, FrontMatterError>> {
+        Some(parse_front_matter(
+            self.front_matter?,
+            self.front_matter_format?,
+        ))
+    }
+}
+
+/// Result of [`parse_full`]: the HTML plus what rendering collected.
+pub struct FullParseResult<'a> {
+    /// Rendered HTML output.
+    pub html: String,
+    /// Raw front matter content (between delimiters), if detected.
+    pub front_matter: Option<&'a str>,
+    /// Syntax of `front_matter`, from its delimiter.
+    pub front_matter_format: Option<FrontMatterFormat>,
+    /// The Markdown after the front matter. Ranges in `footnotes` index
+    /// into it.
+    pub body: &'a str,
+    /// Link reference definitions, with the duplicate and usage records of
+    /// [`to_html_with_link_refs`].
+    pub link_refs: LinkRefStore,
+    /// Footnote definitions, when `options.footnotes` is set.
+    pub footnotes: Option<FootnoteStore>,
+    /// Nested heading outline, as [`table_of_contents`] builds it.
+    pub headings: Vec<TocEntry>,
+}
+
+#[cfg(feature = "front-matter-parse")]
+impl FullParseResult<'_> {
+    /// Parse the front matter; see [`ParseResult::parsed_front_matter`].
+    pub fn parsed_front_matter(&self) -> Option<Result<FrontMatter, FrontMatterError>> {
+        Some(parse_front_matter(
+            self.front_matter?,
+            self.front_matter_format?,
+        ))
+    }
+}
+
+/// Front matter syntax, chosen by the delimiter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrontMatterFormat {
+    /// YAML between `---` lines.
+    Yaml,
+    /// TOML between `+++` lines.
+    Toml,
+}
+
+impl FrontMatterFormat {
+    /// Format of the front matter that `input` starts with.
+    fn of(input: &str) -> Self {
+        if input.starts_with('+') {
+            Self::Toml
+        } else {
+            Self::Yaml
+        }
+    }
+}
+
+/// Parsed front matter, from [`ParseResult::parsed_front_matter`].
+#[cfg(feature = "front-matter-parse")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrontMatter {
+    /// YAML front matter; empty content is `Null`.
+    Yaml(serde_yaml::Value),
+    /// TOML front matter, always a table.
+    Toml(toml::Value),
+}
+
+/// Error from [`ParseResult::parsed_front_matter`].
+#[cfg(feature = "front-matter-parse")]
+#[derive(Debug)]
+pub enum FrontMatterError {
+    /// The `---` front matter is not valid YAML.
+    Yaml(serde_yaml::Error),
+    /// The `+++` front matter is not valid TOML.
+    Toml(toml::de::Error),
+}
+
+#[cfg(feature = "front-matter-parse")]
+impl core::fmt::Display for FrontMatterError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Yaml(error) => write!(f, "invalid YAML front matter: {error}"),
+            Self::Toml(error) => write!(f, "invalid TOML front matter: {error}"),
+        }
+    }
+}
+
+#[cfg(feature = "front-matter-parse")]
+impl core::error::Error for FrontMatterError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Yaml(error) => Some(error),
+            Self::Toml(error) => Some(error),
+        }
+    }
+}
+
+#[cfg(feature = "front-matter-parse")]
+fn parse_front_matter(
+    raw: &str,
+    format: FrontMatterFormat,
+) -> Result<FrontMatter, FrontMatterError> {
+    match format {
+        FrontMatterFormat::Yaml => serde_yaml::from_str(raw)
+            .map(FrontMatter::Yaml)
+            .map_err(FrontMatterError::Yaml),
+        FrontMatterFormat::Toml => toml::from_str(raw)
+            .map(FrontMatter::Toml)
+            .map_err(FrontMatterError::Toml),
+    }
 }
 
 /// Extract front matter from the start of a document.
@@ -357,6 +1364,18 @@ fn extract_front_matter(input: &str) -> Option<(&str, usize)> {
     None
 }
 
+/// Return the Markdown body, skipping front matter when `options.front_matter` is set.
+fn strip_front_matter<'a>(input: &'a str, options: &Options) -> &'a str {
+    if options.front_matter {
+        match extract_front_matter(input) {
+            Some((_, offset)) => &input[offset..],
+            None => input,
+        }
+    } else {
+        input
+    }
+}
+
 /// Parse Markdown and return both HTML and front matter (if present).
 ///
 /// Uses default options with `front_matter: true`.
@@ -389,7 +1408,57 @@ pub fn parse_with_options<'a>(input: &'a str, options: &Options) -> ParseResult<
     };
 
     let html = to_html_with_options(markdown, options);
-    ParseResult { html, front_matter }
+    ParseResult {
+        html,
+        front_matter,
+        front_matter_format: front_matter.map(|_| FrontMatterFormat::of(input)),
+    }
+}
+
+/// Parse Markdown with options and return the HTML together with the link
+/// reference definitions, footnote definitions and heading outline that
+/// rendering collected, without parsing a second time.
+///
+/// Front matter is only extracted when `options.front_matter` is `true`.
+///
+/// # Example
+/// ```
+/// let options = ferromark::Options {
+///     footnotes: true,
+///     ..ferromark::Options::default()
+/// };
+/// let markdown = "# Intro\n\nSee [docs][d].[^n]\n\n[d]: /docs\n\n[^n]: Note.\n";
+/// let result = ferromark::parse_full(markdown, &options);
+/// assert!(result.html.contains("href=\"/docs\""));
+/// assert_eq!(result.link_refs.unused(), [] as [&str; 0]);
+/// assert_eq!(result.footnotes.map(|footnotes| footnotes.len()), Some(1));
+/// assert_eq!(result.headings[0].id, "intro");
+/// ```
+pub fn parse_full<'a>(input: &'a str, options: &Options) -> FullParseResult<'a> {
+    let (front_matter, body) = if options.front_matter {
+        match extract_front_matter(input) {
+            Some((fm, offset)) => (Some(fm), &input[offset..]),
+            None => (None, input),
+        }
+    } else {
+        (None, input)
+    };
+
+    let mut writer = HtmlWriter::with_capacity_for(body.len());
+    let rendered =
+        renderer::render_document(body.as_bytes(), options, &mut writer, None, true, None);
+    let html = writer
+        .into_string()
+        .expect("rendering from a UTF-8 Markdown string must produce UTF-8 HTML");
+    FullParseResult {
+        html,
+        front_matter,
+        front_matter_format: front_matter.map(|_| FrontMatterFormat::of(input)),
+        body,
+        link_refs: rendered.link_refs,
+        footnotes: rendered.footnote_store,
+        headings: toc::nest_entries(&mut rendered.outline.into_iter().peekable(), 0),
+    }
 }
 
 /// Convert Markdown to HTML.
@@ -410,9 +1479,38 @@ pub fn to_html(input: &str) -> String {
         .expect("rendering from a UTF-8 Markdown string must produce UTF-8 HTML")
 }
 
+/// Convert a Markdown byte buffer to HTML after validating it as UTF-8.
+///
+/// With the `simdutf8` feature, validation uses SIMD; otherwise it is
+/// [`core::str::from_utf8`]. Either way invalid input yields the standard
+/// [`Utf8Error`](core::str::Utf8Error).
+///
+/// # Example
+/// ```
+/// let html = ferromark::to_html_from_bytes(b"# Hello").unwrap();
+/// assert_eq!(html, "<h1 id=\"hello\">Hello</h1>\n");
+/// assert!(ferromark::to_html_from_bytes(b"\xFF").is_err());
+/// ```
+pub fn to_html_from_bytes(input: &[u8]) -> Result<String, core::str::Utf8Error> {
+    Ok(to_html(validate_utf8(input)?))
+}
+
+/// Validate UTF-8, taking the SIMD fast path when enabled. Only the fallback
+/// reports the error position, so invalid input is re-checked by `core`.
+#[inline]
+fn validate_utf8(input: &[u8]) -> Result<&str, core::str::Utf8Error> {
+    #[cfg(feature = "simdutf8")]
+    if let Ok(text) = simdutf8::basic::from_utf8(input) {
+        return Ok(text);
+    }
+    core::str::from_utf8(input)
+}
+
 /// Convert Markdown to HTML, writing into a provided buffer.
 ///
-/// This avoids allocation if the buffer has sufficient capacity.
+/// This avoids allocation if the buffer has sufficient capacity. For
+/// link- or table-heavy documents, reserving
+/// [`HtmlWriter::estimate_capacity`] up front avoids growing the buffer.
 pub fn to_html_into(input: &str, out: &mut Vec<u8>) {
     to_html_into_with_options(input, out, &Options::default());
 }
@@ -422,14 +1520,7 @@ pub fn to_html_into(input: &str, out: &mut Vec<u8>) {
 /// When `options.front_matter` is `true`, any front matter at the start of the
 /// document is silently stripped before parsing.
 pub fn to_html_with_options(input: &str, options: &Options) -> String {
-    let markdown = if options.front_matter {
-        match extract_front_matter(input) {
-            Some((_, offset)) => &input[offset..],
-            None => input,
-        }
-    } else {
-        input
-    };
+    let markdown = strip_front_matter(input, options);
     let mut writer = HtmlWriter::with_capacity_for(markdown.len());
     render_to_writer(markdown.as_bytes(), &mut writer, options);
     writer
@@ -437,26 +1528,86 @@ pub fn to_html_with_options(input: &str, options: &Options) -> String {
         .expect("rendering from a UTF-8 Markdown string must produce UTF-8 HTML")
 }
 
-/// Convert Markdown to HTML with an opt-in fenced-code renderer.
+/// Convert Markdown to HTML and return the document's link reference
+/// definitions for validation.
 ///
-/// The renderer sees only fenced code blocks. Returning `None` preserves the
-/// normal escaped code-block output. Installing a renderer is an explicit HTML
-/// trust boundary; see [`TrustedHtml`].
-pub fn to_html_with_renderer(
-    input: &str,
+/// The returned store records labels defined more than once and which
+/// definitions a reference link or image resolved to; the HTML is the same
+/// as from [`to_html_with_options`].
+///
+/// # Example
+/// ```
+/// let markdown = "[a][x]\n\n[x]: /one\n[x]: /two\n[y]: /unused\n";
+/// let (html, refs) = ferromark::to_html_with_link_refs(markdown, &ferromark::Options::default());
+/// assert!(html.contains("href=\"/one\""));
+/// assert_eq!(refs.duplicates(), ["x"]);
+/// assert_eq!(refs.unused(), ["y"]);
+/// ```
+pub fn to_html_with_link_refs(input: &str, options: &Options) -> (String, LinkRefStore) {
+    let markdown = strip_front_matter(input, options);
+    let mut writer = HtmlWriter::with_capacity_for(markdown.len());
+    let link_refs = render_to_writer(markdown.as_bytes(), &mut writer, options);
+    let html = writer
+        .into_string()
+        .expect("rendering from a UTF-8 Markdown string must produce UTF-8 HTML");
+    (html, link_refs)
+}
+
+/// Convert Markdown to HTML, resolving reference links against the
+/// document's own definitions and then against `shared_refs`.
+///
+/// A definition in `input` takes precedence over a shared one with the same
+/// label, as if the shared definitions followed the document. This lets
+/// several documents use one central definitions file. `shared_refs` is
+/// only read; footnote definitions still come from `input` alone.
+///
+/// # Example
+/// ```
+/// use ferromark::Options;
+///
+/// let definitions = "[docs]: https://docs.rs\n[home]: /\n";
+/// let (_, shared) = ferromark::to_html_with_link_refs(definitions, &Options::default());
+///
+/// let markdown = "[docs] and [home]\n\n[home]: /index.html\n";
+/// let html = ferromark::to_html_with_shared_refs(markdown, &Options::default(), &shared);
+/// assert_eq!(
+///     html,
+///     "<p><a href=\"https://docs.rs\">docs</a> and <a href=\"/index.html\">home</a></p>\n"
+/// );
+/// ```
+pub fn to_html_with_shared_refs(
+    input: &str,
+    options: &Options,
+    shared_refs: &LinkRefStore,
+) -> String {
+    let markdown = strip_front_matter(input, options);
+    let mut writer = HtmlWriter::with_capacity_for(markdown.len());
+    renderer::render_document(
+        markdown.as_bytes(),
+        options,
+        &mut writer,
+        Some(shared_refs),
+        false,
+        None,
+    );
+    writer
+        .into_string()
+        .expect("rendering from a UTF-8 Markdown string must produce UTF-8 HTML")
+}
+
+/// Convert Markdown to HTML with an opt-in fenced-code renderer.
+///
+/// The renderer sees only fenced code blocks. Returning `None` preserves the
+/// normal escaped code-block output. Installing a renderer is an explicit HTML
+/// trust boundary; see [`TrustedHtml`].
+pub fn to_html_with_renderer(
+    input: &str,
     options: &Options,
     renderer: &mut dyn FencedCodeRenderer,
 ) -> String {
-    let markdown = if options.front_matter {
-        match extract_front_matter(input) {
-            Some((_, offset)) => &input[offset..],
-            None => input,
-        }
-    } else {
-        input
-    };
+    let markdown = strip_front_matter(input, options);
     let mut writer = HtmlWriter::with_capacity_for(markdown.len());
-    render_to_writer_with_renderer(markdown.as_bytes(), &mut writer, options, Some(renderer));
+    render_to_writer_with_renderer(markdown.as_bytes(), &mut writer, options, renderer);
     writer
         .into_string()
         .expect("rendering from a UTF-8 Markdown string must produce UTF-8 HTML")
@@ -467,21 +1618,14 @@ pub fn to_html_with_renderer(
 /// When `options.front_matter` is `true`, any front matter at the start of the
 /// document is silently stripped before parsing.
 pub fn to_html_into_with_options(input: &str, out: &mut Vec<u8>, options: &Options) {
-    let markdown = if options.front_matter {
-        match extract_front_matter(input) {
-            Some((_, offset)) => &input[offset..],
-            None => input,
-        }
-    } else {
-        input
-    };
+    let markdown = strip_front_matter(input, options);
     out.clear();
     out.reserve(markdown.len() + markdown.len() / 4);
     let mut writer = HtmlWriter::with_capacity(0);
     // Use the provided buffer directly
-    std::mem::swap(writer.buffer_mut(), out);
+    core::mem::swap(writer.buffer_mut(), out);
     render_to_writer(markdown.as_bytes(), &mut writer, options);
-    std::mem::swap(writer.buffer_mut(), out);
+    core::mem::swap(writer.buffer_mut(), out);
 }
 
 /// Convert Markdown into a reusable buffer with an opt-in fenced-code renderer.
@@ -491,22 +1635,61 @@ pub fn to_html_into_with_renderer(
     options: &Options,
     renderer: &mut dyn FencedCodeRenderer,
 ) {
-    let markdown = if options.front_matter {
-        match extract_front_matter(input) {
-            Some((_, offset)) => &input[offset..],
-            None => input,
-        }
-    } else {
-        input
-    };
+    let markdown = strip_front_matter(input, options);
     out.clear();
     out.reserve(markdown.len() + markdown.len() / 4);
     let mut writer = HtmlWriter::with_capacity(0);
-    std::mem::swap(writer.buffer_mut(), out);
-    render_to_writer_with_renderer(markdown.as_bytes(), &mut writer, options, Some(renderer));
-    std::mem::swap(writer.buffer_mut(), out);
+    core::mem::swap(writer.buffer_mut(), out);
+    render_to_writer_with_renderer(markdown.as_bytes(), &mut writer, options, renderer);
+    core::mem::swap(writer.buffer_mut(), out);
+}
+
+/// Convert Markdown to HTML, writing it to `out` as rendering goes.
+///
+/// The HTML is the same as from [`to_html_with_options`], but only a few
+/// kilobytes of it are held in memory besides the block being rendered:
+/// once enough has collected, the output later blocks can no longer change
+/// is written to `out`. Chunks end after a line or a tag, never inside a
+/// UTF-8 sequence or an entity. A write error stops rendering and is
+/// returned.
+///
+/// # Example
+/// ```
+/// let mut html = Vec::new();
+/// ferromark::to_html_writer("# Title\n\nText.", &mut html, &Default::default())?;
+/// assert_eq!(html, b"<h1 id=\"title\">Title</h1>\n<p>Text.</p>\n");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(feature = "std")]
+pub fn to_html_writer<W: std::io::Write + ?Sized>(
+    input: &str,
+    out: &mut W,
+    options: &Options,
+) -> std::io::Result<()> {
+    let markdown = strip_front_matter(input, options);
+    let mut writer = HtmlWriter::with_capacity(2 * STREAM_CHUNK);
+    let mut result = Ok(());
+    renderer::render_document(
+        markdown.as_bytes(),
+        options,
+        &mut writer,
+        None,
+        false,
+        Some(&mut |writer: &mut HtmlWriter| {
+            if writer.len() >= STREAM_CHUNK {
+                result = writer.write_final(out);
+            }
+            result.is_ok()
+        }),
+    );
+    result?;
+    out.write_all(writer.as_bytes())
 }
 
+/// Output [`to_html_writer`] collects before writing what is final.
+#[cfg(feature = "std")]
+const STREAM_CHUNK: usize = 8 * 1024;
+
 /// State for collecting paragraph content before inline parsing.
 struct ParagraphState {
     /// Collected text content (joined with newlines).
@@ -605,7 +1788,7 @@ impl HeadingState {
 /// and not a hash-DoS surface, so SipHash's cost is not warranted.
 struct HeadingIdTracker {
     /// Maps a base slug to how many times it has been seen so far.
-    used: std::collections::HashMap<String, usize, rustc_hash::FxBuildHasher>,
+    used: hashbrown::HashMap<String, usize, rustc_hash::FxBuildHasher>,
     /// Reusable buffer holding the id returned by `make_id`.
     slug_buf: Vec<u8>,
 }
@@ -613,10 +1796,7 @@ struct HeadingIdTracker {
 impl HeadingIdTracker {
     fn new() -> Self {
         Self {
-            used: std::collections::HashMap::with_capacity_and_hasher(
-                32,
-                rustc_hash::FxBuildHasher,
-            ),
+            used: hashbrown::HashMap::with_capacity_and_hasher(32, rustc_hash::FxBuildHasher),
             slug_buf: Vec::with_capacity(64),
         }
     }
@@ -627,11 +1807,11 @@ impl HeadingIdTracker {
     /// base slug is recorded.
     fn make_id(&mut self, raw: &[u8]) -> &str {
         generate_slug_into(raw, &mut self.slug_buf);
-        if self.slug_buf.is_empty() || std::str::from_utf8(&self.slug_buf).is_err() {
+        if self.slug_buf.is_empty() || core::str::from_utf8(&self.slug_buf).is_err() {
             self.slug_buf.clear();
             self.slug_buf.extend_from_slice(b"heading");
         }
-        let slug = std::str::from_utf8(&self.slug_buf).unwrap_or("heading");
+        let slug = core::str::from_utf8(&self.slug_buf).unwrap_or("heading");
         match self.used.get_mut(slug) {
             Some(count) => {
                 *count += 1;
@@ -643,7 +1823,16 @@ impl HeadingIdTracker {
                 self.used.insert(slug.to_string(), 0);
             }
         }
-        std::str::from_utf8(&self.slug_buf).unwrap_or("heading")
+        core::str::from_utf8(&self.slug_buf).unwrap_or("heading")
+    }
+
+    /// Record an explicit `{#id}` so a later heading with the same slug gets
+    /// a `-1` suffix instead of a duplicate id.
+    fn reserve(&mut self, id: &[u8]) {
+        let id = String::from_utf8_lossy(id);
+        if !self.used.contains_key(id.as_ref()) {
+            self.used.insert(id.into_owned(), 0);
+        }
     }
 }
 
@@ -667,7 +1856,8 @@ fn push_decimal(buf: &mut Vec<u8>, mut n: usize) {
 /// Steps:
 /// 1. Strip inline markup delimiters (`*`, `_`, `~`, `` ` ``, `[`, `]`, `!`, `#`)
 /// 2. Lowercase
-/// 3. Replace whitespace runs with `-`
+/// 3. Replace whitespace runs with `-`, including the soft or hard line breaks
+///    between the lines of a multi-line setext heading
 /// 4. Remove chars that are not alphanumeric, `-`, `_`, or space
 /// 5. Strip leading/trailing `-`
 fn generate_slug_into(raw: &[u8], slug: &mut Vec<u8>) {
@@ -760,598 +1950,87 @@ impl CellState {
     }
 }
 
-/// Buffered state used only while a custom renderer handles a fenced block.
-struct FencedCodeState {
-    info: Option<Range>,
-}
-
-impl FencedCodeState {
-    fn new(info: Option<Range>) -> Self {
-        Self { info }
-    }
-}
-
-/// Mutable state and shared inputs for one HTML rendering pass.
-struct RenderContext<'a, 'r, R: FencedCodeRenderer + ?Sized> {
-    writer: &'a mut HtmlWriter,
-    inline_parser: InlineParser,
-    inline_events: Vec<InlineEvent>,
-    para_state: ParagraphState,
-    heading_state: HeadingState,
-    cell_state: CellState,
-    tight_list_stack: Vec<(bool, u32)>,
-    at_tight_li_start: bool,
-    need_newline_before_block: bool,
-    pending_loose_li_newline: bool,
-    blockquote_depth: u32,
-    in_table_head: bool,
-    pending_task: block::TaskState,
-    link_refs: &'a LinkRefStore,
-    footnote_store: Option<&'a FootnoteStore>,
-    footnote_numbers: FootnoteNumbers,
-    heading_id_tracker: Option<HeadingIdTracker>,
-    callout_stack: Vec<Option<block::CalloutType>>,
-    pending_footnote_backref: Option<(String, usize)>,
-    options: &'a Options,
-    fenced_code_renderer: Option<&'r mut R>,
-    fenced_code_state: Option<FencedCodeState>,
-    fenced_code_buffer: Vec<u8>,
-}
-
-impl<'a, 'r, R: FencedCodeRenderer + ?Sized> RenderContext<'a, 'r, R> {
-    fn new(
-        writer: &'a mut HtmlWriter,
-        link_refs: &'a LinkRefStore,
-        footnote_store: Option<&'a FootnoteStore>,
-        options: &'a Options,
-        fenced_code_renderer: Option<&'r mut R>,
-    ) -> Self {
-        Self {
-            writer,
-            inline_parser: InlineParser::new(),
-            inline_events: Vec::with_capacity(64),
-            para_state: ParagraphState::new(),
-            heading_state: HeadingState::new(),
-            cell_state: CellState::new(),
-            tight_list_stack: Vec::new(),
-            at_tight_li_start: false,
-            need_newline_before_block: false,
-            pending_loose_li_newline: false,
-            blockquote_depth: 0,
-            in_table_head: false,
-            pending_task: block::TaskState::None,
-            link_refs,
-            footnote_store,
-            footnote_numbers: FootnoteNumbers::new(footnote_store.map_or(0, FootnoteStore::len)),
-            heading_id_tracker: options.heading_ids.then(HeadingIdTracker::new),
-            callout_stack: Vec::new(),
-            pending_footnote_backref: None,
-            options,
-            fenced_code_renderer,
-            fenced_code_state: None,
-            fenced_code_buffer: Vec::new(),
-        }
-    }
-}
-
-/// Render Markdown to an HtmlWriter.
-fn render_to_writer(input: &[u8], writer: &mut HtmlWriter, options: &Options) {
-    render_to_writer_impl::<DisabledFencedCodeRenderer>(input, writer, options, None);
+/// Headings collected while rendering for [`FullParseResult::headings`].
+///
+/// Ids come from a tracker of their own that sees the same headings as the
+/// renderer's, so they match the rendered anchors and [`table_of_contents`]
+/// whether or not `heading_ids` is set.
+struct Outline {
+    entries: Vec<TocEntry>,
+    ids: HeadingIdTracker,
 }
 
-fn render_to_writer_with_renderer(
-    input: &[u8],
-    writer: &mut HtmlWriter,
-    options: &Options,
-    fenced_code_renderer: Option<&mut dyn FencedCodeRenderer>,
-) {
-    render_to_writer_impl(input, writer, options, fenced_code_renderer);
+/// What rendering collected besides the HTML.
+struct RenderedDocument {
+    link_refs: LinkRefStore,
+    footnote_store: Option<FootnoteStore>,
+    outline: Vec<TocEntry>,
 }
 
-struct DisabledFencedCodeRenderer;
-
-impl FencedCodeRenderer for DisabledFencedCodeRenderer {
-    fn render(&mut self, _: FencedCodeBlock<'_>) -> Option<TrustedHtml> {
-        unreachable!("the default render path never installs a fenced-code renderer")
-    }
+/// Render Markdown to an HtmlWriter.
+fn render_to_writer(input: &[u8], writer: &mut HtmlWriter, options: &Options) -> LinkRefStore {
+    renderer::render_document(input, options, writer, None, false, None).link_refs
 }
 
-fn render_to_writer_impl<R: FencedCodeRenderer + ?Sized>(
+/// Render Markdown to an HtmlWriter, passing fenced code blocks to
+/// `fenced_code_renderer`.
+fn render_to_writer_with_renderer(
     input: &[u8],
     writer: &mut HtmlWriter,
     options: &Options,
-    fenced_code_renderer: Option<&mut R>,
+    fenced_code_renderer: &mut dyn FencedCodeRenderer,
 ) {
-    // Parse blocks
-    let mut parser = BlockParser::new_with_options(input, *options);
-    let mut events = Vec::with_capacity((input.len() / 16).max(64));
-    parser.parse(&mut events);
-    #[cfg(feature = "profiling")]
-    profiling::record_block_events(&events, events.capacity());
-    let link_refs = parser.take_link_refs();
-    let footnote_store = if options.footnotes {
-        Some(parser.take_footnote_store())
-    } else {
-        None
-    };
-
-    // Fix up list tight status (ListStart gets its tight value from ListEnd)
-    fixup_list_tight(&mut events);
-
-    let fn_store_ref = footnote_store.as_ref();
-    let mut context = RenderContext::new(
-        writer,
-        &link_refs,
-        fn_store_ref,
+    writer.collect_fenced_code();
+    renderer::render_document(
+        input,
         options,
-        fenced_code_renderer,
-    );
-
-    // Render events to HTML
-    for event in &events {
-        context.render_block_event(input, event);
-    }
-
-    // Render footnote section at document end
-    if !context.footnote_numbers.is_empty() {
-        context.render_footnote_section(input);
-    }
-}
-
-impl<R: FencedCodeRenderer + ?Sized> RenderContext<'_, '_, R> {
-    /// Render a single block event using the context's explicit state boundary.
-    fn render_block_event(&mut self, input: &[u8], event: &BlockEvent) {
-        let writer = &mut *self.writer;
-        let inline_parser = &mut self.inline_parser;
-        let inline_events = &mut self.inline_events;
-        let para_state = &mut self.para_state;
-        let heading_state = &mut self.heading_state;
-        let cell_state = &mut self.cell_state;
-        let tight_list_stack = &mut self.tight_list_stack;
-        let at_tight_li_start = &mut self.at_tight_li_start;
-        let need_newline_before_block = &mut self.need_newline_before_block;
-        let pending_loose_li_newline = &mut self.pending_loose_li_newline;
-        let blockquote_depth = &mut self.blockquote_depth;
-        let in_table_head = &mut self.in_table_head;
-        let pending_task = &mut self.pending_task;
-        let link_refs = self.link_refs;
-        let footnote_store = self.footnote_store;
-        let footnote_numbers = &mut self.footnote_numbers;
-        let heading_id_tracker = &mut self.heading_id_tracker;
-        let callout_stack = &mut self.callout_stack;
-        let pending_footnote_backref = &mut self.pending_footnote_backref;
-        let options = self.options;
-        let fenced_code_renderer = &mut self.fenced_code_renderer;
-        let fenced_code_state = &mut self.fenced_code_state;
-        let fenced_code_buffer = &mut self.fenced_code_buffer;
-
-        // Check if we're in a tight list (innermost list is tight)
-        // BUT: paragraphs inside blockquotes that started AFTER the list need <p> tags
-        let in_tight_list = tight_list_stack
-            .last()
-            .is_some_and(|(tight, bq_depth_at_start)| {
-                *tight && *blockquote_depth <= *bq_depth_at_start
+        writer,
+        None,
+        false,
+        Some(&mut |writer: &mut HtmlWriter| {
+            let Some(block) = writer.take_fenced_code() else {
+                return true;
+            };
+            let info = block.info.as_deref();
+            let language = info.map(HtmlWriter::decode_info_word);
+            let meta = info.and_then(HtmlWriter::decode_info_meta);
+            let code = core::str::from_utf8(&block.code)
+                .expect("fenced code originates from UTF-8 Markdown input");
+            let rendered = fenced_code_renderer.render(FencedCodeBlock {
+                language: language.as_deref().filter(|value| !value.is_empty()),
+                meta: meta.as_deref(),
+                code,
             });
-
-        match event {
-            BlockEvent::ParagraphStart => {
-                // Write pending newline from loose list item start
-                if *pending_loose_li_newline {
-                    writer.newline();
-                    *pending_loose_li_newline = false;
-                }
-                // In tight lists, don't emit <p> tags
-                if !in_tight_list {
-                    writer.paragraph_start();
-                }
-                para_state.start();
-                // Paragraph content is inline, so we don't add newline
-                *at_tight_li_start = false;
-            }
-            BlockEvent::ParagraphEnd => {
-                // Check if we're in a tight list (innermost list is tight)
-                // BUT: paragraphs inside blockquotes that started AFTER the list need </p> tags
-                let in_tight_list =
-                    tight_list_stack
-                        .last()
-                        .is_some_and(|(tight, bq_depth_at_start)| {
-                            *tight && *blockquote_depth <= *bq_depth_at_start
-                        });
-
-                // Parse all accumulated paragraph content at once
-                let content = para_state.finish();
-
-                // Emit pending task checkbox before paragraph content
-                emit_pending_task_checkbox(pending_task, writer);
-
-                if !content.is_empty() {
-                    render_inline_content(
-                        content,
-                        writer,
-                        inline_parser,
-                        inline_events,
-                        link_refs,
-                        footnote_store,
-                        footnote_numbers,
-                        options,
-                    );
-                }
-                if let Some((label, number)) = pending_footnote_backref.take() {
-                    write_footnote_backref(writer, &label, number);
-                }
-                // In tight lists, don't emit </p> tags
-                if !in_tight_list {
-                    writer.paragraph_end();
-                } else {
-                    // Mark that we need newline before next block element
-                    *need_newline_before_block = true;
-                }
-            }
-            BlockEvent::HeadingStart { level } => {
-                if *need_newline_before_block {
-                    writer.newline();
-                    *need_newline_before_block = false;
-                }
-                if *at_tight_li_start {
-                    writer.newline();
-                    *at_tight_li_start = false;
-                }
-                // Defer heading open tag to HeadingEnd so we can generate the slug
-                // from collected content before emitting the tag.
-                heading_state.start();
-                heading_state.level = *level;
-            }
-            BlockEvent::HeadingEnd { level } => {
-                let content = heading_state.finish();
-
-                // Emit heading open tag (deferred from HeadingStart)
-                if let Some(tracker) = heading_id_tracker.as_mut() {
-                    let id = tracker.make_id(content);
-                    writer.heading_start_with_id(*level, id);
-                } else {
-                    writer.heading_start(*level);
-                }
-
-                if !content.is_empty() {
-                    render_inline_content(
-                        content,
-                        writer,
-                        inline_parser,
-                        inline_events,
-                        link_refs,
-                        footnote_store,
-                        footnote_numbers,
-                        options,
-                    );
-                }
-                writer.heading_end(*level);
-            }
-            BlockEvent::ThematicBreak => {
-                // If we're at the start of a tight list item, add newline before block content
-                if *at_tight_li_start {
-                    writer.newline();
-                    *at_tight_li_start = false;
-                }
-                writer.thematic_break();
-            }
-            BlockEvent::HtmlBlockStart => {
-                // Write pending newline from loose list item start
-                if *pending_loose_li_newline {
-                    writer.newline();
-                    *pending_loose_li_newline = false;
-                }
-                // If we're at the start of a tight list item, add newline before block content
-                if *at_tight_li_start {
-                    writer.newline();
-                    *at_tight_li_start = false;
-                }
-            }
-            BlockEvent::HtmlBlockText(range) => {
-                if options.render_policy == RenderPolicy::Untrusted {
-                    writer.write_escaped_text(range.slice(input));
-                } else if options.disallowed_raw_html {
-                    writer.write_html_filtered(range.slice(input));
-                } else {
-                    writer.write_bytes(range.slice(input));
-                }
-            }
-            BlockEvent::HtmlBlockEnd => {}
-            BlockEvent::SoftBreak => {
-                if para_state.in_paragraph {
-                    para_state.add_soft_break();
-                } else if heading_state.in_heading {
-                    heading_state.add_soft_break();
-                } else {
-                    writer.write_str("\n");
-                }
+            match rendered {
+                Some(html) => writer.write_string(html.as_str()),
+                None => writer.write_fenced_code(&block),
             }
-            BlockEvent::Text(range) => {
-                let text = range.slice(input);
-                if para_state.in_paragraph {
-                    // Accumulate for later parsing
-                    para_state.add_text(text);
-                } else if heading_state.in_heading {
-                    heading_state.add_text(text);
-                } else if cell_state.in_cell {
-                    cell_state.add_text(text);
-                } else {
-                    render_inline_content(
-                        text,
-                        writer,
-                        inline_parser,
-                        inline_events,
-                        link_refs,
-                        footnote_store,
-                        footnote_numbers,
-                        options,
-                    );
-                }
-            }
-            BlockEvent::Code(range) => {
-                // Code block content - no inline parsing
-                if fenced_code_state.is_some() {
-                    fenced_code_buffer.extend_from_slice(range.slice(input));
-                } else {
-                    writer.write_escaped_text(range.slice(input));
-                }
-            }
-            BlockEvent::VirtualSpaces(count) => {
-                // Emit spaces for tab expansion in indented code blocks
-                if fenced_code_state.is_some() {
-                    fenced_code_buffer.extend(std::iter::repeat_n(b' ', *count as usize));
-                } else {
-                    for _ in 0..*count {
-                        writer.write_byte(b' ');
-                    }
-                }
-            }
-            BlockEvent::CodeBlockStart { kind } => {
-                // Write pending newline from loose list item start
-                if *pending_loose_li_newline {
-                    writer.newline();
-                    *pending_loose_li_newline = false;
-                }
-                // If we're at the start of a tight list item, add newline before block content
-                if *at_tight_li_start {
-                    writer.newline();
-                    *at_tight_li_start = false;
-                }
-                match kind {
-                    CodeBlockKind::Fenced { info } if fenced_code_renderer.is_some() => {
-                        fenced_code_buffer.clear();
-                        *fenced_code_state = Some(FencedCodeState::new(*info));
-                    }
-                    CodeBlockKind::Fenced { info } => {
-                        writer.code_block_start(info.as_ref().map(|range| range.slice(input)));
-                    }
-                    CodeBlockKind::Indented => writer.code_block_start(None),
-                }
-            }
-            BlockEvent::CodeBlockEnd => {
-                if let Some(state) = fenced_code_state.take() {
-                    let language = state
-                        .info
-                        .map(|range| HtmlWriter::decode_info_word(range.slice(input)));
-                    let code = std::str::from_utf8(fenced_code_buffer)
-                        .expect("fenced code originates from UTF-8 Markdown input");
-                    let rendered = fenced_code_renderer.as_deref_mut().and_then(|renderer| {
-                        renderer.render(FencedCodeBlock {
-                            language: language.as_deref().filter(|value| !value.is_empty()),
-                            code,
-                        })
-                    });
-
-                    if let Some(html) = rendered {
-                        writer.write_string(html.as_str());
-                    } else {
-                        writer
-                            .code_block_start(state.info.as_ref().map(|range| range.slice(input)));
-                        writer.write_escaped_text(fenced_code_buffer);
-                        writer.code_block_end();
-                    }
-                    fenced_code_buffer.clear();
-                } else {
-                    writer.code_block_end();
-                }
-            }
-            BlockEvent::BlockQuoteStart { callout } => {
-                // Write pending newline from loose list item start
-                if *pending_loose_li_newline {
-                    writer.newline();
-                    *pending_loose_li_newline = false;
-                }
-                // If we need newline (after paragraph in tight list), add it
-                if *need_newline_before_block {
-                    writer.newline();
-                    *need_newline_before_block = false;
-                }
-                // If we're at the start of a tight list item, add newline before block content
-                if *at_tight_li_start {
-                    writer.newline();
-                    *at_tight_li_start = false;
-                }
-                *blockquote_depth += 1;
-                callout_stack.push(*callout);
-                if let Some(ct) = callout {
-                    writer.callout_start(*ct);
-                } else {
-                    writer.blockquote_start();
-                }
-            }
-            BlockEvent::BlockQuoteEnd => {
-                *blockquote_depth = blockquote_depth.saturating_sub(1);
-                match callout_stack.pop() {
-                    Some(Some(_)) => writer.callout_end(),
-                    _ => writer.blockquote_end(),
-                }
-            }
-            BlockEvent::ListStart { kind, tight } => {
-                // Write pending newline from loose list item start
-                if *pending_loose_li_newline {
-                    writer.newline();
-                    *pending_loose_li_newline = false;
-                }
-                // If we need newline (after paragraph in tight list), add it
-                if *need_newline_before_block {
-                    writer.newline();
-                    *need_newline_before_block = false;
-                }
-                // If we're at the start of a tight list item, add newline before nested list
-                if *at_tight_li_start {
-                    writer.newline();
-                    *at_tight_li_start = false;
-                }
-                // Push the tight status and current blockquote depth for this list
-                tight_list_stack.push((*tight, *blockquote_depth));
-                match kind {
-                    block::ListKind::Unordered => writer.ul_start(),
-                    block::ListKind::Ordered { start, .. } => {
-                        writer.ol_start(if *start == 1 { None } else { Some(*start) })
-                    }
-                }
-            }
-            BlockEvent::ListEnd { kind, .. } => {
-                match kind {
-                    block::ListKind::Unordered => writer.ul_end(),
-                    block::ListKind::Ordered { .. } => writer.ol_end(),
-                }
-                // Pop the tight status for this list
-                tight_list_stack.pop();
-            }
-            BlockEvent::ListItemStart { task } => {
-                writer.li_start();
-                // In loose lists, defer newline until content appears (for empty items)
-                if !in_tight_list {
-                    *pending_loose_li_newline = true;
-                } else {
-                    // In tight lists, mark that we may need newline if block content follows
-                    *at_tight_li_start = true;
-                }
-                // Store task state for rendering at the start of paragraph content
-                if options.task_lists {
-                    *pending_task = *task;
-                }
-            }
-            BlockEvent::ListItemEnd => {
-                *at_tight_li_start = false;
-                *need_newline_before_block = false;
-                *pending_loose_li_newline = false;
-                *pending_task = block::TaskState::None;
-                writer.li_end();
-            }
-
-            // --- Table events ---
-            BlockEvent::TableStart => {
-                if *pending_loose_li_newline {
-                    writer.newline();
-                    *pending_loose_li_newline = false;
-                }
-                if *need_newline_before_block {
-                    writer.newline();
-                    *need_newline_before_block = false;
-                }
-                if *at_tight_li_start {
-                    writer.newline();
-                    *at_tight_li_start = false;
-                }
-                writer.table_start();
-            }
-            BlockEvent::TableEnd => {
-                writer.table_end();
-            }
-            BlockEvent::TableHeadStart => {
-                *in_table_head = true;
-                writer.thead_start();
-            }
-            BlockEvent::TableHeadEnd => {
-                *in_table_head = false;
-                writer.thead_end();
-            }
-            BlockEvent::TableBodyStart => {
-                writer.tbody_start();
-            }
-            BlockEvent::TableBodyEnd => {
-                writer.tbody_end();
-            }
-            BlockEvent::TableRowStart => {
-                writer.tr_start();
-            }
-            BlockEvent::TableRowEnd => {
-                writer.tr_end();
-            }
-            BlockEvent::TableCellStart { alignment } => {
-                if *in_table_head {
-                    writer.th_start(*alignment);
-                } else {
-                    writer.td_start(*alignment);
-                }
-                cell_state.start();
-            }
-            BlockEvent::TableCellEnd => {
-                let content = cell_state.finish();
-                if !content.is_empty() {
-                    render_inline_content(
-                        content,
-                        writer,
-                        inline_parser,
-                        inline_events,
-                        link_refs,
-                        footnote_store,
-                        footnote_numbers,
-                        options,
-                    );
-                }
-                if *in_table_head {
-                    writer.th_end();
-                } else {
-                    writer.td_end();
-                }
-            }
-        }
-    }
-}
-
-/// Emit a pending task checkbox and reset the state.
-#[inline]
-fn emit_pending_task_checkbox(pending_task: &mut block::TaskState, writer: &mut HtmlWriter) {
-    match *pending_task {
-        block::TaskState::Unchecked => {
-            writer.write_bytes(b"<input type=\"checkbox\" disabled=\"\" /> ");
-        }
-        block::TaskState::Checked => {
-            writer.write_bytes(b"<input type=\"checkbox\" checked=\"\" disabled=\"\" /> ");
-        }
-        block::TaskState::None => {}
-    }
-    *pending_task = block::TaskState::None;
-}
-
-fn write_footnote_backref(writer: &mut HtmlWriter, label: &str, number: usize) {
-    writer.write_str(" <a href=\"#user-content-fnref-");
-    writer.write_string(label);
-    writer.write_str("\" class=\"data-footnote-backref\" aria-label=\"Back to reference ");
-    writer.write_string(&number.to_string());
-    writer.write_str("\">↩</a>");
-}
-
-/// State for tracking image rendering.
-/// Since we need to render: <img src="..." alt="ALT_TEXT_HERE" title="..." />
-/// But alt text comes as Text events between ImageStart and ImageEnd,
-/// we need to track:
-/// 1. The title to render at ImageEnd
-/// 2. The nesting depth (to handle nested images like ![foo ![bar](url1)](url2))
-struct ImageState {
-    title_range: Option<Range>,
-    title_bytes: Option<Vec<u8>>,
-    /// Nesting depth: 1 = in outermost image, 2+ = in nested image
-    depth: u32,
+            true
+        }),
+    );
 }
 
 /// First-reference ordering plus constant-time definition-to-ordinal lookup.
+///
+/// Inline footnotes are definitions too, indexed after the stored ones in
+/// the order they are referenced.
 struct FootnoteNumbers {
     order: Vec<usize>,
     /// Zero means unassigned; stored ordinals are one-based.
     ordinals: Vec<usize>,
+    /// Number of rendered references per definition.
+    references: Vec<usize>,
+    /// Number of stored definitions.
+    definitions: usize,
+    inline: Vec<InlineNote>,
+    /// Number in the label of the last inline footnote.
+    inline_label: usize,
+}
+
+/// The label and text of an inline footnote.
+struct InlineNote {
+    label: String,
+    content: Vec<u8>,
 }
 
 impl FootnoteNumbers {
@@ -1359,9 +2038,62 @@ impl FootnoteNumbers {
         Self {
             order: Vec::new(),
             ordinals: vec![0; definition_count],
+            references: vec![0; definition_count],
+            definitions: definition_count,
+            inline: Vec::new(),
+            inline_label: 0,
+        }
+    }
+
+    /// Add an inline footnote and return its definition index. It is
+    /// labelled `inline-<n>`, skipping labels of stored definitions.
+    fn add_inline(&mut self, content: &[u8], store: Option<&FootnoteStore>) -> usize {
+        let label = loop {
+            self.inline_label += 1;
+            let label = alloc::format!("inline-{}", self.inline_label);
+            if store.is_none_or(|store| store.get_index(&label).is_none()) {
+                break label;
+            }
+        };
+        self.inline.push(InlineNote {
+            label,
+            content: content.to_vec(),
+        });
+        self.ordinals.push(0);
+        self.references.push(0);
+        self.ordinals.len() - 1
+    }
+
+    fn inline_note(&self, definition_index: usize) -> Option<&InlineNote> {
+        self.inline
+            .get(definition_index.checked_sub(self.definitions)?)
+    }
+
+    /// The label of a stored definition or inline footnote.
+    fn label<'a>(
+        &'a self,
+        store: Option<&'a FootnoteStore>,
+        definition_index: usize,
+    ) -> Option<&'a str> {
+        match self.inline_note(definition_index) {
+            Some(note) => Some(&note.label),
+            None => store?.get(definition_index).map(|def| def.label.as_str()),
         }
     }
 
+    /// Number a new reference to a definition: its ordinal, and which
+    /// reference to it this is (one-based).
+    fn reference(&mut self, definition_index: usize) -> Option<(usize, usize)> {
+        let number = self.number(definition_index)?;
+        let count = &mut self.references[definition_index];
+        *count += 1;
+        Some((number, *count))
+    }
+
+    fn references(&self, definition_index: usize) -> usize {
+        self.references.get(definition_index).copied().unwrap_or(0)
+    }
+
     fn number(&mut self, definition_index: usize) -> Option<usize> {
         let ordinal = self.ordinals.get_mut(definition_index)?;
         if *ordinal == 0 {
@@ -1376,471 +2108,36 @@ impl FootnoteNumbers {
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-fn render_inline_content(
+/// The display math range of a paragraph consisting of a single `$$...$$`
+/// or `\[...\]` span.
+fn standalone_display_math(
     text: &[u8],
-    writer: &mut HtmlWriter,
     inline_parser: &mut InlineParser,
     inline_events: &mut Vec<InlineEvent>,
-    link_refs: &LinkRefStore,
-    footnote_store: Option<&FootnoteStore>,
-    footnote_numbers: &mut FootnoteNumbers,
     options: &Options,
-) {
+) -> Option<Range> {
+    let delimited = (text.starts_with(b"$$") && text.ends_with(b"$$"))
+        || (text.starts_with(b"\\[") && text.ends_with(b"\\]"));
+    if !options.math || text.len() < 4 || !delimited {
+        return None;
+    }
     inline_events.clear();
-    inline_events.reserve((text.len() / 8).max(8));
-    let refs = options.allow_link_refs.then_some(link_refs);
     inline_parser.parse_with_options(
         text,
-        refs,
-        options.allow_html,
-        options.strikethrough,
-        options.highlight,
-        options.superscript,
-        options.subscript,
-        options.autolink_literals,
-        options.math,
-        footnote_store,
+        None,
+        false,
+        false,
+        false,
+        false,
+        false,
+        false,
+        true,
+        None,
         inline_events,
     );
-    #[cfg(feature = "profiling")]
-    profiling::record_inline_events(inline_events, inline_events.capacity());
-
-    let mut image_state = None;
-    for event in inline_events.iter() {
-        render_inline_event(
-            text,
-            event,
-            writer,
-            &mut image_state,
-            link_refs,
-            options.disallowed_raw_html,
-            options.render_policy,
-            footnote_store,
-            footnote_numbers,
-        );
-    }
-}
-
-/// Render a single inline event to HTML.
-#[allow(clippy::too_many_arguments)]
-fn render_inline_event(
-    text: &[u8],
-    event: &InlineEvent,
-    writer: &mut HtmlWriter,
-    image_state: &mut Option<ImageState>,
-    link_refs: &LinkRefStore,
-    filter_html: bool,
-    render_policy: RenderPolicy,
-    footnote_store: Option<&FootnoteStore>,
-    footnote_numbers: &mut FootnoteNumbers,
-) {
-    // Check if we're inside an image (for alt text rendering)
-    let in_image = image_state.as_ref().is_some_and(|s| s.depth > 0);
-
-    match event {
-        InlineEvent::Text(range) => {
-            // In image alt text, we still write the text (escaped for attributes)
-            if in_image {
-                writer.write_escaped_attr(range.slice(text));
-            } else {
-                // Decode HTML entities and escape for output
-                writer.write_text_with_entities(range.slice(text));
-            }
-        }
-        #[cfg(feature = "mdx")]
-        InlineEvent::MdxExpression(range)
-        | InlineEvent::MdxJsxOpen(range)
-        | InlineEvent::MdxJsxClose(range)
-        | InlineEvent::MdxJsxSelfClose(range) => {
-            if in_image {
-                writer.write_escaped_attr(range.slice(text));
-            } else if render_policy == RenderPolicy::Trusted {
-                writer.write_bytes(range.slice(text));
-            } else {
-                writer.write_escaped_text(range.slice(text));
-            }
-        }
-        InlineEvent::Code(range) => {
-            // In image alt text, just write the code content as plain text
-            if in_image {
-                let code_content = range.slice(text);
-                for &b in code_content {
-                    if b == b'\n' {
-                        writer.write_str(" ");
-                    } else if b == b'<' {
-                        writer.write_str("&lt;");
-                    } else if b == b'>' {
-                        writer.write_str("&gt;");
-                    } else if b == b'&' {
-                        writer.write_str("&amp;");
-                    } else if b == b'"' {
-                        writer.write_str("&quot;");
-                    } else {
-                        writer.buffer_mut().push(b);
-                    }
-                }
-            } else {
-                writer.write_str("<code>");
-                // CommonMark: line endings in code spans are converted to spaces
-                let code_content = range.slice(text);
-                for &b in code_content {
-                    if b == b'\n' {
-                        writer.write_str(" ");
-                    } else if b == b'<' {
-                        writer.write_str("&lt;");
-                    } else if b == b'>' {
-                        writer.write_str("&gt;");
-                    } else if b == b'&' {
-                        writer.write_str("&amp;");
-                    } else if b == b'"' {
-                        writer.write_str("&quot;");
-                    } else {
-                        writer.buffer_mut().push(b);
-                    }
-                }
-                writer.write_str("</code>");
-            }
-        }
-        InlineEvent::EmphasisStart => {
-            // Suppress HTML tags inside image alt text
-            if !in_image {
-                writer.write_str("<em>");
-            }
-        }
-        InlineEvent::EmphasisEnd => {
-            if !in_image {
-                writer.write_str("</em>");
-            }
-        }
-        InlineEvent::StrongStart => {
-            if !in_image {
-                writer.write_str("<strong>");
-            }
-        }
-        InlineEvent::StrongEnd => {
-            if !in_image {
-                writer.write_str("</strong>");
-            }
-        }
-        InlineEvent::StrikethroughStart => {
-            if !in_image {
-                writer.write_str("<del>");
-            }
-        }
-        InlineEvent::StrikethroughEnd => {
-            if !in_image {
-                writer.write_str("</del>");
-            }
-        }
-        InlineEvent::SubscriptStart => {
-            if !in_image {
-                writer.write_str("<sub>");
-            }
-        }
-        InlineEvent::SubscriptEnd => {
-            if !in_image {
-                writer.write_str("</sub>");
-            }
-        }
-        InlineEvent::SuperscriptStart => {
-            if !in_image {
-                writer.write_str("<sup>");
-            }
-        }
-        InlineEvent::SuperscriptEnd => {
-            if !in_image {
-                writer.write_str("</sup>");
-            }
-        }
-        InlineEvent::HighlightStart => {
-            if !in_image {
-                writer.write_str("<mark>");
-            }
-        }
-        InlineEvent::HighlightEnd => {
-            if !in_image {
-                writer.write_str("</mark>");
-            }
-        }
-        InlineEvent::LinkStart { url, title } => {
-            // Suppress link tags inside image alt text
-            if !in_image {
-                writer.write_str("<a href=\"");
-                writer.write_link_url_with_policy(url.slice(text), render_policy);
-                writer.write_str("\"");
-                if let Some(t) = title {
-                    writer.write_str(" title=\"");
-                    writer.write_link_title(t.slice(text));
-                    writer.write_str("\"");
-                }
-                writer.write_str(">");
-            }
-        }
-        InlineEvent::LinkStartRef { def_index } => {
-            if !in_image {
-                if let Some(def) = link_refs.get(*def_index as usize) {
-                    writer.write_str("<a href=\"");
-                    writer.write_link_url_with_policy(&def.url, render_policy);
-                    writer.write_str("\"");
-                    if let Some(title) = &def.title {
-                        writer.write_str(" title=\"");
-                        writer.write_link_title(title);
-                        writer.write_str("\"");
-                    }
-                    writer.write_str(">");
-                }
-            }
-        }
-        InlineEvent::LinkEnd => {
-            if !in_image {
-                writer.write_str("</a>");
-            }
-        }
-        InlineEvent::ImageStart { url, title } => {
-            // If we're already inside an image, just increment depth
-            // (the inner image's alt text becomes plain text in outer alt)
-            if let Some(state) = image_state.as_mut() {
-                state.depth += 1;
-            } else {
-                // Outermost image - emit the img tag start
-                writer.write_str("<img src=\"");
-                writer.write_link_url_with_policy(url.slice(text), render_policy);
-                writer.write_str("\" alt=\"");
-                *image_state = Some(ImageState {
-                    title_range: *title,
-                    title_bytes: None,
-                    depth: 1,
-                });
-            }
-        }
-        InlineEvent::ImageStartRef { def_index } => {
-            if let Some(state) = image_state.as_mut() {
-                state.depth += 1;
-            } else if let Some(def) = link_refs.get(*def_index as usize) {
-                writer.write_str("<img src=\"");
-                writer.write_link_url_with_policy(&def.url, render_policy);
-                writer.write_str("\" alt=\"");
-                *image_state = Some(ImageState {
-                    title_range: None,
-                    title_bytes: def.title.clone(),
-                    depth: 1,
-                });
-            }
-        }
-        InlineEvent::ImageEnd => {
-            if let Some(state) = image_state.as_mut() {
-                state.depth -= 1;
-                // Only close when we exit the outermost image
-                if state.depth == 0 {
-                    writer.write_str("\"");
-                    // Add title attribute if present
-                    let title_range = state.title_range;
-                    let title_bytes = state.title_bytes.clone();
-                    *image_state = None;
-                    if let Some(bytes) = title_bytes {
-                        writer.write_str(" title=\"");
-                        writer.write_link_title(&bytes);
-                        writer.write_str("\"");
-                    } else if let Some(title_range) = title_range {
-                        writer.write_str(" title=\"");
-                        writer.write_link_title(title_range.slice(text));
-                        writer.write_str("\"");
-                    }
-                    writer.write_str(" />");
-                }
-            }
-        }
-        InlineEvent::AutolinkLiteral { url, kind } => {
-            use crate::inline::AutolinkLiteralKind;
-            if in_image {
-                writer.write_escaped_attr(url.slice(text));
-            } else {
-                writer.write_str("<a href=\"");
-                match kind {
-                    AutolinkLiteralKind::Url => {
-                        writer.write_link_url_with_policy(url.slice(text), render_policy);
-                    }
-                    AutolinkLiteralKind::Www => {
-                        writer.write_str("http://");
-                        writer.write_link_url(url.slice(text));
-                    }
-                    AutolinkLiteralKind::Email => {
-                        writer.write_str("mailto:");
-                        writer.write_link_url(url.slice(text));
-                    }
-                }
-                writer.write_str("\">");
-                writer.write_escaped_text(url.slice(text));
-                writer.write_str("</a>");
-            }
-        }
-        InlineEvent::Autolink { url, is_email } => {
-            // In image alt text, just output the URL as plain text
-            if in_image {
-                writer.write_escaped_attr(url.slice(text));
-            } else {
-                writer.write_str("<a href=\"");
-                if *is_email {
-                    writer.write_str("mailto:");
-                    writer.write_url_encoded(url.slice(text));
-                } else {
-                    writer.write_url_encoded_with_policy(url.slice(text), render_policy);
-                }
-                writer.write_str("\">");
-                // Display text is shown as-is (with HTML escaping)
-                writer.write_escaped_text(url.slice(text));
-                writer.write_str("</a>");
-            }
-        }
-        InlineEvent::Html(range) => {
-            if in_image {
-                writer.write_escaped_attr(range.slice(text));
-            } else if render_policy == RenderPolicy::Untrusted {
-                writer.write_escaped_text(range.slice(text));
-            } else if filter_html {
-                writer.write_html_filtered(range.slice(text));
-            } else {
-                writer.write_bytes(range.slice(text));
-            }
-        }
-        InlineEvent::SoftBreak => {
-            // In image alt text, use space instead of newline
-            if in_image {
-                writer.write_str(" ");
-            } else {
-                writer.write_str("\n");
-            }
-        }
-        InlineEvent::HardBreak => {
-            // In image alt text, use space instead of <br />
-            if in_image {
-                writer.write_str(" ");
-            } else {
-                writer.write_str("<br />\n");
-            }
-        }
-        InlineEvent::EscapedChar(ch) => {
-            // Write the escaped character (the actual char, not the backslash)
-            let bytes = [*ch];
-            if in_image {
-                writer.write_escaped_attr(&bytes);
-            } else {
-                writer.write_escaped_text(&bytes);
-            }
-        }
-        InlineEvent::FootnoteRef { def_index } => {
-            if !in_image {
-                if let Some(fn_store) = footnote_store {
-                    let def_idx = *def_index as usize;
-                    if let (Some(number), Some(def)) =
-                        (footnote_numbers.number(def_idx), fn_store.get(def_idx))
-                    {
-                        writer.write_str("<sup><a href=\"#user-content-fn-");
-                        writer.write_string(&def.label);
-                        writer.write_str("\" id=\"user-content-fnref-");
-                        writer.write_string(&def.label);
-                        writer.write_str("\" data-footnote-ref>");
-                        let num_str = number.to_string();
-                        writer.write_string(&num_str);
-                        writer.write_str("</a></sup>");
-                    }
-                }
-            }
-        }
-        InlineEvent::MathInline(range) => {
-            if in_image {
-                writer.write_escaped_attr(range.slice(text));
-            } else {
-                writer.write_str("<code class=\"language-math math-inline\">");
-                let content = range.slice(text);
-                for &b in content {
-                    if b == b'\n' {
-                        writer.write_str(" ");
-                    } else if b == b'<' {
-                        writer.write_str("&lt;");
-                    } else if b == b'>' {
-                        writer.write_str("&gt;");
-                    } else if b == b'&' {
-                        writer.write_str("&amp;");
-                    } else if b == b'"' {
-                        writer.write_str("&quot;");
-                    } else {
-                        writer.buffer_mut().push(b);
-                    }
-                }
-                writer.write_str("</code>");
-            }
-        }
-        InlineEvent::MathDisplay(range) => {
-            if in_image {
-                writer.write_escaped_attr(range.slice(text));
-            } else {
-                writer.write_str("<code class=\"language-math math-display\">");
-                let content = range.slice(text);
-                for &b in content {
-                    if b == b'\n' {
-                        writer.write_str(" ");
-                    } else if b == b'<' {
-                        writer.write_str("&lt;");
-                    } else if b == b'>' {
-                        writer.write_str("&gt;");
-                    } else if b == b'&' {
-                        writer.write_str("&amp;");
-                    } else if b == b'"' {
-                        writer.write_str("&quot;");
-                    } else {
-                        writer.buffer_mut().push(b);
-                    }
-                }
-                writer.write_str("</code>");
-            }
-        }
-    }
-}
-
-impl<R: FencedCodeRenderer + ?Sized> RenderContext<'_, '_, R> {
-    /// Render collected footnotes with a fresh block state per definition.
-    fn render_footnote_section(&mut self, input: &[u8]) {
-        let Some(footnote_store) = self.footnote_store else {
-            return;
-        };
-        let order = self.footnote_numbers.order.clone();
-        self.writer
-            .write_str("<section data-footnotes class=\"footnotes\">\n<ol>\n");
-
-        for (seq_num, def_idx) in order.into_iter().enumerate() {
-            let Some(def) = footnote_store.get(def_idx) else {
-                continue;
-            };
-            let number = seq_num + 1;
-            self.writer.write_str("<li id=\"user-content-fn-");
-            self.writer.write_string(&def.label);
-            self.writer.write_str("\">\n");
-
-            let last_paragraph_end = def
-                .events
-                .iter()
-                .rposition(|event| matches!(event, BlockEvent::ParagraphEnd));
-            let renderer = self.fenced_code_renderer.as_deref_mut();
-            let mut nested = RenderContext::new(
-                &mut *self.writer,
-                self.link_refs,
-                Some(footnote_store),
-                self.options,
-                renderer,
-            );
-            for (index, event) in def.events.iter().enumerate() {
-                if Some(index) == last_paragraph_end {
-                    nested.pending_footnote_backref = Some((def.label.clone(), number));
-                }
-                nested.render_block_event(input, event);
-            }
-
-            self.writer.write_str("</li>\n");
-        }
-
-        self.writer.write_str("</ol>\n</section>\n");
+    match inline_events.as_slice() {
+        [InlineEvent::MathDisplay(range)] => Some(*range),
+        _ => None,
     }
 }
 
@@ -1848,6 +2145,17 @@ impl<R: FencedCodeRenderer + ?Sized> RenderContext<'_, '_, R> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn to_html_from_bytes_validates_utf8() {
+        assert_eq!(
+            to_html_from_bytes("*héllo* ✓".as_bytes()),
+            Ok(to_html("*héllo* ✓"))
+        );
+        let error = to_html_from_bytes(b"ok \xE2\x9C").unwrap_err();
+        assert_eq!(error.valid_up_to(), 3);
+        assert_eq!(error.error_len(), None);
+    }
+
     #[test]
     fn footnote_numbers_assign_constant_time_stable_ordinals() {
         let mut numbers = FootnoteNumbers::new(4);
@@ -1858,6 +2166,11 @@ mod tests {
         assert_eq!(numbers.number(3), Some(3));
         assert_eq!(numbers.number(4), None);
         assert_eq!(numbers.order, vec![2, 0, 3]);
+        assert_eq!(numbers.reference(0), Some((2, 1)));
+        assert_eq!(numbers.reference(0), Some((2, 2)));
+        assert_eq!(numbers.reference(1), Some((4, 1)));
+        assert_eq!(numbers.references(0), 2);
+        assert_eq!(numbers.references(2), 0);
     }
 
     #[test]
@@ -1931,6 +2244,459 @@ mod tests {
         assert!(html.contains("Hello</h1>"));
     }
 
+    #[test]
+    fn test_setext_underline_after_only_link_ref_defs() {
+        // Matches cmark: with no heading text left, `===` is paragraph text
+        // and `---` is a thematic break.
+        assert_eq!(to_html("[a]: /b\n===\n"), "<p>===</p>\n");
+        assert_eq!(to_html("[a]: /b\n---\n"), "<hr />\n");
+        assert_eq!(
+            to_html("[a]: /b\n===\n\n[a]\n"),
+            "<p>===</p>\n<p><a href=\"/b\">a</a></p>\n"
+        );
+        assert_eq!(
+            to_html("> [a]: /b\n> ---\n"),
+            "<blockquote>\n<hr />\n</blockquote>\n"
+        );
+    }
+
+    #[test]
+    fn test_blockquote_depth_changes_across_lines() {
+        // Outputs match cmark
+        let quote = |inner: &str| format!("<blockquote>\n{inner}</blockquote>\n");
+        // Deeper marker opens a nested quote; a shallower one is lazy continuation
+        assert_eq!(
+            to_html("> a\n>> b\n> c"),
+            quote(&format!("<p>a</p>\n{}", quote("<p>b\nc</p>\n")))
+        );
+        // A blank line with fewer markers closes the deeper quotes
+        assert_eq!(
+            to_html("> a\n>> b\n>\n> c"),
+            quote(&format!("<p>a</p>\n{}<p>c</p>\n", quote("<p>b</p>\n")))
+        );
+        assert_eq!(
+            to_html(">>> a\n>\n> b"),
+            quote(&format!("{}<p>b</p>\n", quote(&quote("<p>a</p>\n"))))
+        );
+        // ...so returning to the same depth opens a fresh nested quote
+        assert_eq!(
+            to_html(">> a\n>\n>> b"),
+            quote(&format!("{}{}", quote("<p>a</p>\n"), quote("<p>b</p>\n")))
+        );
+        assert_eq!(
+            to_html(">>a\n>>>b\n>\n>>c"),
+            quote(&format!(
+                "{}{}",
+                quote(&format!("<p>a</p>\n{}", quote("<p>b</p>\n"))),
+                quote("<p>c</p>\n")
+            ))
+        );
+        // An unmarked blank line closes the quote around a list, not just the list
+        assert_eq!(
+            to_html("> - e\n\n> a"),
+            format!(
+                "{}{}",
+                quote("<ul>\n<li>e</li>\n</ul>\n"),
+                quote("<p>a</p>\n")
+            )
+        );
+        assert_eq!(
+            to_html("- > a\n\n  b"),
+            format!(
+                "<ul>\n<li>\n{}<p>b</p>\n</li>\n</ul>\n",
+                quote("<p>a</p>\n")
+            )
+        );
+    }
+
+    #[test]
+    fn test_lazy_continuation_in_nested_blockquotes() {
+        // Outputs match cmark
+        let nested = |inner: &str| {
+            format!("<blockquote>\n<blockquote>\n{inner}</blockquote>\n</blockquote>\n")
+        };
+        assert_eq!(
+            to_html("> > text\ncontinuation"),
+            nested("<p>text\ncontinuation</p>\n")
+        );
+        assert_eq!(to_html("> > a\n> b\nc"), nested("<p>a\nb\nc</p>\n"));
+        assert_eq!(to_html("> > a\n    b"), nested("<p>a\nb</p>\n"));
+        assert_eq!(to_html("> > a\n==="), nested("<p>a\n===</p>\n"));
+        assert_eq!(
+            to_html("> > - a\nb"),
+            nested("<ul>\n<li>a\nb</li>\n</ul>\n")
+        );
+        assert_eq!(
+            to_html("- > > a\nb"),
+            format!("<ul>\n<li>\n{}</li>\n</ul>\n", nested("<p>a\nb</p>\n"))
+        );
+        // Only paragraphs continue lazily
+        assert_eq!(
+            to_html("> > a\n---"),
+            format!("{}<hr />\n", nested("<p>a</p>\n"))
+        );
+        assert_eq!(
+            to_html("> >     code\nb"),
+            format!("{}<p>b</p>\n", nested("<pre><code>code\n</code></pre>\n"))
+        );
+    }
+
+    #[test]
+    fn test_indented_code_blanks_end_with_the_block() {
+        // A blank line buffered in the list item must not reappear in later code
+        assert_eq!(
+            to_html("-     a\n\nt\n\n    b\n    c"),
+            "<ul>\n<li>\n<pre><code>a\n</code></pre>\n</li>\n</ul>\n<p>t</p>\n<pre><code>b\nc</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn test_setext_heading_after_link_ref_def() {
+        assert_eq!(
+            to_html("[a]: /b\nFoo [a]\n===\n"),
+            "<h1 id=\"foo-a\">Foo <a href=\"/b\">a</a></h1>\n"
+        );
+    }
+
+    #[test]
+    fn test_reference_labels_are_unicode_case_folded() {
+        let resolves = |reference: &str, label: &str| {
+            to_html(&format!("[{reference}]\n\n[{label}]: /u\n")).contains("href")
+        };
+        // Greek, including final sigma and the micro sign
+        assert!(resolves("Δ", "δ"));
+        assert!(resolves("ΣΟΦΟΣ", "σοφος"));
+        assert!(resolves("µ", "Μ"));
+        assert!(resolves("ﬁle", "FILE"));
+        // Turkish dotted and dotless i fold apart from ASCII i
+        assert!(resolves("İ", "i\u{307}"));
+        assert!(!resolves("İ", "i"));
+        assert!(!resolves("ı", "I"));
+        assert!(resolves("ıi", "ıI"));
+        // Runs of spaces, tabs and line endings collapse; other spaces don't
+        assert!(resolves("Ünï \t\n  cödé", "ünï cödé"));
+        assert!(!resolves("a\u{a0}b", "a b"));
+    }
+
+    #[test]
+    fn test_collapsed_and_shortcut_references() {
+        // Labels match after case folding and whitespace collapsing, in
+        // every reference form (spec section 6.3)
+        let link = |markdown: &str| to_html(&format!("{markdown}\n\n[Foo  Bar]: /u\n"));
+        for markdown in ["[foo bar][FOO BAR]", "[foo bar][]", "[foo bar]"] {
+            let text = &markdown[1..markdown.find(']').unwrap()];
+            assert_eq!(
+                link(markdown),
+                format!("<p><a href=\"/u\">{text}</a></p>\n")
+            );
+        }
+        assert_eq!(
+            to_html("[ẞ]\n\n[SS]: /u\n"),
+            "<p><a href=\"/u\">ẞ</a></p>\n"
+        );
+        // No whitespace between the brackets of a collapsed or full reference
+        assert_eq!(
+            to_html("[foo] []\n\n[foo]: /u\n"),
+            "<p><a href=\"/u\">foo</a> []</p>\n"
+        );
+        assert_eq!(
+            to_html("[foo]\n[]\n\n[foo]: /u\n"),
+            "<p><a href=\"/u\">foo</a>\n[]</p>\n"
+        );
+        // The shortcut text may hold emphasis and bracketed links
+        assert_eq!(
+            to_html("[[*foo* bar]]\n\n[*foo* bar]: /u\n"),
+            "<p>[<a href=\"/u\"><em>foo</em> bar</a>]</p>\n"
+        );
+        assert_eq!(
+            to_html("*[foo*]\n\n[foo*]: /u\n"),
+            "<p>*<a href=\"/u\">foo*</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_reference_labels_with_brackets() {
+        // Unescaped brackets are not allowed in labels; escaped ones are
+        assert_eq!(
+            to_html("[foo][ref[]\n\n[ref[]: /uri\n"),
+            "<p>[foo][ref[]</p>\n<p>[ref[]: /uri</p>\n"
+        );
+        assert_eq!(
+            to_html("[foo [bar]]\n\n[foo [bar]]: /u\n"),
+            "<p>[foo [bar]]</p>\n<p>[foo [bar]]: /u</p>\n"
+        );
+        assert_eq!(
+            to_html("[foo \\[bar\\]]\n\n[foo \\[bar\\]]: /u\n"),
+            "<p><a href=\"/u\">foo [bar]</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_inline_link_wins_over_reference() {
+        // An inline destination takes precedence over a definition,
+        // and a following label is tried before the shortcut
+        assert_eq!(
+            to_html("[foo](/inline)\n\n[foo]: /ref\n"),
+            "<p><a href=\"/inline\">foo</a></p>\n"
+        );
+        assert_eq!(
+            to_html("[foo]()\n\n[foo]: /ref\n"),
+            "<p><a href=\"\">foo</a></p>\n"
+        );
+        assert_eq!(
+            to_html("[foo][bar][baz]\n\n[baz]: /u1\n[bar]: /u2\n"),
+            "<p><a href=\"/u2\">foo</a><a href=\"/u1\">baz</a></p>\n"
+        );
+        assert_eq!(
+            to_html("[foo][bar][baz]\n\n[baz]: /u1\n[foo]: /u2\n"),
+            "<p>[foo]<a href=\"/u1\">bar</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_reference_title_on_following_line() {
+        // Outputs match cmark
+        assert_eq!(
+            to_html("[a]: /url\n\"title\"\n\n[a]"),
+            "<p><a href=\"/url\" title=\"title\">a</a></p>\n"
+        );
+        assert_eq!(
+            to_html("[a]:\n/url\n'multi\nline'\n\n[a]"),
+            "<p><a href=\"/url\" title=\"multi\nline\">a</a></p>\n"
+        );
+        // An invalid title line leaves the definition without a title
+        assert_eq!(
+            to_html("[a]: /url\n\"title\" junk\n\n[a]"),
+            "<p>&quot;title&quot; junk</p>\n<p><a href=\"/url\">a</a></p>\n"
+        );
+        assert_eq!(
+            to_html("[a]: /url\n\"unclosed\n\n[a]"),
+            "<p>&quot;unclosed</p>\n<p><a href=\"/url\">a</a></p>\n"
+        );
+        // A blank line ends the definition before the title
+        assert_eq!(
+            to_html("[a]: /url\n\n\"title\"\n\n[a]"),
+            "<p>&quot;title&quot;</p>\n<p><a href=\"/url\">a</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_reference_label_spanning_lines() {
+        // Line breaks inside labels collapse like other whitespace
+        assert_eq!(
+            to_html("[foo bar]: /url\n\nsee [foo\nbar] and [x][Foo\n  Bar]\n"),
+            "<p>see <a href=\"/url\">foo\nbar</a> and <a href=\"/url\">x</a></p>\n"
+        );
+        assert_eq!(
+            to_html("[Foo\n  bar]: /url\n\n[Baz][Foo bar]\n"),
+            "<p><a href=\"/url\">Baz</a></p>\n"
+        );
+        assert_eq!(
+            to_html("> [a\n> b]: /u\n>\n> [a b]\n"),
+            "<blockquote>\n<p><a href=\"/u\">a b</a></p>\n</blockquote>\n"
+        );
+    }
+
+    #[test]
+    fn test_links_cannot_contain_links() {
+        // The inner link wins; the outer brackets stay literal
+        assert_eq!(
+            to_html("[a [b](c) d](e)"),
+            "<p>[a <a href=\"c\">b</a> d](e)</p>\n"
+        );
+        assert_eq!(
+            to_html("[foo [bar [baz](/uri)](/uri)](/uri)"),
+            "<p>[foo [bar <a href=\"/uri\">baz</a>](/uri)](/uri)</p>\n"
+        );
+        // Reference links deactivate an enclosing inline link too
+        assert_eq!(
+            to_html("[a [b][ref] c](/e)\n\n[ref]: /r\n"),
+            "<p>[a <a href=\"/r\">b</a> c](/e)</p>\n"
+        );
+        assert_eq!(
+            to_html("[a [ref] c](/e)\n\n[ref]: /r\n"),
+            "<p>[a <a href=\"/r\">ref</a> c](/e)</p>\n"
+        );
+        // A link inside an image deactivates the link around the image
+        assert_eq!(
+            to_html("[![a [b](c)](i.png)](/u)"),
+            "<p>[<img src=\"i.png\" alt=\"a b\" />](/u)</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_images_inside_links() {
+        assert_eq!(
+            to_html("[![moon](moon.jpg)](/uri)"),
+            "<p><a href=\"/uri\"><img src=\"moon.jpg\" alt=\"moon\" /></a></p>\n"
+        );
+        assert_eq!(
+            to_html("[![ref]](/u)\n\n[ref]: /r\n"),
+            "<p><a href=\"/u\"><img src=\"/r\" alt=\"ref\" /></a></p>\n"
+        );
+        assert_eq!(
+            to_html("![[[foo](uri1)](uri2)](uri3)"),
+            "<p><img src=\"uri3\" alt=\"[foo](uri2)\" /></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_link_ref_diagnostics() {
+        let markdown = "[a][x] ![b][Img] [c][missing]\n\n[x]: /one\n[X]: /two\n[img]: /i.png\n\n> [unused]: /u\n> [x]: /three\n\n[unused]: /again\n";
+        let (html, refs) = to_html_with_link_refs(markdown, &Options::default());
+        // The HTML is unchanged
+        assert_eq!(html, to_html(markdown));
+        assert_eq!(refs.duplicates(), ["x", "unused"]);
+        assert_eq!(refs.unused(), ["unused"]);
+
+        // Links in headings and table cells count as uses
+        let (_, refs) = to_html_with_link_refs(
+            "# [h]\n\n| [t] |\n|---|\n\n[h]: /h\n[t]: /t\n",
+            &Options::default(),
+        );
+        assert!(refs.unused().is_empty() && refs.duplicates().is_empty());
+    }
+
+    #[test]
+    fn test_footnote_like_link_refs() {
+        let markdown = "[^a]\n\n[^a]: /a\n[^b c]: /b\n[d]: /d\n";
+        let (html, refs) = to_html_with_link_refs(markdown, &Options::default());
+        assert_eq!(html, "<p><a href=\"/a\">^a</a></p>\n");
+        assert_eq!(refs.footnote_like(), ["^a", "^b c"]);
+
+        // With footnotes, a valid footnote label defines a footnote instead
+        let options = Options {
+            footnotes: true,
+            ..Options::default()
+        };
+        let (_, refs) = to_html_with_link_refs(markdown, &options);
+        assert_eq!(refs.footnote_like(), ["^b c"]);
+    }
+
+    #[test]
+    fn test_shared_link_refs() {
+        let (_, shared) = to_html_with_link_refs(
+            "[Docs]: /shared \"Shared\"\n[img]: /i.png\n",
+            &Options::default(),
+        );
+        let html = |input| to_html_with_shared_refs(input, &Options::default(), &shared);
+        assert_eq!(
+            html("[docs] ![x][IMG]"),
+            "<p><a href=\"/shared\" title=\"Shared\">docs</a> <img src=\"/i.png\" alt=\"x\" /></p>\n"
+        );
+        // Local definitions take precedence, wherever they appear
+        assert_eq!(
+            html("[docs]\n\n[DOCS]: /local\n"),
+            "<p><a href=\"/local\">docs</a></p>\n"
+        );
+        assert_eq!(html("[other]"), "<p>[other]</p>\n");
+        // Nothing leaks between calls
+        assert_eq!(
+            to_html_with_options("[docs]", &Options::default()),
+            "<p>[docs]</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_every_ascii_punctuation_escape() {
+        let extensions = Options {
+            highlight: true,
+            superscript: true,
+            subscript: true,
+            footnotes: true,
+            math: true,
+            wikilinks: true,
+            emoji: true,
+            ..Options::gfm()
+        };
+        for options in [Options::default(), extensions] {
+            for c in (b'!'..=b'~').filter(u8::is_ascii_punctuation) {
+                let mut escaped = String::new();
+                escape::escape_html_text(&[c], &mut escaped);
+                let c = char::from(c);
+                for (markdown, text) in [
+                    (format!("\\{c}"), escaped.clone()),
+                    (format!("a\\{c}b"), format!("a{escaped}b")),
+                    (
+                        format!("a \\{c}\\{c}x\\{c} b"),
+                        format!("a {escaped}{escaped}x{escaped} b"),
+                    ),
+                ] {
+                    assert_eq!(
+                        to_html_with_options(&markdown, &options),
+                        format!("<p>{text}</p>\n"),
+                        "{markdown}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_unterminated_inline_markup_is_literal() {
+        let options = Options {
+            math: true,
+            ..Options::default()
+        };
+        for opener in ["`", "``", "*", "**", "_", "~~", "[", "![", "$", "$$", "\\("] {
+            for markdown in [
+                format!("a {opener}code"),
+                format!("{opener}code b"),
+                format!("a {opener}co\nde b"),
+            ] {
+                let expected = format!("<p>{}</p>\n", markdown.replace("\\(", "("));
+                assert_eq!(to_html_with_options(&markdown, &options), expected);
+            }
+        }
+        // Closed markup before or inside an unclosed run keeps every byte
+        assert_eq!(
+            to_html("a `b` `c *d*"),
+            "<p>a <code>b</code> `c <em>d</em></p>\n"
+        );
+        assert_eq!(to_html("*a `b* c"), "<p><em>a `b</em> c</p>\n");
+        assert_eq!(to_html("``a` b ```c"), "<p>``a` b ```c</p>\n");
+        assert_eq!(
+            to_html("[a `b](c) [d"),
+            "<p><a href=\"c\">a `b</a> [d</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_allow_nested_links() {
+        let options = Options {
+            allow_nested_links: true,
+            ..Options::default()
+        };
+        let html = |input| to_html_with_options(input, &options);
+        assert_eq!(
+            html("[a [b](/b) c](/a)"),
+            "<p><a href=\"/a\">a <a href=\"/b\">b</a> c</a></p>\n"
+        );
+        assert_eq!(
+            html("[a [b [c](/c)](/b)](/a)"),
+            "<p><a href=\"/a\">a <a href=\"/b\">b <a href=\"/c\">c</a></a></a></p>\n"
+        );
+        assert_eq!(
+            html("[outer [ref]](/a)\n\n[ref]: /r\n"),
+            "<p><a href=\"/a\">outer <a href=\"/r\">ref</a></a></p>\n"
+        );
+        assert_eq!(
+            html("[outer [ref]][o]\n\n[ref]: /r\n[o]: /o\n"),
+            "<p><a href=\"/o\">outer <a href=\"/r\">ref</a></a></p>\n"
+        );
+        // Images already allowed links inside; a link around them is unchanged
+        assert_eq!(
+            html("[![i [x](/x)](/i.png)](/a)"),
+            "<p><a href=\"/a\"><img src=\"/i.png\" alt=\"i x\" /></a></p>\n"
+        );
+        assert_eq!(
+            html("![img [b](/b)](/i.png)"),
+            "<p><img src=\"/i.png\" alt=\"img b\" /></p>\n"
+        );
+        // Unbalanced brackets still fall back to text
+        assert_eq!(html("[a [b](/b)"), "<p>[a <a href=\"/b\">b</a></p>\n");
+    }
+
     #[test]
     fn test_complex_document() {
         let input = r#"# Main Title
@@ -2128,6 +2894,43 @@ More text."#;
         );
         assert!(!html.contains("<strong>"), "No <strong> tags in alt text");
     }
+
+    #[test]
+    fn test_autolink_entities_match_cmark() {
+        assert_eq!(
+            to_html("<http://example.com/&amp;>"),
+            "<p><a href=\"http://example.com/&amp;\">http://example.com/&amp;</a></p>\n"
+        );
+        assert_eq!(
+            to_html("<http://a.b/?x=&copy;&#x41;&bogus;>"),
+            "<p><a href=\"http://a.b/?x=%C2%A9A&amp;bogus;\">http://a.b/?x=©A&amp;bogus;</a></p>\n"
+        );
+        assert_eq!(
+            to_html("![<http://a.b/&lt;>](/i.png)"),
+            "<p><img src=\"/i.png\" alt=\"http://a.b/&lt;\" /></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_autolink_schemes_are_kept() {
+        // Only bare email autolinks get a `mailto:` prefix
+        assert_eq!(
+            to_html("<ftp://host>"),
+            "<p><a href=\"ftp://host\">ftp://host</a></p>\n"
+        );
+        assert_eq!(
+            to_html("<tel:123>"),
+            "<p><a href=\"tel:123\">tel:123</a></p>\n"
+        );
+        assert_eq!(
+            to_html("<mailto:a@b.com>"),
+            "<p><a href=\"mailto:a@b.com\">mailto:a@b.com</a></p>\n"
+        );
+        assert_eq!(
+            to_html("<a@b.com>"),
+            "<p><a href=\"mailto:a@b.com\">a@b.com</a></p>\n"
+        );
+    }
 }
 
 #[cfg(test)]
@@ -2142,4 +2945,56 @@ mod entity_tests {
         assert_eq!(decode_html_entities("&amp;").as_ref(), "&");
         assert_eq!(decode_html_entities("foo%20b&auml;").as_ref(), "foo%20bä");
     }
+
+    #[test]
+    fn test_invalid_numeric_references_become_replacement_char() {
+        use crate::to_html;
+
+        assert_eq!(to_html("&#0; &#x0;"), "<p>\u{FFFD} \u{FFFD}</p>\n");
+        assert_eq!(to_html("&#xD800; &#x110000;"), "<p>\u{FFFD} \u{FFFD}</p>\n");
+        // Too many digits is no reference at all
+        assert_eq!(to_html("&#00000000;"), "<p>&amp;#00000000;</p>\n");
+        assert_eq!(
+            to_html("&#x0000041; &#0000065; &#x000041;"),
+            "<p>&amp;#x0000041; A A</p>\n"
+        );
+        assert_eq!(to_html("&amp;#0;"), "<p>&amp;#0;</p>\n");
+    }
+
+    #[test]
+    fn test_entities_in_link_destinations_and_titles() {
+        use crate::to_html;
+
+        // Numeric, hex, and named references decode before re-escaping
+        assert_eq!(
+            to_html("[x](/a&#42;b \"t&#42;\")"),
+            "<p><a href=\"/a*b\" title=\"t*\">x</a></p>\n"
+        );
+        assert_eq!(
+            to_html("[x](/a&#x2a;b \"t&#X2A;\")"),
+            "<p><a href=\"/a*b\" title=\"t*\">x</a></p>\n"
+        );
+        assert_eq!(
+            to_html("[x](/f&ouml;&amp;b \"f&ouml;&amp;\")"),
+            "<p><a href=\"/f%C3%B6&amp;b\" title=\"fö&amp;\">x</a></p>\n"
+        );
+        assert_eq!(
+            to_html("![i](/a&#x2a; '&copy;')\n\n[r]\n\n[r]: /r&#42; \"&#42;\""),
+            "<p><img src=\"/a*\" alt=\"i\" title=\"©\" /></p>\n<p><a href=\"/r*\" title=\"*\">r</a></p>\n"
+        );
+        // Invalid references become U+FFFD, unknown names stay literal
+        assert_eq!(
+            to_html("[x](/a&#0;&bogus; \"&#xD800;\")"),
+            "<p><a href=\"/a%EF%BF%BD&amp;bogus;\" title=\"\u{FFFD}\">x</a></p>\n"
+        );
+        // An escaped `&` starts no reference, and decoded text is no escape
+        assert_eq!(
+            to_html("[x](/a\\&amp; \"\\&amp;\")"),
+            "<p><a href=\"/a&amp;amp;\" title=\"&amp;amp;\">x</a></p>\n"
+        );
+        assert_eq!(
+            to_html("[x](/a&#92;* \"&#92;*\")"),
+            "<p><a href=\"/a%5C*\" title=\"\\*\">x</a></p>\n"
+        );
+    }
 }
@@ -3,10 +3,12 @@
 //! Every constant in this module is consumed by a parser path and covered by
 //! black-box tests in `tests/resource_limits_tests.rs`.
 
-/// Maximum nesting depth for block containers (lists, blockquotes)
+/// Maximum nesting depth for block containers (lists, blockquotes).
+/// Override with `Options::max_nesting_depth`.
 pub const MAX_BLOCK_NESTING: usize = 32;
 
-/// Maximum number of marks collected during inline parsing
+/// Maximum number of marks collected during inline parsing.
+/// Override with `Options::max_inline_marks`.
 pub const MAX_INLINE_MARKS: usize = 4096;
 
 /// Maximum backtick run length for code spans (prevents O(n^2) matching)
@@ -16,6 +18,10 @@ pub const MAX_CODE_SPAN_BACKTICKS: usize = 32;
 /// Maximum parentheses nesting in link destinations (CommonMark spec: 32)
 pub const MAX_LINK_PAREN_DEPTH: usize = 32;
 
+/// Maximum bytes between the brackets of a link or footnote reference label
+/// (CommonMark spec: 999). Longer labels are not labels
+pub const MAX_REF_LABEL_LEN: usize = 999;
+
 /// Maximum digits in ordered list marker (prevents big-integer parsing)
 pub const MAX_LIST_MARKER_DIGITS: usize = 9;
 
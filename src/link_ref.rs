@@ -1,10 +1,12 @@
 //! Link reference definitions (CommonMark).
 
 use crate::Range;
+use alloc::borrow::Cow;
+use alloc::{string::String, vec::Vec};
+use core::sync::atomic::{AtomicBool, Ordering};
+use hashbrown::HashMap;
 use memchr::memchr;
 use rustc_hash::FxBuildHasher as FastHashBuilder;
-use std::borrow::Cow;
-use std::collections::HashMap;
 
 /// A link reference definition (URL + optional title).
 #[derive(Debug, Clone)]
@@ -14,10 +16,17 @@ pub struct LinkRefDef {
 }
 
 /// Store of link reference definitions, keyed by normalized label.
+///
+/// Besides the definitions, the store records labels that were defined more
+/// than once and which definitions reference links resolved to, so a linter
+/// can report [`duplicates`](Self::duplicates), [`unused`](Self::unused) and
+/// [`footnote_like`](Self::footnote_like) definitions after a render.
 #[derive(Debug, Default)]
 pub struct LinkRefStore {
     defs: Vec<LinkRefDef>,
     by_label: HashMap<String, usize, FastHashBuilder>,
+    duplicates: Vec<String>,
+    used: Vec<AtomicBool>,
 }
 
 impl LinkRefStore {
@@ -25,16 +34,26 @@ impl LinkRefStore {
         Self::default()
     }
 
-    /// Add a definition if the label is new. First definition wins.
+    /// Add a definition if the label is new. First definition wins; a later
+    /// one is dropped and its label recorded as a duplicate.
     pub fn insert(&mut self, label: String, def: LinkRefDef) {
         if self.by_label.contains_key(&label) {
+            self.record_duplicate(&label);
             return;
         }
         let idx = self.defs.len();
         self.defs.push(def);
+        self.used.push(AtomicBool::new(false));
         self.by_label.insert(label, idx);
     }
 
+    /// Record that `label` was defined again after its first definition.
+    pub(crate) fn record_duplicate(&mut self, label: &str) {
+        if !self.duplicates.iter().any(|dup| dup == label) {
+            self.duplicates.push(String::from(label));
+        }
+    }
+
     pub fn get_index(&self, label: &str) -> Option<usize> {
         self.by_label.get(label).copied()
     }
@@ -47,6 +66,66 @@ impl LinkRefStore {
         self.defs.is_empty()
     }
 
+    /// Record that a reference link or image resolved to definition `idx`.
+    pub fn mark_used(&self, idx: usize) {
+        if let Some(used) = self.used.get(idx) {
+            used.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Normalized labels defined more than once, in source order.
+    pub fn duplicates(&self) -> Vec<&str> {
+        self.duplicates.iter().map(String::as_str).collect()
+    }
+
+    /// Normalized labels of definitions no reference resolved to, in
+    /// definition order.
+    pub fn unused(&self) -> Vec<&str> {
+        self.labels_where(|idx, _| !self.used[idx].load(Ordering::Relaxed))
+    }
+
+    /// Normalized labels starting with `^`, in definition order.
+    ///
+    /// `[^label]: url` reads like a footnote definition. Without footnotes,
+    /// every such line defines a link reference; with footnotes, only those
+    /// whose label is no valid footnote label (ASCII letters, digits, `-`
+    /// and `_`) do, and the rest define footnotes.
+    pub fn footnote_like(&self) -> Vec<&str> {
+        self.labels_where(|_, label| label.starts_with('^'))
+    }
+
+    /// Normalized labels of every definition, in definition order.
+    pub(crate) fn labels(&self) -> Vec<&str> {
+        self.labels_where(|_, _| true)
+    }
+
+    /// Labels whose definition passes `keep`, in definition order.
+    fn labels_where(&self, keep: impl Fn(usize, &str) -> bool) -> Vec<&str> {
+        let mut labels: Vec<(usize, &str)> = self
+            .by_label
+            .iter()
+            .filter(|&(label, &idx)| keep(idx, label))
+            .map(|(label, &idx)| (idx, label.as_str()))
+            .collect();
+        labels.sort_unstable_by_key(|&(idx, _)| idx);
+        labels.into_iter().map(|(_, label)| label).collect()
+    }
+
+    /// Add the definitions from `other` whose labels are not defined here.
+    /// A label defined in both is not recorded as a duplicate.
+    pub(crate) fn extend_undefined(&mut self, other: &Self) {
+        let mut labels: Vec<(usize, &String)> = other
+            .by_label
+            .iter()
+            .filter(|(label, _)| !self.by_label.contains_key(label.as_str()))
+            .map(|(label, &idx)| (idx, label))
+            .collect();
+        labels.sort_unstable_by_key(|&(idx, _)| idx);
+        for (idx, label) in labels {
+            self.insert(label.clone(), other.defs[idx].clone());
+        }
+    }
+
     #[cfg(feature = "mdx")]
     pub(crate) fn merge_first_wins(&mut self, other: Self) {
         let mut labels = vec![None; other.defs.len()];
@@ -60,6 +139,9 @@ impl LinkRefStore {
                 .expect("every link reference definition must have a label");
             self.insert(label, definition);
         }
+        for label in other.duplicates {
+            self.record_duplicate(&label);
+        }
     }
 
     #[cfg(feature = "mdx")]
@@ -69,7 +151,7 @@ impl LinkRefStore {
 }
 
 /// Normalize a link label per CommonMark: decode entities, process backslash escapes,
-/// collapse internal whitespace to single spaces, trim, and case-fold.
+/// collapse internal whitespace to single spaces, trim, and apply Unicode case folding.
 pub fn normalize_label(bytes: &[u8]) -> String {
     let mut out = String::new();
     normalize_label_into(bytes, &mut out);
@@ -80,7 +162,7 @@ pub fn normalize_label(bytes: &[u8]) -> String {
 pub fn normalize_label_into(bytes: &[u8], out: &mut String) {
     out.clear();
 
-    let label_str = match std::str::from_utf8(bytes) {
+    let label_str = match core::str::from_utf8(bytes) {
         Ok(s) => s,
         Err(_) => return,
     };
@@ -112,7 +194,7 @@ pub fn normalize_label_into(bytes: &[u8], out: &mut String) {
             }
         }
 
-        let Ok(unescaped_str) = std::str::from_utf8(&unescaped) else {
+        let Ok(unescaped_str) = core::str::from_utf8(&unescaped) else {
             return;
         };
         normalize_label_text(unescaped_str, out);
@@ -131,7 +213,8 @@ fn normalize_label_text(input: &str, out: &mut String) {
     let mut last_was_space = true;
 
     for ch in input.chars() {
-        if ch.is_whitespace() {
+        // Only spaces, tabs and line endings collapse, as on the ASCII path
+        if ch.is_ascii_whitespace() {
             if !last_was_space {
                 out.push(' ');
                 last_was_space = true;
@@ -140,13 +223,8 @@ fn normalize_label_text(input: &str, out: &mut String) {
         }
 
         last_was_space = false;
-        if ch == 'ß' || ch == 'ẞ' {
-            out.push('s');
-            out.push('s');
-        } else {
-            for lc in ch.to_lowercase() {
-                out.push(lc);
-            }
+        for lc in ch.to_lowercase() {
+            push_case_folded(lc, out);
         }
     }
 
@@ -155,6 +233,129 @@ fn normalize_label_text(input: &str, out: &mut String) {
     }
 }
 
+/// Append the full Unicode case folding of a lowercase character.
+///
+/// Folding differs from lowercasing only for Cherokee, whose lowercase
+/// letters fold to uppercase, and the characters in [`FOLD_EXCEPTIONS`].
+fn push_case_folded(lc: char, out: &mut String) {
+    let cherokee = match u32::from(lc) {
+        cp @ 0xAB70..=0xABBF => Some(cp - 0xAB70 + 0x13A0),
+        cp @ 0x13F8..=0x13FD => Some(cp - 8),
+        _ => None,
+    };
+    if let Some(upper) = cherokee.and_then(char::from_u32) {
+        out.push(upper);
+    } else if let Ok(i) = FOLD_EXCEPTIONS.binary_search_by_key(&lc, |&(ch, _)| ch) {
+        out.push_str(FOLD_EXCEPTIONS[i].1);
+    } else {
+        out.push(lc);
+    }
+}
+
+/// Lowercase characters whose case folding (Unicode `CaseFolding.txt`,
+/// statuses C and F) is not the character itself, sorted by character:
+/// `ß` to `ss`, final sigma to `σ`, ligatures, and Greek letters with an
+/// iota subscript or accents that have no precomposed lowercase.
+const FOLD_EXCEPTIONS: &[(char, &str)] = &[
+    ('\u{B5}', "\u{3BC}"),
+    ('\u{DF}', "ss"),
+    ('\u{149}', "\u{2BC}n"),
+    ('\u{17F}', "s"),
+    ('\u{1F0}', "j\u{30C}"),
+    ('\u{345}', "\u{3B9}"),
+    ('\u{390}', "\u{3B9}\u{308}\u{301}"),
+    ('\u{3B0}', "\u{3C5}\u{308}\u{301}"),
+    ('\u{3C2}', "\u{3C3}"),
+    ('\u{3D0}', "\u{3B2}"),
+    ('\u{3D1}', "\u{3B8}"),
+    ('\u{3D5}', "\u{3C6}"),
+    ('\u{3D6}', "\u{3C0}"),
+    ('\u{3F0}', "\u{3BA}"),
+    ('\u{3F1}', "\u{3C1}"),
+    ('\u{3F5}', "\u{3B5}"),
+    ('\u{587}', "\u{565}\u{582}"),
+    ('\u{1C80}', "\u{432}"),
+    ('\u{1C81}', "\u{434}"),
+    ('\u{1C82}', "\u{43E}"),
+    ('\u{1C83}', "\u{441}"),
+    ('\u{1C84}', "\u{442}"),
+    ('\u{1C85}', "\u{442}"),
+    ('\u{1C86}', "\u{44A}"),
+    ('\u{1C87}', "\u{463}"),
+    ('\u{1C88}', "\u{A64B}"),
+    ('\u{1E96}', "h\u{331}"),
+    ('\u{1E97}', "t\u{308}"),
+    ('\u{1E98}', "w\u{30A}"),
+    ('\u{1E99}', "y\u{30A}"),
+    ('\u{1E9A}', "a\u{2BE}"),
+    ('\u{1E9B}', "\u{1E61}"),
+    ('\u{1F50}', "\u{3C5}\u{313}"),
+    ('\u{1F52}', "\u{3C5}\u{313}\u{300}"),
+    ('\u{1F54}', "\u{3C5}\u{313}\u{301}"),
+    ('\u{1F56}', "\u{3C5}\u{313}\u{342}"),
+    ('\u{1F80}', "\u{1F00}\u{3B9}"),
+    ('\u{1F81}', "\u{1F01}\u{3B9}"),
+    ('\u{1F82}', "\u{1F02}\u{3B9}"),
+    ('\u{1F83}', "\u{1F03}\u{3B9}"),
+    ('\u{1F84}', "\u{1F04}\u{3B9}"),
+    ('\u{1F85}', "\u{1F05}\u{3B9}"),
+    ('\u{1F86}', "\u{1F06}\u{3B9}"),
+    ('\u{1F87}', "\u{1F07}\u{3B9}"),
+    ('\u{1F90}', "\u{1F20}\u{3B9}"),
+    ('\u{1F91}', "\u{1F21}\u{3B9}"),
+    ('\u{1F92}', "\u{1F22}\u{3B9}"),
+    ('\u{1F93}', "\u{1F23}\u{3B9}"),
+    ('\u{1F94}', "\u{1F24}\u{3B9}"),
+    ('\u{1F95}', "\u{1F25}\u{3B9}"),
+    ('\u{1F96}', "\u{1F26}\u{3B9}"),
+    ('\u{1F97}', "\u{1F27}\u{3B9}"),
+    ('\u{1FA0}', "\u{1F60}\u{3B9}"),
+    ('\u{1FA1}', "\u{1F61}\u{3B9}"),
+    ('\u{1FA2}', "\u{1F62}\u{3B9}"),
+    ('\u{1FA3}', "\u{1F63}\u{3B9}"),
+    ('\u{1FA4}', "\u{1F64}\u{3B9}"),
+    ('\u{1FA5}', "\u{1F65}\u{3B9}"),
+    ('\u{1FA6}', "\u{1F66}\u{3B9}"),
+    ('\u{1FA7}', "\u{1F67}\u{3B9}"),
+    ('\u{1FB2}', "\u{1F70}\u{3B9}"),
+    ('\u{1FB3}', "\u{3B1}\u{3B9}"),
+    ('\u{1FB4}', "\u{3AC}\u{3B9}"),
+    ('\u{1FB6}', "\u{3B1}\u{342}"),
+    ('\u{1FB7}', "\u{3B1}\u{342}\u{3B9}"),
+    ('\u{1FBE}', "\u{3B9}"),
+    ('\u{1FC2}', "\u{1F74}\u{3B9}"),
+    ('\u{1FC3}', "\u{3B7}\u{3B9}"),
+    ('\u{1FC4}', "\u{3AE}\u{3B9}"),
+    ('\u{1FC6}', "\u{3B7}\u{342}"),
+    ('\u{1FC7}', "\u{3B7}\u{342}\u{3B9}"),
+    ('\u{1FD2}', "\u{3B9}\u{308}\u{300}"),
+    ('\u{1FD3}', "\u{3B9}\u{308}\u{301}"),
+    ('\u{1FD6}', "\u{3B9}\u{342}"),
+    ('\u{1FD7}', "\u{3B9}\u{308}\u{342}"),
+    ('\u{1FE2}', "\u{3C5}\u{308}\u{300}"),
+    ('\u{1FE3}', "\u{3C5}\u{308}\u{301}"),
+    ('\u{1FE4}', "\u{3C1}\u{313}"),
+    ('\u{1FE6}', "\u{3C5}\u{342}"),
+    ('\u{1FE7}', "\u{3C5}\u{308}\u{342}"),
+    ('\u{1FF2}', "\u{1F7C}\u{3B9}"),
+    ('\u{1FF3}', "\u{3C9}\u{3B9}"),
+    ('\u{1FF4}', "\u{3CE}\u{3B9}"),
+    ('\u{1FF6}', "\u{3C9}\u{342}"),
+    ('\u{1FF7}', "\u{3C9}\u{342}\u{3B9}"),
+    ('\u{FB00}', "ff"),
+    ('\u{FB01}', "fi"),
+    ('\u{FB02}', "fl"),
+    ('\u{FB03}', "ffi"),
+    ('\u{FB04}', "ffl"),
+    ('\u{FB05}', "st"),
+    ('\u{FB06}', "st"),
+    ('\u{FB13}', "\u{574}\u{576}"),
+    ('\u{FB14}', "\u{574}\u{565}"),
+    ('\u{FB15}', "\u{574}\u{56B}"),
+    ('\u{FB16}', "\u{57E}\u{576}"),
+    ('\u{FB17}', "\u{574}\u{56D}"),
+];
+
 #[inline]
 fn normalize_label_text_ascii(input: &[u8], out: &mut String) {
     let mut last_was_space = true;
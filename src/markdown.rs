@@ -0,0 +1,1150 @@
+//! Markdown output for formatters.
+//!
+//! [`to_markdown`] walks the block and inline events the HTML renderer sees
+//! and writes them back as Markdown in one canonical form. Container blocks
+//! are tracked as a stack whose prefixes (`> `, list markers and their
+//! continuation indent) start every written line, so leaf blocks only deal
+//! with their own lines.
+
+use crate::block::{Alignment, BlockEvent, BlockParser, CodeBlockKind, ListKind, TaskState};
+use crate::footnote::FootnoteStore;
+use crate::inline::{InlineEvent, InlineParser, shortcode};
+use crate::link_ref::{LinkRefStore, normalize_label};
+use crate::{CellState, HeadingState, Options, ParagraphState, Range, strip_front_matter};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Parse Markdown and write it back in canonical form.
+///
+/// The document is parsed with `options`, so extensions that are turned off
+/// are copied as text. The output renders to the same HTML and follows these
+/// rules:
+///
+/// - Headings use the ATX form (`# Title`). Level 1 and 2 headings that
+///   span lines keep the setext form.
+/// - Bullet lists use `-` and ordered lists `1.`, numbered up from the
+///   list's start. A list directly after another list of the same type uses
+///   `+` or `1)` instead, so the two stay separate.
+/// - Code blocks are fenced with backticks, or tildes when the info string
+///   holds a backtick. Fences are longer than any backtick run in the code.
+/// - Emphasis uses `*` and strong emphasis `**`, or `_` and `__` where `*`
+///   would run into a neighbouring delimiter. Hard line breaks use `\`.
+/// - Thematic breaks are `***`.
+/// - Blocks are separated by one blank line; items of tight lists are not.
+/// - Table columns are padded to a common width.
+/// - Reference links use the collapsed form `[text][]` when the text matches
+///   the label and the full form `[text][label]` otherwise. Footnote
+///   definitions and then link reference definitions are collected at the
+///   bottom in definition order.
+/// - Emoji are written as the first shortcode for their glyph, so aliases
+///   such as `:thumbsup:` become `:+1:`.
+///
+/// Text, entity references, raw HTML and autolinks are copied from the
+/// source, with `*` and `_` escaped where they could start or end emphasis.
+/// Front matter is copied unchanged.
+///
+/// # Example
+/// ```
+/// let markdown = ferromark::to_markdown(
+///     "Title\n=====\n\n* __bold__ and _em_\n* [docs][Ref]\n\n[ref]: /docs",
+///     &Default::default(),
+/// );
+/// assert_eq!(
+///     markdown,
+///     "# Title\n\n- **bold** and *em*\n- [docs][ref]\n\n[ref]: /docs\n"
+/// );
+/// ```
+pub fn to_markdown(input: &str, options: &Options) -> String {
+    let body = strip_front_matter(input, options);
+    let front_matter = &input[..input.len() - body.len()];
+    let markdown = body.as_bytes();
+    let mut parser = BlockParser::new_with_options(markdown, *options);
+    let mut events = Vec::with_capacity((markdown.len() / 16).max(64));
+    parser.parse(&mut events);
+    let link_refs = parser.take_link_refs();
+    let footnote_store = options.footnotes.then(|| parser.take_footnote_store());
+
+    let mut writer = MarkdownWriter::new(&link_refs, footnote_store.as_ref(), options);
+    if !front_matter.is_empty() {
+        writer.lines.start_block(None);
+        writer.lines.write_line(front_matter.trim_end().as_bytes());
+    }
+    for event in &events {
+        writer.block_event(markdown, event);
+    }
+    if let Some(footnote_store) = &footnote_store {
+        for index in 0..footnote_store.len() {
+            if let Some(def) = footnote_store.get(index) {
+                writer.footnote_definition(markdown, &def.label, &def.events);
+            }
+        }
+    }
+    writer.link_ref_definitions();
+
+    String::from_utf8(writer.lines.out)
+        .expect("writing from a UTF-8 Markdown string must produce UTF-8 Markdown")
+}
+
+/// A container block whose prefix starts every line written inside it.
+struct Container {
+    kind: ContainerKind,
+    /// Whether a block was started directly inside.
+    has_blocks: bool,
+    /// Whether a line was written inside.
+    has_lines: bool,
+    /// Marker character of the previous block, if it was a list.
+    previous_list: Option<u8>,
+}
+
+enum ContainerKind {
+    Document,
+    Quote,
+    /// A fenced div, written between its fences.
+    Div,
+    List {
+        /// `-` or `+` for bullet lists, `.` or `)` for ordered lists.
+        delimiter: u8,
+        /// Number of the next item in an ordered list.
+        number: Option<u32>,
+        tight: bool,
+        items: usize,
+    },
+    /// A list item or footnote definition.
+    Item {
+        /// Prefix of the first line, until it is written.
+        marker: Option<Vec<u8>>,
+        /// Indent of the following lines.
+        indent: usize,
+        tight: bool,
+    },
+}
+
+impl Container {
+    fn new(kind: ContainerKind) -> Self {
+        Self {
+            kind,
+            has_blocks: false,
+            has_lines: false,
+            previous_list: None,
+        }
+    }
+}
+
+/// Output lines prefixed by the open containers.
+struct Lines {
+    out: Vec<u8>,
+    containers: Vec<Container>,
+}
+
+impl Lines {
+    /// Separate a new block from the previous one in the innermost container.
+    /// `list` is the marker character if the block is a list.
+    fn start_block(&mut self, list: Option<u8>) {
+        let container = self
+            .containers
+            .last_mut()
+            .expect("the document container is never closed");
+        let separate = container.has_blocks
+            && !matches!(container.kind, ContainerKind::Item { tight: true, .. });
+        container.has_blocks = true;
+        container.previous_list = list;
+        if separate {
+            self.write_line(b"");
+        }
+    }
+
+    fn pop_container(&mut self) {
+        if self.containers.len() > 1 {
+            self.containers.pop();
+        }
+    }
+
+    fn write_line(&mut self, line: &[u8]) {
+        let start = self.out.len();
+        for container in &mut self.containers {
+            container.has_lines = true;
+            match &mut container.kind {
+                ContainerKind::Quote => self.out.extend_from_slice(b"> "),
+                ContainerKind::Item { marker, indent, .. } => match marker.take() {
+                    Some(marker) => self.out.extend_from_slice(&marker),
+                    None => self.out.extend(core::iter::repeat_n(b' ', *indent)),
+                },
+                ContainerKind::Document | ContainerKind::Div | ContainerKind::List { .. } => {}
+            }
+        }
+        if line.is_empty() {
+            while self.out.last() == Some(&b' ') {
+                self.out.pop();
+            }
+            // Markers of nested empty items can read as a thematic break
+            let prefix = &self.out[start..];
+            let dashes = prefix.iter().filter(|&&b| b == b'-').count();
+            if dashes >= 3 && prefix.iter().all(|&b| b == b'-' || b == b' ') {
+                let list = self.containers.iter_mut().rev().find_map(|container| {
+                    match &mut container.kind {
+                        ContainerKind::List { delimiter, .. } => Some(delimiter),
+                        _ => None,
+                    }
+                });
+                if let Some(delimiter) = list {
+                    *delimiter = b'+';
+                }
+                if let Some(last) = self.out.last_mut() {
+                    *last = b'+';
+                }
+            }
+        }
+        self.out.extend_from_slice(line);
+        self.out.push(b'\n');
+    }
+
+    fn write_lines(&mut self, text: &[u8]) {
+        for line in text.split(|&b| b == b'\n') {
+            self.write_line(line);
+        }
+    }
+
+    /// Write paragraph lines, escaping a first line and indenting continuation
+    /// lines that would otherwise start a block. Indented code cannot
+    /// interrupt a paragraph, so the indent keeps them in it.
+    fn write_paragraph(&mut self, text: &[u8]) {
+        let mut indented = Vec::new();
+        for (index, line) in text.split(|&b| b == b'\n').enumerate() {
+            let raw = matches!(line.first(), Some(b'<' | b'>' | b'`' | b'~' | b'|'));
+            if index == 0 && !raw && could_start_block(line) {
+                // Text left over from link reference definitions
+                let at = line.iter().position(|b| !b.is_ascii_digit()).unwrap_or(0);
+                indented.clear();
+                indented.extend_from_slice(&line[..at]);
+                indented.push(b'\\');
+                indented.extend_from_slice(&line[at..]);
+                self.write_line(&indented);
+            } else if index > 0 && could_start_block(line) {
+                indented.clear();
+                indented.extend_from_slice(b"    ");
+                indented.extend_from_slice(line);
+                self.write_line(&indented);
+            } else {
+                self.write_line(line);
+            }
+        }
+    }
+}
+
+/// A table collected until its end, so columns can be padded.
+#[derive(Default)]
+struct Table {
+    alignments: Vec<Alignment>,
+    /// Rows of cells with their column spans; the first row is the header.
+    rows: Vec<Vec<(Vec<u8>, u16)>>,
+    in_head: bool,
+}
+
+/// Mutable state for one Markdown writing pass.
+struct MarkdownWriter<'a> {
+    lines: Lines,
+    inline: InlineWriter<'a>,
+    para_state: ParagraphState,
+    heading_state: HeadingState,
+    cell_state: CellState,
+    /// Info string and content of the open code block.
+    code_block: Option<(Option<Range>, Vec<u8>)>,
+    table: Option<Table>,
+    /// Marker line of a just-opened callout, pending a custom title.
+    pending_callout: Option<Vec<u8>>,
+    /// Attribute list of the open heading or code block.
+    attributes: Option<Range>,
+    link_refs: &'a LinkRefStore,
+}
+
+impl<'a> MarkdownWriter<'a> {
+    fn new(
+        link_refs: &'a LinkRefStore,
+        footnote_store: Option<&'a FootnoteStore>,
+        options: &'a Options,
+    ) -> Self {
+        let mut parser = InlineParser::new();
+        parser.configure(options);
+        // Typographic punctuation is a rendering choice; the source keeps
+        // its straight quotes and dashes
+        parser.set_smart_punctuation(false);
+        Self {
+            lines: Lines {
+                out: Vec::new(),
+                containers: vec![Container::new(ContainerKind::Document)],
+            },
+            inline: InlineWriter {
+                parser,
+                events: Vec::with_capacity(64),
+                out: Vec::new(),
+                link_refs,
+                link_labels: link_refs.labels(),
+                footnote_store,
+                options,
+            },
+            para_state: ParagraphState::new(),
+            heading_state: HeadingState::new(),
+            cell_state: CellState::new(),
+            code_block: None,
+            table: None,
+            pending_callout: None,
+            attributes: None,
+            link_refs,
+        }
+    }
+
+    fn block_event(&mut self, input: &[u8], event: &BlockEvent) {
+        if let Some(mut marker) = self.pending_callout.take() {
+            if let BlockEvent::CalloutTitle(title) = event {
+                marker.push(b' ');
+                marker.extend_from_slice(title.slice(input));
+                self.lines.write_line(&marker);
+                return;
+            }
+            self.lines.write_line(&marker);
+        }
+
+        match event {
+            BlockEvent::ParagraphStart => {
+                self.lines.start_block(None);
+                self.para_state.start();
+            }
+            BlockEvent::ParagraphEnd => {
+                let content = self.para_state.finish();
+                self.inline.write(content, false);
+                self.lines.write_paragraph(&self.inline.out);
+            }
+            BlockEvent::HeadingStart { level } => {
+                self.lines.start_block(None);
+                self.heading_state.start();
+                self.heading_state.level = *level;
+            }
+            BlockEvent::HeadingEnd { level } => {
+                let content = self.heading_state.finish();
+                self.inline.write(content, false);
+                let attributes = self.attributes.take().map(|range| range.slice(input));
+                // Keep line breaks in the setext form
+                if *level <= 2 && self.inline.out.contains(&b'\n') {
+                    if let Some(attributes) = attributes {
+                        self.inline.out.push(b' ');
+                        self.inline.out.extend_from_slice(attributes);
+                    }
+                    self.lines.write_paragraph(&self.inline.out);
+                    self.lines
+                        .write_line(if *level == 1 { b"===" } else { b"---" });
+                    return;
+                }
+                let mut line = vec![b'#'; usize::from(*level)];
+                if !self.inline.out.is_empty() {
+                    line.push(b' ');
+                    line.extend_from_slice(&self.inline.out);
+                    // A closing sequence would be dropped
+                    let hashes = line.iter().rev().take_while(|&&b| b == b'#').count();
+                    if hashes > 0 && line[line.len() - hashes - 1] != b'\\' {
+                        line.insert(line.len() - hashes, b'\\');
+                    }
+                }
+                if let Some(attributes) = attributes {
+                    line.push(b' ');
+                    line.extend_from_slice(attributes);
+                }
+                self.lines.write_line(&line);
+            }
+            BlockEvent::Attributes(range) => self.attributes = Some(*range),
+            BlockEvent::CodeBlockStart { kind } => {
+                self.lines.start_block(None);
+                let info = match kind {
+                    CodeBlockKind::Fenced { info } => *info,
+                    CodeBlockKind::Indented => None,
+                };
+                self.code_block = Some((info, Vec::new()));
+            }
+            BlockEvent::Code(range) => {
+                if let Some((_, code)) = &mut self.code_block {
+                    code.extend_from_slice(range.slice(input));
+                }
+            }
+            BlockEvent::VirtualSpaces(count) => {
+                if let Some((_, code)) = &mut self.code_block {
+                    code.extend(core::iter::repeat_n(b' ', usize::from(*count)));
+                }
+            }
+            BlockEvent::CodeBlockEnd => {
+                if let Some((info, code)) = self.code_block.take() {
+                    let attributes = self.attributes.take().map(|range| range.slice(input));
+                    self.write_code_block(info.map(|info| info.slice(input)), attributes, &code);
+                }
+            }
+            BlockEvent::BlockQuoteStart { callout, fold } => {
+                self.lines.start_block(None);
+                self.lines
+                    .containers
+                    .push(Container::new(ContainerKind::Quote));
+                if let Some(callout) = callout {
+                    let mut marker = Vec::new();
+                    marker.extend_from_slice(b"[!");
+                    marker.extend(callout.css_suffix().bytes().map(|b| b.to_ascii_uppercase()));
+                    marker.push(b']');
+                    match fold {
+                        Some(fold) => {
+                            marker.push(if fold.expanded { b'+' } else { b'-' });
+                            if let Some(title) = fold.title {
+                                marker.push(b' ');
+                                marker.extend_from_slice(title.slice(input));
+                            }
+                            self.lines.write_line(&marker);
+                        }
+                        None => self.pending_callout = Some(marker),
+                    }
+                }
+            }
+            BlockEvent::CalloutTitle(_) => {}
+            BlockEvent::BlockQuoteCite(url) => {
+                let mut line = Vec::new();
+                line.extend_from_slice(b"[cite:");
+                line.extend_from_slice(url.slice(input));
+                line.push(b']');
+                self.lines.write_line(&line);
+            }
+            BlockEvent::BlockQuoteEnd => self.close_container(),
+            BlockEvent::DivStart { class } => {
+                self.lines.start_block(None);
+                // A bare fence would close an enclosing div
+                let mut fence = b"::: ".to_vec();
+                match class {
+                    Some(class) => fence.extend_from_slice(class.slice(input)),
+                    None => fence.extend_from_slice(b"{}"),
+                }
+                self.lines.write_line(&fence);
+                self.lines
+                    .containers
+                    .push(Container::new(ContainerKind::Div));
+            }
+            BlockEvent::DivEnd => {
+                self.lines.pop_container();
+                self.lines.write_line(b":::");
+            }
+            BlockEvent::ListStart { kind, tight } => {
+                let (delimiter, alternate, number) = match kind {
+                    ListKind::Unordered => (b'-', b'+', None),
+                    ListKind::Ordered { start, .. } => (b'.', b')', Some(*start)),
+                };
+                let parent = self
+                    .lines
+                    .containers
+                    .last()
+                    .expect("the document container is never closed");
+                let delimiter = if parent.previous_list == Some(delimiter) {
+                    alternate
+                } else {
+                    delimiter
+                };
+                self.lines.start_block(Some(delimiter));
+                self.lines
+                    .containers
+                    .push(Container::new(ContainerKind::List {
+                        delimiter,
+                        number,
+                        tight: *tight,
+                        items: 0,
+                    }));
+            }
+            BlockEvent::ListEnd { .. } => self.lines.pop_container(),
+            BlockEvent::ListItemStart { task } => {
+                let Some(ContainerKind::List {
+                    delimiter,
+                    number,
+                    tight,
+                    items,
+                }) = self.lines.containers.last_mut().map(|list| &mut list.kind)
+                else {
+                    return;
+                };
+                let separate = *items > 0 && !*tight;
+                *items += 1;
+                let mut marker = Vec::new();
+                if let Some(number) = number {
+                    marker.extend_from_slice(number.to_string().as_bytes());
+                    *number = number.saturating_add(1);
+                }
+                marker.extend_from_slice(&[*delimiter, b' ']);
+                let indent = marker.len();
+                match task {
+                    TaskState::None => {}
+                    TaskState::Unchecked => marker.extend_from_slice(b"[ ] "),
+                    TaskState::Checked => marker.extend_from_slice(b"[x] "),
+                }
+                let tight = *tight;
+                if separate {
+                    self.lines.write_line(b"");
+                }
+                self.lines
+                    .containers
+                    .push(Container::new(ContainerKind::Item {
+                        marker: Some(marker),
+                        indent,
+                        tight,
+                    }));
+            }
+            BlockEvent::ListItemEnd => self.close_container(),
+            BlockEvent::ThematicBreak => {
+                self.lines.start_block(None);
+                self.lines.write_line(b"***");
+            }
+            BlockEvent::HtmlBlockStart => self.lines.start_block(None),
+            BlockEvent::HtmlBlockText(range) => {
+                let line = range.slice(input);
+                self.lines
+                    .write_line(line.strip_suffix(b"\n").unwrap_or(line));
+            }
+            BlockEvent::HtmlBlockEnd => {}
+            BlockEvent::SoftBreak => {
+                if self.para_state.in_paragraph {
+                    self.para_state.add_soft_break();
+                } else if self.heading_state.in_heading {
+                    self.heading_state.add_soft_break();
+                }
+            }
+            BlockEvent::Text(range) => {
+                let text = range.slice(input);
+                if self.para_state.in_paragraph {
+                    self.para_state.add_text(text);
+                } else if self.heading_state.in_heading {
+                    self.heading_state.add_text(text);
+                } else if self.cell_state.in_cell {
+                    self.cell_state.add_text(text);
+                }
+            }
+            BlockEvent::TableStart => {
+                self.lines.start_block(None);
+                self.table = Some(Table::default());
+            }
+            BlockEvent::TableHeadStart => {
+                if let Some(table) = &mut self.table {
+                    table.in_head = true;
+                }
+            }
+            BlockEvent::TableHeadEnd => {
+                if let Some(table) = &mut self.table {
+                    table.in_head = false;
+                }
+            }
+            BlockEvent::TableBodyStart | BlockEvent::TableBodyEnd | BlockEvent::TableRowEnd => {}
+            BlockEvent::TableRowStart => {
+                if let Some(table) = &mut self.table {
+                    table.rows.push(Vec::new());
+                }
+            }
+            BlockEvent::TableCellStart { alignment, colspan } => {
+                if let Some(table) = self.table.as_mut().filter(|table| table.in_head) {
+                    table
+                        .alignments
+                        .extend(core::iter::repeat_n(*alignment, usize::from(*colspan)));
+                }
+                self.cell_state.start();
+                if let Some(row) = self.table.as_mut().and_then(|table| table.rows.last_mut()) {
+                    row.push((Vec::new(), *colspan));
+                }
+            }
+            BlockEvent::TableCellEnd => {
+                let content = self.cell_state.finish();
+                self.inline.write(content, true);
+                let cell = self
+                    .table
+                    .as_mut()
+                    .and_then(|table| table.rows.last_mut())
+                    .and_then(|row| row.last_mut());
+                if let Some((text, _)) = cell {
+                    // Cell content is split at pipes before inline parsing
+                    for &b in &self.inline.out {
+                        if b == b'|' {
+                            text.push(b'\\');
+                        }
+                        text.push(b);
+                    }
+                }
+            }
+            BlockEvent::TableEnd => {
+                if let Some(table) = self.table.take() {
+                    self.write_table(&table);
+                }
+            }
+        }
+    }
+
+    /// Close a quote or list item, marking it if it would otherwise vanish.
+    fn close_container(&mut self) {
+        let Some(container) = self.lines.containers.last_mut() else {
+            return;
+        };
+        if !container.has_lines {
+            // An empty task item has no checkbox
+            if let ContainerKind::Item {
+                marker: Some(marker),
+                indent,
+                ..
+            } = &mut container.kind
+            {
+                marker.truncate(*indent);
+            }
+            self.lines.write_line(b"");
+        }
+        self.lines.pop_container();
+    }
+
+    fn write_code_block(&mut self, info: Option<&[u8]>, attributes: Option<&[u8]>, code: &[u8]) {
+        let fence_char = if [info, attributes]
+            .iter()
+            .flatten()
+            .any(|text| text.contains(&b'`'))
+        {
+            b'~'
+        } else {
+            b'`'
+        };
+        let mut fence = vec![fence_char; longest_run(code, fence_char).max(2) + 1];
+        let fence_len = fence.len();
+        fence.extend_from_slice(info.unwrap_or_default());
+        if let Some(attributes) = attributes {
+            if info.is_some() {
+                fence.push(b' ');
+            }
+            fence.extend_from_slice(attributes);
+        }
+        self.lines.write_line(&fence);
+        if !code.is_empty() {
+            self.lines
+                .write_lines(code.strip_suffix(b"\n").unwrap_or(code));
+        }
+        self.lines.write_line(&fence[..fence_len]);
+    }
+
+    fn write_table(&mut self, table: &Table) {
+        let mut widths = vec![3; table.alignments.len()];
+        for row in &table.rows {
+            let mut column = 0;
+            for (text, colspan) in row {
+                if *colspan == 1 {
+                    if let Some(width) = widths.get_mut(column) {
+                        *width = (*width).max(display_width(text));
+                    }
+                }
+                column += usize::from(*colspan);
+            }
+        }
+
+        let mut line = Vec::new();
+        for (index, row) in table.rows.iter().enumerate() {
+            line.clear();
+            line.push(b'|');
+            let mut column = 0;
+            for (text, colspan) in row {
+                let width = widths.get(column).copied().unwrap_or(0);
+                line.push(b' ');
+                line.extend_from_slice(text);
+                line.extend(core::iter::repeat_n(
+                    b' ',
+                    width.saturating_sub(display_width(text)) + 1,
+                ));
+                line.extend(core::iter::repeat_n(b'|', usize::from(*colspan)));
+                column += usize::from(*colspan);
+            }
+            self.lines.write_line(&line);
+
+            if index == 0 {
+                line.clear();
+                line.push(b'|');
+                for (alignment, width) in table.alignments.iter().zip(&widths) {
+                    let (left, right) = match alignment {
+                        Alignment::None => (b'-', b'-'),
+                        Alignment::Left => (b':', b'-'),
+                        Alignment::Right => (b'-', b':'),
+                        Alignment::Center => (b':', b':'),
+                    };
+                    line.extend_from_slice(&[b' ', left]);
+                    line.extend(core::iter::repeat_n(b'-', width - 2));
+                    line.extend_from_slice(&[right, b' ', b'|']);
+                }
+                self.lines.write_line(&line);
+            }
+        }
+    }
+
+    fn footnote_definition(&mut self, input: &[u8], label: &str, events: &[BlockEvent]) {
+        self.lines.start_block(None);
+        let mut marker = Vec::new();
+        marker.extend_from_slice(b"[^");
+        marker.extend_from_slice(label.as_bytes());
+        marker.extend_from_slice(b"]: ");
+        self.lines
+            .containers
+            .push(Container::new(ContainerKind::Item {
+                marker: Some(marker),
+                indent: 4,
+                tight: false,
+            }));
+        for event in events {
+            self.block_event(input, event);
+        }
+        self.close_container();
+    }
+
+    fn link_ref_definitions(&mut self) {
+        let mut line = Vec::new();
+        for (index, label) in self.inline.link_labels.iter().enumerate() {
+            let Some(def) = self.link_refs.get(index) else {
+                continue;
+            };
+            if index == 0 {
+                self.lines.start_block(None);
+            }
+            line.clear();
+            line.push(b'[');
+            write_label(&mut line, label);
+            line.extend_from_slice(b"]: ");
+            if def.url.is_empty() {
+                line.extend_from_slice(b"<>");
+            } else {
+                write_destination(&mut line, &def.url);
+            }
+            if let Some(title) = &def.title {
+                line.push(b' ');
+                write_title(&mut line, title);
+            }
+            self.lines.write_line(&line);
+        }
+    }
+}
+
+/// A link or image whose closing bracket is not written yet.
+enum OpenLink {
+    Inline { url: Range, title: Option<Range> },
+    Reference { def_index: u32, text_start: usize },
+}
+
+/// Inline parsing and writing, reused for every block.
+struct InlineWriter<'a> {
+    parser: InlineParser,
+    events: Vec<InlineEvent>,
+    /// Markdown of the last written content; soft breaks are newlines.
+    out: Vec<u8>,
+    link_refs: &'a LinkRefStore,
+    /// Normalized link reference labels by definition index.
+    link_labels: Vec<&'a str>,
+    footnote_store: Option<&'a FootnoteStore>,
+    options: &'a Options,
+}
+
+impl InlineWriter<'_> {
+    /// Write `content`; `in_cell` escapes what table rows would split at.
+    fn write(&mut self, content: &[u8], in_cell: bool) {
+        let options = self.options;
+        self.events.clear();
+        self.parser.parse_with_options(
+            content,
+            options.allow_link_refs.then_some(self.link_refs),
+            options.inline_html(),
+            options.strikethrough,
+            options.highlight,
+            options.superscript,
+            options.subscript,
+            options.autolink_literals,
+            options.math,
+            self.footnote_store,
+            &mut self.events,
+        );
+
+        let escape_backticks = in_cell
+            || self
+                .events
+                .iter()
+                .any(|event| matches!(event, InlineEvent::Code(_)));
+        let out = &mut self.out;
+        out.clear();
+        // Openers whose span is directly followed by one of the same length
+        let mut before_twin = vec![false; self.events.len()];
+        let mut starts = Vec::new();
+        for (index, event) in self.events.iter().enumerate() {
+            match event {
+                InlineEvent::EmphasisStart | InlineEvent::StrongStart => starts.push(index),
+                InlineEvent::EmphasisEnd | InlineEvent::StrongEnd => {
+                    if let Some(start) = starts.pop() {
+                        before_twin[start] =
+                            self.events.get(index + 1) == Some(&self.events[start]);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut emphasis: Vec<(u8, bool)> = Vec::new();
+        let mut opener_end = 0;
+        let (mut closer_end, mut closer_len) = (0, 0);
+        let mut links: Vec<OpenLink> = Vec::new();
+        for (index, event) in self.events.iter().enumerate() {
+            match event {
+                InlineEvent::Text(range) => write_text(out, range.slice(content), escape_backticks),
+                InlineEvent::Code(range) => write_code_span(out, range.slice(content)),
+                InlineEvent::EmphasisStart | InlineEvent::StrongStart => {
+                    let strong = matches!(event, InlineEvent::StrongStart);
+                    // `***` opens emphasis around strong emphasis, and a
+                    // closer joined with an opener of another length still
+                    // splits by the rule of 3; other runs of `*` would merge
+                    let len = if strong { 2 } else { 1 };
+                    let around_strong = strong
+                        && out.len() == opener_end
+                        && emphasis.last() == Some(&(b'*', false));
+                    // The joined run can only open again before a word
+                    let before_word = matches!(
+                        self.events.get(index + 1),
+                        Some(InlineEvent::Text(range))
+                            if range.slice(content).first().is_some_and(u8::is_ascii_alphanumeric)
+                    );
+                    let after_closer = out.len() == closer_end
+                        && closer_len <= 2
+                        && closer_len != len
+                        && before_word;
+                    let joins = out.ends_with(b"*") && !out.ends_with(b"\\*");
+                    // Adjacent spans of one length are told apart by `_` on
+                    // the first, which cannot follow a word
+                    let twin = before_twin[index]
+                        && !out
+                            .last()
+                            .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_');
+                    let marker = if twin || (joins && !around_strong && !after_closer) {
+                        b'_'
+                    } else {
+                        b'*'
+                    };
+                    emphasis.push((marker, strong));
+                    out.push(marker);
+                    if strong {
+                        out.push(marker);
+                    }
+                    opener_end = out.len();
+                }
+                InlineEvent::EmphasisEnd | InlineEvent::StrongEnd => {
+                    let (marker, strong) = emphasis.pop().unwrap_or((b'*', false));
+                    let run = if out.len() == closer_end {
+                        closer_len
+                    } else {
+                        0
+                    };
+                    out.push(marker);
+                    if strong {
+                        out.push(marker);
+                    }
+                    closer_len = if marker == b'*' {
+                        run + 1 + usize::from(strong)
+                    } else {
+                        0
+                    };
+                    closer_end = out.len();
+                }
+                InlineEvent::StrikethroughStart | InlineEvent::StrikethroughEnd => {
+                    out.extend_from_slice(b"~~");
+                }
+                InlineEvent::SubscriptStart | InlineEvent::SubscriptEnd => out.push(b'~'),
+                InlineEvent::SuperscriptStart | InlineEvent::SuperscriptEnd => out.push(b'^'),
+                InlineEvent::HighlightStart | InlineEvent::HighlightEnd => {
+                    out.extend_from_slice(b"==");
+                }
+                InlineEvent::CustomStart(index) => {
+                    if let Some(delimiter) = options.custom_delimiters.get(usize::from(*index)) {
+                        out.extend_from_slice(delimiter.open.as_bytes());
+                    }
+                }
+                InlineEvent::CustomEnd(index) => {
+                    if let Some(delimiter) = options.custom_delimiters.get(usize::from(*index)) {
+                        out.extend_from_slice(delimiter.close.as_bytes());
+                    }
+                }
+                InlineEvent::LinkStart { url, title } | InlineEvent::ImageStart { url, title } => {
+                    if matches!(event, InlineEvent::ImageStart { .. }) {
+                        out.push(b'!');
+                    }
+                    out.push(b'[');
+                    links.push(OpenLink::Inline {
+                        url: *url,
+                        title: *title,
+                    });
+                }
+                InlineEvent::LinkStartRef { def_index }
+                | InlineEvent::ImageStartRef { def_index } => {
+                    if matches!(event, InlineEvent::ImageStartRef { .. }) {
+                        out.push(b'!');
+                    }
+                    out.push(b'[');
+                    links.push(OpenLink::Reference {
+                        def_index: *def_index,
+                        text_start: out.len(),
+                    });
+                }
+                InlineEvent::LinkEnd | InlineEvent::ImageEnd => match links.pop() {
+                    Some(OpenLink::Inline { url, title }) => {
+                        out.extend_from_slice(b"](");
+                        write_destination(out, url.slice(content));
+                        if let Some(title) = title {
+                            out.push(b' ');
+                            write_title(out, title.slice(content));
+                        }
+                        out.push(b')');
+                    }
+                    Some(OpenLink::Reference {
+                        def_index,
+                        text_start,
+                    }) => {
+                        let label = self
+                            .link_labels
+                            .get(def_index as usize)
+                            .copied()
+                            .unwrap_or_default();
+                        let collapsed = normalize_label(&out[text_start..]) == label;
+                        out.extend_from_slice(b"][");
+                        if !collapsed {
+                            write_label(out, label);
+                        }
+                        out.push(b']');
+                    }
+                    None => {}
+                },
+                InlineEvent::Autolink { url, .. } => {
+                    out.push(b'<');
+                    out.extend_from_slice(url.slice(content));
+                    out.push(b'>');
+                }
+                InlineEvent::AutolinkLiteral { url: range, .. } | InlineEvent::Html(range) => {
+                    out.extend_from_slice(range.slice(content));
+                }
+                InlineEvent::WikiLink { target, label } => {
+                    out.extend_from_slice(b"[[");
+                    out.extend_from_slice(target.slice(content));
+                    if label != target {
+                        out.push(b'|');
+                        out.extend_from_slice(label.slice(content));
+                    }
+                    out.extend_from_slice(b"]]");
+                }
+                InlineEvent::Emoji(glyph) => match shortcode(glyph) {
+                    Some(name) => {
+                        out.push(b':');
+                        out.extend_from_slice(name.as_bytes());
+                        out.push(b':');
+                    }
+                    None => out.extend_from_slice(glyph.as_bytes()),
+                },
+                // Not produced: smart punctuation is turned off in `new`
+                InlineEvent::SmartPunctuation(glyph) => out.extend_from_slice(glyph.as_bytes()),
+                InlineEvent::SoftBreak => out.push(b'\n'),
+                InlineEvent::HardBreak => {
+                    // A literal backslash before it would escape the break
+                    let backslashes = out.iter().rev().take_while(|&&b| b == b'\\').count();
+                    if backslashes % 2 == 1 {
+                        out.push(b'\\');
+                    }
+                    out.extend_from_slice(b"\\\n");
+                }
+                InlineEvent::EscapedChar(ch) => out.extend_from_slice(&[b'\\', *ch]),
+                InlineEvent::FootnoteRef { def_index } => {
+                    let def = self
+                        .footnote_store
+                        .and_then(|store| store.get(*def_index as usize));
+                    if let Some(def) = def {
+                        out.extend_from_slice(b"[^");
+                        out.extend_from_slice(def.label.as_bytes());
+                        out.push(b']');
+                    }
+                }
+                InlineEvent::InlineFootnote(range) => {
+                    out.extend_from_slice(b"^[");
+                    out.extend_from_slice(range.slice(content));
+                    out.push(b']');
+                }
+                InlineEvent::MathInline(range) => {
+                    write_math(out, range.slice(content), b"$", b"\\(", b"\\)");
+                }
+                InlineEvent::MathDisplay(range) => {
+                    write_math(out, range.slice(content), b"$$", b"\\[", b"\\]");
+                }
+                #[cfg(feature = "mdx")]
+                InlineEvent::MdxExpression(range)
+                | InlineEvent::MdxJsxOpen(range)
+                | InlineEvent::MdxJsxClose(range)
+                | InlineEvent::MdxJsxSelfClose(range) => {
+                    out.extend_from_slice(range.slice(content));
+                }
+            }
+        }
+    }
+}
+
+/// Whether a paragraph continuation line could start a block of its own.
+fn could_start_block(line: &[u8]) -> bool {
+    let Some(&first) = line.first() else {
+        return false;
+    };
+    let blank = |b: Option<&u8>| b.is_none_or(|&b| b == b' ' || b == b'\t');
+    let only = |marker: u8| line.iter().all(|&b| b == marker || b == b' ' || b == b'\t');
+    // A table delimiter row turns the line before it into a header
+    let delimiter_row =
+        line.contains(&b'|') && line.contains(&b'-') && line.iter().all(|b| b"|-: \t".contains(b));
+    match first {
+        _ if delimiter_row => true,
+        b'>' | b'<' => true,
+        b'#' => {
+            let hashes = line.iter().take_while(|&&b| b == b'#').count();
+            hashes <= 6 && blank(line.get(hashes))
+        }
+        b'-' | b'+' | b'*' => blank(line.get(1)) || only(first),
+        b'=' | b'_' => only(first),
+        b'`' | b'~' | b':' => line.starts_with(&[first; 3]),
+        b'0'..=b'9' => {
+            let digits = line.iter().take_while(|b| b.is_ascii_digit()).count();
+            digits <= 9
+                && matches!(line.get(digits), Some(b'.' | b')'))
+                && blank(line.get(digits + 1))
+        }
+        _ => false,
+    }
+}
+
+/// Write text, escaping runs of `*` and `_` that could open or close emphasis, and
+/// backticks if `escape_backticks` is set.
+fn write_text(out: &mut Vec<u8>, text: &[u8], escape_backticks: bool) {
+    let mut i = 0;
+    while i < text.len() {
+        if text[i] == b'`' && escape_backticks {
+            // Could join the fence of a rewritten code span or hide a pipe
+            out.extend_from_slice(b"\\`");
+            i += 1;
+            continue;
+        }
+        let delimiter = text[i];
+        if delimiter != b'*' && delimiter != b'_' {
+            out.push(delimiter);
+            i += 1;
+            continue;
+        }
+        let end = i + text[i..].iter().take_while(|&&b| b == delimiter).count();
+        let before = i.checked_sub(1).and_then(|before| text.get(before));
+        let both = |f: fn(&u8) -> bool| before.is_some_and(f) && text.get(end).is_some_and(f);
+        // `_` cannot open or close emphasis inside a word
+        let flanking = !(both(u8::is_ascii_whitespace)
+            || (delimiter == b'_' && both(u8::is_ascii_alphanumeric)));
+        for _ in i..end {
+            if flanking {
+                out.push(b'\\');
+            }
+            out.push(delimiter);
+        }
+        i = end;
+    }
+}
+
+fn write_code_span(out: &mut Vec<u8>, code: &[u8]) {
+    let fence = longest_run(code, b'`') + 1;
+    let padded = code.first() == Some(&b'`')
+        || code.last() == Some(&b'`')
+        || (code.first() == Some(&b' ')
+            && code.last() == Some(&b' ')
+            && code.iter().any(|&b| b != b' '));
+    out.extend(core::iter::repeat_n(b'`', fence));
+    if padded {
+        out.push(b' ');
+    }
+    // Line endings in code spans render as spaces
+    out.extend(code.iter().map(|&b| if b == b'\n' { b' ' } else { b }));
+    if padded {
+        out.push(b' ');
+    }
+    out.extend(core::iter::repeat_n(b'`', fence));
+}
+
+fn write_math(out: &mut Vec<u8>, math: &[u8], dollars: &[u8], open: &[u8], close: &[u8]) {
+    let bracketed = math.is_empty()
+        || math.contains(&b'$')
+        || math.first().is_some_and(u8::is_ascii_whitespace)
+        || math.last().is_some_and(u8::is_ascii_whitespace);
+    out.extend_from_slice(if bracketed { open } else { dollars });
+    out.extend_from_slice(math);
+    out.extend_from_slice(if bracketed { close } else { dollars });
+}
+
+/// Write a link destination, in angle brackets unless it can stand bare.
+fn write_destination(out: &mut Vec<u8>, url: &[u8]) {
+    let mut depth = 0usize;
+    let mut balanced = true;
+    let mut escaped = false;
+    for &b in url {
+        match b {
+            _ if escaped => escaped = false,
+            b'\\' => escaped = true,
+            b'(' => depth += 1,
+            b')' if depth == 0 => balanced = false,
+            b')' => depth -= 1,
+            b' ' | b'\t' | b'\n' => balanced = false,
+            _ => {}
+        }
+    }
+    if balanced && depth == 0 {
+        out.extend_from_slice(url);
+    } else {
+        out.push(b'<');
+        out.extend_from_slice(url);
+        out.push(b'>');
+    }
+}
+
+/// Write a link title in the first quotes it contains no unescaped copy of.
+fn write_title(out: &mut Vec<u8>, title: &[u8]) {
+    let unescaped = |quote: u8| {
+        title.iter().enumerate().any(|(i, &b)| {
+            b == quote && title[..i].iter().rev().take_while(|&&b| b == b'\\').count() % 2 == 0
+        })
+    };
+    let quote = if !unescaped(b'"') {
+        b'"'
+    } else if !unescaped(b'\'') {
+        b'\''
+    } else {
+        out.push(b'"');
+        let mut backslashes = 0;
+        for &b in title {
+            if b == b'"' && backslashes % 2 == 0 {
+                out.push(b'\\');
+            }
+            backslashes = if b == b'\\' { backslashes + 1 } else { 0 };
+            out.push(b);
+        }
+        out.push(b'"');
+        return;
+    };
+    out.push(quote);
+    out.extend_from_slice(title);
+    out.push(quote);
+}
+
+/// Write a normalized link label, escaping what normalization unescaped.
+fn write_label(out: &mut Vec<u8>, label: &str) {
+    for &b in label.as_bytes() {
+        if matches!(b, b'[' | b']' | b'\\') {
+            out.push(b'\\');
+        }
+        out.push(b);
+    }
+}
+
+fn longest_run(bytes: &[u8], byte: u8) -> usize {
+    bytes
+        .split(|&b| b != byte)
+        .map(<[u8]>::len)
+        .max()
+        .unwrap_or(0)
+}
+
+fn display_width(text: &[u8]) -> usize {
+    core::str::from_utf8(text).map_or(text.len(), |text| text.chars().count())
+}
@@ -7,7 +7,6 @@
 use crate::block::CodeBlockKind;
 use crate::{
     BlockEvent, BlockParser, InlineEvent, InlineParser, LinkRefDef, LinkRefStore, Options, Range,
-    fixup_list_tight,
 };
 
 use super::{MdxDiagnostic, Segment, segment_spanned};
@@ -222,6 +221,9 @@ fn build_event_stream(
                 spanned.range,
                 content_start,
             ))),
+            Segment::JsxInline(_) => {
+                unreachable!("block segmentation keeps inline JSX in Markdown")
+            }
         }
     }
     debug_assert!(markdown_block_events.next().is_none());
@@ -231,7 +233,7 @@ fn build_event_stream(
 }
 
 fn promote_container_flow_events(source: &[u8], events: &mut Vec<MdxEvent>) {
-    let input = std::mem::take(events);
+    let input = core::mem::take(events);
     let mut output = Vec::with_capacity(input.len());
     let mut paragraph = Vec::new();
     let mut input = input.into_iter();
@@ -354,7 +356,6 @@ fn parse_markdown_segments(
         let mut parser = BlockParser::new_with_options(markdown.as_bytes(), semantic_options());
         let mut events = Vec::new();
         parser.parse(&mut events);
-        fixup_list_tight(&mut events);
         link_refs.merge_first_wins(parser.take_link_refs());
         parsed.push(events);
     }
@@ -507,18 +508,13 @@ fn emit_inline_range(
     }
 }
 
-fn offset_range(range: Range, offset: usize) -> Range {
+pub(super) fn offset_range(range: Range, offset: usize) -> Range {
     Range::from_usize(offset + range.start_usize(), offset + range.end_usize())
 }
 
-fn offset_block_event(mut event: BlockEvent, offset: usize) -> BlockEvent {
-    match &mut event {
-        BlockEvent::CodeBlockStart {
-            kind: CodeBlockKind::Fenced { info: Some(range) },
-        }
-        | BlockEvent::HtmlBlockText(range)
-        | BlockEvent::Code(range) => *range = offset_range(*range, offset),
-        _ => {}
+pub(super) fn offset_block_event(mut event: BlockEvent, offset: usize) -> BlockEvent {
+    if let Some(range) = event.range_mut() {
+        *range = offset_range(*range, offset);
     }
     event
 }
@@ -543,6 +539,10 @@ fn offset_inline_event(mut event: InlineEvent, offset: usize) -> InlineEvent {
         InlineEvent::Autolink { url, .. } | InlineEvent::AutolinkLiteral { url, .. } => {
             *url = offset_range(*url, offset);
         }
+        InlineEvent::WikiLink { target, label } => {
+            *target = offset_range(*target, offset);
+            *label = offset_range(*label, offset);
+        }
         _ => {}
     }
     event
@@ -555,7 +555,9 @@ fn block_event_range(event: &BlockEvent) -> Option<Range> {
         }
         | BlockEvent::HtmlBlockText(range)
         | BlockEvent::Text(range)
-        | BlockEvent::Code(range) => Some(*range),
+        | BlockEvent::Code(range)
+        | BlockEvent::CalloutTitle(range)
+        | BlockEvent::BlockQuoteCite(range) => Some(*range),
         _ => None,
     }
 }
@@ -575,6 +577,7 @@ fn inline_event_range(event: &InlineEvent) -> Option<Range> {
         | InlineEvent::ImageStart { url, .. }
         | InlineEvent::Autolink { url, .. }
         | InlineEvent::AutolinkLiteral { url, .. } => Some(*url),
+        InlineEvent::WikiLink { target, .. } => Some(*target),
         _ => None,
     }
 }
@@ -585,6 +588,6 @@ mod tests {
 
     #[test]
     fn semantic_event_stays_compact() {
-        assert!(std::mem::size_of::<MdxEvent>() <= 40);
+        assert!(core::mem::size_of::<MdxEvent>() <= 40);
     }
 }
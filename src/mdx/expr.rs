@@ -7,6 +7,11 @@
 /// - Brace depth (`{` / `}`)
 /// - Double-quoted strings (`"..."` with `\"` escapes)
 /// - Single-quoted strings (`'...'` with `\'` escapes)
+///
+/// Braces inside strings and comments do not count. As in JavaScript, a
+/// quoted string cannot contain an unescaped line break, so one that reaches
+/// the end of its line leaves the expression unterminated instead of
+/// consuming the following lines.
 /// - Template literals (`` `...` `` with `${...}` nesting)
 /// - Line comments (`// ...`)
 /// - Block comments (`/* ... */`)
@@ -57,27 +62,31 @@ pub fn find_expression_end(bytes: &[u8]) -> Option<usize> {
 /// Skip a `"..."` string. `pos` points at the opening `"`.
 /// Returns position after the closing `"`, or `None` if unterminated.
 fn skip_double_quoted(bytes: &[u8], start: usize) -> Option<usize> {
-    let len = bytes.len();
-    let mut pos = start + 1;
-    while pos < len {
-        match bytes[pos] {
-            b'\\' => pos += 2, // skip escaped char
-            b'"' => return Some(pos + 1),
-            _ => pos += 1,
-        }
-    }
-    None
+    skip_quoted(bytes, start, b'"')
 }
 
 /// Skip a `'...'` string. `pos` points at the opening `'`.
 /// Returns position after the closing `'`, or `None` if unterminated.
 fn skip_single_quoted(bytes: &[u8], start: usize) -> Option<usize> {
+    skip_quoted(bytes, start, b'\'')
+}
+
+/// Skip a string closed by `quote` on the same line; an escaped line break
+/// (`\` at line end) continues it.
+fn skip_quoted(bytes: &[u8], start: usize, quote: u8) -> Option<usize> {
     let len = bytes.len();
     let mut pos = start + 1;
     while pos < len {
         match bytes[pos] {
-            b'\\' => pos += 2,
-            b'\'' => return Some(pos + 1),
+            b'\\' => {
+                pos += if bytes[pos + 1..].starts_with(b"\r\n") {
+                    3
+                } else {
+                    2
+                }
+            }
+            b'\n' | b'\r' => return None,
+            b if b == quote => return Some(pos + 1),
             _ => pos += 1,
         }
     }
@@ -201,6 +210,34 @@ mod tests {
         assert_eq!(find_expression_end(input), Some(input.len()));
     }
 
+    #[test]
+    fn closing_brace_in_strings_and_comments() {
+        assert_eq!(find_expression_end(b"{ \"a}b\" }"), Some(9));
+        assert_eq!(find_expression_end(b"{ 'a{b' }"), Some(9));
+        assert_eq!(find_expression_end(b"{ f('}', \"{\") }"), Some(15));
+        assert_eq!(find_expression_end(b"{ a /* } { */ }"), Some(15));
+        assert_eq!(find_expression_end(b"{ a // }\n}"), Some(10));
+    }
+
+    #[test]
+    fn multiline_expression_with_strings_and_comments() {
+        let input = b"{items.map(item => {\n  // close with }\n  return item.label ?? \"}\";\n  /* { */\n})}\nafter";
+        assert_eq!(find_expression_end(input), Some(input.len() - 6));
+        assert_eq!(&input[input.len() - 6..], b"\nafter");
+    }
+
+    #[test]
+    fn quoted_string_ends_at_line_break() {
+        // The apostrophe starts a string that cannot reach the later quote
+        assert_eq!(find_expression_end(b"{ it's\n} and 'x' }"), None);
+        assert_eq!(find_expression_end(b"{ \"a\r\n\" }"), None);
+        // An escaped line break continues the string
+        assert_eq!(find_expression_end(b"{ \"a\\\nb}\" }"), Some(11));
+        assert_eq!(find_expression_end(b"{ 'a\\\r\nb' }"), Some(11));
+        // Template literals may span lines
+        assert_eq!(find_expression_end(b"{ `a\n}` }"), Some(9));
+    }
+
     #[test]
     fn slash_not_comment() {
         // A lone `/` inside an expression is not a comment start
@@ -1,3 +1,7 @@
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
 use super::expr::find_expression_end;
 
 /// Information about a parsed JSX tag.
@@ -28,6 +32,89 @@ pub struct TagInfo<'a> {
 /// - Attributes with string values, expression values (`{...}`), and bare attributes
 /// - Multiline attributes (byte-based, not line-based)
 pub fn parse_jsx_tag(input: &[u8]) -> Option<TagInfo<'_>> {
+    parse_tag(input, |_, _, _| {})
+}
+
+/// A string-literal attribute of a JSX tag, e.g. `title="Hello"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaticAttribute<'a> {
+    /// Attribute name (e.g. `"title"`, `"aria-label"`, `"xlink:href"`).
+    pub name: &'a str,
+    /// Attribute value without quotes, with HTML entities decoded.
+    pub value: Cow<'a, str>,
+}
+
+/// Extract the string-literal attributes of the JSX tag at the start of
+/// `input`, in source order.
+///
+/// Returns `None` when [`parse_jsx_tag`] would. Expression values
+/// (`count={1}`), spreads (`{...props}`), and bare attributes (`disabled`)
+/// are skipped, since their values are only known at runtime or are not
+/// strings. Both quote styles are accepted; a backslash before the closing
+/// quote character yields that quote, and entities such as `&amp;` are
+/// decoded as JSX does.
+///
+/// # Example
+/// ```
+/// use ferromark::mdx::jsx_tag::static_attributes;
+///
+/// let attrs = static_attributes(b"<Card title=\"Hello\" count={1} />").unwrap();
+/// let html: String = attrs
+///     .iter()
+///     .map(|attr| format!(" data-{}=\"{}\"", attr.name, attr.value))
+///     .collect();
+/// assert_eq!(format!("<div{html}>"), "<div data-title=\"Hello\">");
+/// ```
+pub fn static_attributes(input: &[u8]) -> Option<Vec<StaticAttribute<'_>>> {
+    let mut attributes = Vec::new();
+    parse_tag(input, |name, quote, raw| {
+        let (Some(quote), Ok(name), Ok(raw)) =
+            (quote, core::str::from_utf8(name), core::str::from_utf8(raw))
+        else {
+            return;
+        };
+        attributes.push(StaticAttribute {
+            name,
+            value: static_value(raw, quote),
+        });
+    })?;
+    Some(attributes)
+}
+
+/// Resolve escaped quotes and HTML entities in a quoted attribute value.
+fn static_value(raw: &str, quote: u8) -> Cow<'_, str> {
+    let escaped_quote = [b'\\', quote];
+    let unescaped: Cow<'_, str> = if raw.as_bytes().windows(2).any(|w| w == escaped_quote) {
+        let mut value = String::with_capacity(raw.len());
+        let mut chars = raw.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\\' && chars.peek() == Some(&char::from(quote)) {
+                continue;
+            }
+            value.push(c);
+        }
+        Cow::Owned(value)
+    } else {
+        Cow::Borrowed(raw)
+    };
+    if !unescaped.contains('&') {
+        return unescaped;
+    }
+    match unescaped {
+        Cow::Borrowed(raw) => crate::render::decode_entities_commonmark(raw),
+        Cow::Owned(value) => {
+            Cow::Owned(crate::render::decode_entities_commonmark(&value).into_owned())
+        }
+    }
+}
+
+/// Parse a tag, reporting each `name=value` attribute to `on_attribute` as
+/// its name, the quote byte for string values (`None` otherwise), and the
+/// value source without quotes.
+fn parse_tag<'a>(
+    input: &'a [u8],
+    mut on_attribute: impl FnMut(&'a [u8], Option<u8>, &'a [u8]),
+) -> Option<TagInfo<'a>> {
     let len = input.len();
     if len < 2 || input[0] != b'<' {
         return None;
@@ -81,7 +168,7 @@ pub fn parse_jsx_tag(input: &[u8]) -> Option<TagInfo<'_>> {
         }
     }
 
-    let name = std::str::from_utf8(&input[name_start..pos]).ok()?;
+    let name = core::str::from_utf8(&input[name_start..pos]).ok()?;
 
     // For closing tags, just expect `>`
     if is_closing {
@@ -110,6 +197,7 @@ pub fn parse_jsx_tag(input: &[u8]) -> Option<TagInfo<'_>> {
             pos += end;
         } else if input[pos].is_ascii_alphabetic() || input[pos] == b'_' {
             // Attribute name
+            let attr_start = pos;
             while pos < len
                 && (input[pos].is_ascii_alphanumeric()
                     || input[pos] == b'_'
@@ -119,6 +207,7 @@ pub fn parse_jsx_tag(input: &[u8]) -> Option<TagInfo<'_>> {
             {
                 pos += 1;
             }
+            let attr_name = &input[attr_start..pos];
             pos = skip_whitespace(input, pos);
             // Check for `=`
             if pos < len && input[pos] == b'=' {
@@ -128,16 +217,20 @@ pub fn parse_jsx_tag(input: &[u8]) -> Option<TagInfo<'_>> {
                 if pos >= len {
                     return None;
                 }
+                let value_start = pos;
                 match input[pos] {
                     b'"' => {
                         pos = skip_double_quoted(input, pos)?;
+                        on_attribute(attr_name, Some(b'"'), &input[value_start + 1..pos - 1]);
                     }
                     b'\'' => {
                         pos = skip_single_quoted(input, pos)?;
+                        on_attribute(attr_name, Some(b'\''), &input[value_start + 1..pos - 1]);
                     }
                     b'{' => {
                         let end = find_expression_end(&input[pos..])?;
                         pos += end;
+                        on_attribute(attr_name, None, &input[value_start..pos]);
                     }
                     _ => {
                         // Bare value (not standard JSX, but be lenient)
@@ -371,4 +464,53 @@ mod tests {
         let info = parse_jsx_tag(b"<A.B.C.D>").unwrap();
         assert_eq!(info.name, "A.B.C.D");
     }
+
+    fn static_pairs(input: &[u8]) -> Vec<(&str, String)> {
+        static_attributes(input)
+            .unwrap()
+            .into_iter()
+            .map(|attr| (attr.name, attr.value.into_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn static_attributes_skip_expressions_spreads_and_bare() {
+        let input =
+            b"<Card title=\"Hello\" count={1} {...rest} open kind='tip' aria-label=\"x\" />";
+        assert_eq!(
+            static_pairs(input),
+            [
+                ("title", "Hello".into()),
+                ("kind", "tip".into()),
+                ("aria-label", "x".into()),
+            ]
+        );
+        assert!(static_attributes(b"<Card>").unwrap().is_empty());
+        assert!(static_attributes(b"</Card>").unwrap().is_empty());
+    }
+
+    #[test]
+    fn static_attributes_resolve_quotes_and_entities() {
+        let input = br#"<A a="say \"hi\"" b='it\'s' c="it's" d='"q"' e="Tom &amp; Jerry &#x41;" f="C:\dir">"#;
+        assert_eq!(
+            static_pairs(input),
+            [
+                ("a", "say \"hi\"".into()),
+                ("b", "it's".into()),
+                ("c", "it's".into()),
+                ("d", "\"q\"".into()),
+                ("e", "Tom & Jerry A".into()),
+                ("f", "C:\\dir".into()),
+            ]
+        );
+        // Plain values borrow from the input
+        let attrs = static_attributes(b"<A t=\"plain\">").unwrap();
+        assert!(matches!(attrs[0].value, Cow::Borrowed("plain")));
+    }
+
+    #[test]
+    fn static_attributes_need_a_valid_tag() {
+        assert!(static_attributes(b"<Foo bar=\"baz").is_none());
+        assert!(static_attributes(b"< 5").is_none());
+    }
 }
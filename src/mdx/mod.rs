@@ -58,10 +58,13 @@
 //! [`segment`] detects JSX and expressions at block level (start of a line).
 //! Inline JSX (`paragraph with <em>JSX</em> inside`) and inline expressions
 //! (`text {variable} here`) stay inside Markdown segments and are **not** split
-//! out. For consumers that need typed inline constructs, the opt-in
-//! [`crate::InlineParser::parse_mdx`] method emits source-ranged MDX inline
-//! events while preserving the surrounding Markdown events. The official mdxjs
-//! compiler handles both flow and text positions in a single parse.
+//! out; [`segment_inline_jsx`] opts into splitting the inline JSX tags. [`render()`] recognizes them while rendering those segments and writes
+//! them through unescaped instead of as HTML text; code spans, math, and
+//! backslash escapes keep their precedence. For consumers that need typed
+//! inline constructs, the opt-in [`crate::InlineParser::parse_mdx`] method
+//! emits source-ranged MDX inline events while preserving the surrounding
+//! Markdown events. The official mdxjs compiler handles both flow and text
+//! positions in a single parse.
 //!
 //! ## No JavaScript validation
 //!
@@ -100,6 +103,9 @@
 //! repeated container prefixes and container-local ESM remain Markdown
 //! recovery.
 //!
+//! [`segment_container_aware`] is an opt-in segmentation that also detects a
+//! JSX tag line after blockquote and list markers (`> <Note>`, `- <Card />`).
+//!
 //! ## No TypeScript generics in JSX
 //!
 //! `<Component<T>>` with TypeScript generics is not supported by the tag
@@ -113,6 +119,8 @@
 //! [`segment_strict`] is an opt-in validation pass that returns structural MDX
 //! diagnostics with source ranges instead. It does not validate JavaScript or
 //! TypeScript syntax inside otherwise well-delimited ESM and expressions.
+//! [`render_with_invalid_expression`] can replace an unterminated expression
+//! with a placeholder comment or collect it as a diagnostic instead.
 
 mod events;
 pub mod expr;
@@ -142,6 +150,9 @@ pub enum Segment<'a> {
     JsxBlockSelfClose(&'a str),
     /// JavaScript expression (e.g. `{expression}`).
     Expression(&'a str),
+    /// JSX tag inside Markdown text (e.g. the `<Icon />` in
+    /// `text <Icon /> more`). Only produced by [`segment_inline_jsx`].
+    JsxInline(&'a str),
 }
 
 /// A typed MDX segment together with its exact byte range in the input.
@@ -214,6 +225,31 @@ pub fn segment(input: &str) -> Vec<Segment<'_>> {
     splitter::split(input)
 }
 
+/// Segment an MDX document, also detecting JSX tags inside containers.
+///
+/// Like [`segment`], but a line whose blockquote or list item content is a
+/// single JSX tag (`> <Note>`, `- <Card />`, `1. </Note>`) becomes a JSX
+/// segment. The segment text still starts with the line's container markers,
+/// so the returned segments cover the entire input. Expressions and ESM
+/// inside containers stay Markdown.
+#[must_use]
+pub fn segment_container_aware(input: &str) -> Vec<Segment<'_>> {
+    splitter::split_container_aware(input)
+}
+
+/// Segment an MDX document, also splitting inline JSX out of Markdown.
+///
+/// Like [`segment`], but each JSX tag inside Markdown text becomes a
+/// [`Segment::JsxInline`] and the Markdown around it is split at the tag, so
+/// `text <Icon /> more` yields `Markdown("text ")`, `JsxInline("<Icon />")`,
+/// and `Markdown(" more")`. Tags are recognized by the MDX-aware inline
+/// parser: a `<` that is not a valid tag (`a < b`, `x<5`), an autolink
+/// (`<https://example.com>`), or a tag inside code stays Markdown.
+#[must_use]
+pub fn segment_inline_jsx(input: &str) -> Vec<Segment<'_>> {
+    splitter::split_inline_jsx(input)
+}
+
 /// Segment an MDX document and retain exact byte ranges for each segment.
 ///
 /// This is the source-location-aware counterpart to [`segment`]. It has the
@@ -305,9 +341,12 @@ impl<'a> Segment<'a> {
             | Self::JsxBlockOpen(text)
             | Self::JsxBlockClose(text)
             | Self::JsxBlockSelfClose(text)
-            | Self::Expression(text) => text,
+            | Self::Expression(text)
+            | Self::JsxInline(text) => text,
         }
     }
 }
 
-pub use render::{MdxOutput, render, render_with_options};
+pub use render::{
+    InvalidExpression, MdxOutput, render, render_with_invalid_expression, render_with_options,
+};
@@ -1,8 +1,12 @@
-use std::fmt::Write;
+use core::fmt::Write;
 
-use crate::{Options, RenderPolicy};
+use crate::block::BlockParser;
+use crate::{FootnoteStore, LinkRefStore, Options, Range, RenderPolicy};
 
-use super::{Segment, segment};
+use super::events::offset_block_event;
+use super::expr::find_expression_end;
+use super::strict::UNTERMINATED_EXPRESSION;
+use super::{MdxDiagnostic, MdxDiagnosticCode, Segment, segment_spanned};
 
 /// Error returned when a component name cannot be used as a JavaScript binding.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -17,8 +21,8 @@ pub enum ComponentNameError {
     ReservedWord,
 }
 
-impl std::fmt::Display for ComponentNameError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for ComponentNameError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::Empty => f.write_str("component name cannot be empty"),
             Self::InvalidStart(ch) => write!(
@@ -34,7 +38,7 @@ impl std::fmt::Display for ComponentNameError {
     }
 }
 
-impl std::error::Error for ComponentNameError {}
+impl core::error::Error for ComponentNameError {}
 
 fn validate_component_name(name: &str) -> Result<(), ComponentNameError> {
     let mut chars = name.chars();
@@ -127,8 +131,40 @@ pub struct MdxOutput<'a> {
     pub body: String,
     /// ESM statements (import/export), in document order.
     pub esm: Vec<&'a str>,
+    /// The `import` statements of [`Self::esm`], in document order.
+    pub imports: Vec<&'a str>,
+    /// The `export` statements of [`Self::esm`], including `export default`,
+    /// in document order.
+    pub exports: Vec<&'a str>,
     /// Front matter content (if present in first Markdown segment).
     pub front_matter: Option<&'a str>,
+    /// Unterminated flow expressions, recorded with
+    /// [`InvalidExpression::Collect`].
+    pub errors: Vec<MdxDiagnostic>,
+}
+
+/// How [`render_with_invalid_expression`] handles a flow expression that has
+/// no closing `}`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InvalidExpression {
+    /// Render the expression as Markdown, like [`render_with_options`].
+    #[default]
+    Markdown,
+    /// Replace the line that opens the expression with
+    /// `<!-- invalid expression -->`; following lines stay Markdown.
+    Placeholder,
+    /// Render the expression as Markdown and record an
+    /// [`MdxDiagnosticCode::UnterminatedExpression`] in [`MdxOutput::errors`].
+    Collect,
+}
+
+/// A rendering unit: a Markdown run, a replaced invalid expression, or a
+/// segment passed through as written.
+enum Piece<'a> {
+    Markdown { text: &'a str, offset: usize },
+    InvalidExpression,
+    Esm(&'a str),
+    Flow(&'a str),
 }
 
 impl MdxOutput<'_> {
@@ -189,38 +225,195 @@ pub fn render(input: &str) -> MdxOutput<'_> {
 }
 
 /// Render MDX to HTML body with custom Markdown options.
+///
+/// `options` applies to every Markdown segment. Inline JSX tags and `{...}`
+/// expressions inside Markdown are kept as written (escaped under
+/// [`RenderPolicy::Untrusted`]), and inline HTML is read as JSX. Link reference and footnote
+/// definitions are collected across all Markdown segments before rendering,
+/// so a reference resolves no matter which segment defines it, and the
+/// footnote section is written once at the end of the body. Footnote numbers
+/// and heading ids also continue from one segment to the next.
 pub fn render_with_options<'a>(input: &'a str, options: &Options) -> MdxOutput<'a> {
-    let segments = segment(input);
-    let mut body = String::with_capacity(input.len());
-    let mut esm: Vec<&'a str> = Vec::new();
+    render_with_invalid_expression(input, options, InvalidExpression::Markdown)
+}
+
+/// Render MDX like [`render_with_options`], handling unterminated flow
+/// expressions with `on_invalid_expression`.
+///
+/// ```
+/// use ferromark::mdx::{InvalidExpression, render_with_invalid_expression};
+///
+/// let out = render_with_invalid_expression(
+///     "{draft\n\nText\n",
+///     &ferromark::Options::default(),
+///     InvalidExpression::Placeholder,
+/// );
+/// assert_eq!(out.body, "<!-- invalid expression -->\n<p>Text</p>\n");
+/// ```
+pub fn render_with_invalid_expression<'a>(
+    input: &'a str,
+    options: &Options,
+    on_invalid_expression: InvalidExpression,
+) -> MdxOutput<'a> {
+    let mut errors = Vec::new();
+    let mut pieces = Vec::new();
+    for spanned in segment_spanned(input) {
+        match spanned.segment {
+            Segment::Markdown(text) => push_markdown_pieces(
+                input,
+                text,
+                spanned.range.start_usize(),
+                on_invalid_expression,
+                &mut pieces,
+                &mut errors,
+            ),
+            Segment::Esm(text) => pieces.push(Piece::Esm(text)),
+            segment => pieces.push(Piece::Flow(segment.as_str())),
+        }
+    }
+
     let mut front_matter: Option<&'a str> = None;
+    let mut link_refs = LinkRefStore::new();
+    let mut footnote_store = FootnoteStore::new();
+    let mut parts = Vec::new();
+
+    for piece in &pieces {
+        let Piece::Markdown {
+            text: mut markdown,
+            mut offset,
+        } = *piece
+        else {
+            continue;
+        };
+        if options.front_matter {
+            if let Some((content, body_offset)) = crate::extract_front_matter(markdown) {
+                front_matter.get_or_insert(content);
+                markdown = &markdown[body_offset..];
+                offset += body_offset;
+            }
+        }
+
+        let mut parser = BlockParser::new_with_options(markdown.as_bytes(), *options);
+        let mut events = Vec::new();
+        parser.parse(&mut events);
+        link_refs.merge_first_wins(parser.take_link_refs());
+        if options.footnotes {
+            // Definitions render in the shared footnote section, which reads
+            // from the whole input rather than from this segment.
+            footnote_store.merge_first_wins(parser.take_footnote_store(), |event| {
+                offset_block_event(event, offset)
+            });
+        }
+        parts.push((markdown.as_bytes(), events));
+    }
 
-    for seg in &segments {
-        match seg {
-            Segment::Esm(s) => {
+    let (html, footnotes) = crate::renderer::render_markdown_parts(
+        &parts,
+        &link_refs,
+        options.footnotes.then_some(&footnote_store),
+        input.as_bytes(),
+        options,
+    );
+    let mut html = html.into_iter();
+    let mut body = String::with_capacity(input.len());
+    let mut esm: Vec<&'a str> = Vec::new();
+    let mut imports: Vec<&'a str> = Vec::new();
+    let mut exports: Vec<&'a str> = Vec::new();
+
+    for piece in pieces {
+        match piece {
+            Piece::Esm(s) => {
+                // The splitter starts every ESM segment with its keyword
+                if s.starts_with("import") {
+                    imports.push(s);
+                } else {
+                    exports.push(s);
+                }
                 esm.push(s);
             }
-            Segment::Markdown(s) => {
-                let result = crate::parse_with_options(s, options);
-                body.push_str(&result.html);
-                if front_matter.is_none() {
-                    front_matter = result.front_matter;
-                }
+            Piece::Markdown { .. } => {
+                let part = html
+                    .next()
+                    .expect("every Markdown segment must have rendered HTML");
+                body.push_str(&part);
             }
-            Segment::JsxBlockOpen(s)
-            | Segment::JsxBlockClose(s)
-            | Segment::JsxBlockSelfClose(s)
-            | Segment::Expression(s) => {
+            Piece::InvalidExpression => body.push_str("<!-- invalid expression -->\n"),
+            Piece::Flow(s) => {
                 body.push_str(s.trim());
                 body.push('\n');
             }
         }
     }
+    body.push_str(&footnotes);
 
     MdxOutput {
         body,
         esm,
+        imports,
+        exports,
         front_matter,
+        errors,
+    }
+}
+
+/// Push a Markdown segment, splitting it around lines that open an
+/// unterminated flow expression when the strategy replaces them.
+///
+/// The splitter leaves such lines in Markdown: a `{` that starts a line and
+/// has no closing `}` anywhere in the rest of the input.
+fn push_markdown_pieces<'a>(
+    input: &str,
+    markdown: &'a str,
+    offset: usize,
+    on_invalid_expression: InvalidExpression,
+    pieces: &mut Vec<Piece<'a>>,
+    errors: &mut Vec<MdxDiagnostic>,
+) {
+    if on_invalid_expression == InvalidExpression::Markdown {
+        pieces.push(Piece::Markdown {
+            text: markdown,
+            offset,
+        });
+        return;
+    }
+
+    let bytes = markdown.as_bytes();
+    let mut start = 0;
+    let mut line = 0;
+    while line < bytes.len() {
+        let line_end = memchr::memchr(b'\n', &bytes[line..]).map_or(bytes.len(), |i| line + i + 1);
+        let first = bytes[line..line_end]
+            .iter()
+            .position(|&b| b != b' ' && b != b'\t')
+            .map_or(line_end, |i| line + i);
+        let unterminated = bytes.get(first) == Some(&b'{')
+            && find_expression_end(&input.as_bytes()[offset + first..]).is_none();
+        if unterminated {
+            if on_invalid_expression == InvalidExpression::Collect {
+                errors.push(MdxDiagnostic {
+                    code: MdxDiagnosticCode::UnterminatedExpression,
+                    message: UNTERMINATED_EXPRESSION,
+                    primary_range: Range::from_usize(offset + first, input.len()),
+                    related_range: None,
+                });
+            } else {
+                if start < line {
+                    pieces.push(Piece::Markdown {
+                        text: &markdown[start..line],
+                        offset: offset + start,
+                    });
+                }
+                pieces.push(Piece::InvalidExpression);
+                start = line_end;
+            }
+        }
+        line = line_end;
+    }
+    if start < bytes.len() {
+        pieces.push(Piece::Markdown {
+            text: &markdown[start..],
+            offset: offset + start,
+        });
     }
 }
 
@@ -348,6 +541,116 @@ Paragraph.
         assert!(!out.body.contains("id="));
     }
 
+    #[test]
+    fn footnotes_resolve_across_jsx_boundaries() {
+        let input = "\
+Intro with a note[^1].
+
+<Card>
+
+[^1]: The note.
+
+</Card>
+";
+        let opts = Options {
+            footnotes: true,
+            ..mdx_default_options()
+        };
+        let out = render_with_options(input, &opts);
+        assert_eq!(
+            out.body,
+            "<p>Intro with a note<sup><a href=\"#user-content-fn-1\" id=\"user-content-fnref-1\" data-footnote-ref>1</a></sup>.</p>\n\
+             <Card>\n\
+             </Card>\n\
+             <section data-footnotes class=\"footnotes\">\n<ol>\n<li id=\"user-content-fn-1\">\n\
+             <p>The note. <a href=\"#user-content-fnref-1\" class=\"data-footnote-backref\" aria-label=\"Back to reference 1\">↩</a></p>\n\
+             </li>\n</ol>\n</section>\n"
+        );
+    }
+
+    #[test]
+    fn footnote_numbers_continue_across_segments() {
+        let input = "A[^b]\n\n<Card />\n\nB[^a]\n\n[^a]: First def.\n[^b]: Second def.\n";
+        let opts = Options {
+            footnotes: true,
+            ..mdx_default_options()
+        };
+        let out = render_with_options(input, &opts);
+        assert!(
+            out.body
+                .contains("id=\"user-content-fnref-b\" data-footnote-ref>1</a>")
+        );
+        assert!(
+            out.body
+                .contains("id=\"user-content-fnref-a\" data-footnote-ref>2</a>")
+        );
+        // One section, in reference order
+        assert_eq!(out.body.matches("<section").count(), 1);
+        let b = out.body.find("Second def.").unwrap();
+        let a = out.body.find("First def.").unwrap();
+        assert!(b < a);
+    }
+
+    #[test]
+    fn link_refs_resolve_across_segments() {
+        let input = "[^n] and [docs]\n\n<Card>\n\n[docs]: /docs\n\n</Card>\n\n[^n]: See [docs].\n";
+        let opts = Options {
+            footnotes: true,
+            ..mdx_default_options()
+        };
+        let out = render_with_options(input, &opts);
+        assert!(out.body.contains("and <a href=\"/docs\">docs</a></p>"));
+        assert!(out.body.contains("<p>See <a href=\"/docs\">docs</a>."));
+    }
+
+    #[test]
+    fn heading_ids_stay_unique_across_segments() {
+        let out = render("# Intro\n\n<Card>\n\n# Intro\n\n</Card>\n");
+        assert!(out.body.contains("<h1 id=\"intro\">"));
+        assert!(out.body.contains("<h1 id=\"intro-1\">"));
+    }
+
+    #[test]
+    fn inline_jsx_and_expressions_pass_through() {
+        let out = render("Hi {user.name}, {a && b} <Badge count={n > 1} /> & more.\n");
+        assert_eq!(
+            out.body,
+            "<p>Hi {user.name}, {a && b} <Badge count={n > 1} /> &amp; more.</p>\n"
+        );
+    }
+
+    #[test]
+    fn inline_expressions_skip_code_math_and_escapes() {
+        let opts = Options {
+            math: true,
+            ..mdx_default_options()
+        };
+        let out = render_with_options("`{a < b}` $x^{<}$ \\{a < b} {ok}\n", &opts);
+        assert_eq!(
+            out.body,
+            "<p><code>{a &lt; b}</code> <code class=\"language-math math-inline\">x^{&lt;}</code> {a &lt; b} {ok}</p>\n"
+        );
+        // Malformed constructs stay text
+        assert_eq!(render("{open <Broken\n").body, "<p>{open &lt;Broken</p>\n");
+    }
+
+    #[test]
+    fn inline_mdx_in_footnotes_and_untrusted_output() {
+        let opts = Options {
+            footnotes: true,
+            ..mdx_default_options()
+        };
+        let out = render_with_options("A[^1]\n\n[^1]: Press <Kbd>{key}</Kbd>.\n", &opts);
+        assert!(out.body.contains("<p>Press <Kbd>{key}</Kbd>. <a href="));
+
+        let opts = Options {
+            render_policy: RenderPolicy::Untrusted,
+            ..mdx_default_options()
+        };
+        let out = render_with_options("<Badge /> {a && b}\n", &opts);
+        assert_eq!(out.body, "<p>&lt;Badge /&gt; {a &amp;&amp; b}</p>\n");
+    }
+
     #[test]
     fn to_component_full() {
         let input = "\
@@ -1,6 +1,7 @@
-use super::Segment;
 use super::expr::find_expression_end;
 use super::jsx_tag::parse_jsx_tag;
+use super::{MdxEvent, Segment};
+use crate::InlineEvent;
 
 /// Split MDX input into typed segments.
 ///
@@ -11,6 +12,66 @@ use super::jsx_tag::parse_jsx_tag;
 ///
 /// The returned `Vec<Segment>` covers the entire input (no bytes are dropped).
 pub fn split(input: &str) -> Vec<Segment<'_>> {
+    split_with(input, false)
+}
+
+/// Split MDX input, also detecting JSX tags that start the content of a
+/// blockquote or list item (`> <Note>`, `- <Card />`).
+///
+/// Such segments include the container markers of their line.
+pub fn split_container_aware(input: &str) -> Vec<Segment<'_>> {
+    split_with(input, true)
+}
+
+/// Split MDX input, then split each Markdown segment at its inline JSX tags.
+///
+/// The tags come from the MDX event stream, so code spans, code blocks, and
+/// autolinks keep their Markdown precedence.
+pub fn split_inline_jsx(input: &str) -> Vec<Segment<'_>> {
+    let mut tags = super::parse_events(input)
+        .events
+        .into_iter()
+        .filter_map(|event| match event {
+            MdxEvent::Inline(
+                InlineEvent::MdxJsxOpen(range)
+                | InlineEvent::MdxJsxClose(range)
+                | InlineEvent::MdxJsxSelfClose(range),
+            ) => Some(range),
+            _ => None,
+        })
+        .peekable();
+    let input_start = input.as_ptr() as usize;
+    let mut segments = Vec::new();
+
+    for segment in split(input) {
+        let Segment::Markdown(text) = segment else {
+            segments.push(segment);
+            continue;
+        };
+        let start = text.as_ptr() as usize - input_start;
+        let end = start + text.len();
+        let mut pos = start;
+        while let Some(tag) = tags.next_if(|tag| tag.end_usize() <= end) {
+            if tag.start_usize() < pos {
+                continue;
+            }
+            if pos < tag.start_usize() {
+                segments.push(Segment::Markdown(&input[pos..tag.start_usize()]));
+            }
+            segments.push(Segment::JsxInline(
+                &input[tag.start_usize()..tag.end_usize()],
+            ));
+            pos = tag.end_usize();
+        }
+        if pos < end {
+            segments.push(Segment::Markdown(&input[pos..end]));
+        }
+    }
+
+    segments
+}
+
+fn split_with(input: &str, containers: bool) -> Vec<Segment<'_>> {
     let bytes = input.as_bytes();
     let len = bytes.len();
     let mut segments: Vec<Segment<'_>> = Vec::new();
@@ -32,6 +93,13 @@ pub fn split(input: &str) -> Vec<Segment<'_>> {
         }
 
         let first = bytes[first_non_ws];
+        // JSX may follow container markers in container-aware mode
+        let tag_start = if containers {
+            skip_container_markers(bytes, first_non_ws)
+        } else {
+            first_non_ws
+        };
+        let tag_first = bytes.get(tag_start).copied().unwrap_or(b'\n');
 
         // Detect blank lines (only newline after whitespace) — reset paragraph state
         if first == b'\n' || first == b'\r' {
@@ -42,10 +110,10 @@ pub fn split(input: &str) -> Vec<Segment<'_>> {
         }
 
         // 1. Closing tag: `</`
-        if first == b'<' && first_non_ws + 1 < len && bytes[first_non_ws + 1] == b'/' {
-            if let Some(tag_info) = parse_jsx_tag(&bytes[first_non_ws..]) {
+        if tag_first == b'<' && tag_start + 1 < len && bytes[tag_start + 1] == b'/' {
+            if let Some(tag_info) = parse_jsx_tag(&bytes[tag_start..]) {
                 if tag_info.is_closing {
-                    let end = first_non_ws + tag_info.end_offset;
+                    let end = tag_start + tag_info.end_offset;
                     // Flow JSX requires no trailing non-whitespace content on the line
                     if has_trailing_content(bytes, end) {
                         // Fall through to markdown
@@ -99,12 +167,12 @@ pub fn split(input: &str) -> Vec<Segment<'_>> {
         }
 
         // 4. JSX opening/self-closing tag: `<` followed by letter or `>`
-        if first == b'<'
-            && first_non_ws + 1 < len
-            && (bytes[first_non_ws + 1].is_ascii_alphabetic() || bytes[first_non_ws + 1] == b'>')
+        if tag_first == b'<'
+            && tag_start + 1 < len
+            && (bytes[tag_start + 1].is_ascii_alphabetic() || bytes[tag_start + 1] == b'>')
         {
-            if let Some(tag_info) = parse_jsx_tag(&bytes[first_non_ws..]) {
-                let end = first_non_ws + tag_info.end_offset;
+            if let Some(tag_info) = parse_jsx_tag(&bytes[tag_start..]) {
+                let end = tag_start + tag_info.end_offset;
                 // Flow JSX requires no trailing non-whitespace content on the line
                 if !has_trailing_content(bytes, end) {
                     flush_markdown(input, &mut md_start, line_start, &mut segments);
@@ -178,6 +246,35 @@ fn next_line(bytes: &[u8], mut pos: usize) -> usize {
     }
 }
 
+/// Skip blockquote markers (`>`) and list markers (`-`, `+`, `*`, `1.`,
+/// `1)`) with their following whitespace, returning where content starts.
+fn skip_container_markers(bytes: &[u8], mut pos: usize) -> usize {
+    let len = bytes.len();
+    loop {
+        let marker_end = match bytes.get(pos) {
+            Some(b'>') => pos + 1,
+            Some(b'-' | b'+' | b'*') => pos + 1,
+            Some(b'0'..=b'9') => {
+                let digits = bytes[pos..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_digit())
+                    .count();
+                match bytes.get(pos + digits) {
+                    Some(b'.' | b')') if digits <= 9 => pos + digits + 1,
+                    _ => return pos,
+                }
+            }
+            _ => return pos,
+        };
+        // List markers need whitespace after them; `>` does not
+        let spaced = marker_end < len && (bytes[marker_end] == b' ' || bytes[marker_end] == b'\t');
+        if bytes[pos] != b'>' && !spaced {
+            return pos;
+        }
+        pos = skip_whitespace_offset(bytes, marker_end);
+    }
+}
+
 /// Return the offset of the first non-whitespace byte at or after `pos`.
 fn skip_whitespace_offset(bytes: &[u8], mut pos: usize) -> usize {
     let len = bytes.len();
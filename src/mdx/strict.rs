@@ -4,7 +4,7 @@ use super::splitter::{is_esm_start, try_esm};
 use super::{MdxDiagnostic, MdxDiagnosticCode, SpannedSegment, segment_spanned};
 use crate::Range;
 
-const UNTERMINATED_EXPRESSION: &str = "expected `}` to close this flow expression";
+pub(super) const UNTERMINATED_EXPRESSION: &str = "expected `}` to close this flow expression";
 const UNTERMINATED_JSX_TAG: &str = "expected `>` to close this JSX tag";
 const INVALID_JSX_TAG: &str = "invalid JSX tag structure";
 const UNEXPECTED_JSX_CLOSING_TAG: &str = "closing JSX tag has no matching opening tag";
@@ -0,0 +1,128 @@
+//! Fluent construction of [`Options`].
+
+use crate::inline::CustomDelimiter;
+use crate::{
+    CalloutTitles, CodeSpanRenderer, ExternalLinks, ImageSrcTransform, Options, PrettyConfig,
+    RenderPolicy, Sanitizer, WikiLinkUrl, WwwScheme,
+};
+
+/// Chainable builder for [`Options`].
+///
+/// Start from [`Options::builder`] (the defaults) or convert a preset with
+/// `OptionsBuilder::from(Options::gfm())`. Every setter takes the field's own
+/// type, so `Option` fields take `Some(..)` or `None`.
+///
+/// ```
+/// use ferromark::Options;
+///
+/// let options = Options::builder().footnotes(true).math(true).build();
+/// assert!(options.footnotes && options.math);
+/// assert!(options.tables); // untouched fields keep their defaults
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use]
+pub struct OptionsBuilder {
+    options: Options,
+}
+
+macro_rules! setters {
+    ($($field:ident: $ty:ty,)*) => {
+        $(
+            #[doc = concat!("Set [`Options::", stringify!($field), "`].")]
+            #[inline]
+            pub const fn $field(mut self, value: $ty) -> Self {
+                self.options.$field = value;
+                self
+            }
+        )*
+    };
+}
+
+impl OptionsBuilder {
+    /// Start from `options` instead of the defaults.
+    pub const fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    /// Return the configured options.
+    #[must_use]
+    pub const fn build(self) -> Options {
+        self.options
+    }
+
+    setters! {
+        render_policy: RenderPolicy,
+        allow_html: bool,
+        allow_link_refs: bool,
+        tables: bool,
+        strikethrough: bool,
+        highlight: bool,
+        superscript: bool,
+        subscript: bool,
+        task_lists: bool,
+        autolink_literals: bool,
+        disallowed_raw_html: bool,
+        footnotes: bool,
+        front_matter: bool,
+        heading_ids: bool,
+        math: bool,
+        callouts: bool,
+        untrusted_content: bool,
+        wikilinks: bool,
+        wikilink_url: Option<WikiLinkUrl>,
+        ordered_list_start_override: Option<u32>,
+        emoji: bool,
+        loose_list_continuation: bool,
+        pretty: Option<PrettyConfig>,
+        callout_aria: bool,
+        collapsible_callouts: bool,
+        max_nesting_depth: Option<usize>,
+        max_inline_marks: Option<usize>,
+        custom_delimiters: &'static [CustomDelimiter],
+        code_info_meta: bool,
+        task_list_classes: bool,
+        allow_nested_links: bool,
+        allow_block_html: bool,
+        allow_inline_html: bool,
+        table_cell_merge: bool,
+        code_span_renderer: Option<CodeSpanRenderer>,
+        image_src_transform: Option<ImageSrcTransform>,
+        numeric_footnote_ids: bool,
+        callout_titles: CalloutTitles,
+        callout_custom_titles: bool,
+        compact_list_items: bool,
+        external_links: Option<ExternalLinks>,
+        blockquote_cite_url: bool,
+        strikethrough_single_tilde: bool,
+        sanitizer: Sanitizer,
+        hard_wrap: bool,
+        www_autolink_scheme: WwwScheme,
+        task_list_interactive: bool,
+        smart_punctuation: bool,
+        xhtml: bool,
+        inline_footnotes: bool,
+        footnote_backref_label: &'static str,
+        footnote_id_prefix: &'static str,
+        footnote_section_title: Option<&'static str>,
+        fenced_divs: bool,
+        attributes: bool,
+    }
+}
+
+impl Default for OptionsBuilder {
+    fn default() -> Self {
+        Self::new(Options::default())
+    }
+}
+
+impl From<Options> for OptionsBuilder {
+    fn from(options: Options) -> Self {
+        Self::new(options)
+    }
+}
+
+impl From<OptionsBuilder> for Options {
+    fn from(builder: OptionsBuilder) -> Self {
+        builder.build()
+    }
+}
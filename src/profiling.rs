@@ -96,6 +96,8 @@ pub(crate) fn record_block_events(events: &[BlockEvent], capacity: usize) {
                 }
                 BlockEvent::BlockQuoteStart { .. }
                 | BlockEvent::BlockQuoteEnd
+                | BlockEvent::DivStart { .. }
+                | BlockEvent::DivEnd
                 | BlockEvent::ListStart { .. }
                 | BlockEvent::ListEnd { .. }
                 | BlockEvent::ListItemStart { .. }
@@ -125,7 +127,10 @@ pub(crate) fn record_inline_events(events: &[InlineEvent], capacity: usize) {
             counters.max_inline_event_capacity.max(capacity as u64);
         for event in events {
             match event {
-                InlineEvent::Text(_) | InlineEvent::Code(_) => counters.inline_text_events += 1,
+                InlineEvent::Text(_)
+                | InlineEvent::Code(_)
+                | InlineEvent::Emoji(_)
+                | InlineEvent::SmartPunctuation(_) => counters.inline_text_events += 1,
                 #[cfg(feature = "mdx")]
                 InlineEvent::MdxExpression(_)
                 | InlineEvent::MdxJsxOpen(_)
@@ -138,7 +143,8 @@ pub(crate) fn record_inline_events(events: &[InlineEvent], capacity: usize) {
                 | InlineEvent::ImageStartRef { .. }
                 | InlineEvent::ImageEnd
                 | InlineEvent::Autolink { .. }
-                | InlineEvent::AutolinkLiteral { .. } => counters.inline_link_events += 1,
+                | InlineEvent::AutolinkLiteral { .. }
+                | InlineEvent::WikiLink { .. } => counters.inline_link_events += 1,
                 InlineEvent::Html(_) => counters.inline_html_events += 1,
                 _ => {}
             }
@@ -23,7 +23,7 @@ pub struct Range {
 }
 
 // Compile-time size verification
-const _: () = assert!(std::mem::size_of::<Range>() == 8);
+const _: () = assert!(core::mem::size_of::<Range>() == 8);
 
 impl Range {
     /// Create a new range.
@@ -63,15 +63,15 @@ impl Range {
 
     /// Get the slice as a string, validating its UTF-8 encoding.
     #[inline]
-    pub fn slice_str<'a>(&self, input: &'a [u8]) -> Result<&'a str, std::str::Utf8Error> {
-        std::str::from_utf8(self.slice(input))
+    pub fn slice_str<'a>(&self, input: &'a [u8]) -> Result<&'a str, core::str::Utf8Error> {
+        core::str::from_utf8(self.slice(input))
     }
 
     /// Get the slice as a string, validating its UTF-8 encoding.
     ///
     /// This compatibility alias is equivalent to [`Self::slice_str`].
     #[inline]
-    pub fn try_slice_str<'a>(&self, input: &'a [u8]) -> Result<&'a str, std::str::Utf8Error> {
+    pub fn try_slice_str<'a>(&self, input: &'a [u8]) -> Result<&'a str, core::str::Utf8Error> {
         self.slice_str(input)
     }
 
@@ -129,21 +129,21 @@ impl Range {
     }
 }
 
-impl From<std::ops::Range<u32>> for Range {
+impl From<core::ops::Range<u32>> for Range {
     #[inline]
-    fn from(r: std::ops::Range<u32>) -> Self {
+    fn from(r: core::ops::Range<u32>) -> Self {
         Self::new(r.start, r.end)
     }
 }
 
-impl From<std::ops::Range<usize>> for Range {
+impl From<core::ops::Range<usize>> for Range {
     #[inline]
-    fn from(r: std::ops::Range<usize>) -> Self {
+    fn from(r: core::ops::Range<usize>) -> Self {
         Self::from_usize(r.start, r.end)
     }
 }
 
-impl From<Range> for std::ops::Range<usize> {
+impl From<Range> for core::ops::Range<usize> {
     #[inline]
     fn from(r: Range) -> Self {
         r.start_usize()..r.end_usize()
@@ -156,7 +156,7 @@ mod tests {
 
     #[test]
     fn test_range_size() {
-        assert_eq!(std::mem::size_of::<Range>(), 8);
+        assert_eq!(core::mem::size_of::<Range>(), 8);
     }
 
     #[test]
@@ -249,6 +249,6 @@ mod tests {
     #[test]
     fn test_cache_line_fit() {
         // 8 ranges should fit in a 64-byte L1 cache line
-        assert!(std::mem::size_of::<[Range; 8]>() <= 64);
+        assert!(core::mem::size_of::<[Range; 8]>() <= 64);
     }
 }
@@ -2,14 +2,39 @@
 //!
 //! Uses md4c's growth strategy: 1.5x + 128-byte alignment.
 
+use crate::block::{
+    Alignment, Attribute, CalloutType, CodeBlockKind, ListKind, TaskState, attribute_id,
+    attribute_list, div_attributes,
+};
 use crate::escape;
-use crate::{Range, RenderPolicy};
+use crate::inline::{AutolinkLiteralKind, CustomDelimiter};
+use crate::link_ref::LinkRefDef;
+use crate::sanitize::HtmlAllowlist;
+use crate::{
+    CodeSpanRenderer, ExternalLinks, Options, Range, RenderPolicy, Renderer, Sanitizer,
+    wikilink_percent_encoded,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use memchr::memchr;
 
 /// Decode HTML entities with CommonMark compliance.
-/// - Replaces null bytes (from &#0;) with U+FFFD replacement character
+/// - Replaces null bytes and invalid code points (from &#0;, &#xD800;, ...)
+///   with U+FFFD replacement character
+/// - Keeps numeric references with more than 7 decimal or 6 hex digits as
+///   text
 /// - Handles multi-codepoint entities that html_escape doesn't support
-fn decode_entities_commonmark(input: &str) -> std::borrow::Cow<'_, str> {
+pub(crate) fn decode_entities_commonmark(input: &str) -> alloc::borrow::Cow<'_, str> {
+    match replace_numeric_refs(input) {
+        Some(replaced) => alloc::borrow::Cow::Owned(replaced),
+        None => decode_entities_html_escape(input),
+    }
+}
+
+/// Decode with html_escape, then fix up what it gets wrong.
+fn decode_entities_html_escape(input: &str) -> alloc::borrow::Cow<'_, str> {
     let decoded = html_escape::decode_html_entities(input);
 
     // Check if we need to fix null bytes or missing multi-codepoint entities
@@ -35,7 +60,84 @@ fn decode_entities_commonmark(input: &str) -> std::borrow::Cow<'_, str> {
         result = result.replace('≧', "\u{2267}\u{0338}");
     }
 
-    std::borrow::Cow::Owned(result)
+    alloc::borrow::Cow::Owned(result)
+}
+
+/// Decode `input`, handling the numeric references html_escape does not
+/// treat as CommonMark requires: code points it refuses (NUL, surrogates,
+/// beyond U+10FFFF) become U+FFFD, and references with too many digits stay
+/// text. Returns `None` when there are none.
+fn replace_numeric_refs(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut result: Option<String> = None;
+    let mut copied = 0;
+    let mut pos = 0;
+    while let Some(offset) = memchr(b'&', &bytes[pos..]) {
+        let start = pos + offset;
+        pos = start + 1;
+        if bytes.get(pos) != Some(&b'#') {
+            continue;
+        }
+        let hex = matches!(bytes.get(pos + 1), Some(b'x' | b'X'));
+        let digits_start = pos + 1 + usize::from(hex);
+        let (radix, max_digits) = if hex { (16, 6) } else { (10, 7) };
+        let digits = bytes[digits_start..]
+            .iter()
+            .take_while(|b| (**b as char).is_digit(radix))
+            .count();
+        let end = digits_start + digits;
+        if digits == 0 || bytes.get(end) != Some(&b';') {
+            continue;
+        }
+        let replacement = if digits > max_digits {
+            // No reference at all, whatever html_escape would make of it
+            &input[start..=end]
+        } else {
+            let value = u32::from_str_radix(&input[digits_start..end], radix).unwrap_or(0);
+            if value != 0 && char::from_u32(value).is_some() {
+                continue;
+            }
+            "\u{FFFD}"
+        };
+        let result = result.get_or_insert_with(|| String::with_capacity(input.len()));
+        result.push_str(&decode_entities_html_escape(&input[copied..start]));
+        result.push_str(replacement);
+        copied = end + 1;
+        pos = copied;
+    }
+    let mut result = result?;
+    result.push_str(&decode_entities_html_escape(&input[copied..]));
+    Some(result)
+}
+
+/// Resolve backslash escapes and entity references in a link destination or
+/// title. Entities decode only between escapes, so `\&amp;` stays `&amp;`,
+/// and decoded text is never read as an escape (`&#92;*` stays `\*`).
+pub(crate) fn decode_link_text(input: &[u8]) -> String {
+    fn push_decoded(out: &mut String, chunk: &[u8]) {
+        let chunk = core::str::from_utf8(chunk).unwrap_or("");
+        if memchr(b'&', chunk.as_bytes()).is_some() {
+            out.push_str(&decode_entities_commonmark(chunk));
+        } else {
+            out.push_str(chunk);
+        }
+    }
+
+    let mut out = String::with_capacity(input.len());
+    let mut start = 0;
+    let mut pos = 0;
+    while pos < input.len() {
+        if input[pos] == b'\\' && pos + 1 < input.len() && is_link_escapable(input[pos + 1]) {
+            push_decoded(&mut out, &input[start..pos]);
+            out.push(input[pos + 1] as char);
+            pos += 2;
+            start = pos;
+        } else {
+            pos += 1;
+        }
+    }
+    push_decoded(&mut out, &input[start..]);
+    out
 }
 
 /// Return whether a URL is safe to place in an untrusted HTML attribute.
@@ -45,7 +147,7 @@ fn decode_entities_commonmark(input: &str) -> std::borrow::Cow<'_, str> {
 /// normalized before checking the scheme so browser-equivalent spellings of
 /// `javascript:` and similar schemes cannot bypass the boundary.
 fn is_safe_url(url: &[u8]) -> bool {
-    let Ok(url) = std::str::from_utf8(url) else {
+    let Ok(url) = core::str::from_utf8(url) else {
         return false;
     };
     if memchr(b'&', url.as_bytes()).is_none() {
@@ -102,6 +204,53 @@ fn is_safe_normalized_url(url: &str) -> bool {
     true
 }
 
+/// Extra output bytes expected per Markdown source byte, indexed by byte value.
+///
+/// Each weight approximates the markup its construct expands into: table
+/// pipes become cell tags, `#` opens a heading with an id, `[` a link, `<`
+/// and `&` an entity, and newlines close paragraphs and list items.
+const CAPACITY_WEIGHTS: [u8; 256] = {
+    let mut weights = [0u8; 256];
+    weights[b'\n' as usize] = 4;
+    weights[b'#' as usize] = 24;
+    weights[b'[' as usize] = 12;
+    weights[b'!' as usize] = 6;
+    weights[b'*' as usize] = 4;
+    weights[b'_' as usize] = 4;
+    weights[b'~' as usize] = 3;
+    weights[b'`' as usize] = 6;
+    weights[b'|' as usize] = 14;
+    weights[b'<' as usize] = 3;
+    weights[b'>' as usize] = 3;
+    weights[b'&' as usize] = 4;
+    weights[b'"' as usize] = 5;
+    weights[b'-' as usize] = 2;
+    weights
+};
+
+/// Pretty-printing settings for [`HtmlWriter`].
+///
+/// Pretty mode only touches block structure: block tags that start a line are
+/// indented by their nesting depth. Whitespace inside paragraphs, headings,
+/// code blocks and raw HTML is left exactly as in the default output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PrettyConfig {
+    /// Spaces per nesting level of block containers.
+    pub indent_width: u8,
+    /// Put the inline content of tight list items and table cells on its own
+    /// line between the opening and closing tag.
+    pub wrap_block_tags: bool,
+}
+
+impl Default for PrettyConfig {
+    fn default() -> Self {
+        Self {
+            indent_width: 2,
+            wrap_block_tags: false,
+        }
+    }
+}
+
 /// HTML output writer with pre-allocated, reusable buffer.
 ///
 /// # Example
@@ -118,6 +267,20 @@ fn is_safe_normalized_url(url: &str) -> bool {
 /// ```
 pub struct HtmlWriter {
     out: Vec<u8>,
+    /// Pretty-printing mode, `None` for the default compact output.
+    pretty: Option<PrettyConfig>,
+    /// Current block nesting depth (only tracked in pretty mode).
+    depth: u32,
+    /// Output length right after the most recent list item or cell opening tag.
+    open_tag_end: usize,
+    /// Drop the newline between `</li>` and the next `<li>`.
+    compact_list_items: bool,
+    /// Close void elements with ` />` rather than `>`.
+    xhtml: bool,
+    /// State of [`Self::write_html_allowlisted`] within an HTML block.
+    html_allowlist: HtmlAllowlist,
+    /// State of the [`Renderer`] implementation between events.
+    events: EventState,
 }
 
 impl HtmlWriter {
@@ -126,6 +289,13 @@ impl HtmlWriter {
     pub fn new() -> Self {
         Self {
             out: Vec::with_capacity(1024),
+            pretty: None,
+            depth: 0,
+            open_tag_end: 0,
+            compact_list_items: false,
+            xhtml: true,
+            html_allowlist: HtmlAllowlist::default(),
+            events: EventState::default(),
         }
     }
 
@@ -137,17 +307,75 @@ impl HtmlWriter {
         let capacity = input_len + input_len / 4;
         Self {
             out: Vec::with_capacity(capacity),
+            pretty: None,
+            depth: 0,
+            open_tag_end: 0,
+            compact_list_items: false,
+            xhtml: true,
+            html_allowlist: HtmlAllowlist::default(),
+            events: EventState::default(),
         }
     }
 
+    /// Estimate the rendered HTML size of a Markdown source.
+    ///
+    /// A single table-driven pass weighs the bytes that expand into markup
+    /// (headings, links, emphasis, table pipes, escaped characters), so
+    /// link- and table-heavy documents get a buffer that rarely has to grow.
+    ///
+    /// The pass costs more than it saves on typical documents, so the
+    /// `to_html*` functions size their buffer from the input length instead.
+    /// Opt in by reserving the estimate in the buffer given to
+    /// [`to_html_into`](crate::to_html_into), which keeps existing capacity.
+    pub fn estimate_capacity(input: &[u8]) -> usize {
+        let extra: usize = input
+            .iter()
+            .map(|&b| CAPACITY_WEIGHTS[b as usize] as usize)
+            .sum();
+        input.len() + input.len() / 16 + extra + 64
+    }
+
     /// Create with explicit capacity.
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
             out: Vec::with_capacity(capacity),
+            pretty: None,
+            depth: 0,
+            open_tag_end: 0,
+            compact_list_items: false,
+            xhtml: true,
+            html_allowlist: HtmlAllowlist::default(),
+            events: EventState::default(),
         }
     }
 
+    /// Enable or disable pretty-printing for subsequent output.
+    #[inline]
+    pub fn set_pretty(&mut self, config: Option<PrettyConfig>) {
+        self.pretty = config;
+        self.depth = 0;
+    }
+
+    /// Write consecutive list items without whitespace between them.
+    #[inline]
+    pub fn set_compact_list_items(&mut self, compact: bool) {
+        self.compact_list_items = compact;
+    }
+
+    /// Close void elements XHTML-style (`<hr />`), or HTML5-style (`<hr>`)
+    /// when `false`. On by default.
+    #[inline]
+    pub fn set_xhtml(&mut self, xhtml: bool) {
+        self.xhtml = xhtml;
+    }
+
+    /// Current pretty-printing settings, if enabled.
+    #[inline]
+    pub fn pretty(&self) -> Option<PrettyConfig> {
+        self.pretty
+    }
+
     /// Grow buffer using md4c's strategy: 1.5x + 128-byte alignment.
     #[cold]
     #[inline(never)]
@@ -257,41 +485,7 @@ impl HtmlWriter {
             self.write_escaped_link_attr(title);
             return;
         }
-
-        // First decode entities
-        let title_str = core::str::from_utf8(title).unwrap_or("");
-        let decoded = decode_entities_commonmark(title_str);
-        let decoded_bytes = decoded.as_bytes();
-
-        if memchr(b'\\', decoded_bytes).is_none() {
-            escape::escape_full_into(&mut self.out, decoded_bytes);
-            return;
-        }
-
-        // Then process backslash escapes and HTML-escape
-        let mut pos = 0;
-        while pos < decoded_bytes.len() {
-            if decoded_bytes[pos] == b'\\'
-                && pos + 1 < decoded_bytes.len()
-                && is_link_escapable(decoded_bytes[pos + 1])
-            {
-                // Skip backslash, write escaped char (with HTML escaping)
-                pos += 1;
-                escape::escape_full_into(&mut self.out, &decoded_bytes[pos..pos + 1]);
-                pos += 1;
-            } else {
-                // Find next backslash or end
-                let start = pos;
-                while pos < decoded_bytes.len()
-                    && !(decoded_bytes[pos] == b'\\'
-                        && pos + 1 < decoded_bytes.len()
-                        && is_link_escapable(decoded_bytes[pos + 1]))
-                {
-                    pos += 1;
-                }
-                escape::escape_full_into(&mut self.out, &decoded_bytes[start..pos]);
-            }
-        }
+        escape::escape_full_into(&mut self.out, decode_link_text(title).as_bytes());
     }
 
     /// Write autolink URL with percent-encoding and HTML escaping.
@@ -346,6 +540,11 @@ impl HtmlWriter {
     #[inline]
     pub fn clear(&mut self) {
         self.out.clear();
+        self.depth = 0;
+        self.events = EventState {
+            options: self.events.options,
+            ..EventState::default()
+        };
     }
 
     /// Get output as byte slice.
@@ -356,8 +555,8 @@ impl HtmlWriter {
 
     /// Get output as a string slice, validating its UTF-8 encoding.
     #[inline]
-    pub fn as_str(&self) -> Result<&str, std::str::Utf8Error> {
-        std::str::from_utf8(&self.out)
+    pub fn as_str(&self) -> Result<&str, core::str::Utf8Error> {
+        core::str::from_utf8(&self.out)
     }
 
     /// Take ownership of output buffer.
@@ -368,7 +567,7 @@ impl HtmlWriter {
 
     /// Take ownership as a string, validating its UTF-8 encoding.
     #[inline]
-    pub fn into_string(self) -> Result<String, std::string::FromUtf8Error> {
+    pub fn into_string(self) -> Result<String, alloc::string::FromUtf8Error> {
         String::from_utf8(self.out)
     }
 
@@ -378,6 +577,106 @@ impl HtmlWriter {
         &mut self.out
     }
 
+    /// Length of the output that later writes can no longer change, up to
+    /// `limit`: it ends after the last `\n` or `>` outside the last
+    /// [`REWRITABLE_TAIL`] bytes, so it never ends inside a UTF-8 sequence
+    /// or an entity.
+    #[cfg(feature = "std")]
+    fn final_len(&self, limit: usize) -> usize {
+        let end = limit.min(self.out.len().saturating_sub(REWRITABLE_TAIL));
+        self.out[..end]
+            .iter()
+            .rposition(|&b| b == b'\n' || b == b'>')
+            .map_or(0, |i| i + 1)
+    }
+
+    /// Drop the first `len` bytes of output once they have been written
+    /// elsewhere.
+    #[cfg(feature = "std")]
+    fn discard_front(&mut self, len: usize) {
+        self.out.drain(..len);
+        // Only compared with the output length, which stays above `len`
+        self.open_tag_end = self.open_tag_end.saturating_sub(len);
+        // The rest are at or after `len`, see `write_final`
+        for tag_start in self.events.list_tags.iter_mut().flatten() {
+            *tag_start -= len;
+        }
+        if let Some((start, content_start)) = self.events.cell_content.as_mut() {
+            *start -= len;
+            *content_start -= len;
+        }
+    }
+
+    // --- Pretty-Printing ---
+
+    /// Indent a block tag that starts a new line (pretty mode only).
+    #[inline]
+    fn block_indent(&mut self) {
+        if let Some(config) = self.pretty {
+            self.write_indent(config);
+        }
+    }
+
+    #[cold]
+    fn write_indent(&mut self, config: PrettyConfig) {
+        if self.out.last().is_none_or(|&b| b == b'\n') {
+            let width = self.depth as usize * config.indent_width as usize;
+            self.out.resize(self.out.len() + width, b' ');
+        }
+    }
+
+    /// Indent and enter a block container.
+    #[inline]
+    fn enter_block(&mut self) {
+        if let Some(config) = self.pretty {
+            self.write_indent(config);
+            self.depth += 1;
+        }
+    }
+
+    /// Leave a block container and indent its closing tag.
+    #[inline]
+    fn leave_block(&mut self) {
+        if let Some(config) = self.pretty {
+            self.depth = self.depth.saturating_sub(1);
+            if config.wrap_block_tags
+                && self.out.len() != self.open_tag_end
+                && self.out.last() != Some(&b'\n')
+            {
+                self.out.push(b'\n');
+            }
+            self.write_indent(config);
+        }
+    }
+
+    /// Start inline content directly inside a tight list item or table cell.
+    ///
+    /// With [`PrettyConfig::wrap_block_tags`] the content moves to its own
+    /// indented line; otherwise this writes nothing.
+    #[inline]
+    pub fn wrapped_inline_start(&mut self) {
+        if let Some(config) = self.pretty.filter(|config| config.wrap_block_tags) {
+            if self.out.last().is_some_and(|&b| b != b'\n') {
+                self.out.push(b'\n');
+            }
+            self.write_indent(config);
+        }
+    }
+
+    /// Write a raw opening tag of a block container, e.g. `<section ...>\n`.
+    #[inline]
+    pub(crate) fn block_open_raw(&mut self, html: &'static str) {
+        self.enter_block();
+        self.write_str(html);
+    }
+
+    /// Write a raw closing tag of a block container, e.g. `</section>\n`.
+    #[inline]
+    pub(crate) fn block_close_raw(&mut self, html: &'static str) {
+        self.leave_block();
+        self.write_str(html);
+    }
+
     // --- HTML Tag Helpers ---
 
     /// Write opening tag: `<tagname>`
@@ -401,7 +700,13 @@ impl HtmlWriter {
     pub fn self_closing_tag(&mut self, tag: &'static str) {
         self.write_byte(b'<');
         self.write_str(tag);
-        self.write_str(" />");
+        self.write_void_end();
+    }
+
+    /// Close a void element tag: ` />`, or `>` with XHTML style off.
+    #[inline]
+    pub fn write_void_end(&mut self) {
+        self.write_str(if self.xhtml { " />" } else { ">" });
     }
 
     /// Write opening tag with newline: `<tagname>\n`
@@ -423,6 +728,7 @@ impl HtmlWriter {
     /// Write paragraph start: `<p>`
     #[inline]
     pub fn paragraph_start(&mut self) {
+        self.block_indent();
         self.write_str("<p>");
     }
 
@@ -432,10 +738,22 @@ impl HtmlWriter {
         self.write_str("</p>\n");
     }
 
+    /// Replace a just-opened `<p>` with a display math block:
+    /// `<div class="math display">...</div>\n`
+    #[inline]
+    pub(crate) fn paragraph_to_math_block(&mut self, content: &[u8]) {
+        debug_assert!(self.out.ends_with(b"<p>"));
+        self.out.truncate(self.out.len() - 3);
+        self.write_str("<div class=\"math display\">");
+        self.write_escaped_text(content);
+        self.write_str("</div>\n");
+    }
+
     /// Write heading start: `<hN>`
     #[inline]
     pub fn heading_start(&mut self, level: u8) {
         debug_assert!((1..=6).contains(&level));
+        self.block_indent();
         self.write_str("<h");
         self.write_byte(b'0' + level);
         self.write_byte(b'>');
@@ -445,6 +763,7 @@ impl HtmlWriter {
     #[inline]
     pub fn heading_start_with_id(&mut self, level: u8, id: &str) {
         debug_assert!((1..=6).contains(&level));
+        self.block_indent();
         self.write_str("<h");
         self.write_byte(b'0' + level);
         self.write_str(" id=\"");
@@ -452,6 +771,39 @@ impl HtmlWriter {
         self.write_str("\">");
     }
 
+    /// Write heading start with an attribute list (`{#id .class key=value}`):
+    /// its `#id` replaces `id`, its classes form the `class` attribute, and
+    /// other keys become `data-` attributes.
+    ///
+    /// `# Title {#intro .lead}` becomes `<h1 id="intro" class="lead">`.
+    pub fn heading_start_with_attributes(
+        &mut self,
+        level: u8,
+        id: Option<&str>,
+        attributes: &[u8],
+    ) {
+        debug_assert!((1..=6).contains(&level));
+        let attributes = attribute_list(attributes).unwrap_or_default();
+        self.block_indent();
+        self.write_str("<h");
+        self.write_byte(b'0' + level);
+        match attribute_id(&attributes) {
+            Some(id) => self.write_id_attr(id),
+            None => {
+                if let Some(id) = id {
+                    self.write_id_attr(id.as_bytes());
+                }
+            }
+        }
+        if attributes.iter().any(|a| matches!(a, Attribute::Class(_))) {
+            self.write_str(" class=\"");
+            self.write_attribute_classes(&attributes);
+            self.write_byte(b'"');
+        }
+        self.write_data_attrs(&attributes);
+        self.write_byte(b'>');
+    }
+
     /// Write heading end: `</hN>\n`
     #[inline]
     pub fn heading_end(&mut self, level: u8) {
@@ -465,22 +817,71 @@ impl HtmlWriter {
     /// Processes backslash escapes in the language string.
     #[inline]
     pub fn code_block_start(&mut self, lang: Option<&[u8]>) {
-        match lang {
-            Some(l) if !l.is_empty() => {
-                let first = Self::first_word(l);
-                if first.is_empty() {
-                    self.write_str("<pre><code>");
-                    return;
-                }
-                self.write_str("<pre><code class=\"language-");
-                // Decode entities and escape for attribute
-                self.write_info_string_attr(first);
-                self.write_str("\">");
-            }
-            _ => {
-                self.write_str("<pre><code>");
+        self.write_code_block_start(lang, false, None);
+    }
+
+    /// Write code block start like [`code_block_start`](Self::code_block_start),
+    /// exposing the info string after the language word as `data-meta`.
+    ///
+    /// ` ```rust title="main.rs" ` becomes
+    /// `<pre><code class="language-rust" data-meta="title=&quot;main.rs&quot;">`.
+    #[inline]
+    pub fn code_block_start_with_meta(&mut self, info: Option<&[u8]>) {
+        self.write_code_block_start(info, true, None);
+    }
+
+    /// Write code block start with an attribute list (`{#id .class key=value}`)
+    /// from the end of the info string: the id and `data-` attributes go on
+    /// `<pre>`, and the classes follow the language class on `<code>`.
+    /// `with_meta` is as in [`code_block_start_with_meta`](Self::code_block_start_with_meta).
+    ///
+    /// ` ```rust {#main .numbered} ` becomes
+    /// `<pre id="main"><code class="language-rust numbered">`.
+    #[inline]
+    pub fn code_block_start_with_attributes(
+        &mut self,
+        info: Option<&[u8]>,
+        with_meta: bool,
+        attributes: &[u8],
+    ) {
+        self.write_code_block_start(info, with_meta, Some(attributes));
+    }
+
+    fn write_code_block_start(
+        &mut self,
+        info: Option<&[u8]>,
+        with_meta: bool,
+        attributes: Option<&[u8]>,
+    ) {
+        self.block_indent();
+        let attributes = attributes.and_then(attribute_list).unwrap_or_default();
+        self.write_str("<pre");
+        if let Some(id) = attribute_id(&attributes) {
+            self.write_id_attr(id);
+        }
+        self.write_data_attrs(&attributes);
+        self.write_str("><code");
+        let language = info.map(Self::split_info).filter(|(w, _)| !w.is_empty());
+        let has_classes = attributes.iter().any(|a| matches!(a, Attribute::Class(_)));
+        if language.is_none() && !has_classes {
+            self.write_byte(b'>');
+            return;
+        }
+        self.write_str(" class=\"");
+        if let Some((first, _)) = language {
+            self.write_str("language-");
+            // Decode entities and escape for attribute
+            self.write_info_string_attr(first);
+            if has_classes {
+                self.write_byte(b' ');
             }
         }
+        self.write_attribute_classes(&attributes);
+        if let Some(meta) = language.and_then(|(_, meta)| meta).filter(|_| with_meta) {
+            self.write_str("\" data-meta=\"");
+            self.write_info_string_attr(meta);
+        }
+        self.write_str("\">");
     }
 
     /// Write fenced code info string with entity decoding and attribute escaping.
@@ -503,6 +904,14 @@ impl HtmlWriter {
         decode_entities_commonmark(info_str).into_owned()
     }
 
+    /// Decode the info string after the language word, if any.
+    pub(crate) fn decode_info_meta(info: &[u8]) -> Option<String> {
+        let (_, meta) = Self::split_info(info);
+        let unescaped = Self::unescape_backslashes(meta?);
+        let info_str = core::str::from_utf8(&unescaped).unwrap_or("");
+        Some(decode_entities_commonmark(info_str).into_owned())
+    }
+
     fn unescape_backslashes(input: &[u8]) -> Vec<u8> {
         let mut out = Vec::with_capacity(input.len());
         let mut i = 0usize;
@@ -569,6 +978,20 @@ impl HtmlWriter {
         &info[..end]
     }
 
+    /// Split an info string into its first word and the trimmed remainder.
+    fn split_info(info: &[u8]) -> (&[u8], Option<&[u8]>) {
+        let first = Self::first_word(info);
+        let mut start = first.len();
+        let mut end = info.len();
+        while start < end && Self::is_html_whitespace(info[start]) {
+            start += 1;
+        }
+        while end > start && Self::is_html_whitespace(info[end - 1]) {
+            end -= 1;
+        }
+        (first, (start < end).then(|| &info[start..end]))
+    }
+
     /// Write code block end: `</code></pre>\n`
     #[inline]
     pub fn code_block_end(&mut self) {
@@ -578,52 +1001,227 @@ impl HtmlWriter {
     /// Write thematic break: `<hr />\n`
     #[inline]
     pub fn thematic_break(&mut self) {
-        self.write_str("<hr />\n");
+        self.block_indent();
+        self.write_str("<hr");
+        self.write_void_end();
+        self.write_byte(b'\n');
     }
 
     /// Write blockquote start: `<blockquote>\n`
     #[inline]
     pub fn blockquote_start(&mut self) {
+        self.enter_block();
         self.write_str("<blockquote>\n");
     }
 
+    /// Add a `cite` attribute to the `<blockquote>` tag just written.
+    ///
+    /// Does nothing unless the tag is the last output, or when `policy`
+    /// rejects the URL.
+    pub fn blockquote_cite(&mut self, url: &[u8], policy: RenderPolicy) {
+        if !self.out.ends_with(b"<blockquote>\n")
+            || (policy == RenderPolicy::Untrusted && !is_safe_url(url))
+        {
+            return;
+        }
+        self.out.truncate(self.out.len() - 2);
+        self.write_str(" cite=\"");
+        self.write_link_url(url);
+        self.write_str("\">\n");
+    }
+
     /// Write blockquote end: `</blockquote>\n`
     #[inline]
     pub fn blockquote_end(&mut self) {
+        self.leave_block();
         self.write_str("</blockquote>\n");
     }
 
+    /// Write a fenced div start: `<div>` with the attributes in `class`, a
+    /// class name or an attribute list (`{#id .class key="value"}`). Keys
+    /// other than the id and classes are written as `data-` attributes.
+    pub fn div_start(&mut self, class: Option<&[u8]>) {
+        self.enter_block();
+        self.write_str("<div");
+        let attributes = class.and_then(div_attributes).unwrap_or_default();
+        if let Some(id) = attribute_id(&attributes) {
+            self.write_id_attr(id);
+        }
+        if attributes.iter().any(|a| matches!(a, Attribute::Class(_))) {
+            self.write_str(" class=\"");
+            self.write_attribute_classes(&attributes);
+            self.write_byte(b'"');
+        }
+        self.write_data_attrs(&attributes);
+        self.write_str(">\n");
+    }
+
+    /// Write ` id="..."`, escaped.
+    fn write_id_attr(&mut self, id: &[u8]) {
+        self.write_str(" id=\"");
+        self.write_escaped_attr(id);
+        self.write_byte(b'"');
+    }
+
+    /// Write the classes of an attribute list, escaped and space-separated.
+    fn write_attribute_classes(&mut self, attributes: &[Attribute<'_>]) {
+        let classes = attributes.iter().filter_map(|attribute| match attribute {
+            Attribute::Class(class) => Some(class),
+            _ => None,
+        });
+        for (i, class) in classes.enumerate() {
+            if i > 0 {
+                self.write_byte(b' ');
+            }
+            self.write_escaped_attr(class);
+        }
+    }
+
+    /// Write the `key=value` pairs of an attribute list as `data-`
+    /// attributes.
+    fn write_data_attrs(&mut self, attributes: &[Attribute<'_>]) {
+        for attribute in attributes {
+            if let Attribute::Pair(key, value) = attribute {
+                self.write_str(" data-");
+                self.write_escaped_attr(key);
+                self.write_str("=\"");
+                self.write_escaped_attr(value);
+                self.write_byte(b'"');
+            }
+        }
+    }
+
+    /// Write fenced div end: `</div>\n`
+    #[inline]
+    pub fn div_end(&mut self) {
+        self.leave_block();
+        self.write_str("</div>\n");
+    }
+
     /// Write callout/admonition start.
     #[inline]
     pub fn callout_start(&mut self, callout: crate::block::CalloutType) {
+        self.write_callout_start(callout, None);
+    }
+
+    /// Write callout/admonition start with its ARIA `role` attribute.
+    ///
+    /// See [`CalloutType::aria_role`](crate::block::CalloutType::aria_role).
+    #[inline]
+    pub fn callout_start_with_role(&mut self, callout: crate::block::CalloutType) {
+        self.write_callout_start(callout, Some(callout.aria_role()));
+    }
+
+    fn write_callout_start(
+        &mut self,
+        callout: crate::block::CalloutType,
+        role: Option<&'static str>,
+    ) {
+        self.callout_start_untitled(callout, role);
+        self.callout_title_start();
+        self.write_str(callout.title());
+        self.callout_title_end();
+    }
+
+    /// Write callout/admonition start without its title, and with an
+    /// optional ARIA `role`. Write a title with
+    /// [`callout_title_start`](Self::callout_title_start).
+    #[inline]
+    pub fn callout_start_untitled(
+        &mut self,
+        callout: crate::block::CalloutType,
+        role: Option<&'static str>,
+    ) {
+        self.enter_block();
         self.write_str("<div class=\"markdown-alert markdown-alert-");
         self.write_str(callout.css_suffix());
-        self.write_str("\">\n<p class=\"markdown-alert-title\">");
-        self.write_str(callout.title());
+        if let Some(role) = role {
+            self.write_str("\" role=\"");
+            self.write_str(role);
+        }
+        self.write_str("\">\n");
+    }
+
+    /// Write callout title start: `<p class="markdown-alert-title">`. The
+    /// caller writes the title, then calls
+    /// [`callout_title_end`](Self::callout_title_end).
+    #[inline]
+    pub fn callout_title_start(&mut self) {
+        self.block_indent();
+        self.write_str("<p class=\"markdown-alert-title\">");
+    }
+
+    /// Write callout title end: `</p>\n`
+    #[inline]
+    pub fn callout_title_end(&mut self) {
         self.write_str("</p>\n");
     }
 
+    /// Write collapsible callout start: `<details ...>` and the opening
+    /// `<summary>` tag. The caller writes the title, then calls
+    /// [`collapsible_callout_title_end`](Self::collapsible_callout_title_end).
+    #[inline]
+    pub fn collapsible_callout_start(
+        &mut self,
+        callout: crate::block::CalloutType,
+        expanded: bool,
+        role: Option<&'static str>,
+    ) {
+        self.enter_block();
+        self.write_str("<details class=\"markdown-alert markdown-alert-");
+        self.write_str(callout.css_suffix());
+        self.write_byte(b'"');
+        if let Some(role) = role {
+            self.write_str(" role=\"");
+            self.write_str(role);
+            self.write_byte(b'"');
+        }
+        if expanded {
+            self.write_str(" open");
+        }
+        self.write_str(">\n");
+        self.block_indent();
+        self.write_str("<summary class=\"markdown-alert-title\">");
+    }
+
+    /// Write collapsible callout title end: `</summary>\n`
+    #[inline]
+    pub fn collapsible_callout_title_end(&mut self) {
+        self.write_str("</summary>\n");
+    }
+
+    /// Write collapsible callout end: `</details>\n`
+    #[inline]
+    pub fn collapsible_callout_end(&mut self) {
+        self.leave_block();
+        self.write_str("</details>\n");
+    }
+
     /// Write callout/admonition end.
     #[inline]
     pub fn callout_end(&mut self) {
+        self.leave_block();
         self.write_str("</div>\n");
     }
 
     /// Write list start (unordered): `<ul>\n`
     #[inline]
     pub fn ul_start(&mut self) {
+        self.enter_block();
         self.write_str("<ul>\n");
     }
 
     /// Write list end (unordered): `</ul>\n`
     #[inline]
     pub fn ul_end(&mut self) {
+        self.leave_block();
         self.write_str("</ul>\n");
     }
 
     /// Write list start (ordered): `<ol>\n` or `<ol start="N">\n`
     #[inline]
     pub fn ol_start(&mut self, start: Option<u32>) {
+        self.enter_block();
         match start {
             Some(n) if n != 1 => {
                 self.write_str("<ol start=\"");
@@ -639,18 +1237,57 @@ impl HtmlWriter {
     /// Write list end (ordered): `</ol>\n`
     #[inline]
     pub fn ol_end(&mut self) {
+        self.leave_block();
         self.write_str("</ol>\n");
     }
 
+    /// Drop the newline after a directly preceding `</li>` in compact mode.
+    #[inline]
+    fn join_list_items(&mut self) {
+        if self.compact_list_items && self.out.ends_with(b"</li>\n") {
+            self.out.pop();
+        }
+    }
+
     /// Write list item start: `<li>`
     #[inline]
     pub fn li_start(&mut self) {
+        self.join_list_items();
+        self.enter_block();
         self.write_str("<li>");
+        self.open_tag_end = self.out.len();
+    }
+
+    /// Write task list item start: `<li class="task-list-item">`
+    #[inline]
+    pub fn task_li_start(&mut self) {
+        self.join_list_items();
+        self.enter_block();
+        self.write_str("<li class=\"task-list-item\">");
+        self.open_tag_end = self.out.len();
+    }
+
+    /// Add a `class` attribute to an open tag already written at `tag_start`.
+    ///
+    /// Used when a list only learns it holds task items after `<ul>` or
+    /// `<ol>` was written. Everything after the tag shifts by the inserted
+    /// length.
+    pub fn add_class_to_tag(&mut self, tag_start: usize, class: &str) {
+        let Some(offset) = self.out[tag_start..].iter().position(|&b| b == b'>') else {
+            return;
+        };
+        let at = tag_start + offset;
+        let attr = [b" class=\"", class.as_bytes(), b"\""].concat();
+        self.out.splice(at..at, attr.iter().copied());
+        if self.open_tag_end > at {
+            self.open_tag_end += attr.len();
+        }
     }
 
     /// Write list item end: `</li>\n`
     #[inline]
     pub fn li_end(&mut self) {
+        self.leave_block();
         self.write_str("</li>\n");
     }
 
@@ -659,82 +1296,106 @@ impl HtmlWriter {
     /// Write table start: `<table>\n`
     #[inline]
     pub fn table_start(&mut self) {
+        self.enter_block();
         self.write_str("<table>\n");
     }
 
     /// Write table end: `</table>\n`
     #[inline]
     pub fn table_end(&mut self) {
+        self.leave_block();
         self.write_str("</table>\n");
     }
 
     /// Write thead start: `<thead>\n`
     #[inline]
     pub fn thead_start(&mut self) {
+        self.enter_block();
         self.write_str("<thead>\n");
     }
 
     /// Write thead end: `</thead>\n`
     #[inline]
     pub fn thead_end(&mut self) {
+        self.leave_block();
         self.write_str("</thead>\n");
     }
 
     /// Write tbody start: `<tbody>\n`
     #[inline]
     pub fn tbody_start(&mut self) {
+        self.enter_block();
         self.write_str("<tbody>\n");
     }
 
     /// Write tbody end: `</tbody>\n`
     #[inline]
     pub fn tbody_end(&mut self) {
+        self.leave_block();
         self.write_str("</tbody>\n");
     }
 
     /// Write tr start: `<tr>\n`
     #[inline]
     pub fn tr_start(&mut self) {
+        self.enter_block();
         self.write_str("<tr>\n");
     }
 
     /// Write tr end: `</tr>\n`
     #[inline]
     pub fn tr_end(&mut self) {
+        self.leave_block();
         self.write_str("</tr>\n");
     }
 
-    /// Write th start with optional alignment: `<th>` or `<th align="...">`
+    /// Write th start with optional alignment and span: `<th>`,
+    /// `<th align="...">`, or `<th colspan="n">`
     #[inline]
-    pub fn th_start(&mut self, align: crate::block::Alignment) {
+    pub fn th_start(&mut self, align: crate::block::Alignment, colspan: u16) {
+        self.enter_block();
+        self.write_str("<th");
+        self.write_cell_attrs(align, colspan);
+    }
+
+    /// Write the alignment and `colspan` (when above 1) of a table cell and
+    /// close its start tag.
+    fn write_cell_attrs(&mut self, align: crate::block::Alignment, colspan: u16) {
         match align {
-            crate::block::Alignment::None => self.write_str("<th>"),
-            crate::block::Alignment::Left => self.write_str("<th align=\"left\">"),
-            crate::block::Alignment::Center => self.write_str("<th align=\"center\">"),
-            crate::block::Alignment::Right => self.write_str("<th align=\"right\">"),
+            crate::block::Alignment::None => {}
+            crate::block::Alignment::Left => self.write_str(" align=\"left\""),
+            crate::block::Alignment::Center => self.write_str(" align=\"center\""),
+            crate::block::Alignment::Right => self.write_str(" align=\"right\""),
         }
+        if colspan > 1 {
+            self.write_str(" colspan=\"");
+            self.write_u32(u32::from(colspan));
+            self.write_str("\"");
+        }
+        self.write_str(">");
+        self.open_tag_end = self.out.len();
     }
 
     /// Write th end: `</th>\n`
     #[inline]
     pub fn th_end(&mut self) {
+        self.leave_block();
         self.write_str("</th>\n");
     }
 
-    /// Write td start with optional alignment: `<td>` or `<td align="...">`
+    /// Write td start with optional alignment and span: `<td>`,
+    /// `<td align="...">`, or `<td colspan="n">`
     #[inline]
-    pub fn td_start(&mut self, align: crate::block::Alignment) {
-        match align {
-            crate::block::Alignment::None => self.write_str("<td>"),
-            crate::block::Alignment::Left => self.write_str("<td align=\"left\">"),
-            crate::block::Alignment::Center => self.write_str("<td align=\"center\">"),
-            crate::block::Alignment::Right => self.write_str("<td align=\"right\">"),
-        }
+    pub fn td_start(&mut self, align: crate::block::Alignment, colspan: u16) {
+        self.enter_block();
+        self.write_str("<td");
+        self.write_cell_attrs(align, colspan);
     }
 
     /// Write td end: `</td>\n`
     #[inline]
     pub fn td_end(&mut self) {
+        self.leave_block();
         self.write_str("</td>\n");
     }
 
@@ -803,7 +1464,9 @@ impl HtmlWriter {
     /// Write line break: `<br />\n`
     #[inline]
     pub fn line_break(&mut self) {
-        self.write_str("<br />\n");
+        self.write_str("<br");
+        self.write_void_end();
+        self.write_byte(b'\n');
     }
 
     /// Write raw HTML with GFM disallowed-tag filtering.
@@ -829,6 +1492,22 @@ impl HtmlWriter {
         }
     }
 
+    /// Write raw HTML keeping only the tags, attributes and URL protocols
+    /// GitHub allows (see [`Sanitizer::GitHubAllowlist`](crate::Sanitizer::GitHubAllowlist)).
+    ///
+    /// A tag cut off at the end of `html` continues in the next call, so the
+    /// lines of an HTML block can be written one by one. Call
+    /// [`Self::finish_html_allowlisted`] after the last one.
+    pub fn write_html_allowlisted(&mut self, html: &[u8]) {
+        self.html_allowlist.write(&mut self.out, html);
+    }
+
+    /// End raw HTML written with [`Self::write_html_allowlisted`], escaping a
+    /// tag left unfinished.
+    pub fn finish_html_allowlisted(&mut self) {
+        self.html_allowlist.finish(&mut self.out);
+    }
+
     /// Write a u32 as decimal.
     fn write_u32(&mut self, mut n: u32) {
         if n == 0 {
@@ -925,14 +1604,986 @@ fn is_link_escapable(b: u8) -> bool {
     )
 }
 
+/// Bytes at the end of the output that later writes may still inspect or
+/// rewrite, such as `<blockquote>\n` before a cite URL or `<p>` before
+/// display math.
+#[cfg(feature = "std")]
+const REWRITABLE_TAIL: usize = 16;
+
+/// State of the [`Renderer`] implementation of [`HtmlWriter`] between events.
+#[derive(Default)]
+struct EventState {
+    /// Options of the document, from [`Renderer::document_start`].
+    options: Options,
+    /// Tightness of each open list.
+    tight_lists: Vec<bool>,
+    /// Output offset of each open list tag that may still get its task list
+    /// class.
+    list_tags: Vec<Option<usize>>,
+    /// A tight list item just started, so block content needs a newline.
+    at_tight_li_start: bool,
+    /// A paragraph of a tight list item ended without `</p>`.
+    need_newline_before_block: bool,
+    /// A loose list item started; its newline waits for content.
+    pending_loose_li_newline: bool,
+    in_table_head: bool,
+    /// Output offsets before and after the start of the open cell's content,
+    /// to take back the line break of a pretty-printed empty cell.
+    cell_content: Option<(usize, usize)>,
+    /// Checkbox of the current task list item, written before its text.
+    pending_task: TaskState,
+    /// A callout title element is open.
+    callout_title: bool,
+    /// Nesting depth of images, whose descriptions are written as alt text.
+    image_depth: u32,
+    /// Title of the outermost open image.
+    image_title: Option<Vec<u8>>,
+    /// Attribute list of the heading or code block about to start.
+    pending_attributes: Option<Vec<u8>>,
+    /// Fenced code blocks are collected into `fenced_code` instead of written.
+    collect_fenced_code: bool,
+    fenced_code: Option<FencedCode>,
+}
+
+/// A fenced code block collected for a
+/// [`FencedCodeRenderer`](crate::FencedCodeRenderer).
+pub(crate) struct FencedCode {
+    pub(crate) info: Option<Vec<u8>>,
+    attributes: Option<Vec<u8>>,
+    pub(crate) code: Vec<u8>,
+    /// The block has ended.
+    complete: bool,
+}
+
+impl HtmlWriter {
+    /// Collect fenced code blocks for [`Self::take_fenced_code`] instead of
+    /// writing them.
+    pub(crate) fn collect_fenced_code(&mut self) {
+        self.events.collect_fenced_code = true;
+    }
+
+    /// The collected fenced code block that just ended, if any.
+    pub(crate) fn take_fenced_code(&mut self) -> Option<FencedCode> {
+        if self.events.fenced_code.as_ref()?.complete {
+            self.events.fenced_code.take()
+        } else {
+            None
+        }
+    }
+
+    /// Write a collected fenced code block as if it had not been collected.
+    pub(crate) fn write_fenced_code(&mut self, block: &FencedCode) {
+        self.fenced_code_start(block.info.as_deref(), block.attributes.as_deref());
+        self.write_escaped_text(&block.code);
+        HtmlWriter::code_block_end(self);
+    }
+
+    /// Write the output later events can no longer change to `out` and drop
+    /// it. A list tag that may still get its task list class, and an open
+    /// table cell, hold back everything after them.
+    #[cfg(feature = "std")]
+    pub(crate) fn write_final<W: std::io::Write + ?Sized>(
+        &mut self,
+        out: &mut W,
+    ) -> std::io::Result<()> {
+        let events = &self.events;
+        let limit = events
+            .list_tags
+            .iter()
+            .flatten()
+            .copied()
+            .chain(events.cell_content.map(|(start, _)| start))
+            .min();
+        let len = self.final_len(limit.unwrap_or(usize::MAX));
+        out.write_all(&self.out[..len])?;
+        self.discard_front(len);
+        Ok(())
+    }
+
+    /// Write the newlines pending before a block container in a list item.
+    fn events_block_start(&mut self) {
+        if core::mem::take(&mut self.events.pending_loose_li_newline) {
+            self.newline();
+        }
+        if core::mem::take(&mut self.events.need_newline_before_block) {
+            self.newline();
+        }
+        if core::mem::take(&mut self.events.at_tight_li_start) {
+            self.newline();
+        }
+    }
+
+    fn in_image(&self) -> bool {
+        self.events.image_depth > 0
+    }
+
+    /// Write a tag, unless in image alt text.
+    fn events_tag(&mut self, tag: &'static str) {
+        if !self.in_image() {
+            self.write_str(tag);
+        }
+    }
+
+    /// Write code span or math content with line endings as spaces.
+    fn write_code_text(&mut self, code: &[u8]) {
+        for &b in code {
+            match b {
+                b'\n' => self.write_str(" "),
+                b'<' => self.write_str("&lt;"),
+                b'>' => self.write_str("&gt;"),
+                b'&' => self.write_str("&amp;"),
+                b'"' => self.write_str("&quot;"),
+                _ => self.out.push(b),
+            }
+        }
+    }
+
+    /// Write a fenced code block start, with `data-meta` when
+    /// `code_info_meta` is set.
+    fn fenced_code_start(&mut self, info: Option<&[u8]>, attributes: Option<&[u8]>) {
+        let code_info_meta = self.events.options.code_info_meta;
+        if let Some(attributes) = attributes {
+            self.code_block_start_with_attributes(info, code_info_meta, attributes);
+        } else if code_info_meta {
+            self.code_block_start_with_meta(info);
+        } else {
+            HtmlWriter::code_block_start(self, info);
+        }
+    }
+
+    /// Write trusted raw HTML through the [`Sanitizer`] in effect.
+    fn write_raw_html(&mut self, html: &[u8]) {
+        match self.events.options.raw_html_sanitizer() {
+            Sanitizer::None => self.write_bytes(html),
+            Sanitizer::GitHubBlacklist => self.write_html_filtered(html),
+            Sanitizer::GitHubAllowlist => self.write_html_allowlisted(html),
+        }
+    }
+
+    /// Write the `rel` and `target` attributes for a link to `url`: `ugc
+    /// nofollow` for user-generated content, plus
+    /// [`Options::external_links`].
+    fn write_link_rel(&mut self, url: &[u8]) {
+        let external = self
+            .events
+            .options
+            .external_links
+            .filter(|external| external.is_external(url));
+        self.write_link_attrs(external);
+    }
+
+    fn write_link_attrs(&mut self, external: Option<ExternalLinks>) {
+        let untrusted_content = self.events.options.untrusted_content;
+        let external_rel = external.and_then(|external| external.rel);
+        if untrusted_content || external_rel.is_some() {
+            self.write_str(" rel=\"");
+            if untrusted_content {
+                self.write_str("ugc nofollow");
+                if external_rel.is_some() {
+                    self.write_str(" ");
+                }
+            }
+            if let Some(rel) = external_rel {
+                self.write_escaped_attr(rel.as_bytes());
+            }
+            self.write_str("\"");
+        }
+        if let Some(target) = external.and_then(|external| external.target) {
+            self.write_str(" target=\"");
+            self.write_escaped_attr(target.as_bytes());
+            self.write_str("\"");
+        }
+    }
+
+    fn events_link_start(&mut self, url: &[u8], title: Option<&[u8]>) {
+        if self.in_image() {
+            return;
+        }
+        self.write_str("<a href=\"");
+        self.write_link_url_with_policy(url, self.events.options.render_policy);
+        self.write_str("\"");
+        if let Some(title) = title {
+            self.write_str(" title=\"");
+            self.write_link_title(title);
+            self.write_str("\"");
+        }
+        self.write_link_rel(url);
+        self.write_str(">");
+    }
+
+    fn events_image_start(&mut self, url: &[u8], title: Option<&[u8]>) {
+        self.events.image_depth += 1;
+        if self.events.image_depth == 1 {
+            self.write_str("<img src=\"");
+            self.write_image_src(url);
+            self.write_str(" alt=\"");
+            self.events.image_title = title.map(<[u8]>::to_vec);
+        }
+    }
+
+    /// Write an image `src` value and its closing quote, followed by any
+    /// `srcset` and `sizes` from [`Options::image_src_transform`].
+    fn write_image_src(&mut self, url: &[u8]) {
+        let policy = self.events.options.render_policy;
+        let Some(transform) = self.events.options.image_src_transform else {
+            self.write_link_url_with_policy(url, policy);
+            self.write_str("\"");
+            return;
+        };
+        let src = decode_link_text(url);
+        if src
+            .get(..5)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("data:"))
+        {
+            self.write_link_url_with_policy(url, policy);
+            self.write_str("\"");
+            return;
+        }
+        let attrs = (transform.0)(&src);
+        self.write_url_encoded_with_policy(attrs.src.as_bytes(), policy);
+        self.write_str("\"");
+        for (name, value) in [(" srcset=\"", &attrs.srcset), (" sizes=\"", &attrs.sizes)] {
+            if let Some(value) = value {
+                self.write_str(name);
+                self.write_escaped_attr(value.as_bytes());
+                self.write_str("\"");
+            }
+        }
+    }
+
+    /// Write a footnote anchor id: [`Options::footnote_id_prefix`], `kind`
+    /// (`fn-` or `fnref-`), then the label, or the number with
+    /// [`Options::numeric_footnote_ids`].
+    fn write_footnote_anchor(&mut self, kind: &'static str, label: &str, number: usize) {
+        let options = &self.events.options;
+        let (prefix, numeric) = (options.footnote_id_prefix, options.numeric_footnote_ids);
+        self.write_escaped_attr(prefix.as_bytes());
+        self.write_str(kind);
+        if numeric {
+            self.write_string(&number.to_string());
+        } else {
+            self.write_string(label);
+        }
+    }
+
+    /// Write `-<n>` for every reference to a footnote after the first.
+    fn write_footnote_reference_suffix(&mut self, reference: usize) {
+        if reference > 1 {
+            self.write_str("-");
+            self.write_string(&reference.to_string());
+        }
+    }
+}
+
+/// Run a [`CodeSpanRenderer`] on code span content, with line endings
+/// already turned into spaces.
+fn render_code_span_with(renderer: CodeSpanRenderer, content: &[u8]) -> Option<String> {
+    let content = core::str::from_utf8(content).ok()?;
+    if content.contains('\n') {
+        (renderer.0)(&content.replace('\n', " "))
+    } else {
+        (renderer.0)(content)
+    }
+}
+
+/// The HTML of [`to_html_with_options`](crate::to_html_with_options), which
+/// renders through this implementation, for the options passed to
+/// [`Renderer::document_start`]. They also set the writer's pretty-printing,
+/// compact list items and XHTML output.
+impl Renderer for HtmlWriter {
+    fn document_start(&mut self, options: &Options) {
+        self.set_pretty(options.pretty);
+        self.set_compact_list_items(options.compact_list_items);
+        self.set_xhtml(options.xhtml);
+        self.events.options = *options;
+    }
+
+    fn paragraph_start(&mut self, tight: bool) {
+        if core::mem::take(&mut self.events.pending_loose_li_newline) {
+            self.newline();
+        }
+        self.events.at_tight_li_start = false;
+        if tight {
+            self.wrapped_inline_start();
+        } else {
+            HtmlWriter::paragraph_start(self);
+        }
+        let checked = match core::mem::take(&mut self.events.pending_task) {
+            TaskState::None => return,
+            TaskState::Unchecked => false,
+            TaskState::Checked => true,
+        };
+        self.write_str("<input type=\"checkbox\"");
+        if self.events.options.task_list_classes {
+            self.write_str(" class=\"task-list-item-checkbox\"");
+        }
+        if checked {
+            self.write_str(" checked=\"\"");
+        }
+        if !self.events.options.task_list_interactive {
+            self.write_str(" disabled=\"\"");
+        }
+        self.write_void_end();
+        self.write_byte(b' ');
+    }
+
+    fn paragraph_end(&mut self, tight: bool) {
+        if tight {
+            self.events.need_newline_before_block = true;
+        } else {
+            HtmlWriter::paragraph_end(self);
+        }
+    }
+
+    fn math_block(&mut self, content: &[u8], range: Range) {
+        if core::mem::take(&mut self.events.pending_loose_li_newline) {
+            self.newline();
+        }
+        self.events.at_tight_li_start = false;
+        HtmlWriter::paragraph_start(self);
+        self.paragraph_to_math_block(range.slice(content));
+    }
+
+    fn heading_start(&mut self, level: u8, id: Option<&str>) {
+        if core::mem::take(&mut self.events.need_newline_before_block) {
+            self.newline();
+        }
+        if core::mem::take(&mut self.events.at_tight_li_start) {
+            self.newline();
+        }
+        match (self.events.pending_attributes.take(), id) {
+            (Some(attributes), id) => self.heading_start_with_attributes(level, id, &attributes),
+            (None, Some(id)) => self.heading_start_with_id(level, id),
+            (None, None) => HtmlWriter::heading_start(self, level),
+        }
+    }
+
+    fn heading_end(&mut self, level: u8) {
+        HtmlWriter::heading_end(self, level);
+    }
+
+    fn block_attributes(&mut self, input: &[u8], attributes: Range) {
+        self.events.pending_attributes = Some(attributes.slice(input).to_vec());
+    }
+
+    fn code_block_start(&mut self, input: &[u8], kind: CodeBlockKind) {
+        if core::mem::take(&mut self.events.pending_loose_li_newline) {
+            self.newline();
+        }
+        if core::mem::take(&mut self.events.at_tight_li_start) {
+            self.newline();
+        }
+        match kind {
+            CodeBlockKind::Fenced { info } if self.events.collect_fenced_code => {
+                self.events.fenced_code = Some(FencedCode {
+                    info: info.map(|info| info.slice(input).to_vec()),
+                    attributes: self.events.pending_attributes.take(),
+                    code: Vec::new(),
+                    complete: false,
+                });
+            }
+            CodeBlockKind::Fenced { info } => {
+                let attributes = self.events.pending_attributes.take();
+                self.fenced_code_start(info.map(|info| info.slice(input)), attributes.as_deref());
+            }
+            CodeBlockKind::Indented => HtmlWriter::code_block_start(self, None),
+        }
+    }
+
+    fn code(&mut self, input: &[u8], range: Range) {
+        match self.events.fenced_code.as_mut() {
+            Some(block) => block.code.extend_from_slice(range.slice(input)),
+            None => self.write_escaped_text(range.slice(input)),
+        }
+    }
+
+    fn virtual_spaces(&mut self, count: u8) {
+        let spaces = core::iter::repeat_n(b' ', usize::from(count));
+        match self.events.fenced_code.as_mut() {
+            Some(block) => block.code.extend(spaces),
+            None => self.out.extend(spaces),
+        }
+    }
+
+    fn code_block_end(&mut self) {
+        match self.events.fenced_code.as_mut() {
+            Some(block) => block.complete = true,
+            None => HtmlWriter::code_block_end(self),
+        }
+    }
+
+    fn block_quote_start(&mut self) {
+        self.events_block_start();
+        self.blockquote_start();
+    }
+
+    fn block_quote_cite(&mut self, input: &[u8], url: Range) {
+        self.blockquote_cite(url.slice(input), self.events.options.render_policy);
+    }
+
+    fn block_quote_end(&mut self) {
+        self.blockquote_end();
+    }
+
+    fn fenced_div_start(&mut self, input: &[u8], class: Option<Range>) {
+        self.events_block_start();
+        self.div_start(class.map(|class| class.slice(input)));
+    }
+
+    fn fenced_div_end(&mut self) {
+        self.div_end();
+    }
+
+    fn callout_start(&mut self, callout: CalloutType, expanded: Option<bool>) {
+        self.events_block_start();
+        let role = self
+            .events
+            .options
+            .callout_aria
+            .then(|| callout.aria_role());
+        match expanded {
+            Some(expanded) => self.collapsible_callout_start(callout, expanded, role),
+            None => self.callout_start_untitled(callout, role),
+        }
+    }
+
+    fn callout_title_start(&mut self, callout: CalloutType, expanded: Option<bool>, custom: bool) {
+        let title = self.events.options.callout_titles.get(callout);
+        // A callout without a title of any kind has no title element
+        if expanded.is_none() && (custom || !title.is_empty()) {
+            HtmlWriter::callout_title_start(self);
+            self.events.callout_title = true;
+        }
+        if !custom {
+            self.write_escaped_text(title.as_bytes());
+        }
+    }
+
+    fn callout_title_end(&mut self, _callout: CalloutType, expanded: Option<bool>) {
+        match expanded {
+            Some(_) => self.collapsible_callout_title_end(),
+            None => {
+                if core::mem::take(&mut self.events.callout_title) {
+                    HtmlWriter::callout_title_end(self);
+                }
+            }
+        }
+    }
+
+    fn callout_end(&mut self, _callout: CalloutType, expanded: Option<bool>) {
+        match expanded {
+            Some(_) => self.collapsible_callout_end(),
+            None => HtmlWriter::callout_end(self),
+        }
+    }
+
+    fn list_start(&mut self, kind: ListKind, tight: bool) {
+        self.events_block_start();
+        self.events.tight_lists.push(tight);
+        // Kept until a task item adds its class
+        let options = &self.events.options;
+        let task_classes = options.task_lists && options.task_list_classes;
+        let tag_start = task_classes.then(|| self.len());
+        self.events.list_tags.push(tag_start);
+        match kind {
+            ListKind::Unordered => self.ul_start(),
+            ListKind::Ordered { start, .. } => {
+                let start = match self.events.options.ordered_list_start_override {
+                    Some(start) if self.events.tight_lists.len() == 1 => start,
+                    _ => start,
+                };
+                self.ol_start((start != 1).then_some(start))
+            }
+        }
+    }
+
+    fn list_end(&mut self, kind: ListKind, _tight: bool) {
+        match kind {
+            ListKind::Unordered => self.ul_end(),
+            ListKind::Ordered { .. } => self.ol_end(),
+        }
+        self.events.tight_lists.pop();
+        self.events.list_tags.pop();
+    }
+
+    fn list_item_start(&mut self, task: TaskState) {
+        let options = &self.events.options;
+        let task_lists = options.task_lists;
+        if task_lists && options.task_list_classes && task != TaskState::None {
+            if let Some(tag_start) = self.events.list_tags.last_mut().and_then(Option::take) {
+                self.add_class_to_tag(tag_start, "contains-task-list");
+            }
+            self.task_li_start();
+        } else {
+            self.li_start();
+        }
+        if self.events.tight_lists.last() == Some(&true) {
+            self.events.at_tight_li_start = true;
+        } else {
+            self.events.pending_loose_li_newline = true;
+        }
+        if task_lists {
+            self.events.pending_task = task;
+        }
+    }
+
+    fn list_item_end(&mut self) {
+        let events = &mut self.events;
+        events.at_tight_li_start = false;
+        events.need_newline_before_block = false;
+        events.pending_loose_li_newline = false;
+        events.pending_task = TaskState::None;
+        self.li_end();
+    }
+
+    fn thematic_break(&mut self) {
+        if core::mem::take(&mut self.events.at_tight_li_start) {
+            self.newline();
+        }
+        HtmlWriter::thematic_break(self);
+    }
+
+    fn html_block_start(&mut self) {
+        if core::mem::take(&mut self.events.pending_loose_li_newline) {
+            self.newline();
+        }
+        if core::mem::take(&mut self.events.at_tight_li_start) {
+            self.newline();
+        }
+    }
+
+    fn html_block_text(&mut self, input: &[u8], range: Range) {
+        if self.events.options.render_policy == RenderPolicy::Untrusted {
+            self.write_escaped_text(range.slice(input));
+        } else {
+            self.write_raw_html(range.slice(input));
+        }
+    }
+
+    fn html_block_end(&mut self) {
+        let options = &self.events.options;
+        if options.render_policy == RenderPolicy::Trusted
+            && options.raw_html_sanitizer() == Sanitizer::GitHubAllowlist
+        {
+            self.finish_html_allowlisted();
+        }
+    }
+
+    fn table_start(&mut self) {
+        self.events_block_start();
+        HtmlWriter::table_start(self);
+    }
+
+    fn table_end(&mut self) {
+        HtmlWriter::table_end(self);
+    }
+
+    fn table_head_start(&mut self) {
+        self.events.in_table_head = true;
+        self.thead_start();
+    }
+
+    fn table_head_end(&mut self) {
+        self.events.in_table_head = false;
+        self.thead_end();
+    }
+
+    fn table_body_start(&mut self) {
+        self.tbody_start();
+    }
+
+    fn table_body_end(&mut self) {
+        self.tbody_end();
+    }
+
+    fn table_row_start(&mut self) {
+        self.tr_start();
+    }
+
+    fn table_row_end(&mut self) {
+        self.tr_end();
+    }
+
+    fn table_cell_start(&mut self, alignment: Alignment, colspan: u16) {
+        if self.events.in_table_head {
+            self.th_start(alignment, colspan);
+        } else {
+            self.td_start(alignment, colspan);
+        }
+        let start = self.len();
+        self.wrapped_inline_start();
+        if self.len() != start {
+            self.events.cell_content = Some((start, self.len()));
+        }
+    }
+
+    fn table_cell_end(&mut self) {
+        if let Some((start, content_start)) = self.events.cell_content.take() {
+            if self.len() == content_start {
+                self.out.truncate(start);
+            }
+        }
+        if self.events.in_table_head {
+            self.th_end();
+        } else {
+            self.td_end();
+        }
+    }
+
+    fn footnotes_start(&mut self) {
+        self.block_open_raw("<section data-footnotes class=\"footnotes\">\n");
+        if let Some(title) = self.events.options.footnote_section_title {
+            HtmlWriter::heading_start(self, 2);
+            self.write_escaped_text(title.as_bytes());
+            HtmlWriter::heading_end(self, 2);
+        }
+        self.ol_start(None);
+    }
+
+    fn footnotes_end(&mut self) {
+        self.ol_end();
+        self.block_close_raw("</section>\n");
+    }
+
+    fn footnote_definition_start(&mut self, label: &str, number: usize) {
+        self.block_open_raw("<li id=\"");
+        self.write_footnote_anchor("fn-", label, number);
+        self.write_str("\">\n");
+    }
+
+    /// One back reference per reference, GitHub style: the first links to
+    /// `fnref-<id>`, later ones to `fnref-<id>-<n>` and show `n`.
+    fn footnote_backrefs(&mut self, label: &str, number: usize, references: usize) {
+        for reference in 1..=references.max(1) {
+            self.write_str(" <a href=\"#");
+            self.write_footnote_anchor("fnref-", label, number);
+            self.write_footnote_reference_suffix(reference);
+            self.write_str("\" class=\"data-footnote-backref\" aria-label=\"Back to reference ");
+            self.write_string(&number.to_string());
+            self.write_footnote_reference_suffix(reference);
+            self.write_str("\">");
+            let backref_label = self.events.options.footnote_backref_label;
+            self.write_escaped_text(backref_label.as_bytes());
+            if reference > 1 {
+                self.write_str("<sup>");
+                self.write_string(&reference.to_string());
+                self.write_str("</sup>");
+            }
+            self.write_str("</a>");
+        }
+    }
+
+    fn footnote_definition_end(&mut self) {
+        self.li_end();
+    }
+
+    fn text(&mut self, content: &[u8], range: Range) {
+        if self.in_image() {
+            self.write_escaped_attr(range.slice(content));
+        } else {
+            self.write_text_with_entities(range.slice(content));
+        }
+    }
+
+    fn code_span(&mut self, content: &[u8], range: Range) {
+        let code = range.slice(content);
+        if self.in_image() {
+            self.write_code_text(code);
+        } else if let Some(html) = self
+            .events
+            .options
+            .code_span_renderer
+            .and_then(|renderer| render_code_span_with(renderer, code))
+        {
+            self.write_string(&html);
+        } else {
+            self.write_str("<code>");
+            self.write_code_text(code);
+            self.write_str("</code>");
+        }
+    }
+
+    fn emphasis_start(&mut self) {
+        self.events_tag("<em>");
+    }
+
+    fn emphasis_end(&mut self) {
+        self.events_tag("</em>");
+    }
+
+    fn strong_start(&mut self) {
+        self.events_tag("<strong>");
+    }
+
+    fn strong_end(&mut self) {
+        self.events_tag("</strong>");
+    }
+
+    fn strikethrough_start(&mut self) {
+        self.events_tag("<del>");
+    }
+
+    fn strikethrough_end(&mut self) {
+        self.events_tag("</del>");
+    }
+
+    fn subscript_start(&mut self) {
+        self.events_tag("<sub>");
+    }
+
+    fn subscript_end(&mut self) {
+        self.events_tag("</sub>");
+    }
+
+    fn superscript_start(&mut self) {
+        self.events_tag("<sup>");
+    }
+
+    fn superscript_end(&mut self) {
+        self.events_tag("</sup>");
+    }
+
+    fn highlight_start(&mut self) {
+        self.events_tag("<mark>");
+    }
+
+    fn highlight_end(&mut self) {
+        self.events_tag("</mark>");
+    }
+
+    fn custom_start(&mut self, delimiter: &CustomDelimiter) {
+        if !self.in_image() {
+            self.write_str("<");
+            self.write_str(delimiter.tag);
+            self.write_str(">");
+        }
+    }
+
+    fn custom_end(&mut self, delimiter: &CustomDelimiter) {
+        if !self.in_image() {
+            self.write_str("</");
+            self.write_str(delimiter.tag);
+            self.write_str(">");
+        }
+    }
+
+    fn link_start(&mut self, content: &[u8], url: Range, title: Option<Range>) {
+        self.events_link_start(url.slice(content), title.map(|title| title.slice(content)));
+    }
+
+    fn link_start_ref(&mut self, definition: &LinkRefDef) {
+        self.events_link_start(&definition.url, definition.title.as_deref());
+    }
+
+    fn link_end(&mut self) {
+        self.events_tag("</a>");
+    }
+
+    fn image_start(&mut self, content: &[u8], url: Range, title: Option<Range>) {
+        self.events_image_start(url.slice(content), title.map(|title| title.slice(content)));
+    }
+
+    fn image_start_ref(&mut self, definition: &LinkRefDef) {
+        self.events_image_start(&definition.url, definition.title.as_deref());
+    }
+
+    fn image_end(&mut self) {
+        if self.events.image_depth == 0 {
+            return;
+        }
+        self.events.image_depth -= 1;
+        if self.events.image_depth > 0 {
+            return;
+        }
+        self.write_str("\"");
+        if let Some(title) = self.events.image_title.take() {
+            self.write_str(" title=\"");
+            self.write_link_title(&title);
+            self.write_str("\"");
+        }
+        if self.events.options.untrusted_content {
+            self.write_str(" referrerpolicy=\"no-referrer\"");
+        }
+        self.write_void_end();
+    }
+
+    fn autolink(&mut self, content: &[u8], url: Range, is_email: bool) {
+        // Like cmark, entities are decoded before the URL is encoded and
+        // before the display text is escaped
+        let raw = String::from_utf8_lossy(url.slice(content));
+        let url = decode_entities_commonmark(&raw);
+        if self.in_image() {
+            self.write_escaped_attr(url.as_bytes());
+            return;
+        }
+        self.write_str("<a href=\"");
+        if is_email {
+            self.write_str("mailto:");
+            self.write_url_encoded(url.as_bytes());
+        } else {
+            self.write_url_encoded_with_policy(url.as_bytes(), self.events.options.render_policy);
+        }
+        self.write_str("\"");
+        self.write_link_rel(url.as_bytes());
+        self.write_str(">");
+        self.write_escaped_text(url.as_bytes());
+        self.write_str("</a>");
+    }
+
+    fn autolink_literal(&mut self, content: &[u8], url: Range, kind: AutolinkLiteralKind) {
+        let url = url.slice(content);
+        if self.in_image() {
+            self.write_escaped_attr(url);
+            return;
+        }
+        let options = &self.events.options;
+        let (policy, scheme) = (options.render_policy, options.www_autolink_scheme.prefix());
+        let external = options.external_links.filter(|external| match kind {
+            AutolinkLiteralKind::Url => external.is_external(url),
+            AutolinkLiteralKind::Www => external.is_external_www(url, scheme),
+            AutolinkLiteralKind::Email => false,
+        });
+        self.write_str("<a href=\"");
+        match kind {
+            AutolinkLiteralKind::Url => self.write_link_url_with_policy(url, policy),
+            AutolinkLiteralKind::Www => {
+                self.write_str(scheme);
+                self.write_link_url(url);
+            }
+            AutolinkLiteralKind::Email => {
+                self.write_str("mailto:");
+                self.write_link_url(url);
+            }
+        }
+        self.write_str("\"");
+        self.write_link_attrs(external);
+        self.write_str(">");
+        self.write_escaped_text(url);
+        self.write_str("</a>");
+    }
+
+    fn wikilink(&mut self, content: &[u8], target: Range, label: Range) {
+        if self.in_image() {
+            self.write_escaped_attr(label.slice(content));
+            return;
+        }
+        let transform = self.events.options.wikilink_url.map_or(
+            wikilink_percent_encoded as fn(&str, &mut String),
+            |custom| custom.0,
+        );
+        let mut url = String::new();
+        transform(&String::from_utf8_lossy(target.slice(content)), &mut url);
+        self.write_str("<a href=\"");
+        self.write_link_url_with_policy(url.as_bytes(), self.events.options.render_policy);
+        self.write_str("\"");
+        self.write_link_rel(url.as_bytes());
+        self.write_str(">");
+        self.write_escaped_text(label.slice(content));
+        self.write_str("</a>");
+    }
+
+    fn emoji(&mut self, glyph: &'static str) {
+        self.write_str(glyph);
+    }
+
+    fn smart_punctuation(&mut self, glyph: &'static str) {
+        self.write_str(glyph);
+    }
+
+    fn inline_html(&mut self, content: &[u8], range: Range) {
+        let html = range.slice(content);
+        if self.in_image() {
+            self.write_escaped_attr(html);
+        } else if self.events.options.render_policy == RenderPolicy::Untrusted {
+            self.write_escaped_text(html);
+        } else {
+            self.write_raw_html(html);
+            if self.events.options.raw_html_sanitizer() == Sanitizer::GitHubAllowlist {
+                self.finish_html_allowlisted();
+            }
+        }
+    }
+
+    fn soft_break(&mut self) {
+        if self.in_image() {
+            self.write_str(" ");
+        } else if self.events.options.hard_wrap {
+            self.line_break();
+        } else {
+            self.write_str("\n");
+        }
+    }
+
+    fn hard_break(&mut self) {
+        if self.in_image() {
+            self.write_str(" ");
+        } else {
+            self.line_break();
+        }
+    }
+
+    fn escaped_char(&mut self, ch: u8) {
+        if self.in_image() {
+            self.write_escaped_attr(&[ch]);
+        } else {
+            self.write_escaped_text(&[ch]);
+        }
+    }
+
+    fn footnote_ref(&mut self, label: &str, number: usize, reference: usize) {
+        if self.in_image() {
+            return;
+        }
+        self.write_str("<sup><a href=\"#");
+        self.write_footnote_anchor("fn-", label, number);
+        self.write_str("\" id=\"");
+        self.write_footnote_anchor("fnref-", label, number);
+        self.write_footnote_reference_suffix(reference);
+        self.write_str("\" data-footnote-ref>");
+        self.write_string(&number.to_string());
+        self.write_str("</a></sup>");
+    }
+
+    fn math_inline(&mut self, content: &[u8], range: Range) {
+        if self.in_image() {
+            self.write_escaped_attr(range.slice(content));
+        } else {
+            self.write_str("<code class=\"language-math math-inline\">");
+            self.write_code_text(range.slice(content));
+            self.write_str("</code>");
+        }
+    }
+
+    fn math_display(&mut self, content: &[u8], range: Range) {
+        if self.in_image() {
+            self.write_escaped_attr(range.slice(content));
+        } else {
+            self.write_str("<code class=\"language-math math-display\">");
+            self.write_code_text(range.slice(content));
+            self.write_str("</code>");
+        }
+    }
+
+    #[cfg(feature = "mdx")]
+    fn mdx_inline(&mut self, content: &[u8], range: Range) {
+        if self.in_image() {
+            self.write_escaped_attr(range.slice(content));
+        } else if self.events.options.render_policy == RenderPolicy::Trusted {
+            self.write_bytes(range.slice(content));
+        } else {
+            self.write_escaped_text(range.slice(content));
+        }
+    }
+}
+
 impl Default for HtmlWriter {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl std::fmt::Write for HtmlWriter {
-    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+impl core::fmt::Write for HtmlWriter {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
         self.out.extend_from_slice(s.as_bytes());
         Ok(())
     }
@@ -954,6 +2605,14 @@ mod tests {
         assert!(writer.out.capacity() >= 1250);
     }
 
+    #[test]
+    fn test_estimate_capacity_weighs_markup_bytes() {
+        let plain = HtmlWriter::estimate_capacity(b"plain words only");
+        let table = HtmlWriter::estimate_capacity(b"| a | b |\n| c | d |");
+        assert!((16..128).contains(&plain));
+        assert!(table > plain);
+    }
+
     #[test]
     fn test_writer_write_str() {
         let mut writer = HtmlWriter::new();
@@ -0,0 +1,849 @@
+//! Event-driven output formats.
+//!
+//! [`render_with`] does the work every output format shares: it parses the
+//! blocks, collects the text of paragraphs, headings and table cells, runs
+//! the inline parser over it, numbers footnotes, generates heading ids and
+//! calls one [`Renderer`] method per event. A renderer only decides what to
+//! write. [`to_html_with_options`](crate::to_html_with_options) is
+//! [`HtmlWriter`](crate::HtmlWriter) driven this way.
+
+use crate::block::{
+    Alignment, BlockEvent, BlockParser, CalloutType, CodeBlockKind, ListKind, TaskState,
+    attribute_id, attribute_list,
+};
+use crate::footnote::FootnoteStore;
+use crate::inline::{AutolinkLiteralKind, CustomDelimiter, InlineEvent, InlineParser};
+use crate::link_ref::{LinkRefDef, LinkRefStore};
+use crate::{
+    CellState, FootnoteNumbers, HeadingIdTracker, HeadingState, Options, Outline, ParagraphState,
+    Range, RenderedDocument, TocEntry, standalone_display_math, strip_front_matter, toc,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Output format driven by [`render_with`].
+///
+/// There is one method per block and inline event, and each does nothing by
+/// default, so an implementation only overrides what it writes. Ranges are
+/// passed with the bytes they index: `input` is the Markdown source for
+/// block events, and `content` is the inline content of the enclosing
+/// paragraph, heading, table cell or callout title for inline events. Lines
+/// of a paragraph are joined with `\n`, so `content` is the source itself
+/// only for blocks outside containers.
+///
+/// [`HtmlWriter`] implements this trait, and [`to_html_with_options`] is
+/// [`render_with`] into an `HtmlWriter`. Output options that reach across
+/// events arrive as arguments: heading ids with [`Self::heading_start`],
+/// footnote back references with [`Self::footnote_backrefs`], and the
+/// options themselves with [`Self::document_start`].
+///
+/// [`HtmlWriter`]: crate::HtmlWriter
+/// [`to_html_with_options`]: crate::to_html_with_options
+///
+/// # Example
+/// ```
+/// use ferromark::{Options, Range, Renderer, render_with};
+///
+/// /// Collects the text of a document, one paragraph per line.
+/// #[derive(Default)]
+/// struct PlainText(String);
+///
+/// impl Renderer for PlainText {
+///     fn text(&mut self, content: &[u8], range: Range) {
+///         self.0.push_str(std::str::from_utf8(range.slice(content)).unwrap());
+///     }
+///
+///     fn soft_break(&mut self) {
+///         self.0.push(' ');
+///     }
+///
+///     fn paragraph_end(&mut self, _tight: bool) {
+///         self.0.push('\n');
+///     }
+/// }
+///
+/// let mut text = PlainText::default();
+/// render_with("Some *emphasis*\nand [a link](/url).", &Options::default(), &mut text);
+/// assert_eq!(text.0, "Some emphasis and a link.\n");
+/// ```
+#[allow(unused_variables)]
+pub trait Renderer {
+    /// Start of a document rendered with `options`, before any other event.
+    fn document_start(&mut self, options: &Options) {}
+
+    // --- Blocks ---
+
+    /// Start of a paragraph. `tight` is set for a paragraph directly inside
+    /// an item of a tight list, which HTML writes without `<p>`.
+    fn paragraph_start(&mut self, tight: bool) {}
+    /// End of a paragraph.
+    fn paragraph_end(&mut self, tight: bool) {}
+    /// A paragraph of nothing but one display math span, with
+    /// [`Options::math`]. By default it is a paragraph like any other.
+    fn math_block(&mut self, content: &[u8], range: Range) {
+        self.paragraph_start(false);
+        self.math_display(content, range);
+        self.paragraph_end(false);
+    }
+    /// Attribute list (`{#id .class key="value"}`) of the heading or fenced
+    /// code block that starts next, with `attributes`.
+    fn block_attributes(&mut self, input: &[u8], attributes: Range) {}
+    /// Start of a heading of `level` 1 to 6. `id` comes from its attribute
+    /// list or, with [`Options::heading_ids`], from its text; generated ids
+    /// are unique within the document.
+    fn heading_start(&mut self, level: u8, id: Option<&str>) {}
+    /// End of a heading.
+    fn heading_end(&mut self, level: u8) {}
+    /// Start of a code block. The info string of a fenced block indexes
+    /// `input`.
+    fn code_block_start(&mut self, input: &[u8], kind: CodeBlockKind) {}
+    /// A line of code, with its line ending.
+    fn code(&mut self, input: &[u8], range: Range) {}
+    /// Spaces from a tab expanded at the start of the next code line.
+    fn virtual_spaces(&mut self, count: u8) {}
+    /// End of a code block.
+    fn code_block_end(&mut self) {}
+    /// Start of a blockquote that is not a callout.
+    fn block_quote_start(&mut self) {}
+    /// URL of a `[cite:URL]` line, right after its blockquote starts.
+    fn block_quote_cite(&mut self, input: &[u8], url: Range) {}
+    /// End of a blockquote.
+    fn block_quote_end(&mut self) {}
+    /// Start of a fenced div. `class` is its class name or attribute list
+    /// (`{#id .class key="value"}`).
+    fn fenced_div_start(&mut self, input: &[u8], class: Option<Range>) {}
+    /// End of a fenced div.
+    fn fenced_div_end(&mut self) {}
+    /// Start of a callout. `expanded` is set for a collapsible callout and
+    /// tells whether it starts open.
+    fn callout_start(&mut self, callout: CalloutType, expanded: Option<bool>) {}
+    /// Start of a callout's title, right after the callout starts. The
+    /// inline events of a `custom` title follow; otherwise the renderer
+    /// writes a default title.
+    fn callout_title_start(&mut self, callout: CalloutType, expanded: Option<bool>, custom: bool) {}
+    /// End of a callout's title.
+    fn callout_title_end(&mut self, callout: CalloutType, expanded: Option<bool>) {}
+    /// End of a callout.
+    fn callout_end(&mut self, callout: CalloutType, expanded: Option<bool>) {}
+    /// Start of a list.
+    fn list_start(&mut self, kind: ListKind, tight: bool) {}
+    /// End of a list.
+    fn list_end(&mut self, kind: ListKind, tight: bool) {}
+    /// Start of a list item, with its checkbox when task lists are enabled.
+    fn list_item_start(&mut self, task: TaskState) {}
+    /// End of a list item.
+    fn list_item_end(&mut self) {}
+    /// A thematic break.
+    fn thematic_break(&mut self) {}
+    /// Start of an HTML block.
+    fn html_block_start(&mut self) {}
+    /// A line of an HTML block, with its line ending.
+    fn html_block_text(&mut self, input: &[u8], range: Range) {}
+    /// End of an HTML block.
+    fn html_block_end(&mut self) {}
+    /// Start of a table.
+    fn table_start(&mut self) {}
+    /// End of a table.
+    fn table_end(&mut self) {}
+    /// Start of the header row's section.
+    fn table_head_start(&mut self) {}
+    /// End of the header row's section.
+    fn table_head_end(&mut self) {}
+    /// Start of the body rows, if there are any.
+    fn table_body_start(&mut self) {}
+    /// End of the body rows.
+    fn table_body_end(&mut self) {}
+    /// Start of a table row.
+    fn table_row_start(&mut self) {}
+    /// End of a table row.
+    fn table_row_end(&mut self) {}
+    /// Start of a table cell spanning `colspan` columns.
+    fn table_cell_start(&mut self, alignment: Alignment, colspan: u16) {}
+    /// End of a table cell.
+    fn table_cell_end(&mut self) {}
+    /// Start of the footnote definitions, after the document. Only called
+    /// when a footnote is referenced.
+    fn footnotes_start(&mut self) {}
+    /// End of the footnote definitions.
+    fn footnotes_end(&mut self) {}
+    /// Start of a referenced footnote definition, in the order of the first
+    /// references. Its blocks index the same `input` as the document's.
+    fn footnote_definition_start(&mut self, label: &str, number: usize) {}
+    /// Links back to the `references` references of a footnote definition,
+    /// at the end of its last paragraph.
+    fn footnote_backrefs(&mut self, label: &str, number: usize, references: usize) {}
+    /// End of a footnote definition.
+    fn footnote_definition_end(&mut self) {}
+
+    // --- Inlines ---
+
+    /// Text, with entity references as written in the source.
+    fn text(&mut self, content: &[u8], range: Range) {}
+    /// Content of a code span; line endings are not yet turned into spaces.
+    fn code_span(&mut self, content: &[u8], range: Range) {}
+    /// Start of emphasis.
+    fn emphasis_start(&mut self) {}
+    /// End of emphasis.
+    fn emphasis_end(&mut self) {}
+    /// Start of strong emphasis.
+    fn strong_start(&mut self) {}
+    /// End of strong emphasis.
+    fn strong_end(&mut self) {}
+    /// Start of strikethrough.
+    fn strikethrough_start(&mut self) {}
+    /// End of strikethrough.
+    fn strikethrough_end(&mut self) {}
+    /// Start of subscript.
+    fn subscript_start(&mut self) {}
+    /// End of subscript.
+    fn subscript_end(&mut self) {}
+    /// Start of superscript.
+    fn superscript_start(&mut self) {}
+    /// End of superscript.
+    fn superscript_end(&mut self) {}
+    /// Start of highlighted text.
+    fn highlight_start(&mut self) {}
+    /// End of highlighted text.
+    fn highlight_end(&mut self) {}
+    /// Start of a span of one of [`Options::custom_delimiters`].
+    fn custom_start(&mut self, delimiter: &CustomDelimiter) {}
+    /// End of a custom delimiter span.
+    fn custom_end(&mut self, delimiter: &CustomDelimiter) {}
+    /// Start of an inline link; its text follows. The destination and title
+    /// are as written, with escapes and entity references.
+    fn link_start(&mut self, content: &[u8], url: Range, title: Option<Range>) {}
+    /// Start of a reference link to `definition`; its text follows.
+    fn link_start_ref(&mut self, definition: &LinkRefDef) {}
+    /// End of a link.
+    fn link_end(&mut self) {}
+    /// Start of an inline image; its description follows, including the
+    /// events of any nested image or link.
+    fn image_start(&mut self, content: &[u8], url: Range, title: Option<Range>) {}
+    /// Start of a reference image to `definition`.
+    fn image_start_ref(&mut self, definition: &LinkRefDef) {}
+    /// End of an image.
+    fn image_end(&mut self) {}
+    /// Autolink `<url>` or `<email>`, without the brackets.
+    fn autolink(&mut self, content: &[u8], url: Range, is_email: bool) {}
+    /// Bare URL, `www.` link or email address.
+    fn autolink_literal(&mut self, content: &[u8], url: Range, kind: AutolinkLiteralKind) {}
+    /// Wikilink to the page `target`, showing `label`.
+    fn wikilink(&mut self, content: &[u8], target: Range, label: Range) {}
+    /// Emoji written as a `:shortcode:`.
+    fn emoji(&mut self, glyph: &'static str) {}
+    /// Typographic quote, dash or ellipsis, with
+    /// [`Options::smart_punctuation`].
+    fn smart_punctuation(&mut self, glyph: &'static str) {}
+    /// Raw inline HTML.
+    fn inline_html(&mut self, content: &[u8], range: Range) {}
+    /// Line ending inside a paragraph.
+    fn soft_break(&mut self) {}
+    /// Hard line break.
+    fn hard_break(&mut self) {}
+    /// Character escaped with a backslash.
+    fn escaped_char(&mut self, ch: u8) {}
+    /// Reference to the footnote `label`, numbered by first reference.
+    /// `reference` counts the references to it so far, starting at 1.
+    fn footnote_ref(&mut self, label: &str, number: usize, reference: usize) {}
+    /// Inline math, without its delimiters.
+    fn math_inline(&mut self, content: &[u8], range: Range) {}
+    /// Display math, without its delimiters.
+    fn math_display(&mut self, content: &[u8], range: Range) {}
+    /// MDX expression or JSX tag in the Markdown text of an MDX document,
+    /// as written.
+    #[cfg(feature = "mdx")]
+    fn mdx_inline(&mut self, content: &[u8], range: Range) {}
+}
+
+/// Parse Markdown with `options` and pass its events to `renderer`.
+///
+/// Front matter is skipped when `options.front_matter` is set. Link
+/// reference and footnote definitions are resolved before the first event,
+/// so [`Renderer::link_start_ref`] gets the definition and footnotes are
+/// numbered. Referenced footnote definitions follow the document.
+pub fn render_with<R: Renderer + ?Sized>(input: &str, options: &Options, renderer: &mut R) {
+    let input = strip_front_matter(input, options).as_bytes();
+    render_document(input, options, renderer, None, false, None);
+}
+
+/// Called after each block event is rendered; `false` stops rendering.
+pub(crate) type AfterEvent<'f, R> = dyn FnMut(&mut R) -> bool + 'f;
+
+/// Render a document and its footnote definitions, unless `after_event`
+/// stops rendering first.
+///
+/// Reference links that `input` does not define resolve against
+/// `shared_refs`. With `collect_outline`, the headings outside footnotes
+/// are returned for [`FullParseResult::headings`](crate::FullParseResult).
+pub(crate) fn render_document<R: Renderer + ?Sized>(
+    input: &[u8],
+    options: &Options,
+    renderer: &mut R,
+    shared_refs: Option<&LinkRefStore>,
+    collect_outline: bool,
+    mut after_event: Option<&mut AfterEvent<'_, R>>,
+) -> RenderedDocument {
+    renderer.document_start(options);
+    let mut parser = BlockParser::new_with_options(input, *options);
+    let mut events = Vec::with_capacity((input.len() / 16).max(64));
+    parser.parse(&mut events);
+    #[cfg(feature = "profiling")]
+    crate::profiling::record_block_events(&events, events.capacity());
+    let mut link_refs = parser.take_link_refs();
+    if let Some(shared_refs) = shared_refs {
+        link_refs.extend_undefined(shared_refs);
+    }
+    let footnote_store = options.footnotes.then(|| parser.take_footnote_store());
+
+    let mut driver = Driver::new(renderer, &link_refs, footnote_store.as_ref(), options);
+    if collect_outline {
+        driver.outline = Some(Outline {
+            entries: Vec::new(),
+            ids: HeadingIdTracker::new(),
+        });
+    }
+    let finished = driver.block_events(input, &events, after_event.as_deref_mut());
+    // Headings in footnotes are not part of the outline
+    let outline = driver.outline.take();
+    if finished {
+        driver.footnotes(input, after_event);
+    }
+    drop(driver);
+    RenderedDocument {
+        link_refs,
+        footnote_store,
+        outline: outline.map_or_else(Vec::new, |outline| outline.entries),
+    }
+}
+
+/// Render the Markdown parts of one document against shared definitions.
+///
+/// Each part is rendered from its own parsed events, with inline MDX
+/// expressions and JSX tags recognized in text. Footnote numbers and heading
+/// ids carry over from one part to the next. Footnote definition events
+/// index into `footnote_source`. Returns the HTML of every part and the
+/// footnote section, which is empty when no footnote was referenced.
+#[cfg(feature = "mdx")]
+pub(crate) fn render_markdown_parts(
+    parts: &[(&[u8], Vec<BlockEvent>)],
+    link_refs: &LinkRefStore,
+    footnote_store: Option<&FootnoteStore>,
+    footnote_source: &[u8],
+    options: &Options,
+) -> (Vec<String>, String) {
+    use crate::HtmlWriter;
+
+    let mut footnote_numbers = FootnoteNumbers::new(footnote_store.map_or(0, FootnoteStore::len));
+    let mut heading_ids = options.heading_ids.then(HeadingIdTracker::new);
+    let mut html = Vec::with_capacity(parts.len());
+
+    for (input, events) in parts {
+        let mut writer = HtmlWriter::with_capacity_for(input.len());
+        writer.document_start(options);
+        let mut driver = Driver::new(&mut writer, link_refs, footnote_store, options);
+        driver.inline.parser.set_mdx_text(true);
+        driver.inline.footnote_numbers = footnote_numbers;
+        driver.heading_ids = heading_ids;
+        driver.block_events(input, events, None);
+        footnote_numbers = driver.inline.footnote_numbers;
+        heading_ids = driver.heading_ids;
+        html.push(
+            writer
+                .into_string()
+                .expect("rendering from a UTF-8 Markdown string must produce UTF-8 HTML"),
+        );
+    }
+
+    let mut writer = HtmlWriter::with_capacity(0);
+    if !footnote_numbers.is_empty() {
+        writer.document_start(options);
+        let mut driver = Driver::new(&mut writer, link_refs, footnote_store, options);
+        driver.inline.parser.set_mdx_text(true);
+        driver.inline.footnote_numbers = footnote_numbers;
+        driver.footnotes(footnote_source, None);
+    }
+    let footnotes = writer
+        .into_string()
+        .expect("rendering from a UTF-8 Markdown string must produce UTF-8 HTML");
+    (html, footnotes)
+}
+
+/// Block-level state of one rendering pass.
+struct Driver<'a, 'r, R: Renderer + ?Sized> {
+    renderer: &'r mut R,
+    inline: Inlines<'a>,
+    para_state: ParagraphState,
+    heading_state: HeadingState,
+    cell_state: CellState,
+    /// Tightness of each open list and the blockquote depth it started at.
+    tight_lists: Vec<(bool, u32)>,
+    blockquote_depth: u32,
+    /// Callout of each open blockquote, with its `expanded` flag.
+    callouts: Vec<Option<(CalloutType, Option<bool>)>>,
+    /// Attribute list of the heading or code block about to start.
+    attributes: Option<Range>,
+    /// Generator of heading ids, with [`Options::heading_ids`].
+    heading_ids: Option<HeadingIdTracker>,
+    outline: Option<Outline>,
+    /// A task list item started, and its checkbox waits for a paragraph.
+    pending_task: bool,
+    /// Label, number and reference count of the footnote whose back
+    /// references end the next paragraph.
+    pending_backrefs: Option<(String, usize, usize)>,
+}
+
+impl<'a, 'r, R: Renderer + ?Sized> Driver<'a, 'r, R> {
+    fn new(
+        renderer: &'r mut R,
+        link_refs: &'a LinkRefStore,
+        footnote_store: Option<&'a FootnoteStore>,
+        options: &'a Options,
+    ) -> Self {
+        let mut parser = InlineParser::new();
+        parser.configure(options);
+        Self {
+            renderer,
+            inline: Inlines {
+                parser,
+                events: Vec::with_capacity(64),
+                link_refs,
+                footnote_store,
+                footnote_numbers: FootnoteNumbers::new(
+                    footnote_store.map_or(0, FootnoteStore::len),
+                ),
+                options,
+            },
+            para_state: ParagraphState::new(),
+            heading_state: HeadingState::new(),
+            cell_state: CellState::new(),
+            tight_lists: Vec::new(),
+            blockquote_depth: 0,
+            callouts: Vec::new(),
+            attributes: None,
+            heading_ids: options.heading_ids.then(HeadingIdTracker::new),
+            outline: None,
+            pending_task: false,
+            pending_backrefs: None,
+        }
+    }
+
+    /// Render `events`, calling `after_event` after each. Returns `false`
+    /// when `after_event` stopped rendering.
+    fn block_events(
+        &mut self,
+        input: &[u8],
+        events: &[BlockEvent],
+        mut after_event: Option<&mut AfterEvent<'_, R>>,
+    ) -> bool {
+        for (index, event) in events.iter().enumerate() {
+            self.block_event(input, event, events.get(index + 1));
+            if let Some(after_event) = after_event.as_mut() {
+                if !after_event(&mut *self.renderer) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Render the referenced footnote definitions in the order of their
+    /// first references. Each gets a fresh block state, so definitions
+    /// first referenced from another definition are not listed.
+    fn footnotes(&mut self, input: &[u8], mut after_event: Option<&mut AfterEvent<'_, R>>) {
+        let Some(footnote_store) = self.inline.footnote_store else {
+            return;
+        };
+        if self.inline.footnote_numbers.is_empty() {
+            return;
+        }
+        self.renderer.footnotes_start();
+        let numbers = &self.inline.footnote_numbers;
+        for (position, &index) in numbers.order.iter().enumerate() {
+            let number = position + 1;
+            let note_events;
+            let (label, source, events) = match numbers.inline_note(index) {
+                // An inline footnote's text is a paragraph of its own
+                Some(note) => {
+                    note_events = [
+                        BlockEvent::ParagraphStart,
+                        BlockEvent::Text(Range::from_usize(0, note.content.len())),
+                        BlockEvent::ParagraphEnd,
+                    ];
+                    (
+                        note.label.as_str(),
+                        note.content.as_slice(),
+                        &note_events[..],
+                    )
+                }
+                None => match footnote_store.get(index) {
+                    Some(def) => (def.label.as_str(), input, def.events.as_slice()),
+                    None => continue,
+                },
+            };
+            let last_paragraph_end = events
+                .iter()
+                .rposition(|event| matches!(event, BlockEvent::ParagraphEnd));
+
+            let mut nested = Driver::new(
+                &mut *self.renderer,
+                self.inline.link_refs,
+                Some(footnote_store),
+                self.inline.options,
+            );
+            #[cfg(feature = "mdx")]
+            nested
+                .inline
+                .parser
+                .set_mdx_text(self.inline.parser.mdx_text());
+            nested.inline.parser.set_inline_footnotes(false);
+            nested.renderer.footnote_definition_start(label, number);
+            for (event_index, event) in events.iter().enumerate() {
+                if Some(event_index) == last_paragraph_end {
+                    nested.pending_backrefs =
+                        Some((label.to_string(), number, numbers.references(index)));
+                }
+                nested.block_event(source, event, events.get(event_index + 1));
+                if let Some(after_event) = after_event.as_mut() {
+                    if !after_event(&mut *nested.renderer) {
+                        return;
+                    }
+                }
+            }
+            nested.renderer.footnote_definition_end();
+        }
+        self.renderer.footnotes_end();
+    }
+
+    /// Whether a paragraph starting now sits directly in a tight list item.
+    /// A blockquote opened inside the item brings back `<p>` in HTML.
+    fn in_tight_list(&self) -> bool {
+        self.tight_lists
+            .last()
+            .is_some_and(|&(tight, depth)| tight && self.blockquote_depth <= depth)
+    }
+
+    fn block_event(&mut self, input: &[u8], event: &BlockEvent, next: Option<&BlockEvent>) {
+        match event {
+            // Started at its end, once it is known whether it is display math
+            BlockEvent::ParagraphStart => self.para_state.start(),
+            BlockEvent::ParagraphEnd => {
+                let tight = self.in_tight_list();
+                let content = self.para_state.finish();
+                let backrefs = self.pending_backrefs.take();
+                if !tight && !self.pending_task && backrefs.is_none() {
+                    if let Some(range) = standalone_display_math(
+                        content,
+                        &mut self.inline.parser,
+                        &mut self.inline.events,
+                        self.inline.options,
+                    ) {
+                        self.renderer.math_block(content, range);
+                        return;
+                    }
+                }
+                self.pending_task = false;
+                self.renderer.paragraph_start(tight);
+                self.inline.render(content, &mut *self.renderer);
+                if let Some((label, number, references)) = backrefs {
+                    self.renderer.footnote_backrefs(&label, number, references);
+                }
+                self.renderer.paragraph_end(tight);
+            }
+            BlockEvent::HeadingStart { .. } => self.heading_state.start(),
+            BlockEvent::HeadingEnd { level } => {
+                let content = self.heading_state.finish();
+                let explicit_id = self
+                    .attributes
+                    .take()
+                    .and_then(|range| attribute_list(range.slice(input)))
+                    .and_then(|list| attribute_id(&list));
+                if let Some(outline) = self.outline.as_mut() {
+                    let id = match explicit_id {
+                        Some(id) => {
+                            outline.ids.reserve(id);
+                            String::from_utf8_lossy(id).into_owned()
+                        }
+                        None => outline.ids.make_id(content).to_string(),
+                    };
+                    let inline = &mut self.inline;
+                    let text = toc::plain_text(
+                        content,
+                        &mut inline.parser,
+                        &mut inline.events,
+                        inline.link_refs,
+                        inline.footnote_store,
+                        inline.options,
+                    );
+                    outline.entries.push(TocEntry {
+                        level: *level,
+                        text,
+                        id,
+                        children: Vec::new(),
+                    });
+                }
+                let id = match (explicit_id, self.heading_ids.as_mut()) {
+                    (Some(id), tracker) => {
+                        if let Some(tracker) = tracker {
+                            tracker.reserve(id);
+                        }
+                        core::str::from_utf8(id).ok()
+                    }
+                    (None, tracker) => tracker.map(|tracker| tracker.make_id(content)),
+                };
+                self.renderer.heading_start(*level, id);
+                self.inline.render(content, &mut *self.renderer);
+                self.renderer.heading_end(*level);
+            }
+            BlockEvent::Attributes(range) => {
+                self.attributes = Some(*range);
+                self.renderer.block_attributes(input, *range);
+            }
+            BlockEvent::CodeBlockStart { kind } => {
+                self.attributes = None;
+                self.renderer.code_block_start(input, *kind);
+            }
+            BlockEvent::Code(range) => self.renderer.code(input, *range),
+            BlockEvent::VirtualSpaces(count) => self.renderer.virtual_spaces(*count),
+            BlockEvent::CodeBlockEnd => self.renderer.code_block_end(),
+            BlockEvent::BlockQuoteStart { callout, fold } => {
+                self.blockquote_depth += 1;
+                let Some(callout) = *callout else {
+                    self.callouts.push(None);
+                    self.renderer.block_quote_start();
+                    return;
+                };
+                let expanded = fold.map(|fold| fold.expanded);
+                self.callouts.push(Some((callout, expanded)));
+                self.renderer.callout_start(callout, expanded);
+                // A custom title is in the fold marker of a collapsible
+                // callout and in the next event otherwise
+                let title = match (fold, next) {
+                    (Some(fold), _) => fold.title,
+                    (None, Some(BlockEvent::CalloutTitle(title))) => Some(*title),
+                    (None, _) => None,
+                };
+                self.renderer
+                    .callout_title_start(callout, expanded, title.is_some());
+                if let Some(title) = title {
+                    self.inline.render(title.slice(input), &mut *self.renderer);
+                }
+                self.renderer.callout_title_end(callout, expanded);
+            }
+            // Written with its callout
+            BlockEvent::CalloutTitle(_) => {}
+            BlockEvent::BlockQuoteCite(url) => self.renderer.block_quote_cite(input, *url),
+            BlockEvent::BlockQuoteEnd => {
+                self.blockquote_depth = self.blockquote_depth.saturating_sub(1);
+                match self.callouts.pop().flatten() {
+                    Some((callout, expanded)) => self.renderer.callout_end(callout, expanded),
+                    None => self.renderer.block_quote_end(),
+                }
+            }
+            BlockEvent::DivStart { class } => {
+                // Like a blockquote, a div keeps its paragraphs in a tight list
+                self.blockquote_depth += 1;
+                self.renderer.fenced_div_start(input, *class);
+            }
+            BlockEvent::DivEnd => {
+                self.blockquote_depth = self.blockquote_depth.saturating_sub(1);
+                self.renderer.fenced_div_end();
+            }
+            BlockEvent::ListStart { kind, tight } => {
+                self.tight_lists.push((*tight, self.blockquote_depth));
+                self.renderer.list_start(*kind, *tight);
+            }
+            BlockEvent::ListEnd { kind, tight } => {
+                self.tight_lists.pop();
+                self.renderer.list_end(*kind, *tight);
+            }
+            BlockEvent::ListItemStart { task } => {
+                self.pending_task = self.inline.options.task_lists && *task != TaskState::None;
+                self.renderer.list_item_start(*task);
+            }
+            BlockEvent::ListItemEnd => {
+                self.pending_task = false;
+                self.renderer.list_item_end();
+            }
+            BlockEvent::ThematicBreak => self.renderer.thematic_break(),
+            BlockEvent::HtmlBlockStart => self.renderer.html_block_start(),
+            BlockEvent::HtmlBlockText(range) => self.renderer.html_block_text(input, *range),
+            BlockEvent::HtmlBlockEnd => self.renderer.html_block_end(),
+            BlockEvent::SoftBreak => {
+                if self.para_state.in_paragraph {
+                    self.para_state.add_soft_break();
+                } else if self.heading_state.in_heading {
+                    self.heading_state.add_soft_break();
+                } else {
+                    self.renderer.soft_break();
+                }
+            }
+            BlockEvent::Text(range) => {
+                let text = range.slice(input);
+                if self.para_state.in_paragraph {
+                    self.para_state.add_text(text);
+                } else if self.heading_state.in_heading {
+                    self.heading_state.add_text(text);
+                } else if self.cell_state.in_cell {
+                    self.cell_state.add_text(text);
+                } else {
+                    self.inline.render(text, &mut *self.renderer);
+                }
+            }
+            BlockEvent::TableStart => self.renderer.table_start(),
+            BlockEvent::TableEnd => self.renderer.table_end(),
+            BlockEvent::TableHeadStart => self.renderer.table_head_start(),
+            BlockEvent::TableHeadEnd => self.renderer.table_head_end(),
+            BlockEvent::TableBodyStart => self.renderer.table_body_start(),
+            BlockEvent::TableBodyEnd => self.renderer.table_body_end(),
+            BlockEvent::TableRowStart => self.renderer.table_row_start(),
+            BlockEvent::TableRowEnd => self.renderer.table_row_end(),
+            BlockEvent::TableCellStart { alignment, colspan } => {
+                self.renderer.table_cell_start(*alignment, *colspan);
+                self.cell_state.start();
+            }
+            BlockEvent::TableCellEnd => {
+                let content = self.cell_state.finish();
+                self.inline.render(content, &mut *self.renderer);
+                self.renderer.table_cell_end();
+            }
+        }
+    }
+}
+
+/// Inline parsing and dispatch, shared by every block with inline content.
+struct Inlines<'a> {
+    parser: InlineParser,
+    events: Vec<InlineEvent>,
+    link_refs: &'a LinkRefStore,
+    footnote_store: Option<&'a FootnoteStore>,
+    footnote_numbers: FootnoteNumbers,
+    options: &'a Options,
+}
+
+impl Inlines<'_> {
+    fn render<R: Renderer + ?Sized>(&mut self, content: &[u8], renderer: &mut R) {
+        if content.is_empty() {
+            return;
+        }
+        let options = self.options;
+        self.events.clear();
+        self.events.reserve((content.len() / 8).max(8));
+        self.parser.parse_with_options(
+            content,
+            options.allow_link_refs.then_some(self.link_refs),
+            options.inline_html(),
+            options.strikethrough,
+            options.highlight,
+            options.superscript,
+            options.subscript,
+            options.autolink_literals,
+            options.math,
+            self.footnote_store,
+            &mut self.events,
+        );
+        #[cfg(feature = "profiling")]
+        crate::profiling::record_inline_events(&self.events, self.events.capacity());
+
+        // Footnote references in image descriptions are dropped, not numbered
+        let mut image_depth = 0u32;
+        for event in &self.events {
+            match *event {
+                InlineEvent::Text(range) => renderer.text(content, range),
+                InlineEvent::Code(range) => renderer.code_span(content, range),
+                InlineEvent::EmphasisStart => renderer.emphasis_start(),
+                InlineEvent::EmphasisEnd => renderer.emphasis_end(),
+                InlineEvent::StrongStart => renderer.strong_start(),
+                InlineEvent::StrongEnd => renderer.strong_end(),
+                InlineEvent::StrikethroughStart => renderer.strikethrough_start(),
+                InlineEvent::StrikethroughEnd => renderer.strikethrough_end(),
+                InlineEvent::SubscriptStart => renderer.subscript_start(),
+                InlineEvent::SubscriptEnd => renderer.subscript_end(),
+                InlineEvent::SuperscriptStart => renderer.superscript_start(),
+                InlineEvent::SuperscriptEnd => renderer.superscript_end(),
+                InlineEvent::HighlightStart => renderer.highlight_start(),
+                InlineEvent::HighlightEnd => renderer.highlight_end(),
+                InlineEvent::CustomStart(index) => {
+                    if let Some(delimiter) = options.custom_delimiters.get(usize::from(index)) {
+                        renderer.custom_start(delimiter);
+                    }
+                }
+                InlineEvent::CustomEnd(index) => {
+                    if let Some(delimiter) = options.custom_delimiters.get(usize::from(index)) {
+                        renderer.custom_end(delimiter);
+                    }
+                }
+                InlineEvent::LinkStart { url, title } => renderer.link_start(content, url, title),
+                InlineEvent::LinkStartRef { def_index } => {
+                    if let Some(def) = self.link_refs.get(def_index as usize) {
+                        renderer.link_start_ref(def);
+                    }
+                }
+                InlineEvent::LinkEnd => renderer.link_end(),
+                InlineEvent::ImageStart { url, title } => {
+                    image_depth += 1;
+                    renderer.image_start(content, url, title);
+                }
+                InlineEvent::ImageStartRef { def_index } => {
+                    if let Some(def) = self.link_refs.get(def_index as usize) {
+                        image_depth += 1;
+                        renderer.image_start_ref(def);
+                    }
+                }
+                InlineEvent::ImageEnd => {
+                    image_depth = image_depth.saturating_sub(1);
+                    renderer.image_end();
+                }
+                InlineEvent::Autolink { url, is_email } => {
+                    renderer.autolink(content, url, is_email)
+                }
+                InlineEvent::AutolinkLiteral { url, kind } => {
+                    renderer.autolink_literal(content, url, kind);
+                }
+                InlineEvent::WikiLink { target, label } => {
+                    renderer.wikilink(content, target, label)
+                }
+                InlineEvent::Emoji(glyph) => renderer.emoji(glyph),
+                InlineEvent::SmartPunctuation(glyph) => renderer.smart_punctuation(glyph),
+                InlineEvent::Html(range) => renderer.inline_html(content, range),
+                InlineEvent::SoftBreak => renderer.soft_break(),
+                InlineEvent::HardBreak => renderer.hard_break(),
+                InlineEvent::EscapedChar(ch) => renderer.escaped_char(ch),
+                InlineEvent::FootnoteRef { def_index } => {
+                    let index = def_index as usize;
+                    if image_depth > 0 {
+                        continue;
+                    }
+                    if let (Some(def), Some((number, reference))) = (
+                        self.footnote_store.and_then(|store| store.get(index)),
+                        self.footnote_numbers.reference(index),
+                    ) {
+                        renderer.footnote_ref(&def.label, number, reference);
+                    }
+                }
+                InlineEvent::InlineFootnote(range) => {
+                    if image_depth > 0 || self.footnote_store.is_none() {
+                        continue;
+                    }
+                    let index = self
+                        .footnote_numbers
+                        .add_inline(range.slice(content), self.footnote_store);
+                    if let Some((number, reference)) = self.footnote_numbers.reference(index) {
+                        if let Some(label) = self.footnote_numbers.label(self.footnote_store, index)
+                        {
+                            renderer.footnote_ref(label, number, reference);
+                        }
+                    }
+                }
+                InlineEvent::MathInline(range) => renderer.math_inline(content, range),
+                InlineEvent::MathDisplay(range) => renderer.math_display(content, range),
+                #[cfg(feature = "mdx")]
+                InlineEvent::MdxExpression(range)
+                | InlineEvent::MdxJsxOpen(range)
+                | InlineEvent::MdxJsxClose(range)
+                | InlineEvent::MdxJsxSelfClose(range) => renderer.mdx_inline(content, range),
+            }
+        }
+    }
+}
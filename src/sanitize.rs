@@ -0,0 +1,533 @@
+//! GitHub's raw HTML allowlist (see [`Sanitizer::GitHubAllowlist`]).
+//!
+//! The tables follow the sanitization filter of `github-markup`: allowed
+//! elements, attributes per element, and URL protocols per attribute. Tags
+//! outside the allowlist are dropped while their content stays, except for
+//! `script`, whose content goes too. Comments, processing instructions and
+//! declarations are dropped, and a `<` that starts no tag is escaped.
+//!
+//! [`Sanitizer::GitHubAllowlist`]: crate::Sanitizer::GitHubAllowlist
+
+use crate::render::decode_entities_commonmark;
+use alloc::vec::Vec;
+use memchr::{memchr, memmem};
+
+const ELEMENTS: &[&str] = &[
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "h7",
+    "h8",
+    "br",
+    "b",
+    "i",
+    "strong",
+    "em",
+    "a",
+    "pre",
+    "code",
+    "img",
+    "tt",
+    "div",
+    "ins",
+    "del",
+    "sup",
+    "sub",
+    "p",
+    "ol",
+    "ul",
+    "table",
+    "thead",
+    "tbody",
+    "tfoot",
+    "blockquote",
+    "dl",
+    "dt",
+    "dd",
+    "kbd",
+    "q",
+    "samp",
+    "var",
+    "hr",
+    "ruby",
+    "rt",
+    "rp",
+    "li",
+    "tr",
+    "td",
+    "th",
+    "s",
+    "strike",
+    "summary",
+    "details",
+    "caption",
+    "figure",
+    "figcaption",
+    "abbr",
+    "bdo",
+    "cite",
+    "dfn",
+    "mark",
+    "small",
+    "span",
+    "time",
+    "wbr",
+];
+
+/// Attributes allowed on every element.
+const GLOBAL_ATTRIBUTES: &[&str] = &[
+    "abbr",
+    "accept",
+    "accept-charset",
+    "accesskey",
+    "action",
+    "align",
+    "alt",
+    "aria-describedby",
+    "aria-hidden",
+    "aria-label",
+    "aria-labelledby",
+    "axis",
+    "border",
+    "cellpadding",
+    "cellspacing",
+    "char",
+    "charoff",
+    "charset",
+    "checked",
+    "clear",
+    "cols",
+    "colspan",
+    "color",
+    "compact",
+    "coords",
+    "datetime",
+    "dir",
+    "disabled",
+    "enctype",
+    "for",
+    "frame",
+    "headers",
+    "height",
+    "hreflang",
+    "hspace",
+    "ismap",
+    "label",
+    "lang",
+    "maxlength",
+    "media",
+    "method",
+    "multiple",
+    "name",
+    "nohref",
+    "noshade",
+    "nowrap",
+    "open",
+    "progress",
+    "prompt",
+    "readonly",
+    "rel",
+    "rev",
+    "role",
+    "rows",
+    "rowspan",
+    "rules",
+    "scope",
+    "selected",
+    "shape",
+    "size",
+    "span",
+    "start",
+    "summary",
+    "tabindex",
+    "target",
+    "title",
+    "type",
+    "usemap",
+    "valign",
+    "value",
+    "vspace",
+    "width",
+    "itemprop",
+];
+
+/// Protocols allowed in link targets, besides relative URLs.
+const LINK_PROTOCOLS: &[&str] = &[
+    "http",
+    "https",
+    "mailto",
+    "github-windows",
+    "github-mac",
+    "x-github-client",
+];
+
+/// Protocols allowed in other URL attributes, besides relative URLs.
+const URL_PROTOCOLS: &[&str] = &["http", "https"];
+
+/// Elements whose content is dropped along with the tags.
+const REMOVE_CONTENTS: &[&str] = &["script"];
+
+/// Attributes allowed on `element` beyond [`GLOBAL_ATTRIBUTES`], with the
+/// protocols allowed if the attribute holds a URL.
+fn element_attribute(element: &str, attribute: &str) -> Option<Option<&'static [&'static str]>> {
+    let protocols = match (element, attribute) {
+        ("a", "href") => Some(LINK_PROTOCOLS),
+        ("img", "src" | "longdesc") | ("blockquote" | "del" | "ins" | "q", "cite") => {
+            Some(URL_PROTOCOLS)
+        }
+        ("div", "itemscope" | "itemtype") => None,
+        _ => return None,
+    };
+    Some(protocols)
+}
+
+/// Whether `url` is relative or uses one of `protocols`.
+fn allowed_url(url: &[u8], protocols: &[&str]) -> bool {
+    let Ok(url) = core::str::from_utf8(url) else {
+        return false;
+    };
+    let decoded = decode_entities_commonmark(url);
+    let url = decoded.trim_start_matches(|c: char| c <= ' ');
+    match url.find([':', '/', '#']) {
+        Some(colon) if url.as_bytes()[colon] == b':' => {
+            let protocol: alloc::string::String = url[..colon]
+                .chars()
+                .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+                .collect();
+            protocols
+                .iter()
+                .any(|allowed| protocol.eq_ignore_ascii_case(allowed))
+        }
+        _ => true,
+    }
+}
+
+/// A raw HTML construct starting at a `<`.
+enum Scan {
+    /// A start or end tag ending before `end`.
+    Tag(Tag),
+    /// A comment, processing instruction or declaration ending before `end`.
+    Markup { end: usize },
+    /// The construct may continue past the end of the input.
+    Incomplete,
+    /// The `<` starts no construct.
+    Text,
+}
+
+/// Start and end of a byte range in the scanned HTML.
+type Span = (usize, usize);
+
+struct Tag {
+    end: usize,
+    closing: bool,
+    self_closing: bool,
+    name: Span,
+    /// Name and optional value of each attribute.
+    attributes: Vec<(Span, Option<Span>)>,
+}
+
+fn is_space(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r' | b'\x0c')
+}
+
+fn scan(html: &[u8], start: usize) -> Scan {
+    let rest = &html[start..];
+    let find_end = |open: usize, close: &[u8]| match memmem::find(&rest[open..], close) {
+        Some(i) => Scan::Markup {
+            end: start + open + i + close.len(),
+        },
+        None => Scan::Incomplete,
+    };
+    match rest.get(1) {
+        None => Scan::Incomplete,
+        Some(b'!') => {
+            if rest.starts_with(b"<!--") {
+                find_end(4, b"-->")
+            } else if rest.starts_with(b"<![CDATA[") {
+                find_end(9, b"]]>")
+            } else if rest.get(2).is_some_and(u8::is_ascii_alphabetic) {
+                find_end(2, b">")
+            } else if b"<![CDATA[".starts_with(rest) || b"<!--".starts_with(rest) {
+                // Cut off before the opening is complete
+                Scan::Incomplete
+            } else {
+                Scan::Text
+            }
+        }
+        Some(b'?') => find_end(2, b"?>"),
+        Some(_) => scan_tag(html, start),
+    }
+}
+
+fn scan_tag(html: &[u8], start: usize) -> Scan {
+    let mut pos = start + 1;
+    let closing = html.get(pos) == Some(&b'/');
+    if closing {
+        pos += 1;
+    }
+    let name_start = pos;
+    if !html.get(pos).is_some_and(u8::is_ascii_alphabetic) {
+        return if pos == html.len() {
+            Scan::Incomplete
+        } else {
+            Scan::Text
+        };
+    }
+    while html
+        .get(pos)
+        .is_some_and(|&b| b.is_ascii_alphanumeric() || b == b'-')
+    {
+        pos += 1;
+    }
+    let name = (name_start, pos);
+    let mut attributes = Vec::new();
+    loop {
+        let before_space = pos;
+        while html.get(pos).copied().is_some_and(is_space) {
+            pos += 1;
+        }
+        let Some(&b) = html.get(pos) else {
+            return Scan::Incomplete;
+        };
+        match b {
+            b'>' => {
+                return Scan::Tag(Tag {
+                    end: pos + 1,
+                    closing,
+                    self_closing: false,
+                    name,
+                    attributes,
+                });
+            }
+            b'/' if !closing => {
+                return match html.get(pos + 1) {
+                    Some(b'>') => Scan::Tag(Tag {
+                        end: pos + 2,
+                        closing,
+                        self_closing: true,
+                        name,
+                        attributes,
+                    }),
+                    Some(_) => Scan::Text,
+                    None => Scan::Incomplete,
+                };
+            }
+            _ if closing || pos == before_space => return Scan::Text,
+            b'"' | b'\'' | b'<' | b'=' | b'`' => return Scan::Text,
+            _ => {}
+        }
+        let attr_start = pos;
+        while html.get(pos).is_some_and(|&b| {
+            !is_space(b) && !matches!(b, b'"' | b'\'' | b'<' | b'>' | b'/' | b'=')
+        }) {
+            pos += 1;
+        }
+        let attr_name = (attr_start, pos);
+        let mut value_pos = pos;
+        while html.get(value_pos).copied().is_some_and(is_space) {
+            value_pos += 1;
+        }
+        let mut value = None;
+        match html.get(value_pos) {
+            None => return Scan::Incomplete,
+            Some(b'=') => {
+                value_pos += 1;
+                while html.get(value_pos).copied().is_some_and(is_space) {
+                    value_pos += 1;
+                }
+                match html.get(value_pos) {
+                    None => return Scan::Incomplete,
+                    Some(&quote @ (b'"' | b'\'')) => {
+                        let Some(len) = memchr(quote, &html[value_pos + 1..]) else {
+                            return Scan::Incomplete;
+                        };
+                        value = Some((value_pos + 1, value_pos + 1 + len));
+                        pos = value_pos + len + 2;
+                    }
+                    Some(_) => {
+                        let value_start = value_pos;
+                        while html.get(value_pos).is_some_and(|&b| {
+                            !is_space(b) && !matches!(b, b'"' | b'\'' | b'=' | b'<' | b'>' | b'`')
+                        }) {
+                            value_pos += 1;
+                        }
+                        if value_pos == value_start {
+                            return Scan::Text;
+                        }
+                        value = Some((value_start, value_pos));
+                        pos = value_pos;
+                    }
+                }
+            }
+            Some(_) => {}
+        }
+        attributes.push((attr_name, value));
+    }
+}
+
+/// Write an attribute value in double quotes, escaping what would end it.
+fn write_value(out: &mut Vec<u8>, value: &[u8]) {
+    out.extend_from_slice(b"=\"");
+    for &b in value {
+        match b {
+            b'"' => out.extend_from_slice(b"&quot;"),
+            b'<' => out.extend_from_slice(b"&lt;"),
+            b'>' => out.extend_from_slice(b"&gt;"),
+            _ => out.push(b),
+        }
+    }
+    out.push(b'"');
+}
+
+fn write_tag(out: &mut Vec<u8>, html: &[u8], tag: &Tag) {
+    let mut name_buf = [0u8; 16];
+    let name = &html[tag.name.0..tag.name.1];
+    if name.len() > name_buf.len() {
+        return;
+    }
+    let name_buf = &mut name_buf[..name.len()];
+    name_buf.copy_from_slice(name);
+    name_buf.make_ascii_lowercase();
+    let Ok(name) = core::str::from_utf8(name_buf) else {
+        return;
+    };
+    if !ELEMENTS.contains(&name) {
+        return;
+    }
+    out.push(b'<');
+    if tag.closing {
+        out.push(b'/');
+    }
+    out.extend_from_slice(name.as_bytes());
+    if !tag.closing {
+        for &((name_start, name_end), value) in &tag.attributes {
+            let attribute = html[name_start..name_end].to_ascii_lowercase();
+            let Ok(attribute) = core::str::from_utf8(&attribute) else {
+                continue;
+            };
+            let protocols = match element_attribute(name, attribute) {
+                Some(protocols) => protocols,
+                None if GLOBAL_ATTRIBUTES.contains(&attribute) => None,
+                None => continue,
+            };
+            let value = value.map(|(start, end)| &html[start..end]);
+            if let Some(protocols) = protocols {
+                if !value.is_some_and(|value| allowed_url(value, protocols)) {
+                    continue;
+                }
+            }
+            out.push(b' ');
+            out.extend_from_slice(attribute.as_bytes());
+            if let Some(value) = value {
+                write_value(out, value);
+            }
+        }
+        if tag.self_closing {
+            out.extend_from_slice(b" /");
+        }
+    }
+    out.push(b'>');
+}
+
+/// Chunks a held-back construct may take in before it counts as text. Each
+/// chunk rescans the construct, so this keeps filtering linear.
+const MAX_HELD_CHUNKS: u32 = 8;
+
+/// Raw HTML allowlist filter, stateful across the lines of an HTML block.
+#[derive(Debug, Default)]
+pub(crate) struct HtmlAllowlist {
+    /// Start of a construct that continues in the next chunk.
+    pending: Vec<u8>,
+    /// Chunks appended to `pending` so far.
+    held_chunks: u32,
+    /// Element whose content is being dropped.
+    removing: Option<&'static str>,
+}
+
+impl HtmlAllowlist {
+    /// Filter `html` into `out`, holding back a construct cut off at its end.
+    pub(crate) fn write(&mut self, out: &mut Vec<u8>, html: &[u8]) {
+        if self.pending.is_empty() {
+            self.held_chunks = 0;
+            self.filter(out, html, false);
+        } else {
+            self.held_chunks += 1;
+            let mut chunk = core::mem::take(&mut self.pending);
+            chunk.extend_from_slice(html);
+            self.filter(out, &chunk, self.held_chunks >= MAX_HELD_CHUNKS);
+        }
+    }
+
+    /// Flush a held-back construct as text and reset the state.
+    pub(crate) fn finish(&mut self, out: &mut Vec<u8>) {
+        let pending = core::mem::take(&mut self.pending);
+        self.filter(out, &pending, true);
+        self.removing = None;
+    }
+
+    fn filter(&mut self, out: &mut Vec<u8>, html: &[u8], last: bool) {
+        let mut pos = 0;
+        while pos < html.len() {
+            if let Some(element) = self.removing {
+                // End tags hold no line break, so none is split across chunks
+                let Some(close) = find_end_tag(&html[pos..], element) else {
+                    return;
+                };
+                self.removing = None;
+                pos += close;
+                continue;
+            }
+            let Some(offset) = memchr(b'<', &html[pos..]) else {
+                out.extend_from_slice(&html[pos..]);
+                return;
+            };
+            let start = pos + offset;
+            out.extend_from_slice(&html[pos..start]);
+            match scan(html, start) {
+                Scan::Tag(tag) => {
+                    write_tag(out, html, &tag);
+                    if !tag.closing && !tag.self_closing {
+                        let name = &html[tag.name.0..tag.name.1];
+                        self.removing = REMOVE_CONTENTS
+                            .iter()
+                            .copied()
+                            .find(|element| name.eq_ignore_ascii_case(element.as_bytes()));
+                    }
+                    pos = tag.end;
+                }
+                Scan::Markup { end } => pos = end,
+                Scan::Incomplete if !last => {
+                    self.pending.extend_from_slice(&html[start..]);
+                    return;
+                }
+                Scan::Incomplete | Scan::Text => {
+                    out.extend_from_slice(b"&lt;");
+                    pos = start + 1;
+                }
+            }
+        }
+    }
+}
+
+/// Offset of the `</element` end tag in `html`, matched case-insensitively.
+fn find_end_tag(html: &[u8], element: &str) -> Option<usize> {
+    let mut pos = 0;
+    while let Some(offset) = memmem::find(&html[pos..], b"</") {
+        let name_start = pos + offset + 2;
+        let name_end = name_start + element.len();
+        if html
+            .get(name_start..name_end)
+            .is_some_and(|name| name.eq_ignore_ascii_case(element.as_bytes()))
+            && html
+                .get(name_end)
+                .is_some_and(|&b| is_space(b) || b == b'>' || b == b'/')
+        {
+            return Some(pos + offset);
+        }
+        pos = name_start;
+    }
+    None
+}
@@ -0,0 +1,217 @@
+//! Table of contents extraction.
+//!
+//! Headings are collected from the same block events the HTML renderer sees
+//! and their ids come from the renderer's slug tracker, so every
+//! [`TocEntry::id`] matches the `id` attribute emitted under
+//! [`Options::heading_ids`]. An id from an attribute list
+//! ([`Options::attributes`]) is taken as is.
+
+use crate::block::{BlockEvent, BlockParser, attribute_id, attribute_list};
+use crate::footnote::FootnoteStore;
+use crate::inline::{InlineEvent, InlineParser};
+use crate::link_ref::LinkRefStore;
+use crate::render::decode_entities_commonmark;
+use crate::{HeadingIdTracker, HeadingState, Options, strip_front_matter};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// One heading in a table of contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TocEntry {
+    /// Heading level (1-6).
+    pub level: u8,
+    /// Plain heading text with inline markup removed.
+    pub text: String,
+    /// Heading id, identical to the rendered anchor.
+    pub id: String,
+    /// Headings nested below this one.
+    pub children: Vec<TocEntry>,
+}
+
+/// Build a nested table of contents.
+///
+/// A heading becomes a child of the closest preceding heading with a lower
+/// level, so an `h3` after an `h2` is nested under it. Front matter is
+/// skipped when `options.front_matter` is set.
+///
+/// # Example
+/// ```
+/// let toc = ferromark::table_of_contents("# Guide\n\n## Install\n\n## Usage", &Default::default());
+/// assert_eq!(toc.len(), 1);
+/// assert_eq!(toc[0].id, "guide");
+/// assert_eq!(toc[0].children[1].id, "usage");
+/// ```
+pub fn table_of_contents(input: &str, options: &Options) -> Vec<TocEntry> {
+    let mut flat = table_of_contents_flat(input, options)
+        .into_iter()
+        .peekable();
+    nest_entries(&mut flat, 0)
+}
+
+/// Collect headings in document order without nesting.
+///
+/// Every returned entry has empty `children`, for callers that build their
+/// own hierarchy.
+pub fn table_of_contents_flat(input: &str, options: &Options) -> Vec<TocEntry> {
+    let markdown = strip_front_matter(input, options).as_bytes();
+    let mut parser = BlockParser::new_with_options(markdown, *options);
+    let mut events = Vec::new();
+    parser.parse(&mut events);
+    let link_refs = parser.take_link_refs();
+    let footnote_store = options.footnotes.then(|| parser.take_footnote_store());
+
+    let mut heading_state = HeadingState::new();
+    let mut tracker = HeadingIdTracker::new();
+    let mut inline_parser = InlineParser::new();
+    inline_parser.configure(options);
+    let mut inline_events = Vec::new();
+    let mut entries = Vec::new();
+    let mut attributes = None;
+
+    for event in &events {
+        match event {
+            BlockEvent::Attributes(range) => attributes = Some(range.slice(markdown)),
+            // Attributes of a code block
+            BlockEvent::CodeBlockStart { .. } => attributes = None,
+            BlockEvent::HeadingStart { level } => {
+                heading_state.start();
+                heading_state.level = *level;
+            }
+            BlockEvent::Text(range) if heading_state.in_heading => {
+                heading_state.add_text(range.slice(markdown));
+            }
+            BlockEvent::SoftBreak if heading_state.in_heading => {
+                heading_state.add_soft_break();
+            }
+            BlockEvent::HeadingEnd { level } => {
+                let content = heading_state.finish();
+                let explicit_id = attributes
+                    .take()
+                    .and_then(attribute_list)
+                    .and_then(|list| attribute_id(&list));
+                let id = match explicit_id {
+                    Some(id) => {
+                        tracker.reserve(id);
+                        String::from_utf8_lossy(id).into_owned()
+                    }
+                    None => tracker.make_id(content).to_string(),
+                };
+                let text = plain_text(
+                    content,
+                    &mut inline_parser,
+                    &mut inline_events,
+                    &link_refs,
+                    footnote_store.as_ref(),
+                    options,
+                );
+                entries.push(TocEntry {
+                    level: *level,
+                    text,
+                    id,
+                    children: Vec::new(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    entries
+}
+
+/// Move consecutive entries deeper than `parent_level` into a subtree.
+pub(crate) fn nest_entries(
+    flat: &mut core::iter::Peekable<alloc::vec::IntoIter<TocEntry>>,
+    parent_level: u8,
+) -> Vec<TocEntry> {
+    let mut nested = Vec::new();
+    while let Some(mut entry) = flat.next_if(|entry| entry.level > parent_level) {
+        entry.children = nest_entries(flat, entry.level);
+        nested.push(entry);
+    }
+    nested
+}
+
+/// Render heading content to plain text, dropping inline markup.
+pub(crate) fn plain_text(
+    content: &[u8],
+    inline_parser: &mut InlineParser,
+    inline_events: &mut Vec<InlineEvent>,
+    link_refs: &LinkRefStore,
+    footnote_store: Option<&FootnoteStore>,
+    options: &Options,
+) -> String {
+    inline_events.clear();
+    inline_parser.parse_with_options(
+        content,
+        options.allow_link_refs.then_some(link_refs),
+        options.inline_html(),
+        options.strikethrough,
+        options.highlight,
+        options.superscript,
+        options.subscript,
+        options.autolink_literals,
+        options.math,
+        footnote_store,
+        inline_events,
+    );
+
+    let mut text = String::with_capacity(content.len());
+    for event in inline_events.iter() {
+        match event {
+            InlineEvent::Text(range) => {
+                let raw = String::from_utf8_lossy(range.slice(content));
+                text.push_str(&decode_entities_commonmark(&raw));
+            }
+            InlineEvent::Code(range)
+            | InlineEvent::MathInline(range)
+            | InlineEvent::MathDisplay(range)
+            | InlineEvent::Autolink { url: range, .. }
+            | InlineEvent::AutolinkLiteral { url: range, .. }
+            | InlineEvent::WikiLink { label: range, .. } => {
+                text.push_str(&String::from_utf8_lossy(range.slice(content)));
+            }
+            InlineEvent::EscapedChar(ch) => text.push(char::from(*ch)),
+            InlineEvent::Emoji(glyph) | InlineEvent::SmartPunctuation(glyph) => {
+                text.push_str(glyph)
+            }
+            InlineEvent::SoftBreak | InlineEvent::HardBreak => text.push(' '),
+            _ => {}
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: u8, id: &str, children: Vec<TocEntry>) -> TocEntry {
+        TocEntry {
+            level,
+            text: id.to_string(),
+            id: id.to_string(),
+            children,
+        }
+    }
+
+    #[test]
+    fn nests_by_level_and_skips_levels() {
+        let flat = vec![
+            entry(1, "a", vec![]),
+            entry(3, "b", vec![]),
+            entry(2, "c", vec![]),
+            entry(1, "d", vec![]),
+        ];
+        let nested = nest_entries(&mut flat.into_iter().peekable(), 0);
+
+        assert_eq!(
+            nested,
+            vec![
+                entry(1, "a", vec![entry(3, "b", vec![]), entry(2, "c", vec![])]),
+                entry(1, "d", vec![]),
+            ]
+        );
+    }
+}
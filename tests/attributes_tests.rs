@@ -0,0 +1,144 @@
+use ferromark::{Options, parse_full, table_of_contents_flat, to_html_with_options, to_markdown};
+
+fn options() -> Options {
+    Options {
+        attributes: true,
+        ..Options::default()
+    }
+}
+
+fn html(input: &str) -> String {
+    to_html_with_options(input, &options())
+}
+
+#[test]
+fn heading_id_overrides_generated_id() {
+    assert_eq!(html("# Title {#intro}\n"), "<h1 id=\"intro\">Title</h1>\n");
+    // After a closing sequence
+    assert_eq!(
+        html("## Title ## {#intro}\n"),
+        "<h2 id=\"intro\">Title</h2>\n"
+    );
+    assert_eq!(
+        html("Title {#intro}\n=====\n"),
+        "<h1 id=\"intro\">Title</h1>\n"
+    );
+    // Without heading ids, only an explicit id is written
+    let options = Options {
+        heading_ids: false,
+        ..options()
+    };
+    assert_eq!(
+        to_html_with_options("# A {#a}\n\n# B {.b}\n", &options),
+        "<h1 id=\"a\">A</h1>\n<h1 class=\"b\">B</h1>\n"
+    );
+}
+
+#[test]
+fn heading_classes_and_pairs() {
+    assert_eq!(
+        html("# Title {.lead .wide}\n"),
+        "<h1 id=\"title\" class=\"lead wide\">Title</h1>\n"
+    );
+    assert_eq!(
+        html("Two\nlines {#x .a title=\"Read me\" level=2}\n---\n"),
+        "<h2 id=\"x\" class=\"a\" data-title=\"Read me\" data-level=\"2\">Two\nlines</h2>\n"
+    );
+    // Values are escaped
+    assert_eq!(
+        html("# T {k=\"<a href='x'>\"}\n"),
+        "<h1 id=\"t\" data-k=\"&lt;a href=&#39;x&#39;&gt;\">T</h1>\n"
+    );
+    // Single quotes work like double quotes
+    assert_eq!(
+        html("# T {k1=\"a b\" k2='c'}\n"),
+        "<h1 id=\"t\" data-k1=\"a b\" data-k2=\"c\">T</h1>\n"
+    );
+}
+
+#[test]
+fn code_fence_attributes() {
+    assert_eq!(
+        html("```rust {#main .numbered start=\"10\"}\nfn main() {}\n```\n"),
+        "<pre id=\"main\" data-start=\"10\"><code class=\"language-rust numbered\">fn main() {}\n</code></pre>\n"
+    );
+    assert_eq!(
+        html("~~~ {.plain}\ntext\n~~~\n"),
+        "<pre><code class=\"plain\">text\n</code></pre>\n"
+    );
+    let options = Options {
+        code_info_meta: true,
+        ..options()
+    };
+    assert_eq!(
+        to_html_with_options("```js title=a.js {#a}\nx\n```\n", &options),
+        "<pre id=\"a\"><code class=\"language-js\" data-meta=\"title=a.js\">x\n</code></pre>\n"
+    );
+}
+
+#[test]
+fn invalid_or_escaped_braces_stay_literal() {
+    assert_eq!(
+        html("# Title \\{#id}\n"),
+        "<h1 id=\"title-id\">Title {#id}</h1>\n"
+    );
+    assert_eq!(
+        html("# Set {of words}\n"),
+        "<h1 id=\"set-of-words\">Set {of words}</h1>\n"
+    );
+    assert_eq!(
+        html("# Title{#id}\n"),
+        "<h1 id=\"titleid\">Title{#id}</h1>\n"
+    );
+    assert_eq!(html("# A {#id} b\n"), "<h1 id=\"a-id-b\">A {#id} b</h1>\n");
+    assert_eq!(
+        html("```js {.a\"b}\nx\n```\n"),
+        "<pre><code class=\"language-js\">x\n</code></pre>\n"
+    );
+}
+
+#[test]
+fn disabled_by_default() {
+    assert_eq!(
+        to_html_with_options("# Title {#intro}\n", &Options::default()),
+        "<h1 id=\"title-intro\">Title {#intro}</h1>\n"
+    );
+}
+
+#[test]
+fn table_of_contents_uses_explicit_ids() {
+    let ids: Vec<String> =
+        table_of_contents_flat("# A {#first}\n\n```sh {#code}\n```\n\n# A\n", &options())
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+    assert_eq!(ids, ["first", "a"]);
+}
+
+#[test]
+fn explicit_ids_reserve_generated_slugs() {
+    let input = "# Intro {#setup}\n\n## Setup\n\n## Setup\n";
+    assert_eq!(
+        html(input),
+        "<h1 id=\"setup\">Intro</h1>\n<h2 id=\"setup-1\">Setup</h2>\n<h2 id=\"setup-2\">Setup</h2>\n"
+    );
+    let ids: Vec<String> = table_of_contents_flat(input, &options())
+        .into_iter()
+        .map(|entry| entry.id)
+        .collect();
+    assert_eq!(ids, ["setup", "setup-1", "setup-2"]);
+    let headings = parse_full(input, &options()).headings;
+    assert_eq!(headings[0].id, "setup");
+    let ids: Vec<&str> = headings[0]
+        .children
+        .iter()
+        .map(|entry| entry.id.as_str())
+        .collect();
+    assert_eq!(ids, ["setup-1", "setup-2"]);
+}
+
+#[test]
+fn markdown_output_keeps_attributes() {
+    let input = "# Title {#intro .lead}\n\n```rust {#main k=\"v w\"}\nfn main() {}\n```\n";
+    assert_eq!(to_markdown(input, &options()), input);
+}
@@ -0,0 +1,80 @@
+use ferromark::{BlockEvent, BlockParser, Options};
+use serde::Deserialize;
+use std::fs;
+
+#[derive(Deserialize)]
+struct SpecExample {
+    markdown: String,
+}
+
+fn parsed(input: &str, options: Options) -> Vec<BlockEvent> {
+    let mut events = Vec::new();
+    BlockParser::new_with_options(input.as_bytes(), options).parse(&mut events);
+    events
+}
+
+fn streamed(input: &str, options: Options) -> Vec<BlockEvent> {
+    BlockParser::new_with_options(input.as_bytes(), options)
+        .events()
+        .collect()
+}
+
+#[test]
+fn iterator_matches_parse_on_spec_examples() {
+    let spec: Vec<SpecExample> =
+        serde_json::from_str(&fs::read_to_string("tests/spec.json").unwrap()).unwrap();
+    let extended = Options {
+        footnotes: true,
+        math: true,
+        callouts: true,
+        ..Options::gfm()
+    };
+    for options in [Options::commonmark(), extended] {
+        for example in &spec {
+            assert_eq!(
+                streamed(&example.markdown, options),
+                parsed(&example.markdown, options),
+                "{:?}",
+                example.markdown
+            );
+        }
+    }
+}
+
+#[test]
+fn list_start_is_yielded_with_final_tightness() {
+    let input = "- a\n- b\n\n- c\n\ntext\n";
+    let events = streamed(input, Options::default());
+    assert!(matches!(
+        events[0],
+        BlockEvent::ListStart { tight: false, .. }
+    ));
+    assert_eq!(events, parsed(input, Options::default()));
+}
+
+#[test]
+fn definitions_are_collected_while_streaming() {
+    let input = "a[^1] [x]\n\n[^1]: Note.\n\n[x]: /x\n\n- [^2]: In a list.\n";
+    let options = Options {
+        footnotes: true,
+        ..Options::default()
+    };
+    let mut parser = BlockParser::new_with_options(input.as_bytes(), options);
+    let events: Vec<_> = parser.events().collect();
+    assert_eq!(events, parsed(input, options));
+    assert_eq!(parser.take_footnote_store().len(), 2);
+    assert!(parser.take_link_refs().get_index("x").is_some());
+}
+
+#[test]
+fn events_are_yielded_before_the_input_is_parsed() {
+    // The first paragraph closes at the first blank line; the rest of the
+    // document is only parsed as later events are pulled
+    let input = format!("first\n\n{}", "- item\n".repeat(10_000));
+    let mut parser = BlockParser::new(input.as_bytes());
+    let mut events = parser.events();
+    assert_eq!(events.next(), Some(BlockEvent::ParagraphStart));
+    assert!(matches!(events.next(), Some(BlockEvent::Text(_))));
+    assert_eq!(events.next(), Some(BlockEvent::ParagraphEnd));
+    assert_eq!(events.count(), parsed(&input, Options::default()).len() - 3);
+}
@@ -0,0 +1,67 @@
+use ferromark::{Options, RenderPolicy, to_html_with_options};
+
+fn cite_options() -> Options {
+    Options {
+        blockquote_cite_url: true,
+        ..Options::default()
+    }
+}
+
+#[test]
+fn cite_line_becomes_blockquote_attribute() {
+    assert_eq!(
+        to_html_with_options(
+            "> [cite:https://example.com/talk]\n> Quoted words.",
+            &cite_options()
+        ),
+        "<blockquote cite=\"https://example.com/talk\">\n<p>Quoted words.</p>\n</blockquote>\n"
+    );
+    // The URL is encoded like a link destination
+    assert_eq!(
+        to_html_with_options("> [cite:/a?b=\"c\"&d]\n> q", &cite_options()),
+        "<blockquote cite=\"/a?b=%22c%22&amp;d\">\n<p>q</p>\n</blockquote>\n"
+    );
+    // Nested quotes take their own citation
+    assert_eq!(
+        to_html_with_options("> > [cite:/a]\n> > b", &cite_options()),
+        "<blockquote>\n<blockquote cite=\"/a\">\n<p>b</p>\n</blockquote>\n</blockquote>\n"
+    );
+}
+
+#[test]
+fn cite_line_must_stand_alone_at_the_start() {
+    for markdown in [
+        "> [cite:/a] text",
+        "> [cite:]",
+        "> [cite:/a b]",
+        "> text\n> [cite:/a]",
+    ] {
+        let html = to_html_with_options(markdown, &cite_options());
+        assert!(!html.contains("cite=") && html.contains("[cite:"), "{html}");
+    }
+    // Callout markers take precedence
+    let callout = to_html_with_options("> [!NOTE]\n> [cite:/a]", &cite_options());
+    assert!(!callout.contains("cite=") && callout.contains("markdown-alert-note"));
+}
+
+#[test]
+fn cite_line_is_text_when_disabled() {
+    assert_eq!(
+        to_html_with_options("> [cite:/a]\n> q", &Options::default()),
+        "<blockquote>\n<p>[cite:/a]\nq</p>\n</blockquote>\n"
+    );
+}
+
+#[test]
+fn unsafe_cite_url_is_dropped_unless_trusted() {
+    let markdown = "> [cite:javascript:alert(1)]\n> q";
+    assert_eq!(
+        to_html_with_options(markdown, &cite_options()),
+        "<blockquote>\n<p>q</p>\n</blockquote>\n"
+    );
+    let trusted = Options {
+        render_policy: RenderPolicy::Trusted,
+        ..cite_options()
+    };
+    assert!(to_html_with_options(markdown, &trusted).contains("cite=\"javascript:alert(1)\""));
+}
@@ -0,0 +1,68 @@
+//! `CachedRenderer`: cache hits, option sensitivity, and LRU eviction.
+
+use ferromark::{CachedRenderer, Options, to_html, to_html_with_options};
+
+#[test]
+fn repeated_render_hits_the_cache() {
+    let mut renderer = CachedRenderer::new(8);
+    let input = "# Card\n\nShared **component** text.";
+
+    let first = renderer.render(input, &Options::default()).to_owned();
+    let second = renderer.render(input, &Options::default()).to_owned();
+
+    assert_eq!(first, to_html(input));
+    assert_eq!(second, first);
+    assert_eq!((renderer.hits(), renderer.misses()), (1, 1));
+    assert_eq!(renderer.len(), 1);
+}
+
+#[test]
+fn options_are_part_of_the_key() {
+    let mut renderer = CachedRenderer::new(8);
+    let input = "~~gone~~ ==marked==";
+    let highlight = Options {
+        highlight: true,
+        ..Options::default()
+    };
+
+    assert_eq!(renderer.render(input, &Options::default()), to_html(input));
+    assert_eq!(
+        renderer.render(input, &highlight),
+        to_html_with_options(input, &highlight)
+    );
+    assert_eq!((renderer.hits(), renderer.misses()), (0, 2));
+}
+
+#[test]
+fn least_recently_used_entry_is_evicted() {
+    let mut renderer = CachedRenderer::new(2);
+    let options = Options::default();
+
+    renderer.render("a", &options);
+    renderer.render("b", &options);
+    renderer.render("a", &options); // `b` is now least recently used
+    renderer.render("c", &options); // evicts `b`
+    assert_eq!(renderer.len(), 2);
+    assert_eq!((renderer.hits(), renderer.misses()), (1, 3));
+
+    assert_eq!(renderer.render("a", &options), "<p>a</p>\n");
+    assert_eq!(renderer.render("c", &options), "<p>c</p>\n");
+    assert_eq!(renderer.hits(), 3);
+    assert_eq!(renderer.render("b", &options), "<p>b</p>\n");
+    assert_eq!(renderer.misses(), 4);
+}
+
+#[test]
+fn zero_capacity_still_renders() {
+    let mut renderer = CachedRenderer::new(0);
+    assert_eq!(renderer.capacity(), 1);
+    assert_eq!(renderer.render("x", &Options::default()), "<p>x</p>\n");
+    assert_eq!(renderer.render("y", &Options::default()), "<p>y</p>\n");
+    assert_eq!(renderer.render("y", &Options::default()), "<p>y</p>\n");
+    assert_eq!((renderer.hits(), renderer.misses()), (1, 2));
+
+    renderer.clear();
+    assert!(renderer.is_empty());
+    renderer.render("y", &Options::default());
+    assert_eq!(renderer.misses(), 3);
+}
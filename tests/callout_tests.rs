@@ -1,4 +1,4 @@
-use ferromark::{Options, to_html, to_html_with_options};
+use ferromark::{CalloutTitles, Options, to_html, to_html_with_options};
 
 fn html(input: &str) -> String {
     to_html(input)
@@ -173,3 +173,198 @@ fn callout_with_code_block() {
     assert!(out.contains("<code>"));
     assert!(out.contains("code"));
 }
+
+// --- ARIA roles ---
+
+fn html_aria(input: &str) -> String {
+    let opts = Options {
+        callout_aria: true,
+        ..Options::default()
+    };
+    to_html_with_options(input, &opts)
+}
+
+#[test]
+fn callout_aria_roles_per_type() {
+    for (marker, suffix, role) in [
+        ("NOTE", "note", "note"),
+        ("TIP", "tip", "note"),
+        ("IMPORTANT", "important", "note"),
+        ("WARNING", "warning", "alert"),
+        ("CAUTION", "caution", "note"),
+    ] {
+        let out = html_aria(&format!("> [!{marker}]\n> Body."));
+        let expected =
+            format!("<div class=\"markdown-alert markdown-alert-{suffix}\" role=\"{role}\">\n");
+        assert!(out.starts_with(&expected), "{marker}: {out}");
+    }
+}
+
+#[test]
+fn callout_aria_disabled_by_default() {
+    let out = html("> [!WARNING]\n> Careful.");
+    assert!(!out.contains("role="));
+}
+
+#[test]
+fn callout_aria_leaves_blockquotes_alone() {
+    let out = html_aria("> plain quote");
+    assert_eq!(out, "<blockquote>\n<p>plain quote</p>\n</blockquote>\n");
+}
+
+#[test]
+fn callout_aria_requires_callouts() {
+    let opts = Options {
+        callouts: false,
+        callout_aria: true,
+        ..Options::default()
+    };
+    let out = to_html_with_options("> [!NOTE]\n> Body.", &opts);
+    assert!(!out.contains("role="));
+}
+
+// --- Collapsible callouts ---
+
+fn html_collapsible(input: &str) -> String {
+    let opts = Options {
+        collapsible_callouts: true,
+        ..Options::default()
+    };
+    to_html_with_options(input, &opts)
+}
+
+#[test]
+fn collapsible_callout_collapsed_with_title() {
+    assert_eq!(
+        html_collapsible("> [!NOTE]- Title\n> body"),
+        "<details class=\"markdown-alert markdown-alert-note\">\n\
+         <summary class=\"markdown-alert-title\">Title</summary>\n\
+         <p>body</p>\n</details>\n"
+    );
+}
+
+#[test]
+fn collapsible_callout_expanded_uses_default_title() {
+    assert_eq!(
+        html_collapsible("> [!TIP]+\n> body"),
+        "<details class=\"markdown-alert markdown-alert-tip\" open>\n\
+         <summary class=\"markdown-alert-title\">Tip</summary>\n\
+         <p>body</p>\n</details>\n"
+    );
+}
+
+#[test]
+fn collapsible_callout_title_is_inline_markdown() {
+    let out = html_collapsible("> [!WARNING]-  *Hot*  <b> \n>\n> - item");
+    assert!(
+        out.contains("<summary class=\"markdown-alert-title\"><em>Hot</em>  &lt;b&gt;</summary>\n")
+    );
+    assert!(out.contains("<ul>\n<li>item</li>\n</ul>\n</details>\n"));
+}
+
+#[test]
+fn collapsible_callout_leaves_plain_callouts_alone() {
+    let out = html_collapsible("> [!NOTE]\n> plain");
+    assert_eq!(
+        out,
+        "<div class=\"markdown-alert markdown-alert-note\">\n\
+         <p class=\"markdown-alert-title\">Note</p>\n<p>plain</p>\n</div>\n"
+    );
+}
+
+#[test]
+fn collapsible_callout_requires_option() {
+    let out = html("> [!NOTE]- Title\n> body");
+    assert_eq!(
+        out,
+        "<blockquote>\n<p>[!NOTE]- Title\nbody</p>\n</blockquote>\n"
+    );
+}
+
+#[test]
+fn collapsible_callout_with_aria_role() {
+    let opts = Options {
+        collapsible_callouts: true,
+        callout_aria: true,
+        ..Options::default()
+    };
+    let out = to_html_with_options("> [!WARNING]+ Heads up\n> body", &opts);
+    assert!(out.starts_with(
+        "<details class=\"markdown-alert markdown-alert-warning\" role=\"alert\" open>\n"
+    ));
+}
+
+// --- Title text ---
+
+const GERMAN: CalloutTitles = CalloutTitles {
+    note: "Hinweis",
+    tip: "Tipp",
+    important: "Wichtig",
+    warning: "Warnung",
+    caution: "Vorsicht & Achtung",
+};
+
+#[test]
+fn callout_titles_are_configurable() {
+    let opts = Options {
+        callout_titles: GERMAN,
+        collapsible_callouts: true,
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("> [!NOTE]\n> body", &opts),
+        "<div class=\"markdown-alert markdown-alert-note\">\n\
+         <p class=\"markdown-alert-title\">Hinweis</p>\n<p>body</p>\n</div>\n"
+    );
+    assert!(
+        to_html_with_options("> [!CAUTION]\n> body", &opts)
+            .contains("markdown-alert-title\">Vorsicht &amp; Achtung</p>")
+    );
+    assert!(
+        to_html_with_options("> [!TIP]-\n> body", &opts)
+            .contains("<summary class=\"markdown-alert-title\">Tipp</summary>")
+    );
+}
+
+#[test]
+fn empty_callout_title_is_omitted() {
+    let opts = Options {
+        callout_titles: CalloutTitles {
+            note: "",
+            ..CalloutTitles::ENGLISH
+        },
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("> [!NOTE]\n> body", &opts),
+        "<div class=\"markdown-alert markdown-alert-note\">\n<p>body</p>\n</div>\n"
+    );
+}
+
+#[test]
+fn custom_callout_title_replaces_default() {
+    let opts = Options {
+        callout_custom_titles: true,
+        callout_titles: GERMAN,
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("> [!NOTE] Read *this* first  \n> body", &opts),
+        "<div class=\"markdown-alert markdown-alert-note\">\n\
+         <p class=\"markdown-alert-title\">Read <em>this</em> first</p>\n<p>body</p>\n</div>\n"
+    );
+    // Whitespace alone keeps the configured title; a title needs a space
+    assert!(
+        to_html_with_options("> [!NOTE]  \n> body", &opts)
+            .contains("markdown-alert-title\">Hinweis</p>")
+    );
+    assert!(to_html_with_options("> [!NOTE]x\n> body", &opts).starts_with("<blockquote>"));
+}
+
+#[test]
+fn custom_callout_title_requires_option() {
+    assert_eq!(
+        html("> [!NOTE] Custom Title\n> body"),
+        "<blockquote>\n<p>[!NOTE] Custom Title\nbody</p>\n</blockquote>\n"
+    );
+}
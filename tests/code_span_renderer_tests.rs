@@ -0,0 +1,64 @@
+use ferromark::{CodeSpanRenderer, Options, to_html, to_html_with_options};
+
+fn with_renderer(renderer: fn(&str) -> Option<String>) -> Options {
+    Options {
+        code_span_renderer: Some(CodeSpanRenderer(renderer)),
+        ..Options::default()
+    }
+}
+
+/// Echo the content the callback sees.
+fn echo(content: &str) -> Option<String> {
+    Some(format!("[{content}]"))
+}
+
+fn copy_button(content: &str) -> Option<String> {
+    let escaped = content.replace('&', "&amp;").replace('<', "&lt;");
+    Some(format!(
+        "<span class=\"copy\"><code>{escaped}</code><button>Copy</button></span>"
+    ))
+}
+
+#[test]
+fn callback_sees_normalized_content() {
+    let options = with_renderer(echo);
+    // One surrounding space is stripped and line endings become spaces
+    assert_eq!(to_html_with_options("` a `", &options), "<p>[a]</p>\n");
+    assert_eq!(
+        to_html_with_options("``a\nb  \nc``", &options),
+        "<p>[a b   c]</p>\n"
+    );
+    // Backslashes are literal inside code spans
+    assert_eq!(to_html_with_options("`a\\*`", &options), "<p>[a\\*]</p>\n");
+}
+
+#[test]
+fn callback_output_replaces_code_element() {
+    let options = with_renderer(copy_button);
+    assert_eq!(
+        to_html_with_options("Run `a<b`.", &options),
+        "<p>Run <span class=\"copy\"><code>a&lt;b</code><button>Copy</button></span>.</p>\n"
+    );
+    assert!(
+        to_html_with_options("# `x`\n\n| `y` |\n|---|", &options)
+            .matches("<button>")
+            .count()
+            == 2
+    );
+}
+
+#[test]
+fn none_keeps_default_rendering() {
+    let options = with_renderer(|_| None);
+    let input = "`<a>` and ``b ` c``";
+    assert_eq!(to_html_with_options(input, &options), to_html(input));
+}
+
+#[test]
+fn image_alt_text_is_not_passed_to_callback() {
+    let options = with_renderer(echo);
+    assert_eq!(
+        to_html_with_options("![`x`](/i.png)", &options),
+        "<p><img src=\"/i.png\" alt=\"x\" /></p>\n"
+    );
+}
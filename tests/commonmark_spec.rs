@@ -2,7 +2,7 @@
 //!
 //! Runs tests from the CommonMark spec.json file to track compliance.
 
-use ferromark::{Options, to_html_with_options};
+use ferromark::{Options, RenderPolicy, to_html_with_options};
 use serde::Deserialize;
 use std::fs;
 
@@ -12,6 +12,18 @@ fn spec_to_html(input: &str) -> String {
     to_html_with_options(input, &Options::commonmark())
 }
 
+/// The same configuration with raw HTML passed through, which is what the
+/// spec's expected output assumes.
+fn spec_to_html_trusted(input: &str) -> String {
+    to_html_with_options(
+        input,
+        &Options {
+            render_policy: RenderPolicy::Trusted,
+            ..Options::commonmark()
+        },
+    )
+}
+
 #[derive(Debug, Deserialize)]
 struct SpecTest {
     markdown: String,
@@ -195,6 +207,18 @@ fn commonmark_spec_report() {
     }
 }
 
+/// Pure parser conformance: with raw HTML trusted, `Options::commonmark()`
+/// reproduces every spec example.
+#[test]
+fn commonmark_spec_trusted_passes_every_example() {
+    let failures: Vec<String> = load_spec_tests()
+        .iter()
+        .filter(|test| spec_to_html_trusted(&test.markdown) != test.html)
+        .map(|test| format!("{} ({})", test.example, test.section))
+        .collect();
+    assert!(failures.is_empty(), "failing examples: {failures:?}");
+}
+
 /// Run only IN-SCOPE spec tests and report results.
 /// This excludes intentionally unsupported features like HTML blocks, setext headings, etc.
 /// Also excludes link/image tests that use reference definitions.
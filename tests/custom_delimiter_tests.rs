@@ -0,0 +1,141 @@
+use ferromark::{CustomDelimiter, Options, to_html_with_options};
+
+const REGISTRY: &[CustomDelimiter] = &[
+    CustomDelimiter {
+        open: "^",
+        close: "^",
+        tag: "sup",
+    },
+    CustomDelimiter {
+        open: "==",
+        close: "==",
+        tag: "mark",
+    },
+];
+
+fn custom_html(input: &str) -> String {
+    to_html_with_options(
+        input,
+        &Options {
+            custom_delimiters: REGISTRY,
+            ..Options::default()
+        },
+    )
+}
+
+#[test]
+fn registered_delimiters_render_their_tags() {
+    assert_eq!(custom_html("2^10^"), "<p>2<sup>10</sup></p>\n");
+    assert_eq!(
+        custom_html("a ==marked== word"),
+        "<p>a <mark>marked</mark> word</p>\n"
+    );
+}
+
+#[test]
+fn registry_matches_builtin_extensions() {
+    let builtin = Options {
+        superscript: true,
+        highlight: true,
+        ..Options::default()
+    };
+    for input in ["x^2^ and ==y==", "==a ^b^ c==", "^ not ^ and == not =="] {
+        assert_eq!(
+            custom_html(input),
+            to_html_with_options(input, &builtin),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn custom_delimiters_nest_with_builtins() {
+    assert_eq!(
+        custom_html("==*em* and **strong**=="),
+        "<p><mark><em>em</em> and <strong>strong</strong></mark></p>\n"
+    );
+    assert_eq!(
+        custom_html("*a ==b==*"),
+        "<p><em>a <mark>b</mark></em></p>\n"
+    );
+    assert_eq!(
+        custom_html("[==link==](/url) ==[x](/y)=="),
+        "<p><a href=\"/url\"><mark>link</mark></a> <mark><a href=\"/y\">x</a></mark></p>\n"
+    );
+}
+
+#[test]
+fn builtins_win_on_overlap() {
+    // Built-in highlight claims the `==` pairs before the registry sees them
+    let both = Options {
+        highlight: true,
+        custom_delimiters: &[CustomDelimiter {
+            open: "==",
+            close: "==",
+            tag: "ins",
+        }],
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("==x==", &both),
+        "<p><mark>x</mark></p>\n"
+    );
+
+    // Code spans, escapes, and link destinations are never scanned
+    assert_eq!(custom_html("`^a^` \\^b^"), "<p><code>^a^</code> ^b^</p>\n");
+    assert_eq!(
+        custom_html("[x](/a^b^c)"),
+        "<p><a href=\"/a^b^c\">x</a></p>\n"
+    );
+
+    // A custom pair may not cross emphasis
+    assert_eq!(custom_html("^a *b^ c*"), "<p>^a <em>b^ c</em></p>\n");
+}
+
+#[test]
+fn earlier_entries_take_precedence() {
+    let registry: &'static [CustomDelimiter] = &[
+        CustomDelimiter {
+            open: "++",
+            close: "++",
+            tag: "ins",
+        },
+        CustomDelimiter {
+            open: "+",
+            close: "+",
+            tag: "small",
+        },
+    ];
+    let options = Options {
+        custom_delimiters: registry,
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("++a +b+ c++", &options),
+        "<p><ins>a <small>b</small> c</ins></p>\n"
+    );
+}
+
+#[test]
+fn asymmetric_delimiters_and_alt_text() {
+    let options = Options {
+        custom_delimiters: &[CustomDelimiter {
+            open: "{+",
+            close: "+}",
+            tag: "ins",
+        }],
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("{+ added +} ![{+alt+}](/i.png)", &options),
+        "<p><ins> added </ins> <img src=\"/i.png\" alt=\"alt\" /></p>\n"
+    );
+}
+
+#[test]
+fn no_registry_leaves_text_unchanged() {
+    assert_eq!(
+        to_html_with_options("2^10^ ==x==", &Options::default()),
+        "<p>2^10^ ==x==</p>\n"
+    );
+}
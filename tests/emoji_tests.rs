@@ -0,0 +1,87 @@
+use ferromark::{Options, to_html_with_options};
+
+fn emoji_html(input: &str) -> String {
+    to_html_with_options(
+        input,
+        &Options {
+            emoji: true,
+            autolink_literals: true,
+            heading_ids: false,
+            ..Options::default()
+        },
+    )
+}
+
+#[test]
+fn basic_shortcode() {
+    assert_eq!(emoji_html("Hi :smile:!"), "<p>Hi \u{1F604}!</p>\n");
+}
+
+#[test]
+fn adjacent_shortcodes() {
+    assert_eq!(
+        emoji_html(":+1::tada: :-1:"),
+        "<p>\u{1F44D}\u{1F389} \u{1F44E}</p>\n"
+    );
+}
+
+#[test]
+fn unknown_shortcode_is_verbatim() {
+    assert_eq!(
+        emoji_html(":not_a_real_emoji: :rocket:"),
+        "<p>:not_a_real_emoji: \u{1F680}</p>\n"
+    );
+}
+
+#[test]
+fn shortcode_inside_emphasis_and_heading() {
+    assert_eq!(emoji_html("*so :fire:*"), "<p><em>so \u{1F525}</em></p>\n");
+    assert_eq!(emoji_html("# Ship :rocket:"), "<h1>Ship \u{1F680}</h1>\n");
+}
+
+#[test]
+fn code_spans_are_not_expanded() {
+    assert_eq!(
+        emoji_html("`:smile:` :smile:"),
+        "<p><code>:smile:</code> \u{1F604}</p>\n"
+    );
+}
+
+#[test]
+fn urls_are_not_expanded() {
+    assert_eq!(
+        emoji_html("<http://x.test/:smile:>"),
+        "<p><a href=\"http://x.test/:smile:\">http://x.test/:smile:</a></p>\n"
+    );
+    assert_eq!(
+        emoji_html("[a](/p/:smile:)"),
+        "<p><a href=\"/p/:smile:\">a</a></p>\n"
+    );
+    let plain = to_html_with_options(
+        "see http://x.test/:smile: now",
+        &Options {
+            emoji: true,
+            ..Options::default()
+        },
+    );
+    assert_eq!(plain, "<p>see http://x.test/:smile: now</p>\n");
+}
+
+#[test]
+fn colons_after_word_characters_are_not_delimiters() {
+    assert_eq!(emoji_html("at 10:30:00"), "<p>at 10:30:00</p>\n");
+    assert_eq!(emoji_html("key:smile:"), "<p>key:smile:</p>\n");
+}
+
+#[test]
+fn escaped_colon_is_not_a_delimiter() {
+    assert_eq!(emoji_html("\\:smile:"), "<p>:smile:</p>\n");
+}
+
+#[test]
+fn emoji_disabled_by_default() {
+    assert_eq!(
+        to_html_with_options(":smile:", &Options::default()),
+        "<p>:smile:</p>\n"
+    );
+}
@@ -0,0 +1,181 @@
+use ferromark::{ExternalLinkPredicate, ExternalLinks, Options, to_html_with_options};
+
+const SITE: ExternalLinks = ExternalLinks {
+    base_host: Some("example.com"),
+    ..ExternalLinks::NEW_TAB
+};
+
+fn render(markdown: &str, external_links: ExternalLinks) -> String {
+    to_html_with_options(
+        markdown,
+        &Options {
+            external_links: Some(external_links),
+            autolink_literals: true,
+            ..Options::default()
+        },
+    )
+}
+
+const NEW_TAB: &str = " rel=\"noopener noreferrer\" target=\"_blank\"";
+
+#[test]
+fn external_links_get_rel_and_target() {
+    assert_eq!(
+        render("[a](https://docs.rs/x \"T\")", SITE),
+        format!("<p><a href=\"https://docs.rs/x\" title=\"T\"{NEW_TAB}>a</a></p>\n")
+    );
+    assert_eq!(
+        render("[a][r]\n\n[r]: HTTP://Docs.rs", SITE),
+        format!("<p><a href=\"HTTP://Docs.rs\"{NEW_TAB}>a</a></p>\n")
+    );
+    assert_eq!(
+        render("<https://docs.rs> https://docs.rs www.docs.rs", SITE),
+        format!(
+            "<p><a href=\"https://docs.rs\"{NEW_TAB}>https://docs.rs</a> \
+             <a href=\"https://docs.rs\"{NEW_TAB}>https://docs.rs</a> \
+             <a href=\"http://www.docs.rs\"{NEW_TAB}>www.docs.rs</a></p>\n"
+        )
+    );
+}
+
+#[test]
+fn links_to_the_base_host_stay_untouched() {
+    for url in [
+        "https://example.com",
+        "http://EXAMPLE.com:8080/a",
+        "https://user@example.com/a?b#c",
+    ] {
+        assert_eq!(
+            render(&format!("[a]({url})"), SITE),
+            format!("<p><a href=\"{url}\">a</a></p>\n")
+        );
+    }
+    // Other hosts include subdomains
+    assert!(render("[a](https://www.example.com)", SITE).contains(NEW_TAB));
+}
+
+#[test]
+fn relative_links_and_other_schemes_stay_untouched() {
+    for markdown in [
+        "[a](/path)",
+        "[a](#top)",
+        "[a](page.html)",
+        "[a](mailto:me@docs.rs)",
+        "[a](ftp://docs.rs)",
+        "<me@docs.rs>",
+        "me@docs.rs",
+    ] {
+        assert!(
+            !render(markdown, ExternalLinks::NEW_TAB).contains("target="),
+            "{markdown}"
+        );
+    }
+}
+
+#[test]
+fn protocol_relative_links_are_checked_like_absolute_ones() {
+    assert_eq!(
+        render("[a](//evil.com/a)", SITE),
+        format!("<p><a href=\"//evil.com/a\"{NEW_TAB}>a</a></p>\n")
+    );
+    assert_eq!(
+        render("[a](///evil.com) [b](/\\evil.com)", SITE)
+            .matches(NEW_TAB)
+            .count(),
+        2
+    );
+    assert_eq!(
+        render("[a](//EXAMPLE.com:8080/a)", SITE),
+        "<p><a href=\"//EXAMPLE.com:8080/a\">a</a></p>\n"
+    );
+    assert!(render("[a](//example.com)", ExternalLinks::NEW_TAB).contains(NEW_TAB));
+}
+
+#[test]
+fn without_base_host_every_http_link_is_external() {
+    assert!(render("[a](https://example.com)", ExternalLinks::NEW_TAB).contains(NEW_TAB));
+}
+
+#[test]
+fn attributes_are_independent_and_join_ugc_rel() {
+    let rel_only = ExternalLinks {
+        target: None,
+        ..SITE
+    };
+    assert_eq!(
+        render("[a](https://docs.rs)", rel_only),
+        "<p><a href=\"https://docs.rs\" rel=\"noopener noreferrer\">a</a></p>\n"
+    );
+    let target_only = ExternalLinks { rel: None, ..SITE };
+    assert_eq!(
+        render("[a](https://docs.rs)", target_only),
+        "<p><a href=\"https://docs.rs\" target=\"_blank\">a</a></p>\n"
+    );
+
+    let options = Options {
+        external_links: Some(SITE),
+        untrusted_content: true,
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("[a](https://docs.rs) [b](/b)", &options),
+        "<p><a href=\"https://docs.rs\" rel=\"ugc nofollow noopener noreferrer\" \
+         target=\"_blank\">a</a> <a href=\"/b\" rel=\"ugc nofollow\">b</a></p>\n"
+    );
+}
+
+fn same_site(url: &str) -> bool {
+    let host = url.split_once("://").map_or("", |(_, rest)| rest);
+    !(host.starts_with("example.com") || host.starts_with("blog.example.com"))
+}
+
+#[test]
+fn predicate_replaces_the_base_host_check() {
+    let links = ExternalLinks {
+        rel: Some("nofollow noopener"),
+        predicate: Some(ExternalLinkPredicate(same_site)),
+        ..SITE
+    };
+    let rel = " rel=\"nofollow noopener\" target=\"_blank\"";
+    assert_eq!(
+        render(
+            "[a](https://blog.example.com/x) [b](https://docs.rs \"T\") <https://example.com>",
+            links
+        ),
+        format!(
+            "<p><a href=\"https://blog.example.com/x\">a</a> \
+             <a href=\"https://docs.rs\" title=\"T\"{rel}>b</a> \
+             <a href=\"https://example.com\">https://example.com</a></p>\n"
+        )
+    );
+    assert_eq!(
+        render("www.example.com www.docs.rs", links),
+        format!(
+            "<p><a href=\"http://www.example.com\"{rel}>www.example.com</a> \
+             <a href=\"http://www.docs.rs\"{rel}>www.docs.rs</a></p>\n"
+        )
+    );
+}
+
+#[test]
+fn predicate_is_not_asked_about_relative_links() {
+    fn everything(_: &str) -> bool {
+        true
+    }
+    let links = ExternalLinks {
+        predicate: Some(ExternalLinkPredicate(everything)),
+        ..ExternalLinks::NEW_TAB
+    };
+    for markdown in [
+        "[a](/path)",
+        "[a](#top \"Top\")",
+        "[a](page.html)",
+        "<me@docs.rs>",
+    ] {
+        assert!(!render(markdown, links).contains("target="), "{markdown}");
+    }
+    assert_eq!(
+        render("[a](#top \"Top\")", links),
+        "<p><a href=\"#top\" title=\"Top\">a</a></p>\n"
+    );
+}
@@ -1,6 +1,6 @@
 use ferromark::{
-    FencedCodeBlock, FencedCodeRenderer, Options, TrustedHtml, to_html, to_html_into_with_renderer,
-    to_html_with_renderer,
+    FencedCodeBlock, FencedCodeRenderer, Highlighter, Options, TrustedHtml, to_html,
+    to_html_into_with_renderer, to_html_with_options, to_html_with_renderer,
 };
 
 #[derive(Default)]
@@ -121,3 +121,123 @@ fn buffer_api_reuses_the_caller_buffer() {
         "<pre><code class=\"language-txt\">hello\n</code></pre>\n"
     );
 }
+
+#[test]
+fn highlighter_wraps_callback_html_in_code_markup() {
+    let highlight = |language: Option<&str>, code: &str| {
+        Some(format!(
+            "<b>{}</b>|{}",
+            language.unwrap_or("-"),
+            code.replace('<', "&lt;")
+        ))
+    };
+
+    let html = to_html_with_renderer(
+        "```c\\+\\+&amp; extra\n<a>\n  b\n```\n\n```\nplain\n```",
+        &Options::default(),
+        &mut Highlighter::new(&highlight),
+    );
+
+    assert_eq!(
+        html,
+        "<pre><code class=\"language-c++&amp;\"><b>c++&</b>|&lt;a>\n  b\n</code></pre>\n\
+         <pre><code><b>-</b>|plain\n</code></pre>\n"
+    );
+}
+
+#[test]
+fn highlighter_falls_back_to_escaped_output_on_none() {
+    let highlight = |language: Option<&str>, _: &str| (language == Some("rust")).then(String::new);
+
+    let html = to_html_with_renderer(
+        "```python\n<x>\n```\n\n```rust\nfn x() {}\n```",
+        &Options::default(),
+        &mut Highlighter::new(&highlight),
+    );
+
+    assert_eq!(
+        html,
+        "<pre><code class=\"language-python\">&lt;x&gt;\n</code></pre>\n\
+         <pre><code class=\"language-rust\"></code></pre>\n"
+    );
+}
+
+#[test]
+fn highlighter_receives_whole_block_and_skips_indented_code() {
+    let seen = std::cell::RefCell::new(Vec::new());
+    let highlight = |_: Option<&str>, code: &str| {
+        seen.borrow_mut().push(code.to_owned());
+        None
+    };
+
+    to_html_with_renderer(
+        "> ```\n>   one\n> two\n> ```\n\n    indented\n",
+        &Options::default(),
+        &mut Highlighter::new(&highlight),
+    );
+
+    assert_eq!(*seen.borrow(), vec!["  one\ntwo\n".to_owned()]);
+}
+
+fn meta_options() -> Options {
+    Options {
+        code_info_meta: true,
+        ..Options::default()
+    }
+}
+
+#[test]
+fn info_meta_is_exposed_as_escaped_data_attribute() {
+    assert_eq!(
+        to_html_with_options(
+            "```rust,ignore title=\"main.rs\"\nfn main() {}\n```",
+            &meta_options()
+        ),
+        "<pre><code class=\"language-rust,ignore\" data-meta=\"title=&quot;main.rs&quot;\">\
+         fn main() {}\n</code></pre>\n"
+    );
+    assert_eq!(
+        to_html_with_options("~~~ js  a&amp;b \\<x>  \ncode\n~~~", &meta_options()),
+        "<pre><code class=\"language-js\" data-meta=\"a&amp;b &lt;x&gt;\">code\n</code></pre>\n"
+    );
+}
+
+#[test]
+fn info_meta_is_omitted_without_option_or_metadata() {
+    let input = "```rust title=\"main.rs\"\nx\n```";
+    assert_eq!(
+        to_html(input),
+        "<pre><code class=\"language-rust\">x\n</code></pre>\n"
+    );
+    assert_eq!(
+        to_html_with_options("```rust   \nx\n```", &meta_options()),
+        "<pre><code class=\"language-rust\">x\n</code></pre>\n"
+    );
+}
+
+#[test]
+fn info_meta_keeps_backtick_fence_validation() {
+    assert_eq!(
+        to_html_with_options("``` rust `meta`\nx\n```", &meta_options()),
+        "<p>``` rust <code>meta</code>\nx</p>\n<pre><code></code></pre>\n"
+    );
+}
+
+#[test]
+fn renderer_receives_info_meta() {
+    struct MetaRenderer(Vec<Option<String>>);
+    impl FencedCodeRenderer for MetaRenderer {
+        fn render(&mut self, block: FencedCodeBlock<'_>) -> Option<TrustedHtml> {
+            self.0.push(block.meta.map(str::to_owned));
+            None
+        }
+    }
+
+    let mut renderer = MetaRenderer(Vec::new());
+    to_html_with_renderer(
+        "```rust {1,3} &amp;\nx\n```\n\n```rust\ny\n```",
+        &Options::default(),
+        &mut renderer,
+    );
+    assert_eq!(renderer.0, vec![Some("{1,3} &".to_owned()), None]);
+}
@@ -0,0 +1,120 @@
+use ferromark::{Options, to_html_with_options};
+
+fn html(input: &str) -> String {
+    let options = Options {
+        fenced_divs: true,
+        ..Options::default()
+    };
+    to_html_with_options(input, &options)
+}
+
+#[test]
+fn div_with_class() {
+    assert_eq!(
+        html("::: warning\nBe *careful*.\n:::\n"),
+        "<div class=\"warning\">\n<p>Be <em>careful</em>.</p>\n</div>\n"
+    );
+    // Closing colons after the class are optional
+    assert_eq!(
+        html("::: note :::\nText.\n:::\n"),
+        "<div class=\"note\">\n<p>Text.</p>\n</div>\n"
+    );
+    assert_eq!(html(":::\nBare.\n:::\n"), "<div>\n<p>Bare.</p>\n</div>\n");
+}
+
+#[test]
+fn disabled_by_default() {
+    assert_eq!(
+        to_html_with_options("::: warning\nText\n:::\n", &Options::default()),
+        "<p>::: warning\nText\n:::</p>\n"
+    );
+}
+
+#[test]
+fn attribute_lists() {
+    assert_eq!(
+        html("::: {#main .note .wide title=\"Read me\" onclick=alert(1)}\nText.\n:::\n"),
+        "<div id=\"main\" class=\"note wide\" data-title=\"Read me\" data-onclick=\"alert(1)\">\n\
+         <p>Text.</p>\n</div>\n"
+    );
+    // Not a class name or attribute list: stays a paragraph
+    assert_eq!(html("::: two words\n"), "<p>::: two words</p>\n");
+    assert_eq!(html("::: {plain}\n"), "<p>::: {plain}</p>\n");
+}
+
+#[test]
+fn nested_divs() {
+    let expected = "<div class=\"outer\">\n<div class=\"inner\">\n<p>In.</p>\n</div>\n\
+                    <p>Out.</p>\n</div>\n";
+    assert_eq!(
+        html("::::: outer\n::: inner\nIn.\n:::\nOut.\n:::::\n"),
+        expected
+    );
+    // Fences of equal length close the innermost div first
+    assert_eq!(
+        html("::: outer\n::: inner\nIn.\n:::\nOut.\n:::\n"),
+        expected
+    );
+    // A bare fence shorter than the innermost div's opens another div
+    assert_eq!(
+        html(":::: outer\n:::\nIn.\n:::\n::::\n"),
+        "<div class=\"outer\">\n<div>\n<p>In.</p>\n</div>\n</div>\n"
+    );
+}
+
+#[test]
+fn div_content_is_markdown() {
+    assert_eq!(
+        html("::: box\n# Title\n\n- a\n- b\n\n> Quote\n:::\nAfter\n"),
+        "<div class=\"box\">\n<h1 id=\"title\">Title</h1>\n<ul>\n<li>a</li>\n<li>b</li>\n</ul>\n\
+         <blockquote>\n<p>Quote</p>\n</blockquote>\n</div>\n<p>After</p>\n"
+    );
+    // Fences interrupt paragraphs; a fence inside code is code
+    assert_eq!(
+        html("Before\n::: box\nIn\n:::\n"),
+        "<p>Before</p>\n<div class=\"box\">\n<p>In</p>\n</div>\n"
+    );
+    assert_eq!(
+        html("::: box\n```\n:::\n```\n:::\n"),
+        "<div class=\"box\">\n<pre><code>:::\n</code></pre>\n</div>\n"
+    );
+}
+
+#[test]
+fn div_in_list_item() {
+    assert_eq!(
+        html("- item\n\n  ::: box\n  In the item.\n  :::\n- next\n"),
+        "<ul>\n<li>\n<p>item</p>\n<div class=\"box\">\n<p>In the item.</p>\n</div>\n</li>\n\
+         <li>\n<p>next</p>\n</li>\n</ul>\n"
+    );
+    // The closing fence closes lists inside the div
+    assert_eq!(
+        html("::: box\n- a\n  :::\nAfter\n"),
+        "<div class=\"box\">\n<ul>\n<li>a</li>\n</ul>\n</div>\n<p>After</p>\n"
+    );
+}
+
+#[test]
+fn unterminated_divs_close_at_end_of_input() {
+    assert_eq!(
+        html("::: outer\n::: inner\n> Quote\n"),
+        "<div class=\"outer\">\n<div class=\"inner\">\n<blockquote>\n<p>Quote</p>\n</blockquote>\n\
+         </div>\n</div>\n"
+    );
+}
+
+#[test]
+fn fences_with_trailing_whitespace() {
+    for space in [" ", "\t"] {
+        assert_eq!(
+            html(&format!(":::{space}\nText.\n:::{space}\n")),
+            "<div>\n<p>Text.</p>\n</div>\n",
+            "{space:?}"
+        );
+        assert_eq!(
+            html(&format!(":::note{space}\nText.\n:::{space}")),
+            "<div class=\"note\">\n<p>Text.</p>\n</div>\n",
+            "{space:?}"
+        );
+    }
+}
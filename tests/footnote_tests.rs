@@ -96,6 +96,33 @@ fn duplicate_reference_same_number() {
     );
 }
 
+#[test]
+fn duplicate_reference_distinct_ids_and_backrefs() {
+    let result = render("A[^a] and B[^a].\n\n[^a]: Note.");
+    assert_eq!(
+        result,
+        "<p>A<sup><a href=\"#user-content-fn-a\" id=\"user-content-fnref-a\" data-footnote-ref>1</a></sup> \
+         and B<sup><a href=\"#user-content-fn-a\" id=\"user-content-fnref-a-2\" data-footnote-ref>1</a></sup>.</p>\n\
+         <section data-footnotes class=\"footnotes\">\n<ol>\n<li id=\"user-content-fn-a\">\n<p>Note. \
+         <a href=\"#user-content-fnref-a\" class=\"data-footnote-backref\" aria-label=\"Back to reference 1\">↩</a> \
+         <a href=\"#user-content-fnref-a-2\" class=\"data-footnote-backref\" aria-label=\"Back to reference 1-2\">↩<sup>2</sup></a></p>\n\
+         </li>\n</ol>\n</section>\n"
+    );
+
+    let numeric = to_html_with_options(
+        "A[^a] and B[^a].\n\n[^a]: Note.",
+        &Options {
+            numeric_footnote_ids: true,
+            ..opts()
+        },
+    );
+    assert!(
+        numeric.contains("id=\"user-content-fnref-1-2\"")
+            && numeric.contains("href=\"#user-content-fnref-1-2\""),
+        "Expected numeric suffixed ids: {numeric}"
+    );
+}
+
 // --- Undefined reference renders as literal text ---
 
 #[test]
@@ -229,6 +256,22 @@ fn no_refs_no_section() {
     );
 }
 
+// --- A list inside a definition ends with it ---
+
+#[test]
+fn list_closes_with_its_definition() {
+    // The empty item closes at the blank line, but its list stays open
+    let result = render("x[^1]\n\n[^1]: -\n\ny");
+    assert!(
+        result.starts_with("<p>x<sup>") && result.contains("</sup></p>\n<p>y</p>\n<section"),
+        "List end leaked into the body: {result}"
+    );
+    assert!(
+        result.contains("<li id=\"user-content-fn-1\">\n<ul>\n<li></li>\n</ul>\n</li>"),
+        "List not closed inside the footnote: {result}"
+    );
+}
+
 // --- Case insensitive labels ---
 
 #[test]
@@ -239,3 +282,155 @@ fn case_insensitive_labels() {
         "Labels should be case-insensitive: {result}"
     );
 }
+
+// --- Numeric anchor ids ---
+
+#[test]
+fn numeric_footnote_ids() {
+    let options = Options {
+        numeric_footnote_ids: true,
+        ..opts()
+    };
+    let result = to_html_with_options(
+        "A[^zeta] and B[^note].\n\n[^note]: Second.\n[^zeta]: First.",
+        &options,
+    );
+    assert_eq!(
+        result,
+        "<p>A<sup><a href=\"#user-content-fn-1\" id=\"user-content-fnref-1\" data-footnote-ref>1</a></sup> \
+         and B<sup><a href=\"#user-content-fn-2\" id=\"user-content-fnref-2\" data-footnote-ref>2</a></sup>.</p>\n\
+         <section data-footnotes class=\"footnotes\">\n<ol>\n\
+         <li id=\"user-content-fn-1\">\n<p>First. <a href=\"#user-content-fnref-1\" class=\"data-footnote-backref\" aria-label=\"Back to reference 1\">↩</a></p>\n</li>\n\
+         <li id=\"user-content-fn-2\">\n<p>Second. <a href=\"#user-content-fnref-2\" class=\"data-footnote-backref\" aria-label=\"Back to reference 2\">↩</a></p>\n</li>\n\
+         </ol>\n</section>\n"
+    );
+    // Labels are used by default
+    let result = render("A[^zeta].\n\n[^zeta]: First.");
+    assert!(
+        result.contains("href=\"#user-content-fn-zeta\" id=\"user-content-fnref-zeta\"")
+            && result.contains("<li id=\"user-content-fn-zeta\">")
+            && result.contains("href=\"#user-content-fnref-zeta\""),
+        "Expected label ids: {result}"
+    );
+}
+
+// --- Inline footnotes ---
+
+fn inline_opts() -> Options {
+    Options {
+        inline_footnotes: true,
+        ..opts()
+    }
+}
+
+#[test]
+fn inline_footnotes_are_numbered_with_references() {
+    let result = to_html_with_options(
+        "A^[Inline *note*.] then b[^x] and c^[Two].\n\n[^x]: Ref.",
+        &inline_opts(),
+    );
+    assert_eq!(
+        result,
+        "<p>A<sup><a href=\"#user-content-fn-inline-1\" id=\"user-content-fnref-inline-1\" data-footnote-ref>1</a></sup> \
+         then b<sup><a href=\"#user-content-fn-x\" id=\"user-content-fnref-x\" data-footnote-ref>2</a></sup> \
+         and c<sup><a href=\"#user-content-fn-inline-2\" id=\"user-content-fnref-inline-2\" data-footnote-ref>3</a></sup>.</p>\n\
+         <section data-footnotes class=\"footnotes\">\n<ol>\n\
+         <li id=\"user-content-fn-inline-1\">\n<p>Inline <em>note</em>. <a href=\"#user-content-fnref-inline-1\" class=\"data-footnote-backref\" aria-label=\"Back to reference 1\">↩</a></p>\n</li>\n\
+         <li id=\"user-content-fn-x\">\n<p>Ref. <a href=\"#user-content-fnref-x\" class=\"data-footnote-backref\" aria-label=\"Back to reference 2\">↩</a></p>\n</li>\n\
+         <li id=\"user-content-fn-inline-2\">\n<p>Two <a href=\"#user-content-fnref-inline-2\" class=\"data-footnote-backref\" aria-label=\"Back to reference 3\">↩</a></p>\n</li>\n\
+         </ol>\n</section>\n"
+    );
+}
+
+#[test]
+fn inline_footnote_contains_links_and_brackets() {
+    let result = to_html_with_options("A^[See [a](/b) and [c].]", &inline_opts());
+    assert!(
+        result.contains(
+            "<p>See <a href=\"/b\">a</a> and [c]. <a href=\"#user-content-fnref-inline-1\""
+        ),
+        "Expected the whole note: {result}"
+    );
+}
+
+#[test]
+fn inline_footnote_literals() {
+    for input in ["`^[code]`", "\\^[escaped]", "A^[ ]"] {
+        let result = to_html_with_options(input, &inline_opts());
+        assert!(!result.contains("<sup>"), "Unexpected note: {result}");
+    }
+    // A note inside a note is part of its text
+    let result = to_html_with_options("A^[outer ^[inner]]", &inline_opts());
+    assert!(
+        result.contains("data-footnote-ref>1</a></sup></p>")
+            && result.contains("<p>outer ^[inner] "),
+        "Expected one note: {result}"
+    );
+}
+
+#[test]
+fn inline_footnote_labels_skip_defined_labels() {
+    let result = to_html_with_options(
+        "A[^inline-1] and B^[Note].\n\n[^inline-1]: Defined.",
+        &inline_opts(),
+    );
+    assert!(
+        result.contains("<li id=\"user-content-fn-inline-1\">\n<p>Defined.")
+            && result.contains("<li id=\"user-content-fn-inline-2\">\n<p>Note "),
+        "Expected distinct labels: {result}"
+    );
+}
+
+#[test]
+fn inline_footnotes_need_both_options() {
+    assert_eq!(render("A^[note]"), "<p>A^[note]</p>\n");
+    let options = Options {
+        inline_footnotes: true,
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("A^[note]", &options),
+        "<p>A^[note]</p>\n"
+    );
+}
+
+// --- Section customization ---
+
+#[test]
+fn custom_footnote_prefix_backref_and_title() {
+    let options = Options {
+        footnote_id_prefix: "doc-",
+        footnote_backref_label: "back",
+        footnote_section_title: Some("Notes & refs"),
+        ..opts()
+    };
+    let result = to_html_with_options("A[^x] and [^x].\n\n[^x]: Note.", &options);
+    assert_eq!(
+        result,
+        "<p>A<sup><a href=\"#doc-fn-x\" id=\"doc-fnref-x\" data-footnote-ref>1</a></sup> \
+         and <sup><a href=\"#doc-fn-x\" id=\"doc-fnref-x-2\" data-footnote-ref>1</a></sup>.</p>\n\
+         <section data-footnotes class=\"footnotes\">\n<h2>Notes &amp; refs</h2>\n<ol>\n\
+         <li id=\"doc-fn-x\">\n<p>Note. \
+         <a href=\"#doc-fnref-x\" class=\"data-footnote-backref\" aria-label=\"Back to reference 1\">back</a> \
+         <a href=\"#doc-fnref-x-2\" class=\"data-footnote-backref\" aria-label=\"Back to reference 1-2\">back<sup>2</sup></a></p>\n</li>\n\
+         </ol>\n</section>\n"
+    );
+}
+
+#[test]
+fn custom_footnote_prefix_applies_to_numeric_and_inline_ids() {
+    let options = Options {
+        footnote_id_prefix: "",
+        numeric_footnote_ids: true,
+        inline_footnotes: true,
+        ..opts()
+    };
+    let result = to_html_with_options("A^[Note].", &options);
+    assert!(
+        result.contains("<a href=\"#fn-1\" id=\"fnref-1\" data-footnote-ref>")
+            && result.contains("<li id=\"fn-1\">")
+            && result.contains("<a href=\"#fnref-1\" class=\"data-footnote-backref\""),
+        "Expected unprefixed ids: {result}"
+    );
+    assert!(!render("A[^x].\n\n[^x]: Note.").contains("<h2>"));
+}
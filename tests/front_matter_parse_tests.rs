@@ -0,0 +1,62 @@
+#![cfg(feature = "front-matter-parse")]
+
+use ferromark::{FrontMatter, FrontMatterError, Options, parse, parse_full};
+
+#[test]
+fn yaml_front_matter() {
+    let result = parse("---\ntitle: Hello\ntags:\n  - rust\n  - markdown\n---\n# Content\n");
+    let Some(Ok(FrontMatter::Yaml(value))) = result.parsed_front_matter() else {
+        panic!("expected YAML front matter");
+    };
+    assert_eq!(value["title"].as_str(), Some("Hello"));
+    assert_eq!(value["tags"][1].as_str(), Some("markdown"));
+}
+
+#[test]
+fn toml_front_matter() {
+    let result = parse("+++\ntitle = \"Hello\"\n[extra]\ndraft = true\n+++\n# Content\n");
+    let Some(Ok(FrontMatter::Toml(value))) = result.parsed_front_matter() else {
+        panic!("expected TOML front matter");
+    };
+    assert_eq!(value["title"].as_str(), Some("Hello"));
+    assert_eq!(value["extra"]["draft"].as_bool(), Some(true));
+}
+
+#[test]
+fn empty_yaml_is_null() {
+    let result = parse("---\n---\nContent");
+    let Some(Ok(FrontMatter::Yaml(value))) = result.parsed_front_matter() else {
+        panic!("expected YAML front matter");
+    };
+    assert!(value.is_null());
+}
+
+#[test]
+fn malformed_front_matter_is_an_error_and_the_body_still_renders() {
+    let yaml = parse("---\ntitle: [unclosed\n---\n# Content\n");
+    assert!(matches!(
+        yaml.parsed_front_matter(),
+        Some(Err(FrontMatterError::Yaml(_)))
+    ));
+    assert_eq!(yaml.html, "<h1 id=\"content\">Content</h1>\n");
+
+    let toml = parse("+++\ntitle = \n+++\n# Content\n");
+    let error = toml.parsed_front_matter().unwrap().unwrap_err();
+    assert!(matches!(error, FrontMatterError::Toml(_)));
+    assert!(error.to_string().starts_with("invalid TOML front matter: "));
+    assert_eq!(toml.html, "<h1 id=\"content\">Content</h1>\n");
+}
+
+#[test]
+fn without_front_matter_there_is_nothing_to_parse() {
+    assert!(parse("# Content").parsed_front_matter().is_none());
+    let options = Options {
+        front_matter: true,
+        ..Options::default()
+    };
+    let result = parse_full("---\ncount: 3\n---\nBody\n", &options);
+    let Some(Ok(FrontMatter::Yaml(value))) = result.parsed_front_matter() else {
+        panic!("expected YAML front matter");
+    };
+    assert_eq!(value["count"].as_u64(), Some(3));
+}
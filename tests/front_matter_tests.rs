@@ -1,4 +1,4 @@
-use ferromark::{Options, parse, parse_with_options, to_html_with_options};
+use ferromark::{FrontMatterFormat, Options, parse, parse_with_options, to_html_with_options};
 
 #[test]
 fn yaml_basic() {
@@ -130,3 +130,16 @@ fn plus_four_not_front_matter() {
     let result = parse("++++\ntitle: Hello\n++++\n# Content");
     assert_eq!(result.front_matter, None);
 }
+
+#[test]
+fn front_matter_format_follows_delimiter() {
+    assert_eq!(
+        parse("---\ntitle: Hello\n---\n").front_matter_format,
+        Some(FrontMatterFormat::Yaml)
+    );
+    assert_eq!(
+        parse("+++\ntitle = 1\n+++\n").front_matter_format,
+        Some(FrontMatterFormat::Toml)
+    );
+    assert_eq!(parse("# Content").front_matter_format, None);
+}
@@ -1,4 +1,4 @@
-use ferromark::{Options, to_html_with_options};
+use ferromark::{Options, WwwScheme, to_html_with_options};
 
 fn autolink_html(input: &str) -> String {
     let options = Options {
@@ -118,3 +118,94 @@ fn autolink_disabled() {
         result
     );
 }
+
+// === GFM Spec Examples (autolinks extension) ===
+
+#[test]
+fn gfm_trailing_punctuation() {
+    assert_eq!(
+        autolink_html("Visit www.commonmark.org.\n\nVisit www.commonmark.org/a.b."),
+        "<p>Visit <a href=\"http://www.commonmark.org\">www.commonmark.org</a>.</p>\n<p>Visit <a href=\"http://www.commonmark.org/a.b\">www.commonmark.org/a.b</a>.</p>\n"
+    );
+    assert_eq!(
+        autolink_html("https://example.com. https://a.b/x?"),
+        "<p><a href=\"https://example.com\">https://example.com</a>. <a href=\"https://a.b/x\">https://a.b/x</a>?</p>\n"
+    );
+}
+
+#[test]
+fn gfm_balanced_parentheses() {
+    let input = "www.google.com/search?q=Markup+(business)\n\nwww.google.com/search?q=Markup+(business)))\n\n(www.google.com/search?q=Markup+(business))\n\n(www.google.com/search?q=Markup+(business)";
+    let expected = "<p><a href=\"http://www.google.com/search?q=Markup+(business)\">www.google.com/search?q=Markup+(business)</a></p>\n<p><a href=\"http://www.google.com/search?q=Markup+(business)\">www.google.com/search?q=Markup+(business)</a>))</p>\n<p>(<a href=\"http://www.google.com/search?q=Markup+(business)\">www.google.com/search?q=Markup+(business)</a>)</p>\n<p>(<a href=\"http://www.google.com/search?q=Markup+(business)\">www.google.com/search?q=Markup+(business)</a></p>\n";
+    assert_eq!(autolink_html(input), expected);
+
+    // Only trailing parens are balanced
+    assert_eq!(
+        autolink_html("www.google.com/search?q=(business))+ok"),
+        "<p><a href=\"http://www.google.com/search?q=(business))+ok\">www.google.com/search?q=(business))+ok</a></p>\n"
+    );
+    assert_eq!(
+        autolink_html("(https://example.com/foo(bar))"),
+        "<p>(<a href=\"https://example.com/foo(bar)\">https://example.com/foo(bar)</a>)</p>\n"
+    );
+}
+
+#[test]
+fn gfm_trailing_entity_reference() {
+    let input =
+        "www.google.com/search?q=commonmark&hl=en\n\nwww.google.com/search?q=commonmark&hl;";
+    let expected = "<p><a href=\"http://www.google.com/search?q=commonmark&amp;hl=en\">www.google.com/search?q=commonmark&amp;hl=en</a></p>\n<p><a href=\"http://www.google.com/search?q=commonmark\">www.google.com/search?q=commonmark</a>&amp;hl;</p>\n";
+    assert_eq!(autolink_html(input), expected);
+}
+
+#[test]
+fn gfm_less_than_ends_link() {
+    assert_eq!(
+        autolink_html("www.commonmark.org/he<lp"),
+        "<p><a href=\"http://www.commonmark.org/he\">www.commonmark.org/he</a>&lt;lp</p>\n"
+    );
+}
+
+#[test]
+fn gfm_email_domain_end() {
+    let input = "a.b-c_d@a.b\n\na.b-c_d@a.b.\n\na.b-c_d@a.b-\n\na.b-c_d@a.b_";
+    let expected = "<p><a href=\"mailto:a.b-c_d@a.b\">a.b-c_d@a.b</a></p>\n<p><a href=\"mailto:a.b-c_d@a.b\">a.b-c_d@a.b</a>.</p>\n<p>a.b-c_d@a.b-</p>\n<p>a.b-c_d@a.b_</p>\n";
+    assert_eq!(autolink_html(input), expected);
+}
+
+#[test]
+fn gfm_mailto_and_xmpp() {
+    let input = "mailto:foo@bar.baz\n\nmailto:a.b-c_d@a.b\n\nmailto:a.b-c_d@a.b.\n\nmailto:a.b-c_d@a.b/\n\nmailto:a.b-c_d@a.b-\n\nmailto:a.b-c_d@a.b_\n\nxmpp:foo@bar.baz\n\nxmpp:foo@bar.baz.";
+    let expected = "<p><a href=\"mailto:foo@bar.baz\">mailto:foo@bar.baz</a></p>\n<p><a href=\"mailto:a.b-c_d@a.b\">mailto:a.b-c_d@a.b</a></p>\n<p><a href=\"mailto:a.b-c_d@a.b\">mailto:a.b-c_d@a.b</a>.</p>\n<p><a href=\"mailto:a.b-c_d@a.b\">mailto:a.b-c_d@a.b</a>/</p>\n<p>mailto:a.b-c_d@a.b-</p>\n<p>mailto:a.b-c_d@a.b_</p>\n<p><a href=\"xmpp:foo@bar.baz\">xmpp:foo@bar.baz</a></p>\n<p><a href=\"xmpp:foo@bar.baz\">xmpp:foo@bar.baz</a>.</p>\n";
+    assert_eq!(autolink_html(input), expected);
+}
+
+#[test]
+fn gfm_xmpp_resource() {
+    let input = "xmpp:foo@bar.baz/txt\n\nxmpp:foo@bar.baz/txt@bin\n\nxmpp:foo@bar.baz/txt@bin.com\n\nxmpp:foo@bar.baz/txt/bin";
+    let expected = "<p><a href=\"xmpp:foo@bar.baz/txt\">xmpp:foo@bar.baz/txt</a></p>\n<p><a href=\"xmpp:foo@bar.baz/txt@bin\">xmpp:foo@bar.baz/txt@bin</a></p>\n<p><a href=\"xmpp:foo@bar.baz/txt@bin.com\">xmpp:foo@bar.baz/txt@bin.com</a></p>\n<p><a href=\"xmpp:foo@bar.baz/txt\">xmpp:foo@bar.baz/txt</a>/bin</p>\n";
+    assert_eq!(autolink_html(input), expected);
+}
+
+#[test]
+fn www_autolink_scheme() {
+    let options = Options {
+        autolink_literals: true,
+        www_autolink_scheme: WwwScheme::Https,
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("Visit www.commonmark.org/help?a=1.", &options),
+        "<p>Visit <a href=\"https://www.commonmark.org/help?a=1\">www.commonmark.org/help?a=1</a>.</p>\n"
+    );
+    // Links that carry a scheme keep it
+    assert_eq!(
+        to_html_with_options("http://a.example me@a.example", &options),
+        "<p><a href=\"http://a.example\">http://a.example</a> <a href=\"mailto:me@a.example\">me@a.example</a></p>\n"
+    );
+    // GFM default
+    assert_eq!(
+        autolink_html("www.commonmark.org"),
+        "<p><a href=\"http://www.commonmark.org\">www.commonmark.org</a></p>\n"
+    );
+}
@@ -154,3 +154,20 @@ fn partial_tag_name_not_filtered() {
     let result = to_html("<scripting>ok</scripting>");
     assert_eq!(result, "<p><scripting>ok</scripting></p>\n");
 }
+
+#[test]
+fn details_and_summary_pass_through() {
+    // Not in the GFM filtered set, so they survive as block and inline HTML
+    assert_eq!(
+        to_html("<details><summary>x</summary>y</details>\n"),
+        "<details><summary>x</summary>y</details>\n"
+    );
+    assert_eq!(
+        to_html("<details>\n<summary>Title</summary>\n\nBody *md*\n\n</details>\n"),
+        "<details>\n<summary>Title</summary>\n<p>Body <em>md</em></p>\n</details>\n"
+    );
+    assert_eq!(
+        to_html("a <details><summary>x</summary>y</details>"),
+        "<p>a <details><summary>x</summary>y</details></p>\n"
+    );
+}
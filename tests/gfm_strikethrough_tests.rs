@@ -93,3 +93,52 @@ fn strikethrough_disabled() {
     );
     assert_eq!(result, "<p>~~test~~</p>\n");
 }
+
+fn single_tilde_html(markdown: &str) -> String {
+    to_html_with_options(
+        markdown,
+        &Options {
+            strikethrough_single_tilde: true,
+            ..Options::default()
+        },
+    )
+}
+
+#[test]
+fn single_tilde_strikethrough() {
+    assert_eq!(single_tilde_html("~x~"), "<p><del>x</del></p>\n");
+    assert_eq!(single_tilde_html("~~x~~"), "<p><del>x</del></p>\n");
+    assert_eq!(single_tilde_html("a ~~~x~~~"), "<p>a ~~~x~~~</p>\n");
+    // Run lengths must match between opener and closer
+    assert_eq!(single_tilde_html("~x~~"), "<p>~x~~</p>\n");
+    assert_eq!(
+        single_tilde_html("~a ~~b~~ c~"),
+        "<p><del>a <del>b</del> c</del></p>\n"
+    );
+}
+
+#[test]
+fn single_tilde_strikethrough_takes_precedence_over_subscript() {
+    let html = to_html_with_options(
+        "H~2~O",
+        &Options {
+            strikethrough_single_tilde: true,
+            subscript: true,
+            ..Options::default()
+        },
+    );
+    assert_eq!(html, "<p>H<del>2</del>O</p>\n");
+}
+
+#[test]
+fn single_tilde_needs_strikethrough() {
+    let html = to_html_with_options(
+        "~x~",
+        &Options {
+            strikethrough: false,
+            strikethrough_single_tilde: true,
+            ..Options::default()
+        },
+    );
+    assert_eq!(html, "<p>~x~</p>\n");
+}
@@ -386,3 +386,126 @@ fn cmark_regression_bare_pipe_dash() {
         "Bare pipe+dash should not be table: {result}"
     );
 }
+
+// === Inline content and escaped pipes ===
+
+/// Render `cell` as the only body cell of a table and return its HTML.
+fn body_cell(cell: &str, options: &Options) -> String {
+    let html = to_html_with_options(&format!("| h |\n| - |\n| {cell} |\n"), options);
+    let start = html.find("<td>").expect("a body cell") + "<td>".len();
+    let end = html.find("</td>").expect("a closed body cell");
+    html[start..end].to_string()
+}
+
+/// `\|` is unescaped before inline parsing, so it never breaks emphasis.
+#[test]
+fn escaped_pipe_inside_emphasis() {
+    let cell = |input| body_cell(input, &Options::default());
+    assert_eq!(cell("*a\\|b*"), "<em>a|b</em>");
+    assert_eq!(cell("_a\\|b_ c"), "<em>a|b</em> c");
+    assert_eq!(cell("***a\\|b***"), "<em><strong>a|b</strong></em>");
+    assert_eq!(cell("~~a\\|b~~"), "<del>a|b</del>");
+    // Flanking sees the unescaped `|`, as cmark-gfm does
+    assert_eq!(cell("*a \\|* b"), "<em>a |</em> b");
+    assert_eq!(cell("*a\\|*b*"), "*a|<em>b</em>");
+}
+
+/// Code spans keep unescaped pipes in the cell and unescape `\|`.
+#[test]
+fn pipes_inside_code_spans() {
+    let cell = |input| body_cell(input, &Options::default());
+    assert_eq!(cell("`a\\|b`"), "<code>a|b</code>");
+    assert_eq!(cell("`a | b`"), "<code>a | b</code>");
+    assert_eq!(cell("`` a\\|`b ``"), "<code>a|`b</code>");
+}
+
+/// Link destinations, titles, and image alt text unescape `\|` too.
+#[test]
+fn escaped_pipe_in_links_and_images() {
+    let cell = |input| body_cell(input, &Options::default());
+    assert_eq!(
+        cell("[x\\|y](/a\\|b \"t\\|u\")"),
+        "<a href=\"/a|b\" title=\"t|u\">x|y</a>"
+    );
+    assert_eq!(
+        cell("![a\\|b](/i\\|.png)"),
+        "<img src=\"/i|.png\" alt=\"a|b\" />"
+    );
+    assert_eq!(
+        cell("<https://a.example/x\\|y>"),
+        "<a href=\"https://a.example/x|y\">https://a.example/x|y</a>"
+    );
+}
+
+/// `<br>` in a cell is raw HTML: kept when trusted, escaped otherwise.
+#[test]
+fn html_line_break_in_cell() {
+    let trusted = Options {
+        render_policy: RenderPolicy::Trusted,
+        ..Options::default()
+    };
+    assert_eq!(body_cell("a<br>b", &trusted), "a<br>b");
+    assert_eq!(body_cell("a<br>b", &Options::default()), "a&lt;br&gt;b");
+}
+
+/// An escaped backslash does not escape the pipe after it.
+#[test]
+fn escaped_backslash_before_pipe() {
+    let cell = |input| body_cell(input, &Options::default());
+    assert_eq!(cell("a \\\\| b"), "a \\");
+    assert_eq!(cell("a \\\\\\| b"), "a \\| b");
+    // Also before the trailing pipe, which must not count as a cell
+    let input = "| a \\\\|\n| - |\n";
+    assert_eq!(
+        to_html(input),
+        "<table>\n<thead>\n<tr>\n<th>a \\</th>\n</tr>\n</thead>\n</table>\n"
+    );
+}
+
+// === Cell merging (`table_cell_merge`) ===
+
+fn merged(input: &str) -> String {
+    to_html_with_options(
+        input,
+        &Options {
+            table_cell_merge: true,
+            ..Options::default()
+        },
+    )
+}
+
+/// A trailing `||` spans the last cell across the remaining columns.
+#[test]
+fn cell_merge_spans_remaining_columns() {
+    let input = "| a | b | c |\n| --- | :-: | --- |\n| wide ||\n| x | y ||\n| 1 | 2 | 3 |\n";
+    let expected = "<table>\n<thead>\n<tr>\n<th>a</th>\n<th align=\"center\">b</th>\n<th>c</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<td colspan=\"3\">wide</td>\n</tr>\n<tr>\n<td>x</td>\n<td align=\"center\" colspan=\"2\">y</td>\n</tr>\n<tr>\n<td>1</td>\n<td align=\"center\">2</td>\n<td>3</td>\n</tr>\n</tbody>\n</table>\n";
+    assert_eq!(merged(input), expected);
+}
+
+/// Header rows merge too.
+#[test]
+fn cell_merge_in_header() {
+    let input = "| title ||\n| --- | --- |\n| a | b |\n";
+    let expected = "<table>\n<thead>\n<tr>\n<th colspan=\"2\">title</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<td>a</td>\n<td>b</td>\n</tr>\n</tbody>\n</table>\n";
+    assert_eq!(merged(input), expected);
+    // A merged header may have fewer cells than the delimiter row
+    assert!(merged("| title ||\n| --- | --- | --- |\n").contains("<th colspan=\"3\">title</th>"));
+}
+
+/// Short rows without the marker, whitespace between the pipes, escaped
+/// pipes, and rows already filling every column are not merged.
+#[test]
+fn cell_merge_only_for_marker() {
+    let html = merged("| a | b | c |\n| - | - | - |\n| x |\n| y | |\n| z \\||\n| 1 | 2 | 3 ||\n");
+    assert!(!html.contains("colspan"), "{html}");
+    assert!(html.contains("<td>x</td>\n<td></td>\n<td></td>"));
+    assert!(html.contains("<td>z |</td>\n<td></td>\n<td></td>"));
+    assert!(html.contains("<td>1</td>\n<td>2</td>\n<td>3</td>"));
+}
+
+/// Standard GFM keeps the trailing empty cell.
+#[test]
+fn cell_merge_is_opt_in() {
+    let html = to_html("| a | b | c |\n| - | - | - |\n| x ||\n");
+    assert!(html.contains("<td>x</td>\n<td></td>\n<td></td>"));
+}
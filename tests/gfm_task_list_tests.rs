@@ -28,6 +28,26 @@ fn non_task_list_items() {
     assert_eq!(to_html(input), expected);
 }
 
+#[test]
+fn task_list_marker_whitespace() {
+    let unchecked = "<ul>\n<li><input type=\"checkbox\" disabled=\"\" /> a</li>\n</ul>\n";
+    let checked =
+        "<ul>\n<li><input type=\"checkbox\" checked=\"\" disabled=\"\" /> a</li>\n</ul>\n";
+    assert_eq!(to_html("- [ ] a"), unchecked);
+    assert_eq!(to_html("* [X] a"), checked);
+    // Up to four spaces or a tab may follow the list marker
+    for input in ["-  [x] a", "-   [x] a", "-    [x] a", "-\t[x] a"] {
+        assert_eq!(to_html(input), checked, "{input:?}");
+    }
+    // A tab may follow the checkbox
+    assert_eq!(to_html("- [x]\ta"), checked);
+    // Five spaces start indented code inside the item
+    assert_eq!(
+        to_html("-     [x] a\n"),
+        "<ul>\n<li>\n<pre><code>[x] a\n</code></pre>\n</li>\n</ul>\n"
+    );
+}
+
 #[test]
 fn mixed_task_and_regular() {
     let input = "- [ ] task\n- regular";
@@ -55,3 +75,66 @@ fn task_list_disabled() {
     };
     assert_eq!(to_html_with_options(input, &options), expected);
 }
+
+fn with_classes(input: &str) -> String {
+    ferromark::to_html_with_options(
+        input,
+        &ferromark::Options {
+            task_list_classes: true,
+            ..ferromark::Options::default()
+        },
+    )
+}
+
+#[test]
+fn task_list_classes() {
+    let input = "- [ ] foo\n- [x] bar";
+    let expected = "<ul class=\"contains-task-list\">\n<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled=\"\" /> foo</li>\n<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" checked=\"\" disabled=\"\" /> bar</li>\n</ul>\n";
+    assert_eq!(with_classes(input), expected);
+}
+
+#[test]
+fn task_list_classes_mixed_and_ordered() {
+    // The list class appears once a later item turns out to be a task
+    let input = "- regular\n- [x] done";
+    let expected = "<ul class=\"contains-task-list\">\n<li>regular</li>\n<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" checked=\"\" disabled=\"\" /> done</li>\n</ul>\n";
+    assert_eq!(with_classes(input), expected);
+
+    let input = "3. [ ] first";
+    let expected = "<ol start=\"3\" class=\"contains-task-list\">\n<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled=\"\" /> first</li>\n</ol>\n";
+    assert_eq!(with_classes(input), expected);
+
+    assert_eq!(with_classes("- plain"), "<ul>\n<li>plain</li>\n</ul>\n");
+}
+
+#[test]
+fn task_list_classes_nested() {
+    // Only the list that directly holds task items gets the class
+    let input = "- outer\n  - [ ] inner\n- [x] second";
+    let expected = "<ul class=\"contains-task-list\">\n<li>outer\n<ul class=\"contains-task-list\">\n<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled=\"\" /> inner</li>\n</ul>\n</li>\n<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" checked=\"\" disabled=\"\" /> second</li>\n</ul>\n";
+    assert_eq!(with_classes(input), expected);
+
+    let input = "- outer\n  - [ ] inner";
+    let expected = "<ul>\n<li>outer\n<ul class=\"contains-task-list\">\n<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled=\"\" /> inner</li>\n</ul>\n</li>\n</ul>\n";
+    assert_eq!(with_classes(input), expected);
+}
+
+#[test]
+fn task_list_interactive() {
+    let interactive = |input, task_list_classes| {
+        ferromark::to_html_with_options(
+            input,
+            &ferromark::Options {
+                task_list_interactive: true,
+                task_list_classes,
+                ..ferromark::Options::default()
+            },
+        )
+    };
+    let input = "- [ ] foo\n- [x] bar\n\n1. [X] loose\n\n2. [ ] item";
+    let expected = "<ul>\n<li><input type=\"checkbox\" /> foo</li>\n<li><input type=\"checkbox\" checked=\"\" /> bar</li>\n</ul>\n<ol>\n<li>\n<p><input type=\"checkbox\" checked=\"\" /> loose</p>\n</li>\n<li>\n<p><input type=\"checkbox\" /> item</p>\n</li>\n</ol>\n";
+    assert_eq!(interactive(input, false), expected);
+
+    let expected = "<ul class=\"contains-task-list\">\n<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" checked=\"\" /> done</li>\n</ul>\n";
+    assert_eq!(interactive("- [x] done", true), expected);
+}
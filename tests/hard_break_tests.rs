@@ -0,0 +1,145 @@
+//! Hard line breaks across the containers whose text is assembled before
+//! inline parsing: paragraphs, headings, list items, blockquotes, and
+//! table cells, plus their boundaries with code spans.
+
+use ferromark::{Options, RenderPolicy, to_html, to_html_with_options};
+
+fn plain_headings(input: &str) -> String {
+    to_html_with_options(
+        input,
+        &Options {
+            heading_ids: false,
+            ..Options::default()
+        },
+    )
+}
+
+#[test]
+fn paragraph_breaks() {
+    assert_eq!(to_html("a\\\nb"), "<p>a<br />\nb</p>\n");
+    assert_eq!(to_html("a  \nb"), "<p>a<br />\nb</p>\n");
+    assert_eq!(to_html("a  \n   b"), "<p>a<br />\nb</p>\n");
+    assert_eq!(to_html("*a  \nb*"), "<p><em>a<br />\nb</em></p>\n");
+    // Not at the end of a block
+    assert_eq!(to_html("a\\"), "<p>a\\</p>\n");
+    assert_eq!(to_html("a  "), "<p>a</p>\n");
+}
+
+#[test]
+fn heading_breaks() {
+    assert_eq!(
+        plain_headings("Foo\\\nbar\n==="),
+        "<h1>Foo<br />\nbar</h1>\n"
+    );
+    assert_eq!(
+        plain_headings("Foo  \nbar\n---"),
+        "<h2>Foo<br />\nbar</h2>\n"
+    );
+    // The last heading line never ends in a break
+    assert_eq!(plain_headings("Foo  \n==="), "<h1>Foo</h1>\n");
+    assert_eq!(plain_headings("Foo\\\n==="), "<h1>Foo\\</h1>\n");
+    assert_eq!(plain_headings("# Foo\\"), "<h1>Foo\\</h1>\n");
+}
+
+#[test]
+fn heading_ids_ignore_breaks() {
+    assert_eq!(
+        to_html("Foo\\\nbar\n==="),
+        "<h1 id=\"foo-bar\">Foo<br />\nbar</h1>\n"
+    );
+    assert_eq!(
+        to_html("Foo  \nbar\n==="),
+        "<h1 id=\"foo-bar\">Foo<br />\nbar</h1>\n"
+    );
+}
+
+#[test]
+fn container_breaks() {
+    assert_eq!(
+        to_html("> a\\\n> b"),
+        "<blockquote>\n<p>a<br />\nb</p>\n</blockquote>\n"
+    );
+    assert_eq!(to_html("- a  \n  b"), "<ul>\n<li>a<br />\nb</li>\n</ul>\n");
+    assert_eq!(
+        to_html("1. x\\\n   y"),
+        "<ol>\n<li>x<br />\ny</li>\n</ol>\n"
+    );
+}
+
+#[test]
+fn code_span_breaks() {
+    // Before a code span
+    assert_eq!(to_html("a  \n`b`"), "<p>a<br />\n<code>b</code></p>\n");
+    assert_eq!(to_html("a\\\n`b`"), "<p>a<br />\n<code>b</code></p>\n");
+    // Inside a code span the line ending is a space and nothing breaks
+    assert_eq!(to_html("`a  \nb`"), "<p><code>a   b</code></p>\n");
+    assert_eq!(to_html("`a\\\nb`"), "<p><code>a\\ b</code></p>\n");
+    // Right after a code span
+    assert_eq!(to_html("`a`  \nb"), "<p><code>a</code><br />\nb</p>\n");
+    assert_eq!(to_html("`a`\\\nb"), "<p><code>a</code><br />\nb</p>\n");
+    assert_eq!(
+        to_html("`a`  \n`b`"),
+        "<p><code>a</code><br />\n<code>b</code></p>\n"
+    );
+    // Trailing spaces inside the span are content; one space after is no break
+    assert_eq!(to_html("`a  ` \nb"), "<p><code>a  </code>\nb</p>\n");
+    // The same inside containers
+    assert_eq!(
+        to_html("- `a  \n  b`"),
+        "<ul>\n<li><code>a   b</code></li>\n</ul>\n"
+    );
+    assert_eq!(
+        to_html("> `a`  \n> b"),
+        "<blockquote>\n<p><code>a</code><br />\nb</p>\n</blockquote>\n"
+    );
+}
+
+#[test]
+fn table_cell_breaks() {
+    // Cells hold one line, so a trailing backslash stays literal
+    let input = "| a |\n|---|\n| x\\ |\n";
+    assert!(to_html(input).contains("<td>x\\</td>"));
+
+    // `<br>` is the GFM idiom; it passes through only for trusted HTML
+    let input = "| a |\n|---|\n| x<br>y |\n";
+    assert!(to_html(input).contains("<td>x&lt;br&gt;y</td>"));
+    let trusted = Options {
+        render_policy: RenderPolicy::Trusted,
+        ..Options::default()
+    };
+    assert!(to_html_with_options(input, &trusted).contains("<td>x<br>y</td>"));
+}
+
+#[test]
+fn hard_wrap_breaks_soft_lines() {
+    let options = Options {
+        hard_wrap: true,
+        heading_ids: false,
+        ..Options::default()
+    };
+    let render = |input| to_html_with_options(input, &options);
+    assert_eq!(render("a\nb\nc"), "<p>a<br />\nb<br />\nc</p>\n");
+    assert_eq!(
+        render("> a\n> *b\nc*"),
+        "<blockquote>\n<p>a<br />\n<em>b<br />\nc</em></p>\n</blockquote>\n"
+    );
+    assert_eq!(render("a\nb\n==="), "<h1>a<br />\nb</h1>\n");
+    // Hard breaks are not doubled
+    assert_eq!(render("a  \nb\\\nc"), "<p>a<br />\nb<br />\nc</p>\n");
+    // Code, raw HTML and image alt text keep their line breaks
+    assert_eq!(render("```\na\nb\n```"), "<pre><code>a\nb\n</code></pre>\n");
+    assert_eq!(render("`a\nb`"), "<p><code>a b</code></p>\n");
+    assert_eq!(
+        render("![a\nb](/i.png)"),
+        "<p><img src=\"/i.png\" alt=\"a b\" /></p>\n"
+    );
+    let trusted = Options {
+        render_policy: RenderPolicy::Trusted,
+        disallowed_raw_html: false,
+        ..options
+    };
+    assert_eq!(
+        to_html_with_options("<div>\na\nb\n</div>\n", &trusted),
+        "<div>\na\nb\n</div>\n"
+    );
+}
@@ -67,6 +67,30 @@ fn test_setext_heading_gets_id() {
     assert!(html.contains("id=\"hello-world\""), "Got: {html}");
 }
 
+#[test]
+fn test_multiline_setext_heading_joins_lines_with_one_hyphen() {
+    let html = html_with_ids("Hello\nWorld\n===");
+    assert_eq!(html, "<h1 id=\"hello-world\">Hello\nWorld</h1>\n");
+
+    let html = html_with_ids("Getting \n   started\n---");
+    assert_eq!(html, "<h2 id=\"getting-started\">Getting\nstarted</h2>\n");
+}
+
+#[test]
+fn test_multiline_setext_heading_with_hard_break() {
+    let html = html_with_ids("Hello  \nWorld\n===");
+    assert!(html.contains("id=\"hello-world\""), "Got: {html}");
+
+    let html = html_with_ids("Hello\\\nWorld\n===");
+    assert!(html.contains("id=\"hello-world\""), "Got: {html}");
+}
+
+#[test]
+fn test_multiline_setext_heading_duplicates_atx_slug() {
+    let html = html_with_ids("# Hello World\n\nHello\nWorld\n---");
+    assert!(html.contains("<h2 id=\"hello-world-1\">"), "Got: {html}");
+}
+
 #[test]
 fn test_heading_all_levels() {
     for level in 1..=6 {
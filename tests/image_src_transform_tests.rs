@@ -0,0 +1,89 @@
+use ferromark::{ImageAttrs, ImageSrcTransform, Options, RenderPolicy, to_html_with_options};
+
+fn with_transform(transform: fn(&str) -> ImageAttrs) -> Options {
+    Options {
+        image_src_transform: Some(ImageSrcTransform(transform)),
+        ..Options::default()
+    }
+}
+
+/// Serve from a CDN with two widths.
+fn responsive(src: &str) -> ImageAttrs {
+    let src = format!("https://cdn.example/{src}");
+    ImageAttrs {
+        srcset: Some(format!("{src}?w=400 400w, {src}?w=800 800w")),
+        sizes: Some("(max-width: 600px) 400px, 800px".to_owned()),
+        src,
+    }
+}
+
+/// Show what the callback received.
+fn mark(src: &str) -> ImageAttrs {
+    ImageAttrs {
+        src: format!("seen/{src}"),
+        ..ImageAttrs::default()
+    }
+}
+
+#[test]
+fn callback_adds_srcset_to_inline_and_reference_images() {
+    let options = with_transform(responsive);
+    let expected = "<img src=\"https://cdn.example/img/a.png\" srcset=\"https://cdn.example/img/a.png?w=400 400w, https://cdn.example/img/a.png?w=800 800w\" sizes=\"(max-width: 600px) 400px, 800px\" alt=\"A\" title=\"T\" />";
+
+    let inline = to_html_with_options("![A](img/a.png \"T\")", &options);
+    assert_eq!(inline, format!("<p>{expected}</p>\n"));
+    let reference = to_html_with_options("![A][a]\n\n[a]: img/a.png \"T\"", &options);
+    assert_eq!(reference, format!("<p>{expected}</p>\n"));
+}
+
+#[test]
+fn relative_paths_reach_the_callback_unchanged() {
+    let options = with_transform(mark);
+    assert!(
+        to_html_with_options("![x](../up/pic.png)", &options)
+            .contains("src=\"seen/../up/pic.png\"")
+    );
+    assert!(to_html_with_options("![x](/abs.png)", &options).contains("src=\"seen//abs.png\""));
+    // Escapes and entities are resolved; the written src is percent-encoded again
+    assert!(
+        to_html_with_options("![x](<a b\\(1\\)&amp;.png>)", &options)
+            .contains("src=\"seen/a%20b(1)&amp;.png\"")
+    );
+}
+
+#[test]
+fn data_uris_bypass_the_callback() {
+    // Untrusted rendering drops data URIs either way, so trust the input
+    let options = Options {
+        render_policy: RenderPolicy::Trusted,
+        ..with_transform(mark)
+    };
+    let html = to_html_with_options("![dot](data:image/png;base64,iVBORw0KGgo=)", &options);
+    assert_eq!(
+        html,
+        "<p><img src=\"data:image/png;base64,iVBORw0KGgo=\" alt=\"dot\" /></p>\n"
+    );
+    assert!(!to_html_with_options("![d](DATA:image/gif,x)", &options).contains("seen/"));
+}
+
+#[test]
+fn transformed_src_is_still_filtered_and_attributes_escaped() {
+    fn hostile(_: &str) -> ImageAttrs {
+        ImageAttrs {
+            src: "javascript:alert(1)".to_owned(),
+            srcset: Some("a.png 1x\" onerror=\"x".to_owned()),
+            sizes: None,
+        }
+    }
+    let html = to_html_with_options("![x](a.png)", &with_transform(hostile));
+    assert_eq!(
+        html,
+        "<p><img src=\"\" srcset=\"a.png 1x&quot; onerror=&quot;x\" alt=\"x\" /></p>\n"
+    );
+}
+
+#[test]
+fn links_are_not_transformed() {
+    let html = to_html_with_options("[a](img.png)", &with_transform(mark));
+    assert_eq!(html, "<p><a href=\"img.png\">a</a></p>\n");
+}
@@ -29,10 +29,10 @@ fn test_inline_math() {
 
 #[test]
 fn test_display_math() {
-    let html = math_html("$$E=mc^2$$");
+    let html = math_html("Energy: $$E=mc^2$$");
     assert_eq!(
         html,
-        "<p><code class=\"language-math math-display\">E=mc^2</code></p>\n"
+        "<p>Energy: <code class=\"language-math math-display\">E=mc^2</code></p>\n"
     );
 }
 
@@ -98,8 +98,10 @@ fn test_code_span_wins_over_math() {
 #[test]
 fn test_display_math_with_newlines() {
     let html = math_html("$$\n\\sum\n$$");
-    // Newlines in math are converted to spaces in output
-    assert!(html.contains("language-math math-display"), "Got: {html}");
+    assert_eq!(html, "<div class=\"math display\">\\sum</div>\n");
+    // Inside a sentence, newlines in math are converted to spaces
+    let html = math_html("See $$\na\nb\n$$ here");
+    assert!(html.contains(">a b</code>"), "Got: {html}");
 }
 
 #[test]
@@ -125,3 +127,158 @@ fn test_math_html_entities_escaped() {
     let html = math_html("$a < b$");
     assert!(html.contains("a &lt; b"), "Got: {html}");
 }
+
+#[test]
+fn test_latex_inline_math() {
+    assert_eq!(
+        math_html("Let \\(x^2\\) hold."),
+        "<p>Let <code class=\"language-math math-inline\">x^2</code> hold.</p>\n"
+    );
+}
+
+#[test]
+fn test_latex_display_math() {
+    assert_eq!(
+        math_html("\\[\\sum_{i=1}^n i\\]"),
+        "<div class=\"math display\">\\sum_{i=1}^n i</div>\n"
+    );
+}
+
+#[test]
+fn test_latex_delimiters_must_balance() {
+    // Mismatched or unclosed delimiters stay ordinary escapes
+    assert_eq!(math_html("\\(x\\]"), "<p>(x]</p>\n");
+    assert_eq!(math_html("\\[x"), "<p>[x</p>\n");
+    // The first closer ends the span
+    assert_eq!(
+        math_html("\\(a\\) b\\)"),
+        "<p><code class=\"language-math math-inline\">a</code> b)</p>\n"
+    );
+}
+
+#[test]
+fn test_latex_math_escapes() {
+    // An escaped backslash cannot open or close math
+    assert_eq!(math_html("\\\\(x\\)"), "<p>\\(x)</p>\n");
+    assert_eq!(
+        math_html("\\(a\\\\)b\\)"),
+        "<p><code class=\"language-math math-inline\">a\\\\)b</code></p>\n"
+    );
+    // Escapes and markup inside math are literal
+    assert_eq!(
+        math_html("\\(\\{*a*\\}\\)"),
+        "<p><code class=\"language-math math-inline\">\\{*a*\\}</code></p>\n"
+    );
+}
+
+#[test]
+fn test_latex_math_and_code_spans() {
+    // Delimiters inside a code span are code
+    assert_eq!(math_html("`\\(x\\)`"), "<p><code>\\(x\\)</code></p>\n");
+    // A code span between the delimiters wins over math
+    assert_eq!(math_html("\\(a `b` c\\)"), "<p>(a <code>b</code> c)</p>\n");
+    // An unmatched backtick inside math is literal
+    assert_eq!(
+        math_html("\\(a`b\\)"),
+        "<p><code class=\"language-math math-inline\">a`b</code></p>\n"
+    );
+}
+
+#[test]
+fn test_latex_math_mixed_with_dollars() {
+    assert_eq!(
+        math_html("$a$ and \\(b\\) and \\[c\\] and $$d$$"),
+        "<p><code class=\"language-math math-inline\">a</code> and <code class=\"language-math math-inline\">b</code> and <code class=\"language-math math-display\">c</code> and <code class=\"language-math math-display\">d</code></p>\n"
+    );
+    assert_eq!(
+        math_html("\\(a $b\\) c$"),
+        "<p><code class=\"language-math math-inline\">a $b</code> c$</p>\n"
+    );
+}
+
+#[test]
+fn test_latex_math_disabled() {
+    assert_eq!(no_math_html("\\(x\\) \\[y\\]"), "<p>(x) [y]</p>\n");
+}
+
+#[test]
+fn test_standalone_display_math_is_a_block() {
+    assert_eq!(
+        math_html("Before\n\n$$x$$\n\nAfter"),
+        "<p>Before</p>\n<div class=\"math display\">x</div>\n<p>After</p>\n"
+    );
+    // Line breaks inside a block are kept
+    assert_eq!(
+        math_html("$$\na &= b \\\\\nc &< d\n$$"),
+        "<div class=\"math display\">a &amp;= b \\\\\nc &amp;&lt; d</div>\n"
+    );
+    assert_eq!(
+        math_html("> $$x$$"),
+        "<blockquote>\n<div class=\"math display\">x</div>\n</blockquote>\n"
+    );
+}
+
+#[test]
+fn test_display_math_in_a_sentence_stays_inline() {
+    assert_eq!(
+        math_html("So $$x$$ holds"),
+        "<p>So <code class=\"language-math math-display\">x</code> holds</p>\n"
+    );
+    // Two spans or trailing text keep the paragraph
+    assert_eq!(
+        math_html("$$x$$ $$y$$"),
+        "<p><code class=\"language-math math-display\">x</code> <code class=\"language-math math-display\">y</code></p>\n"
+    );
+    assert_eq!(
+        math_html("$$x$$."),
+        "<p><code class=\"language-math math-display\">x</code>.</p>\n"
+    );
+    // Inline math and disabled math are unaffected
+    assert_eq!(
+        math_html("$x$"),
+        "<p><code class=\"language-math math-inline\">x</code></p>\n"
+    );
+    assert_eq!(no_math_html("$$x$$"), "<p>$$x$$</p>\n");
+    // Tight list items have no paragraph to replace
+    assert_eq!(
+        math_html("- $$x$$"),
+        "<ul>\n<li><code class=\"language-math math-display\">x</code></li>\n</ul>\n"
+    );
+}
+
+#[test]
+fn test_math_hides_tags_and_autolinks() {
+    let math =
+        |content: &str| format!("<code class=\"language-math math-inline\">{content}</code>");
+    assert_eq!(
+        math_html("$a<b>c$ and \\(x<y>\\)"),
+        format!(
+            "<p>{} and {}</p>\n",
+            math("a&lt;b&gt;c"),
+            math("x&lt;y&gt;")
+        )
+    );
+    assert_eq!(
+        math_html("$<https://x.y>$ $a<!-- b -->$"),
+        format!(
+            "<p>{} {}</p>\n",
+            math("&lt;https://x.y&gt;"),
+            math("a&lt;!-- b --&gt;")
+        )
+    );
+    let options = Options {
+        math: true,
+        autolink_literals: true,
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("$a@b.co$", &options),
+        format!("<p>{}</p>\n", math("a@b.co"))
+    );
+    // Tags and code spans that start first keep their `$`
+    assert_eq!(
+        math_html("<b title=\"$\">x$"),
+        "<p>&lt;b title=&quot;$&quot;&gt;x$</p>\n"
+    );
+    assert_eq!(math_html("$`a<b>`$"), "<p>$<code>a&lt;b&gt;</code>$</p>\n");
+}
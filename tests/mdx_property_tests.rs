@@ -0,0 +1,121 @@
+//! Property tests: every MDX segmentation covers its input exactly, so the
+//! segment slices concatenate back to the original bytes.
+
+#![cfg(feature = "mdx")]
+
+use ferromark::mdx::{
+    Segment, segment, segment_container_aware, segment_inline_jsx, segment_spanned, segment_strict,
+};
+use proptest::prelude::*;
+
+/// Syntax fragments that steer random input toward segment boundaries.
+const FRAGMENTS: &[&str] = &[
+    "import ",
+    "import {a} from 'a'",
+    "import('x')",
+    "export ",
+    "export const a = 1;",
+    "export default {",
+    "<",
+    "</",
+    ">",
+    "/>",
+    "<>",
+    "</>",
+    "<Card>",
+    "</Card>",
+    "<Card />",
+    "<Icon name=\"x\"/>",
+    "<a b={c} {...d}>",
+    "<Foo.Bar>",
+    "<svg:rect/>",
+    "<https://example.com>",
+    "{",
+    "}",
+    "{x}",
+    "{/* c */}",
+    "{`${a}`}",
+    "\"",
+    "'",
+    "`",
+    "```",
+    "$$",
+    "# ",
+    "> ",
+    "- ",
+    "1. ",
+    "---",
+    "---\ntitle: x\n---\n",
+    "\n",
+    "\n\n",
+    "\r\n",
+    "\r",
+    " ",
+    "  ",
+    "\t",
+    "a < b",
+    "é",
+    "🙂",
+];
+
+fn mdx() -> impl Strategy<Value = String> {
+    let piece = prop_oneof![
+        4 => proptest::sample::select(FRAGMENTS).prop_map(String::from),
+        1 => any::<char>().prop_map(String::from),
+        1 => "[a-zA-Z ]{1,8}",
+    ];
+    proptest::collection::vec(piece, 0..40).prop_map(|pieces| pieces.concat())
+}
+
+/// Concatenate the segments, which must each carry at least one byte.
+fn concat(segments: &[Segment<'_>]) -> String {
+    assert!(
+        segments.iter().all(|s| !s.as_str().is_empty()),
+        "empty segment"
+    );
+    segments.iter().map(Segment::as_str).collect()
+}
+
+fn assert_round_trip(input: &str) {
+    assert_eq!(concat(&segment(input)), input, "segment");
+    assert_eq!(
+        concat(&segment_container_aware(input)),
+        input,
+        "segment_container_aware"
+    );
+    assert_eq!(
+        concat(&segment_inline_jsx(input)),
+        input,
+        "segment_inline_jsx"
+    );
+
+    let mut end = 0;
+    for spanned in segment_spanned(input) {
+        assert_eq!(spanned.range.start_usize(), end, "segment_spanned gap");
+        assert_eq!(
+            &input[spanned.range.start_usize()..spanned.range.end_usize()],
+            spanned.segment.as_str()
+        );
+        end = spanned.range.end_usize();
+    }
+    assert_eq!(end, input.len(), "segment_spanned end");
+
+    if let Ok(segments) = segment_strict(input) {
+        let segments: Vec<_> = segments.into_iter().map(|s| s.segment).collect();
+        assert_eq!(concat(&segments), input, "segment_strict");
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(4000))]
+
+    #[test]
+    fn segments_reproduce_mdx_input(input in mdx()) {
+        assert_round_trip(&input);
+    }
+
+    #[test]
+    fn segments_reproduce_arbitrary_unicode(input in "\\PC{0,256}") {
+        assert_round_trip(&input);
+    }
+}
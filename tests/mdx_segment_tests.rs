@@ -2,7 +2,8 @@
 
 use ferromark::Options;
 use ferromark::mdx::{
-    MdxDiagnosticCode, Segment, render, render_with_options, segment, segment_spanned,
+    InvalidExpression, MdxDiagnosticCode, Segment, render, render_with_invalid_expression,
+    render_with_options, segment, segment_container_aware, segment_inline_jsx, segment_spanned,
     segment_strict, source_location,
 };
 
@@ -441,6 +442,96 @@ fn expression_with_comment() {
     assert_eq!(segs[0], Segment::Expression(input));
 }
 
+// ── Container-aware JSX ──────────────────────────────────────────────
+
+#[test]
+fn container_aware_jsx_in_blockquote() {
+    let input = "> <Note type=\"tip\">\n> Be careful.\n> </Note>\n";
+    // Default segmentation keeps the whole blockquote as Markdown
+    assert_eq!(segment(input), vec![Segment::Markdown(input)]);
+    assert_eq!(
+        segment_container_aware(input),
+        vec![
+            Segment::JsxBlockOpen("> <Note type=\"tip\">\n"),
+            Segment::Markdown("> Be careful.\n"),
+            Segment::JsxBlockClose("> </Note>\n"),
+        ]
+    );
+}
+
+#[test]
+fn container_aware_jsx_in_list_items() {
+    let input = "- Intro\n- <Card title=\"A\" />\n1. <Tabs>\n> - <Tab />\n- <Card /> text\n";
+    assert_eq!(
+        segment_container_aware(input),
+        vec![
+            Segment::Markdown("- Intro\n"),
+            Segment::JsxBlockSelfClose("- <Card title=\"A\" />\n"),
+            Segment::JsxBlockOpen("1. <Tabs>\n"),
+            Segment::JsxBlockSelfClose("> - <Tab />\n"),
+            // Trailing content keeps the tag inline
+            Segment::Markdown("- <Card /> text\n"),
+        ]
+    );
+}
+
+#[test]
+fn container_aware_needs_list_marker_whitespace() {
+    // `-<X />` is no list item, `>` needs no space; `{x}` stays Markdown
+    let input = "-<X />\n\n>{x}\n\n>< Y />\n";
+    let segments = segment_container_aware(input);
+    assert_eq!(segments, segment(input));
+    assert_eq!(
+        segment_container_aware(">  <Y />\n"),
+        vec![Segment::JsxBlockSelfClose(">  <Y />\n")]
+    );
+}
+
+// ── Inline JSX ───────────────────────────────────────────────────────
+
+#[test]
+fn inline_jsx_is_split_out_of_paragraphs() {
+    let input = "Click <Icon name=\"star\"/> to <b>save</b>.\n";
+    assert_eq!(segment(input), vec![Segment::Markdown(input)]);
+    assert_eq!(
+        segment_inline_jsx(input),
+        vec![
+            Segment::Markdown("Click "),
+            Segment::JsxInline("<Icon name=\"star\"/>"),
+            Segment::Markdown(" to "),
+            Segment::JsxInline("<b>"),
+            Segment::Markdown("save"),
+            Segment::JsxInline("</b>"),
+            Segment::Markdown(".\n"),
+        ]
+    );
+}
+
+#[test]
+fn inline_jsx_keeps_operators_autolinks_and_code() {
+    let input = "a < b and x<5, <https://example.com>, `<Icon/>`\n\n```\n<Icon/> x\n```\n";
+    assert_eq!(segment_inline_jsx(input), vec![Segment::Markdown(input)]);
+}
+
+#[test]
+fn inline_jsx_leaves_flow_segments_alone() {
+    let input = "import X from 'x'\n\n<Card>\nHi <X />\n</Card>\n";
+    assert_eq!(
+        segment_inline_jsx(input),
+        vec![
+            Segment::Esm("import X from 'x'\n"),
+            Segment::Markdown("\n"),
+            Segment::JsxBlockOpen("<Card>\n"),
+            Segment::Markdown("Hi "),
+            Segment::JsxInline("<X />"),
+            Segment::Markdown("\n"),
+            Segment::JsxBlockClose("</Card>\n"),
+        ]
+    );
+    let joined: String = segment_inline_jsx(input).iter().map(seg_str).collect();
+    assert_eq!(joined, input);
+}
+
 // ── Real-world MDX patterns ─────────────────────────────────────────
 
 #[test]
@@ -1316,6 +1407,78 @@ fn render_empty_input() {
     assert!(out.front_matter.is_none());
 }
 
+#[test]
+fn render_unterminated_expression_falls_back_to_markdown() {
+    let input = "# Title\n\n{count + 1\n";
+    let out = render(input);
+    assert!(out.body.contains("<p>{count + 1</p>"), "{}", out.body);
+    assert!(out.errors.is_empty());
+    let strict =
+        render_with_invalid_expression(input, &Options::default(), InvalidExpression::Markdown);
+    assert_eq!(strict.body, out.body);
+}
+
+#[test]
+fn render_unterminated_expression_as_placeholder() {
+    let input = "Intro\n{count + 1\nafter\n\n<Card />\n";
+    let out =
+        render_with_invalid_expression(input, &Options::default(), InvalidExpression::Placeholder);
+    assert_eq!(
+        out.body,
+        "<p>Intro</p>\n<!-- invalid expression -->\n<p>after</p>\n<Card />\n"
+    );
+    assert!(out.errors.is_empty());
+}
+
+#[test]
+fn render_unterminated_expression_collects_error() {
+    let input = "Intro\n\n{count + 1\n\n{ok}\n";
+    let out =
+        render_with_invalid_expression(input, &Options::default(), InvalidExpression::Collect);
+    assert_eq!(out.body, "<p>Intro</p>\n<p>{count + 1</p>\n{ok}\n");
+    assert_eq!(out.errors.len(), 1);
+    let error = out.errors[0];
+    assert_eq!(error.code, MdxDiagnosticCode::UnterminatedExpression);
+    assert_eq!(error.primary_range.start_usize(), 7);
+    assert_eq!(error.primary_range.end_usize(), input.len());
+    // Matches the strict validator's diagnostic
+    assert_eq!(segment_strict(input).unwrap_err(), out.errors);
+}
+
+#[test]
+fn render_collects_imports_and_exports() {
+    let input = "\
+import {
+  Card,
+  Tabs,
+} from './components'
+import Layout from './layout'
+
+export const meta = { title: 'Docs' }
+
+# Docs
+
+export default function Page({ children }) {
+  return <Layout>{children}</Layout>
+}
+";
+    let out = render(input);
+    assert_eq!(
+        out.imports,
+        [
+            "import {\n  Card,\n  Tabs,\n} from './components'\n",
+            "import Layout from './layout'\n",
+        ]
+    );
+    assert_eq!(out.exports.len(), 2);
+    assert!(out.exports[0].starts_with("export const meta"));
+    assert!(out.exports[1].starts_with("export default function Page"));
+    assert!(out.exports[1].contains("return <Layout>"));
+    assert_eq!(out.esm.len(), 4);
+    assert!(out.body.contains("<h1"));
+    assert!(!out.body.contains("import"));
+}
+
 // ── to_component() integration tests ─────────────────────────────────
 
 #[test]
@@ -1,4 +1,4 @@
-use ferromark::{Options, RenderPolicy, to_html_with_options};
+use ferromark::{CalloutTitles, Options, RenderPolicy, Sanitizer, WwwScheme, to_html_with_options};
 
 #[test]
 fn minimal_should_disable_every_optional_syntax_feature() {
@@ -23,6 +23,45 @@ fn minimal_should_disable_every_optional_syntax_feature() {
             heading_ids: false,
             math: false,
             callouts: false,
+            untrusted_content: false,
+            wikilinks: false,
+            wikilink_url: None,
+            ordered_list_start_override: None,
+            emoji: false,
+            loose_list_continuation: false,
+            pretty: None,
+            callout_aria: false,
+            collapsible_callouts: false,
+            max_nesting_depth: None,
+            max_inline_marks: None,
+            custom_delimiters: &[],
+            code_info_meta: false,
+            task_list_classes: false,
+            allow_nested_links: false,
+            allow_block_html: true,
+            allow_inline_html: true,
+            table_cell_merge: false,
+            code_span_renderer: None,
+            image_src_transform: None,
+            numeric_footnote_ids: false,
+            callout_titles: CalloutTitles::ENGLISH,
+            callout_custom_titles: false,
+            compact_list_items: false,
+            external_links: None,
+            blockquote_cite_url: false,
+            strikethrough_single_tilde: false,
+            sanitizer: Sanitizer::None,
+            hard_wrap: false,
+            www_autolink_scheme: WwwScheme::Http,
+            task_list_interactive: false,
+            smart_punctuation: false,
+            xhtml: true,
+            inline_footnotes: false,
+            footnote_backref_label: "↩",
+            footnote_id_prefix: "user-content-",
+            footnote_section_title: None,
+            fenced_divs: false,
+            attributes: false,
         }
     );
 }
@@ -87,6 +126,45 @@ fn default_should_retain_the_existing_feature_mix() {
             heading_ids: true,
             math: false,
             callouts: true,
+            untrusted_content: false,
+            wikilinks: false,
+            wikilink_url: None,
+            ordered_list_start_override: None,
+            emoji: false,
+            loose_list_continuation: false,
+            pretty: None,
+            callout_aria: false,
+            collapsible_callouts: false,
+            max_nesting_depth: None,
+            max_inline_marks: None,
+            custom_delimiters: &[],
+            code_info_meta: false,
+            task_list_classes: false,
+            allow_nested_links: false,
+            allow_block_html: true,
+            allow_inline_html: true,
+            table_cell_merge: false,
+            code_span_renderer: None,
+            image_src_transform: None,
+            numeric_footnote_ids: false,
+            callout_titles: CalloutTitles::ENGLISH,
+            callout_custom_titles: false,
+            compact_list_items: false,
+            external_links: None,
+            blockquote_cite_url: false,
+            strikethrough_single_tilde: false,
+            sanitizer: Sanitizer::None,
+            hard_wrap: false,
+            www_autolink_scheme: WwwScheme::Http,
+            task_list_interactive: false,
+            smart_punctuation: false,
+            xhtml: true,
+            inline_footnotes: false,
+            footnote_backref_label: "↩",
+            footnote_id_prefix: "user-content-",
+            footnote_section_title: None,
+            fenced_divs: false,
+            attributes: false,
         }
     );
 }
@@ -123,6 +201,47 @@ fn commonmark_should_parse_html_without_implicitly_trusting_it() {
     assert_eq!(trusted, "<div>content</div>");
 }
 
+#[test]
+fn block_and_inline_html_should_be_controlled_separately() {
+    let markdown = "<div>\n*x* <b>y</b>\n</div>";
+    let block_only = Options {
+        render_policy: RenderPolicy::Trusted,
+        allow_inline_html: false,
+        ..Options::commonmark()
+    };
+    let inline_only = Options {
+        allow_block_html: false,
+        allow_inline_html: true,
+        ..block_only
+    };
+
+    // The block passes through verbatim, so its inline markup is not parsed
+    assert_eq!(
+        to_html_with_options(markdown, &block_only),
+        "<div>\n*x* <b>y</b>\n</div>"
+    );
+    assert_eq!(
+        to_html_with_options("a <b>y</b>", &block_only),
+        "<p>a &lt;b&gt;y&lt;/b&gt;</p>\n"
+    );
+    // Without block HTML the tags are inline HTML inside a paragraph
+    assert_eq!(
+        to_html_with_options(markdown, &inline_only),
+        "<p><div>\n<em>x</em> <b>y</b>\n</div></p>\n"
+    );
+    // `allow_html` still switches both off
+    assert_eq!(
+        to_html_with_options(
+            markdown,
+            &Options {
+                allow_html: false,
+                ..inline_only
+            }
+        ),
+        "<p>&lt;div&gt;\n<em>x</em> &lt;b&gt;y&lt;/b&gt;\n&lt;/div&gt;</p>\n"
+    );
+}
+
 #[test]
 fn gfm_should_render_its_extension_set() {
     let markdown =
@@ -136,3 +255,122 @@ fn gfm_should_render_its_extension_set() {
             && html.contains("<table>")
     );
 }
+
+#[test]
+fn ordered_list_start_override_should_apply_to_top_level_lists_only() {
+    let options = Options {
+        ordered_list_start_override: Some(5),
+        ..Options::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("1. a\n2. b", &options),
+        "<ol start=\"5\">\n<li>a</li>\n<li>b</li>\n</ol>\n"
+    );
+    assert_eq!(
+        to_html_with_options("3. a\n   1. nested", &options),
+        "<ol start=\"5\">\n<li>a\n<ol>\n<li>nested</li>\n</ol>\n</li>\n</ol>\n"
+    );
+    assert!(to_html_with_options("- x\n\n  2. nested", &options).contains("<ol start=\"2\">"));
+    assert_eq!(
+        to_html_with_options("- bullet", &options),
+        "<ul>\n<li>bullet</li>\n</ul>\n"
+    );
+}
+
+#[test]
+fn loose_list_continuation_should_keep_sequential_lists_together() {
+    let options = Options {
+        loose_list_continuation: true,
+        ..Options::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("1. a\n\ntext\n\n2. b", &options),
+        "<ol>\n<li>\n<p>a</p>\n<p>text</p>\n</li>\n<li>\n<p>b</p>\n</li>\n</ol>\n"
+    );
+    assert_eq!(
+        to_html_with_options("3) a\n\ntext\nmore\n\n\n4) b\n5) c", &options),
+        "<ol start=\"3\">\n<li>\n<p>a</p>\n<p>text\nmore</p>\n</li>\n<li>\n<p>b</p>\n</li>\n<li>\n<p>c</p>\n</li>\n</ol>\n"
+    );
+}
+
+#[test]
+fn loose_list_continuation_should_start_fresh_otherwise() {
+    let options = Options {
+        loose_list_continuation: true,
+        ..Options::default()
+    };
+    let split = |markdown| {
+        let html = to_html_with_options(markdown, &options);
+        assert_eq!(html, to_html_with_options(markdown, &Options::default()));
+        html
+    };
+
+    // Non-sequential number
+    assert_eq!(
+        split("1. a\n\ntext\n\n3. b"),
+        "<ol>\n<li>a</li>\n</ol>\n<p>text</p>\n<ol start=\"3\">\n<li>b</li>\n</ol>\n"
+    );
+    // Different delimiter, more than one paragraph, other block types
+    split("1. a\n\ntext\n\n2) b");
+    split("1. a\n\ntext\n\nmore\n\n2. b");
+    split("1. a\n\n# heading\n\n2. b");
+    split("- a\n\ntext\n\n- b");
+}
+
+#[test]
+fn builder_starts_from_defaults_and_sets_fields() {
+    use ferromark::OptionsBuilder;
+
+    assert_eq!(Options::builder().build(), Options::default());
+    assert_eq!(
+        Options::builder()
+            .footnotes(true)
+            .math(true)
+            .render_policy(RenderPolicy::Trusted)
+            .max_nesting_depth(Some(8))
+            .build(),
+        Options {
+            footnotes: true,
+            math: true,
+            render_policy: RenderPolicy::Trusted,
+            max_nesting_depth: Some(8),
+            ..Options::default()
+        }
+    );
+    assert_eq!(
+        OptionsBuilder::from(Options::gfm()).tables(false).build(),
+        Options {
+            tables: false,
+            ..Options::gfm()
+        }
+    );
+    let options: Options = OptionsBuilder::new(Options::minimal()).emoji(true).into();
+    assert_eq!(
+        to_html_with_options(":smile: *x*", &options),
+        "<p>😄 <em>x</em></p>\n"
+    );
+}
+
+#[test]
+fn compact_list_items_should_drop_whitespace_between_items() {
+    let options = Options {
+        compact_list_items: true,
+        ..Options::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("1. a\n2. b\n3. c", &options),
+        "<ol>\n<li>a</li><li>b</li><li>c</li>\n</ol>\n"
+    );
+    assert_eq!(
+        to_html_with_options("- a\n\n- b\n  - c\n  - d", &options),
+        "<ul>\n<li>\n<p>a</p>\n</li><li>\n<p>b</p>\n<ul>\n<li>c</li><li>d</li>\n</ul>\n</li>\n</ul>\n"
+    );
+    assert_eq!(
+        to_html_with_options("- [x] a\n- [ ] b", &options),
+        to_html_with_options("- [x] a\n- [ ] b", &Options::default())
+            .replace("</li>\n<li", "</li><li")
+    );
+}
@@ -0,0 +1,111 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use ferromark::{HtmlWriter, to_html, to_html_into};
+
+/// Counts reallocations of the block at `TRACKED`, following it as it moves.
+///
+/// Both are per thread: only the thread owning a buffer reallocates it, so
+/// tests running in parallel do not see each other's allocations.
+struct CountingAllocator;
+
+thread_local! {
+    /// Address of the tracked block, or 0.
+    static TRACKED: Cell<usize> = const { Cell::new(0) };
+    static TRACKED_REALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        // Not available while the thread's locals are torn down
+        let _ = TRACKED.try_with(|tracked| {
+            if tracked.get() == ptr as usize {
+                tracked.set(new_ptr as usize);
+                TRACKED_REALLOCS.with(|reallocs| reallocs.set(reallocs.get() + 1));
+            }
+        });
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const TABLES: &str = include_str!("../benches/fixtures/tables-5k.md");
+
+fn link_heavy_document() -> String {
+    let mut doc = String::from("# Links\n\n");
+    for i in 0..200 {
+        doc.push_str(&format!(
+            "- [entry {i}](https://example.com/docs/{i}) and *note* `{i}`\n"
+        ));
+    }
+    doc
+}
+
+/// Count reallocations of the block at `ptr` on this thread from now on.
+fn track(ptr: *const u8) {
+    TRACKED.with(|tracked| tracked.set(ptr as usize));
+    TRACKED_REALLOCS.with(|reallocs| reallocs.set(0));
+}
+
+/// Stop tracking and return how often the tracked block was reallocated.
+fn untrack() -> usize {
+    TRACKED.with(|tracked| tracked.set(0));
+    TRACKED_REALLOCS.with(Cell::get)
+}
+
+/// Write `html` line by line into a buffer that starts at `capacity` bytes and
+/// report how often that initial block had to grow.
+fn reallocs_writing(html: &str, capacity: usize) -> usize {
+    let mut writer = HtmlWriter::with_capacity(capacity);
+    track(writer.as_bytes().as_ptr());
+    for line in html.split_inclusive('\n') {
+        writer.write_string(line);
+    }
+    let reallocs = untrack();
+    assert_eq!(writer.as_bytes(), html.as_bytes());
+    reallocs
+}
+
+#[test]
+fn estimated_capacity_avoids_output_reallocation() {
+    for doc in [TABLES.to_string(), link_heavy_document()] {
+        let html = to_html(&doc);
+        let mut reused = Vec::new();
+        to_html_into(&doc, &mut reused);
+        assert_eq!(reused, html.as_bytes(), "identical output");
+
+        let naive = doc.len() + doc.len() / 4;
+        let estimated = HtmlWriter::estimate_capacity(doc.as_bytes());
+        assert!(
+            reallocs_writing(&html, naive) > 0,
+            "length heuristic should undersize this document"
+        );
+        assert_eq!(reallocs_writing(&html, estimated), 0);
+
+        let mut rendered = Vec::with_capacity(estimated);
+        track(rendered.as_ptr());
+        to_html_into(&doc, &mut rendered);
+        assert_eq!(untrack(), 0);
+        assert_eq!(rendered, html.as_bytes());
+    }
+}
+
+#[test]
+fn estimated_capacity_stays_proportional_for_prose() {
+    let prose = "Plain prose without any markup at all. ".repeat(100);
+    let estimated = HtmlWriter::estimate_capacity(prose.as_bytes());
+
+    assert!(estimated >= to_html(&prose).len());
+    assert!(estimated < prose.len() * 2);
+}
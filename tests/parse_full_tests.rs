@@ -0,0 +1,92 @@
+use ferromark::{BlockEvent, Options, parse_full, parse_with_options, table_of_contents};
+
+const DOC: &str = "---\ntitle: Guide\n---\n# Guide\n\nSee [docs][d] and [api].[^note]\n\n## Usage\n\n## Usage\n\n[d]: /docs\n[d]: /other\n[unused]: /unused\n[api]: /api \"API\"\n\n[^note]: A *note*.\n";
+
+fn options() -> Options {
+    Options {
+        front_matter: true,
+        footnotes: true,
+        ..Options::default()
+    }
+}
+
+#[test]
+fn html_and_front_matter_match_parse_with_options() {
+    let full = parse_full(DOC, &options());
+    let parsed = parse_with_options(DOC, &options());
+    assert_eq!(full.html, parsed.html);
+    assert_eq!(full.front_matter, Some("title: Guide\n"));
+    assert!(full.body.starts_with("# Guide\n"));
+}
+
+#[test]
+fn link_refs_record_what_the_html_used() {
+    let full = parse_full(DOC, &options());
+    assert!(full.html.contains("<a href=\"/docs\">docs</a>"));
+    assert!(full.html.contains("<a href=\"/api\" title=\"API\">api</a>"));
+
+    let refs = &full.link_refs;
+    let def = refs.get(refs.get_index("d").unwrap()).unwrap();
+    assert_eq!(def.url, b"/docs");
+    assert_eq!(refs.duplicates(), ["d"]);
+    assert_eq!(refs.unused(), ["unused"]);
+}
+
+#[test]
+fn footnotes_index_into_the_body() {
+    let full = parse_full(DOC, &options());
+    let footnotes = full.footnotes.expect("footnotes are enabled");
+    assert_eq!(footnotes.len(), 1);
+    let note = footnotes.get(footnotes.get_index("note").unwrap()).unwrap();
+    assert_eq!(note.label, "note");
+    let text: Vec<&str> = note
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            BlockEvent::Text(range) => Some(&full.body[range.start_usize()..range.end_usize()]),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(text.concat(), "A *note*.");
+    assert!(full.html.contains("<p>A <em>note</em>."));
+
+    let without = parse_full(DOC, &Options::default());
+    assert!(without.footnotes.is_none());
+}
+
+#[test]
+fn headings_match_the_table_of_contents_and_anchors() {
+    for heading_ids in [true, false] {
+        let options = Options {
+            heading_ids,
+            ..options()
+        };
+        let full = parse_full(DOC, &options);
+        assert_eq!(full.headings, table_of_contents(DOC, &options));
+        assert_eq!(full.headings.len(), 1);
+        let usage: Vec<&str> = full.headings[0]
+            .children
+            .iter()
+            .map(|entry| entry.id.as_str())
+            .collect();
+        assert_eq!(usage, ["usage", "usage-1"]);
+        assert_eq!(full.html.contains("<h2 id=\"usage-1\">"), heading_ids);
+    }
+}
+
+#[test]
+fn headings_in_footnotes_are_not_in_the_outline() {
+    let markdown = "# A\n\nx[^n]\n\n[^n]: # B\n\n# C\n";
+    let options = Options {
+        footnotes: true,
+        ..Options::default()
+    };
+    let full = parse_full(markdown, &options);
+    let text: Vec<&str> = full
+        .headings
+        .iter()
+        .map(|entry| entry.text.as_str())
+        .collect();
+    assert_eq!(text, ["A", "C"]);
+    assert!(full.html.contains("<h1 id=\"b\">B</h1>"));
+}
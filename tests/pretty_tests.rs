@@ -0,0 +1,193 @@
+use ferromark::{Options, PrettyConfig, to_html_with_options};
+
+const DOCUMENT: &str = "\
+# Title
+
+Intro with `code  span` and a
+soft break.
+
+- one
+- two
+  - nested *em*
+  - more
+
+> quote
+>
+> 1. first
+>
+>    second para
+
+| a | b |
+|---|:-:|
+| 1 |   |
+
+- [x] done
+
+```rust
+fn main() {
+    indented();
+}
+```
+
+> [!TIP]
+> tip
+
+Footnote[^n].
+
+[^n]: Note text.
+";
+
+fn gfm() -> Options {
+    Options {
+        footnotes: true,
+        callouts: true,
+        ..Options::gfm()
+    }
+}
+
+fn pretty(config: PrettyConfig) -> Options {
+    Options {
+        pretty: Some(config),
+        ..gfm()
+    }
+}
+
+/// Opening tags of block elements that keep their own line when wrapped.
+const BLOCK_OPENERS: [&str; 8] = [
+    "<p",
+    "<ul",
+    "<ol",
+    "<blockquote",
+    "<h",
+    "<pre",
+    "<table",
+    "<div",
+];
+
+/// Closing tags of block elements that end a line in the default output.
+const BLOCK_CLOSERS: [&str; 9] = [
+    "</p>",
+    "</ul>",
+    "</ol>",
+    "</blockquote>",
+    "</h1>",
+    "</h2>",
+    "</pre>",
+    "</table>",
+    "</div>",
+];
+
+/// Undo pretty-printing: drop indentation outside `<pre>` and, for wrapped
+/// output, rejoin inline content with its list item or cell tags.
+fn minify(html: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    let mut in_pre = false;
+    for line in html.split_inclusive('\n') {
+        lines.push(if in_pre { line } else { line.trim_start() });
+        if line.contains("<pre") {
+            in_pre = true;
+        }
+        if line.contains("</pre>") {
+            in_pre = false;
+        }
+    }
+
+    let mut out = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        let next = lines.get(i + 1).copied().unwrap_or("");
+        let opens_cell = ["<li>\n", "<td>\n", "<th>\n"].contains(line)
+            || (line.starts_with("<t") && line.ends_with("\">\n") && !line.contains("</"));
+        let joins_open = opens_cell
+            && !next.starts_with("</")
+            && !BLOCK_OPENERS.iter().any(|tag| next.starts_with(tag));
+        let ends_block = BLOCK_CLOSERS
+            .iter()
+            .any(|tag| line.trim_end().ends_with(tag));
+        let joins_close = !ends_block
+            && ["</li>", "</td>", "</th>"]
+                .iter()
+                .any(|tag| next.starts_with(tag));
+        if joins_open || joins_close {
+            out.push_str(line.trim_end_matches('\n'));
+        } else {
+            out.push_str(line);
+        }
+    }
+    out
+}
+
+#[test]
+fn pretty_indents_nested_blocks() {
+    let html = to_html_with_options("- a\n- b\n  - c\n", &pretty(PrettyConfig::default()));
+    assert_eq!(
+        html,
+        "<ul>\n  <li>a</li>\n  <li>b\n    <ul>\n      <li>c</li>\n    </ul>\n  </li>\n</ul>\n"
+    );
+}
+
+#[test]
+fn pretty_respects_indent_width() {
+    let config = PrettyConfig {
+        indent_width: 4,
+        ..PrettyConfig::default()
+    };
+    let html = to_html_with_options("> > deep\n", &pretty(config));
+    assert_eq!(
+        html,
+        "<blockquote>\n    <blockquote>\n        <p>deep</p>\n    </blockquote>\n</blockquote>\n"
+    );
+}
+
+#[test]
+fn pretty_wraps_tight_items_and_cells() {
+    let config = PrettyConfig {
+        wrap_block_tags: true,
+        ..PrettyConfig::default()
+    };
+    assert_eq!(
+        to_html_with_options("- a\n-\n", &pretty(config)),
+        "<ul>\n  <li>\n    a\n  </li>\n  <li></li>\n</ul>\n"
+    );
+    assert_eq!(
+        to_html_with_options("| h |\n|---|\n| x |\n", &pretty(config)),
+        "<table>\n  <thead>\n    <tr>\n      <th>\n        h\n      </th>\n    </tr>\n  </thead>\n  \
+         <tbody>\n    <tr>\n      <td>\n        x\n      </td>\n    </tr>\n  </tbody>\n</table>\n"
+    );
+}
+
+#[test]
+fn pretty_keeps_inline_and_code_whitespace() {
+    let html = to_html_with_options(
+        "- para `a  b`\n  next\n\n> ```\n>   code\n> ```\n",
+        &pretty(PrettyConfig::default()),
+    );
+    assert_eq!(
+        html,
+        "<ul>\n  <li>para <code>a  b</code>\nnext</li>\n</ul>\n\
+         <blockquote>\n  <pre><code>  code\n</code></pre>\n</blockquote>\n"
+    );
+}
+
+#[test]
+fn pretty_output_minifies_to_default_output() {
+    let expected = to_html_with_options(DOCUMENT, &gfm());
+    for wrap_block_tags in [false, true] {
+        for indent_width in [0, 2, 3] {
+            let config = PrettyConfig {
+                indent_width,
+                wrap_block_tags,
+            };
+            let html = to_html_with_options(DOCUMENT, &pretty(config));
+            assert_eq!(minify(&html), expected, "{config:?}");
+        }
+    }
+}
+
+#[test]
+fn pretty_disabled_by_default() {
+    assert_eq!(Options::default().pretty, None);
+    assert_eq!(
+        to_html_with_options("- a\n", &Options::default()),
+        "<ul>\n<li>a</li>\n</ul>\n"
+    );
+}
@@ -0,0 +1,355 @@
+//! Property tests: random Markdown never panics, always renders valid UTF-8,
+//! never lets a raw `<script` through the HTML filters, and never drops text
+//! around unmatched inline markup.
+
+use ferromark::{
+    BlockParser, Options, Range, RenderPolicy, Sanitizer, to_html, to_html_into_with_options,
+    to_html_with_options,
+};
+use proptest::prelude::*;
+
+/// Syntax fragments that steer random input toward interesting constructs.
+const FRAGMENTS: &[&str] = &[
+    "*",
+    "**",
+    "_",
+    "__",
+    "`",
+    "```",
+    "~~~",
+    "~",
+    "~~",
+    "^",
+    "==",
+    "$",
+    "$$",
+    "[",
+    "]",
+    "(",
+    ")",
+    "![",
+    "](",
+    "<",
+    ">",
+    "\\",
+    "&",
+    "&amp;",
+    "&#",
+    "&#x",
+    ";",
+    "#",
+    "# ",
+    "-",
+    "- ",
+    "+ ",
+    "1. ",
+    "2) ",
+    "> ",
+    "> [!NOTE]",
+    "- [ ] ",
+    "- [x] ",
+    "|",
+    "|---|",
+    ":-:",
+    "---",
+    "===",
+    "\n",
+    "\n\n",
+    "  \n",
+    " ",
+    "    ",
+    "\t",
+    ":",
+    ":smile:",
+    "[^1]",
+    "[^1]: ",
+    "[a]: /u ",
+    "\"t\"",
+    "[[",
+    "]]",
+    "{",
+    "}",
+    "http://",
+    "https://a.b/",
+    "www.",
+    "a@b.c",
+    "mailto:",
+    "<!--",
+    "-->",
+    "<?",
+    "?>",
+    "<![CDATA[",
+    "]]>",
+    "<div>",
+    "</div>",
+    "<a href=\"x\">",
+    "<script>",
+    "</script>",
+    "<ScRiPt src=x>",
+    "<style>",
+    "<textarea>",
+    "<iframe",
+    "<Card>",
+    "</Card>",
+    "\r\n",
+    "\0",
+    "é",
+    "ü",
+    "🙂",
+    "\u{200b}",
+];
+
+fn markdown() -> impl Strategy<Value = String> {
+    let piece = prop_oneof![
+        3 => proptest::sample::select(FRAGMENTS).prop_map(String::from),
+        1 => any::<char>().prop_map(String::from),
+        1 => "[a-z ]{1,8}",
+    ];
+    proptest::collection::vec(piece, 0..48).prop_map(|pieces| pieces.concat())
+}
+
+/// Several generated blocks separated by blank lines.
+fn blocks() -> impl Strategy<Value = String> {
+    proptest::collection::vec(markdown(), 1..8).prop_map(|blocks| blocks.join("\n\n"))
+}
+
+/// Whole lines of block syntax, so that edits often land between blocks.
+fn lines() -> impl Strategy<Value = String> {
+    let line = prop_oneof![
+        proptest::sample::select(
+            &[
+                "",
+                " ",
+                "\t",
+                "```",
+                " ```",
+                "~~~ rust",
+                "::: note",
+                ":::",
+                "::::",
+                "    code",
+                "- item",
+                "  - nested",
+                "1. one",
+                "> quote",
+                ">",
+                "# Title",
+                "---",
+                "| a |",
+                "|---|",
+                "<div>",
+                "$$",
+                "[a]: /u",
+                "[^1]: note",
+            ][..]
+        )
+        .prop_map(String::from),
+        "[a-z ]{1,6}",
+    ];
+    proptest::collection::vec(line, 0..16).prop_map(|lines| lines.join("\n"))
+}
+
+/// Snap a random index to a char boundary of `text`.
+fn boundary(text: &str, index: prop::sample::Index) -> usize {
+    let mut at = index.index(text.len() + 1);
+    while !text.is_char_boundary(at) {
+        at -= 1;
+    }
+    at
+}
+
+/// One paragraph of inline delimiters around the letters `x` and `z`.
+fn inline_delimiters() -> impl Strategy<Value = String> {
+    let piece = proptest::sample::select(&[
+        "`", "``", "*", "**", "_", "~", "~~", "=", "==", "^", "$", "$$", "[", "]", "\\", " ", "x",
+        "z",
+    ]);
+    proptest::collection::vec(piece, 0..24).prop_map(|pieces| ["p ", &pieces.concat()].concat())
+}
+
+/// Text content of `html`, without tags.
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+fn everything_enabled() -> Options {
+    Options {
+        highlight: true,
+        superscript: true,
+        subscript: true,
+        footnotes: true,
+        front_matter: true,
+        math: true,
+        callouts: true,
+        untrusted_content: true,
+        wikilinks: true,
+        emoji: true,
+        collapsible_callouts: true,
+        task_list_classes: true,
+        fenced_divs: true,
+        ..Options::gfm()
+    }
+}
+
+fn trusted_with_filter() -> Options {
+    Options {
+        render_policy: RenderPolicy::Trusted,
+        allow_html: true,
+        disallowed_raw_html: true,
+        ..everything_enabled()
+    }
+}
+
+fn assert_no_raw_script(html: &str) {
+    assert!(
+        !html.to_ascii_lowercase().contains("<script"),
+        "raw <script in output: {html:?}"
+    );
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2000))]
+
+    #[test]
+    fn default_options_never_panic(input in markdown()) {
+        let html = to_html(&input);
+        assert_no_raw_script(&html);
+    }
+
+    #[test]
+    fn all_extensions_render_valid_utf8(input in markdown()) {
+        let mut out = Vec::new();
+        to_html_into_with_options(&input, &mut out, &everything_enabled());
+        let html = String::from_utf8(out).expect("output must be valid UTF-8");
+        assert_no_raw_script(&html);
+    }
+
+    #[test]
+    fn disallowed_raw_html_filters_script(input in markdown()) {
+        let html = to_html_with_options(&input, &trusted_with_filter());
+        assert_no_raw_script(&html);
+    }
+
+    #[test]
+    fn allowlist_sanitizer_filters_script(input in markdown()) {
+        let options = Options {
+            sanitizer: Sanitizer::GitHubAllowlist,
+            ..trusted_with_filter()
+        };
+        let html = to_html_with_options(&input, &options);
+        assert_no_raw_script(&html);
+    }
+
+    #[test]
+    fn reparse_range_matches_full_parse(
+        input in blocks(),
+        insert in markdown(),
+        a in any::<prop::sample::Index>(),
+        b in any::<prop::sample::Index>(),
+    ) {
+        let (a, b) = (boundary(&input, a), boundary(&input, b));
+        let (start, end) = (a.min(b), a.max(b));
+        let edited = [&input[..start], &insert, &input[end..]].concat();
+        for options in [Options::default(), everything_enabled()] {
+            let mut prev_events = Vec::new();
+            BlockParser::new_with_options(input.as_bytes(), options).parse(&mut prev_events);
+            let mut expected = Vec::new();
+            BlockParser::new_with_options(edited.as_bytes(), options).parse(&mut expected);
+            let reparsed = BlockParser::new_with_options(edited.as_bytes(), options).reparse_range(
+                &prev_events,
+                Range::from_usize(start, end),
+                insert.len(),
+            );
+            prop_assert_eq!(reparsed, expected);
+        }
+    }
+
+    #[test]
+    fn reparse_range_matches_full_parse_of_lines(
+        input in lines(),
+        insert in lines(),
+        a in any::<prop::sample::Index>(),
+        b in any::<prop::sample::Index>(),
+    ) {
+        let (a, b) = (boundary(&input, a), boundary(&input, b));
+        let (start, end) = (a.min(b), a.max(b));
+        let edited = [&input[..start], &insert, &input[end..]].concat();
+        for options in [Options::default(), everything_enabled()] {
+            let mut prev_events = Vec::new();
+            BlockParser::new_with_options(input.as_bytes(), options).parse(&mut prev_events);
+            let mut expected = Vec::new();
+            BlockParser::new_with_options(edited.as_bytes(), options).parse(&mut expected);
+            let reparsed = BlockParser::new_with_options(edited.as_bytes(), options).reparse_range(
+                &prev_events,
+                Range::from_usize(start, end),
+                insert.len(),
+            );
+            prop_assert_eq!(reparsed, expected);
+        }
+    }
+
+    #[test]
+    fn events_iterator_matches_parse(input in blocks()) {
+        for options in [Options::default(), everything_enabled()] {
+            let mut expected = Vec::new();
+            BlockParser::new_with_options(input.as_bytes(), options).parse(&mut expected);
+            let mut parser = BlockParser::new_with_options(input.as_bytes(), options);
+            let streamed: Vec<_> = parser.events().collect();
+            prop_assert_eq!(streamed, expected);
+        }
+    }
+
+    #[test]
+    fn unmatched_inline_markup_keeps_text(input in inline_delimiters()) {
+        let text = strip_tags(&to_html_with_options(&input, &everything_enabled()));
+        for letter in ['x', 'z'] {
+            prop_assert_eq!(text.matches(letter).count(), input.matches(letter).count());
+        }
+    }
+
+    #[test]
+    fn arbitrary_unicode_never_panics(input in "\\PC{0,256}") {
+        let html = to_html_with_options(&input, &trusted_with_filter());
+        assert_no_raw_script(&html);
+    }
+}
+
+/// Found by the harness: a NUL byte at line start was taken for end of input
+/// and never consumed, so the block parser looped forever.
+#[test]
+fn nul_bytes_do_not_stall_the_block_parser() {
+    assert_eq!(to_html("\0"), "<p>\0</p>\n");
+    assert_eq!(
+        to_html("- \0\n> \0"),
+        "<ul>\n<li>\0</li>\n</ul>\n<blockquote>\n<p>\0</p>\n</blockquote>\n"
+    );
+    assert!(to_html("| a |\n|---|\n\0").contains("<td>\0</td>"));
+}
+
+/// A blank line inside fenced code, whose range starts after the stripped
+/// indentation, was taken for the blank line before the block.
+#[test]
+fn reparse_range_keeps_blank_lines_in_fenced_code() {
+    let input = "Intro\n ```\n \n";
+    let edited = "Intro\n ```\n \ny";
+    let mut prev_events = Vec::new();
+    BlockParser::new(input.as_bytes()).parse(&mut prev_events);
+    let mut expected = Vec::new();
+    BlockParser::new(edited.as_bytes()).parse(&mut expected);
+    let reparsed = BlockParser::new(edited.as_bytes()).reparse_range(
+        &prev_events,
+        Range::from_usize(13, 13),
+        1,
+    );
+    assert_eq!(reparsed, expected);
+}
@@ -0,0 +1,296 @@
+use ferromark::block::{ListKind, TaskState};
+use ferromark::{
+    CodeBlockKind, ExternalLinks, HtmlWriter, LinkRefDef, Options, PrettyConfig, Range,
+    RenderPolicy, Renderer, Sanitizer, render_with, to_html_with_options,
+};
+use serde::Deserialize;
+use std::fs;
+
+#[derive(Deserialize)]
+struct SpecExample {
+    markdown: String,
+}
+
+fn html(input: &str, options: &Options) -> String {
+    let mut writer = HtmlWriter::new();
+    render_with(input, options, &mut writer);
+    writer.into_string().unwrap()
+}
+
+/// Strips a document down to its text: blocks are separated by a blank
+/// line, list items by a line break, and images keep their description.
+#[derive(Default)]
+struct PlainText {
+    out: String,
+    /// Whether the next block needs a blank line before it.
+    separate: bool,
+}
+
+impl PlainText {
+    fn block(&mut self) {
+        if self.separate {
+            self.out.push_str("\n\n");
+            self.separate = false;
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.out.push_str(std::str::from_utf8(bytes).unwrap());
+    }
+}
+
+impl Renderer for PlainText {
+    fn paragraph_start(&mut self, _tight: bool) {
+        self.block();
+    }
+
+    fn paragraph_end(&mut self, _tight: bool) {
+        self.separate = true;
+    }
+
+    fn heading_start(&mut self, _level: u8, _id: Option<&str>) {
+        self.block();
+    }
+
+    fn heading_end(&mut self, _level: u8) {
+        self.separate = true;
+    }
+
+    fn code_block_start(&mut self, _input: &[u8], _kind: CodeBlockKind) {
+        self.block();
+    }
+
+    fn code(&mut self, input: &[u8], range: Range) {
+        self.push(range.slice(input));
+    }
+
+    fn code_block_end(&mut self) {
+        if self.out.ends_with('\n') {
+            self.out.pop();
+        }
+        self.separate = true;
+    }
+
+    fn list_start(&mut self, _kind: ListKind, _tight: bool) {
+        self.block();
+    }
+
+    fn list_item_start(&mut self, _task: TaskState) {
+        if self.separate {
+            self.out.push('\n');
+            self.separate = false;
+        }
+    }
+
+    fn list_item_end(&mut self) {
+        self.separate = true;
+    }
+
+    fn text(&mut self, content: &[u8], range: Range) {
+        self.push(range.slice(content));
+    }
+
+    fn code_span(&mut self, content: &[u8], range: Range) {
+        self.push(range.slice(content));
+    }
+
+    fn autolink(&mut self, content: &[u8], url: Range, _is_email: bool) {
+        self.push(url.slice(content));
+    }
+
+    fn escaped_char(&mut self, ch: u8) {
+        self.out.push(char::from(ch));
+    }
+
+    fn soft_break(&mut self) {
+        self.out.push(' ');
+    }
+
+    fn hard_break(&mut self) {
+        self.out.push('\n');
+    }
+
+    fn footnote_ref(&mut self, _label: &str, number: usize, _reference: usize) {
+        self.out.push_str(&format!("[{number}]"));
+    }
+
+    fn footnote_definition_start(&mut self, _label: &str, number: usize) {
+        self.block();
+        self.out.push_str(&format!("[{number}] "));
+    }
+}
+
+fn plain_text(input: &str, options: &Options) -> String {
+    let mut text = PlainText::default();
+    render_with(input, options, &mut text);
+    text.out
+}
+
+#[test]
+fn html_writer_matches_to_html_on_spec_examples() {
+    let spec: Vec<SpecExample> =
+        serde_json::from_str(&fs::read_to_string("tests/spec.json").unwrap()).unwrap();
+    for options in [Options::commonmark(), Options::gfm()] {
+        for example in &spec {
+            assert_eq!(
+                html(&example.markdown, &options),
+                to_html_with_options(&example.markdown, &options),
+                "{:?}",
+                example.markdown
+            );
+        }
+    }
+}
+
+#[test]
+fn html_writer_matches_to_html_on_extensions() {
+    let options = Options {
+        callouts: true,
+        math: true,
+        wikilinks: true,
+        highlight: true,
+        superscript: true,
+        subscript: true,
+        emoji: true,
+        fenced_divs: true,
+        attributes: true,
+        ..Options::gfm()
+    };
+    let input = "## Head {#h .c k=v}\n\n```rust {.x}\nlet a;\n```\n\n\
+                 | a | b |\n|:--|--:|\n| `x` | ~~y~~ |\n\n\
+                 ::: {#box .note k=v}\n- tight\n\n  ::: inner\n  text\n  :::\n:::\n\n\
+                 - [ ] todo\n- [x] done\n\n\
+                 > [!NOTE]\n> Plain.\n\n\
+                 > [!TIP]- Custom *title*\n> Folded.\n\n\
+                 www.example.com <a@b.co> :smile: ==m== ^s^ ~t~ $x$ [[Page|label]]\n\
+                 ![a *b* [c](/d)](/img \"t\")\n";
+    assert_eq!(html(input, &options), to_html_with_options(input, &options));
+}
+
+#[test]
+fn html_writer_matches_to_html_with_output_options() {
+    assert_eq!(
+        html("# Title", &Options::default()),
+        "<h1 id=\"title\">Title</h1>\n"
+    );
+    let options = Options {
+        footnotes: true,
+        inline_footnotes: true,
+        callouts: true,
+        callout_aria: true,
+        math: true,
+        attributes: true,
+        task_list_classes: true,
+        hard_wrap: true,
+        untrusted_content: true,
+        render_policy: RenderPolicy::Trusted,
+        sanitizer: Sanitizer::GitHubAllowlist,
+        external_links: Some(ExternalLinks::NEW_TAB),
+        footnote_section_title: Some("Notes"),
+        heading_ids: true,
+        pretty: Some(PrettyConfig {
+            wrap_block_tags: true,
+            ..PrettyConfig::default()
+        }),
+        ..Options::gfm()
+    };
+    let input = "# Intro {#setup}\n\n## Setup\n\n## Setup\n\n\
+                 A[^a], b[^a] and c^[Inline.]\nnext <b onclick=\"x\">bold</b>\n\n\
+                 <div style=\"color: red\"><script>x</script></div>\n\n\
+                 > [!WARNING]\n> Careful.\n\n\
+                 - [x] done\n\n$$\nx^2\n$$\n\n\
+                 | a |\n|---|\n|   |\n\n\
+                 [l](https://example.com) ![i](/i.png)\n\n\
+                 [^a]: Note with `code`.\n";
+    assert_eq!(html(input, &options), to_html_with_options(input, &options));
+}
+
+#[test]
+fn plain_text_renderer_strips_markup() {
+    let input = "# Title\n\nSome *emphasis*, `code` and [a link][ref].\\\nNext <https://x.org>.\n\n\
+                 - one\n- **two**\n\n```rust\nfn main() {}\n```\n\n![alt *text*](/img.png)\n\n\
+                 [ref]: /url\n";
+    assert_eq!(
+        plain_text(input, &Options::default()),
+        "Title\n\nSome emphasis, code and a link.\nNext https://x.org.\n\n\
+         one\ntwo\n\nfn main() {}\n\nalt text"
+    );
+}
+
+#[test]
+fn footnotes_follow_the_document_in_reference_order() {
+    let options = Options {
+        footnotes: true,
+        ..Options::default()
+    };
+    let input = "B[^b] and a[^a].\n\n[^a]: First defined.\n[^b]: Second defined.\n[^c]: Unused.\n";
+    assert_eq!(
+        plain_text(input, &options),
+        "B[1] and a[2].\n\n[1] Second defined.\n\n[2] First defined."
+    );
+}
+
+#[test]
+fn ranges_index_the_source_or_inline_content() {
+    /// Records what each range points at.
+    #[derive(Default)]
+    struct Slices {
+        code: Vec<String>,
+        info: Vec<String>,
+        urls: Vec<String>,
+        texts: Vec<String>,
+    }
+
+    fn string(bytes: &[u8]) -> String {
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    impl Renderer for Slices {
+        fn code_block_start(&mut self, input: &[u8], kind: CodeBlockKind) {
+            if let CodeBlockKind::Fenced { info: Some(info) } = kind {
+                self.info.push(string(info.slice(input)));
+            }
+        }
+
+        fn code(&mut self, input: &[u8], range: Range) {
+            self.code.push(string(range.slice(input)));
+        }
+
+        fn link_start(&mut self, content: &[u8], url: Range, _title: Option<Range>) {
+            self.urls.push(string(url.slice(content)));
+        }
+
+        fn link_start_ref(&mut self, definition: &LinkRefDef) {
+            self.urls.push(string(&definition.url));
+        }
+
+        fn text(&mut self, content: &[u8], range: Range) {
+            self.texts.push(string(range.slice(content)));
+        }
+    }
+
+    let mut slices = Slices::default();
+    render_with(
+        "> ```js\n> let a;\n> ```\n\n- [x](/inline)\n  [y]\n\n[y]: /ref\n",
+        &Options::default(),
+        &mut slices,
+    );
+    assert_eq!(slices.info, ["js"]);
+    assert_eq!(slices.code, ["let a;\n"]);
+    assert_eq!(slices.urls, ["/inline", "/ref"]);
+    assert_eq!(slices.texts, ["x", "y"]);
+}
+
+#[test]
+fn inline_footnotes_are_listed_in_reference_order() {
+    let options = Options {
+        footnotes: true,
+        inline_footnotes: true,
+        ..Options::default()
+    };
+    let input = "A^[Inline *note*.] and b[^b].\n\n[^b]: Defined.\n";
+    assert_eq!(
+        plain_text(input, &options),
+        "A[1] and b[2].\n\n[1] Inline note.\n\n[2] Defined."
+    );
+}
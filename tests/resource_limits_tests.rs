@@ -75,3 +75,99 @@ fn table_columns_are_bounded() {
 
     assert_eq!(html.matches("<th>").count(), limits::MAX_TABLE_COLUMNS);
 }
+
+#[test]
+fn block_nesting_limit_is_configurable() {
+    let options = Options {
+        max_nesting_depth: Some(3),
+        ..Options::default()
+    };
+    let html = to_html_with_options("> > > > > deep", &options);
+
+    assert_eq!(html.matches("<blockquote>").count(), 3);
+    assert!(html.contains("<p>&gt; &gt; deep</p>"), "{html}");
+
+    let html = to_html_with_options("- - - - item", &options);
+    assert_eq!(html.matches("<ul>").count(), 3);
+    assert!(html.contains("<li>- item</li>"), "{html}");
+}
+
+#[test]
+fn inline_mark_limit_is_configurable() {
+    let options = Options {
+        max_inline_marks: Some(4),
+        ..Options::default()
+    };
+    let html = to_html_with_options("*a* *b* *c*", &options);
+
+    assert_eq!(html, "<p><em>a</em> <em>b</em> *c*</p>\n");
+}
+
+#[test]
+fn deeply_nested_blockquote_markers_stay_linear() {
+    fn render_time(markers: usize) -> std::time::Duration {
+        let markdown = format!("{}x\n", ">".repeat(markers));
+        let start = std::time::Instant::now();
+        let html = to_html(&markdown);
+        let elapsed = start.elapsed();
+        assert_eq!(
+            html.matches("<blockquote>").count(),
+            limits::MAX_BLOCK_NESTING
+        );
+        assert!(html.contains("&gt;x</p>"));
+        elapsed
+    }
+
+    render_time(100_000);
+    let small = (0..3).map(|_| render_time(100_000)).min().unwrap();
+    let large = (0..3).map(|_| render_time(400_000)).min().unwrap();
+
+    // Quadratic behavior would make 4x the input take ~16x as long.
+    assert!(
+        large < small * 10 + std::time::Duration::from_millis(20),
+        "100k: {small:?}, 400k: {large:?}"
+    );
+}
+
+#[test]
+fn deeply_nested_brackets_respect_mark_limit() {
+    let options = Options {
+        max_inline_marks: Some(64),
+        ..Options::default()
+    };
+    let markdown = format!("{}x{}", "[".repeat(100_000), "]".repeat(100_000));
+    let html = to_html_with_options(&markdown, &options);
+
+    assert!(html.starts_with("<p>[[[["));
+    assert!(html.ends_with("]]]]</p>\n"));
+}
+
+#[test]
+fn reference_label_length_is_bounded() {
+    let render = |label: &str| to_html(&format!("[{label}]\n\n[{label}]: /url"));
+    let at_limit = "a".repeat(limits::MAX_REF_LABEL_LEN);
+    let over_limit = "a".repeat(limits::MAX_REF_LABEL_LEN + 1);
+
+    assert!(render(&at_limit).contains("<a href=\"/url\">"));
+    let html = render(&over_limit);
+    assert!(!html.contains("<a href="), "{html}");
+    assert!(html.contains(&format!("<p>[{over_limit}]: /url</p>")));
+
+    // Full references check the second label
+    let html = to_html(&format!("[x][{over_limit}]\n\n[{over_limit}]: /url"));
+    assert!(!html.contains("<a href="));
+}
+
+#[test]
+fn footnote_label_length_is_bounded() {
+    let options = Options {
+        footnotes: true,
+        ..Options::default()
+    };
+    // The caret counts towards the label length
+    let render =
+        |label: &str| to_html_with_options(&format!("[^{label}]\n\n[^{label}]: note"), &options);
+
+    assert!(render(&"a".repeat(limits::MAX_REF_LABEL_LEN - 1)).contains("<sup"));
+    assert!(!render(&"a".repeat(limits::MAX_REF_LABEL_LEN)).contains("<sup"));
+}
@@ -0,0 +1,147 @@
+use ferromark::{Options, RenderPolicy, Sanitizer, to_html_with_options};
+
+fn sanitized(input: &str, sanitizer: Sanitizer) -> String {
+    to_html_with_options(
+        input,
+        &Options {
+            render_policy: RenderPolicy::Trusted,
+            sanitizer,
+            ..Options::default()
+        },
+    )
+}
+
+fn allowlisted(input: &str) -> String {
+    sanitized(input, Sanitizer::GitHubAllowlist)
+}
+
+#[test]
+fn allowlist_keeps_allowed_tags_and_attributes() {
+    assert_eq!(
+        allowlisted("<KBD>Ctrl</KBD> <abbr title=\"x\" onclick=\"y()\">a</abbr><br/>"),
+        "<p><kbd>Ctrl</kbd> <abbr title=\"x\">a</abbr><br /></p>\n"
+    );
+    assert_eq!(
+        allowlisted("<details open>\n<summary class=x>More</summary>\n</details>\n"),
+        "<details open>\n<summary>More</summary>\n</details>\n"
+    );
+    // Element-specific attributes stay on their elements only
+    assert_eq!(
+        allowlisted("<div itemscope cite='/a' style=\"color: red\">\n\nx\n\n</div>\n"),
+        "<div itemscope>\n<p>x</p>\n</div>\n"
+    );
+    // Values are re-quoted
+    assert_eq!(
+        allowlisted("<span title='say \"hi\"' lang=en>x</span>"),
+        "<p><span title=\"say &quot;hi&quot;\" lang=\"en\">x</span></p>\n"
+    );
+}
+
+#[test]
+fn allowlist_drops_other_tags_but_keeps_their_content() {
+    assert_eq!(
+        allowlisted("a <u>b</u> <iframe src=/x>c</iframe>"),
+        "<p>a b c</p>\n"
+    );
+    assert_eq!(
+        allowlisted("<form action=/x>\n<input name=q>\n</form>\n"),
+        "\n\n\n"
+    );
+    assert_eq!(allowlisted("<style>p {}</style>\n"), "p {}\n");
+}
+
+#[test]
+fn allowlist_drops_script_content_comments_and_declarations() {
+    assert_eq!(
+        allowlisted("<script>\nalert(1)\n</SCRIPT >\n\nafter"),
+        "\n<p>after</p>\n"
+    );
+    assert_eq!(
+        allowlisted("a <!-- note --> <?php x ?> b"),
+        "<p>a   b</p>\n"
+    );
+    assert_eq!(allowlisted("<!DOCTYPE html>\n"), "\n");
+}
+
+#[test]
+fn allowlist_filters_url_protocols() {
+    assert_eq!(
+        allowlisted("<a href=\"https://a.example\" target=_blank>x</a>"),
+        "<p><a href=\"https://a.example\" target=\"_blank\">x</a></p>\n"
+    );
+    for href in [
+        "javascript:alert(1)",
+        "JaVaScRiPt:alert(1)",
+        " java\tscript:x",
+        "javascript&#58;alert(1)",
+        "data:text/html,x",
+    ] {
+        assert_eq!(
+            allowlisted(&format!("<a href=\"{href}\">x</a>")),
+            "<p><a>x</a></p>\n",
+            "{href}"
+        );
+    }
+    // Relative URLs pass; `ftp` is not allowed for images
+    assert_eq!(
+        allowlisted("<img src=\"/a.png\" alt=\"a\"> <img src=\"ftp://x/a.png\">"),
+        "<p><img src=\"/a.png\" alt=\"a\"> <img></p>\n"
+    );
+    assert_eq!(
+        allowlisted("<a href=\"mailto:me@example.com\">m</a>"),
+        "<p><a href=\"mailto:me@example.com\">m</a></p>\n"
+    );
+}
+
+#[test]
+fn allowlist_handles_tags_across_html_block_lines() {
+    assert_eq!(
+        allowlisted("<div\n  class=\"x\"\n  title=\"t\">\n\nx\n\n</div>\n"),
+        "<div title=\"t\">\n<p>x</p>\n</div>\n"
+    );
+    // A tag never finished before the block ends is text
+    assert_eq!(
+        allowlisted("<div>\n<b title=\"x\n\nafter"),
+        "<div>\n&lt;b title=\"x\n<p>after</p>\n"
+    );
+}
+
+#[test]
+fn allowlist_escapes_stray_angle_brackets() {
+    assert_eq!(
+        allowlisted("<div>\n1 < 2\n</div>\n"),
+        "<div>\n1 &lt; 2\n</div>\n"
+    );
+}
+
+#[test]
+fn blacklist_matches_disallowed_raw_html() {
+    let input = "<div>\n<script>x</script>\n</div>\n";
+    let render = |sanitizer, disallowed_raw_html| {
+        to_html_with_options(
+            input,
+            &Options {
+                render_policy: RenderPolicy::Trusted,
+                sanitizer,
+                disallowed_raw_html,
+                ..Options::default()
+            },
+        )
+    };
+    let filtered = "<div>\n&lt;script>x&lt;/script>\n</div>\n";
+    assert_eq!(render(Sanitizer::None, true), filtered);
+    assert_eq!(render(Sanitizer::GitHubBlacklist, false), filtered);
+    assert_eq!(render(Sanitizer::None, false), input);
+}
+
+#[test]
+fn sanitizer_does_not_apply_to_untrusted_rendering() {
+    let options = Options {
+        sanitizer: Sanitizer::GitHubAllowlist,
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("<b>x</b>", &options),
+        "<p>&lt;b&gt;x&lt;/b&gt;</p>\n"
+    );
+}
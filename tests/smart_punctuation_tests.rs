@@ -0,0 +1,123 @@
+use ferromark::{Options, RenderPolicy, to_html, to_html_with_options};
+
+fn smart_html(input: &str) -> String {
+    to_html_with_options(
+        input,
+        &Options {
+            smart_punctuation: true,
+            render_policy: RenderPolicy::Trusted,
+            autolink_literals: true,
+            heading_ids: false,
+            ..Options::default()
+        },
+    )
+}
+
+#[test]
+fn double_and_single_quotes() {
+    assert_eq!(
+        smart_html("\"Hello,\" she said. 'Hi.'"),
+        "<p>\u{201C}Hello,\u{201D} she said. \u{2018}Hi.\u{2019}</p>\n"
+    );
+}
+
+#[test]
+fn nested_quotes() {
+    assert_eq!(
+        smart_html("\"She said 'no' twice\""),
+        "<p>\u{201C}She said \u{2018}no\u{2019} twice\u{201D}</p>\n"
+    );
+    assert_eq!(
+        smart_html("'outer \"inner\" end'"),
+        "<p>\u{2018}outer \u{201C}inner\u{201D} end\u{2019}</p>\n"
+    );
+}
+
+#[test]
+fn apostrophes() {
+    assert_eq!(
+        smart_html("Don't stop, it's the '90s' rock'n'roll"),
+        "<p>Don\u{2019}t stop, it\u{2019}s the \u{2018}90s\u{2019} rock\u{2019}n\u{2019}roll</p>\n"
+    );
+    assert_eq!(
+        smart_html("the dogs' bones"),
+        "<p>the dogs\u{2019} bones</p>\n"
+    );
+}
+
+#[test]
+fn quotes_across_emphasis() {
+    assert_eq!(
+        smart_html("\"*emphasis*\" and *\"inside\"*"),
+        "<p>\u{201C}<em>emphasis</em>\u{201D} and <em>\u{201C}inside\u{201D}</em></p>\n"
+    );
+}
+
+#[test]
+fn unmatched_quotes() {
+    assert_eq!(smart_html("\"open"), "<p>\u{201C}open</p>\n");
+    assert_eq!(smart_html("close\""), "<p>close\u{201D}</p>\n");
+    assert_eq!(
+        smart_html("[link](/u)'s"),
+        "<p><a href=\"/u\">link</a>\u{2019}s</p>\n"
+    );
+}
+
+#[test]
+fn dashes() {
+    assert_eq!(
+        smart_html("en -- dash, em --- dash, a-b stays"),
+        "<p>en \u{2013} dash, em \u{2014} dash, a-b stays</p>\n"
+    );
+    assert_eq!(
+        smart_html("four ---- five -----"),
+        "<p>four \u{2013}\u{2013} five \u{2014}\u{2013}</p>\n"
+    );
+}
+
+#[test]
+fn dashes_next_to_digits() {
+    assert_eq!(
+        smart_html("pages 10--20, 1999---2001"),
+        "<p>pages 10\u{2013}20, 1999\u{2014}2001</p>\n"
+    );
+    assert_eq!(smart_html("-1 and 3-4"), "<p>-1 and 3-4</p>\n");
+}
+
+#[test]
+fn ellipsis() {
+    assert_eq!(
+        smart_html("Wait... what....."),
+        "<p>Wait\u{2026} what\u{2026}..</p>\n"
+    );
+}
+
+#[test]
+fn code_html_autolinks_and_escapes_stay() {
+    assert_eq!(
+        smart_html("`\"a\" -- ...` <span title=\"x--y\">'s</span>"),
+        "<p><code>&quot;a&quot; -- ...</code> <span title=\"x--y\">\u{2019}s</span></p>\n"
+    );
+    assert_eq!(
+        smart_html("<https://x.org/a--b...> https://y.org/c--d"),
+        "<p><a href=\"https://x.org/a--b...\">https://x.org/a--b...</a> \
+         <a href=\"https://y.org/c--d\">https://y.org/c--d</a></p>\n"
+    );
+    assert_eq!(
+        smart_html("\\\"quoted\\\" \\-- \\..."),
+        "<p>&quot;quoted&quot; -- ...</p>\n"
+    );
+}
+
+#[test]
+fn fenced_code_stays() {
+    assert_eq!(
+        smart_html("```\n\"a\" -- b...\n```\n"),
+        "<pre><code>&quot;a&quot; -- b...\n</code></pre>\n"
+    );
+}
+
+#[test]
+fn disabled_by_default() {
+    assert_eq!(to_html("\"a\" -- b..."), "<p>&quot;a&quot; -- b...</p>\n");
+}
@@ -0,0 +1,197 @@
+use ferromark::{BlockEvent, BlockParser, InlineEvent, InlineParser, Options};
+
+/// Parse `input` with spans on and return each event with its source slice.
+fn block_spans(input: &str, options: Options) -> Vec<(BlockEvent, &str)> {
+    let mut parser = BlockParser::new_with_options(input.as_bytes(), options);
+    parser.set_record_spans(true);
+    let mut events = Vec::new();
+    parser.parse(&mut events);
+    assert_eq!(events.len(), parser.spans().len());
+    events
+        .into_iter()
+        .zip(parser.spans())
+        .map(|(event, span)| (event, &input[span.start as usize..span.end as usize]))
+        .collect()
+}
+
+fn inline_spans(input: &str) -> Vec<(InlineEvent, &str)> {
+    let mut parser = InlineParser::new();
+    parser.set_record_spans(true);
+    parser.set_emoji(true);
+    let mut events = Vec::new();
+    parser.parse_with_options(
+        input.as_bytes(),
+        None,
+        true,
+        true,
+        false,
+        false,
+        false,
+        true,
+        false,
+        None,
+        &mut events,
+    );
+    assert_eq!(events.len(), parser.spans().len());
+    events
+        .into_iter()
+        .zip(parser.spans())
+        .map(|(event, span)| (event, &input[span.start as usize..span.end as usize]))
+        .collect()
+}
+
+fn source_of<'a, E>(spans: &[(E, &'a str)], pred: impl Fn(&E) -> bool) -> Vec<&'a str> {
+    spans
+        .iter()
+        .filter(|(event, _)| pred(event))
+        .map(|(_, source)| *source)
+        .collect()
+}
+
+#[test]
+fn spans_are_off_by_default() {
+    let mut parser = BlockParser::new(b"# a\n");
+    let mut events = Vec::new();
+    parser.parse(&mut events);
+    assert!(parser.spans().is_empty());
+}
+
+#[test]
+fn leaf_blocks_span_their_source() {
+    let input = "# Héllo #\n\nPara ü\nline two\n\n***\n\nTitle\n=====\n";
+    let spans = block_spans(input, Options::default());
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::HeadingStart { .. })),
+        ["# Héllo #", "Title\n====="]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::ParagraphEnd)),
+        ["Para ü\nline two"]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::ThematicBreak)),
+        ["***"]
+    );
+}
+
+#[test]
+fn containers_span_their_children() {
+    let input = "> quote ü\n> more\n\n- a\n- b\n\n  c\n\nafter\n";
+    let spans = block_spans(input, Options::default());
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::BlockQuoteStart { .. })),
+        ["> quote ü\n> more"]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::ListItemStart { .. })),
+        ["- a", "- b\n\n  c"]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::ListEnd { .. })),
+        ["- a\n- b\n\n  c"]
+    );
+}
+
+#[test]
+fn tab_expanded_content_keeps_byte_offsets() {
+    let spans = block_spans("\tcode ü\n\ttwo\n\n-\tfoo\n\n\tbär\n", Options::default());
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::CodeBlockStart { .. })),
+        ["\tcode ü\n\ttwo\n"]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::Code(_))),
+        ["code ü\n", "two\n"]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::ListItemStart { .. })),
+        ["-\tfoo\n\n\tbär"]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::ParagraphStart)),
+        ["foo", "bär"]
+    );
+}
+
+#[test]
+fn fenced_code_html_and_tables() {
+    let input = "```rust\nfn x\n```\n\n<div>\nhi\n</div>\n\n| a | b |\n|---|---|\n| ü | 2 |\n";
+    let spans = block_spans(
+        input,
+        Options {
+            allow_html: true,
+            ..Options::default()
+        },
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::CodeBlockEnd)),
+        ["```rust\nfn x\n```"]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::HtmlBlockStart)),
+        ["<div>\nhi\n</div>\n"]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::TableRowStart)),
+        ["| a | b |", "| ü | 2 |"]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::TableCellEnd)),
+        ["a", "b", "ü", "2"]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, BlockEvent::TableEnd)),
+        ["| a | b |\n|---|---|\n| ü | 2 |"]
+    );
+}
+
+#[test]
+fn footnote_definitions_leave_no_spans() {
+    let input = "[^1]: note\n\nafter ü\n";
+    let spans = block_spans(
+        input,
+        Options {
+            footnotes: true,
+            ..Options::default()
+        },
+    );
+    assert_eq!(
+        source_of(&spans, |_| true),
+        ["after ü", "after ü", "after ü"]
+    );
+}
+
+#[test]
+fn inline_constructs_span_their_source() {
+    let spans = inline_spans("a *émph* [link ü](/url \"t\") `co` \\* :smile:");
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, InlineEvent::EmphasisStart)),
+        ["*émph*"]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, InlineEvent::LinkEnd)),
+        ["[link ü](/url \"t\")"]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, InlineEvent::Code(_))),
+        ["co"]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, InlineEvent::EscapedChar(_))),
+        ["\\*"]
+    );
+    assert_eq!(
+        source_of(&spans, |e| matches!(e, InlineEvent::Emoji(_))),
+        [":smile:"]
+    );
+}
+
+#[test]
+fn inline_breaks_and_plain_text() {
+    let spans = inline_spans("line  \nnext\nthird");
+    assert_eq!(
+        source_of(&spans, |_| true),
+        ["line", "  \n", "next", "\n", "third"]
+    );
+    assert_eq!(source_of(&inline_spans("plain ü"), |_| true), ["plain ü"]);
+}
@@ -105,3 +105,21 @@ fn subscript_and_strikethrough_coexist() {
         "<p>H<sub>2</sub>O and <del>deleted</del></p>\n"
     );
 }
+
+#[test]
+fn spaces_end_subscript_unless_escaped() {
+    assert_eq!(subscript_html("~a b~"), "<p>~a b~</p>\n");
+    assert_eq!(subscript_html("P~a\\ cat~"), "<p>P<sub>a cat</sub></p>\n");
+    assert_eq!(subscript_html("~a\nb~"), "<p>~a\nb~</p>\n");
+    assert_eq!(subscript_html("~\\ ~"), "<p>~\\ ~</p>\n");
+    assert_eq!(subscript_html("a\\ b ~c~"), "<p>a\\ b <sub>c</sub></p>\n");
+}
+
+#[test]
+fn subscript_inside_strikethrough() {
+    assert_eq!(
+        subscript_html("~~H~2~O is water~~"),
+        "<p><del>H<sub>2</sub>O is water</del></p>\n"
+    );
+    assert_eq!(no_subscript_html("H~2~O"), "<p>H~2~O</p>\n");
+}
@@ -108,3 +108,19 @@ fn superscript_does_not_break_footnote_refs() {
     assert!(result.contains("<sup>2</sup>"), "{result}");
     assert!(result.contains("data-footnote-ref>1</a></sup>"), "{result}");
 }
+
+#[test]
+fn exponent_after_text() {
+    assert_eq!(superscript_html("E=mc^2^"), "<p>E=mc<sup>2</sup></p>\n");
+}
+
+#[test]
+fn spaces_end_superscript_unless_escaped() {
+    assert_eq!(superscript_html("^a b^"), "<p>^a b^</p>\n");
+    assert_eq!(
+        superscript_html("x^a\\ b\\ c^"),
+        "<p>x<sup>a b c</sup></p>\n"
+    );
+    assert_eq!(superscript_html("^a\tb^"), "<p>^a\tb^</p>\n");
+    assert_eq!(superscript_html("^a\\\\ b^"), "<p>^a\\ b^</p>\n");
+}
@@ -0,0 +1,90 @@
+//! Escaping of `<`, `>`, `&` and `"` in text. Like the CommonMark reference
+//! renderer, every text context escapes all four; `'` stays literal.
+
+use ferromark::{Options, RenderPolicy, to_html, to_html_with_options};
+
+#[test]
+fn comparison_operators_in_prose() {
+    assert_eq!(
+        to_html("If a < b and b > c, then a <> c & \"maybe\" a <= c or a >= c."),
+        "<p>If a &lt; b and b &gt; c, then a &lt;&gt; c &amp; &quot;maybe&quot; a &lt;= c or a &gt;= c.</p>\n"
+    );
+    assert_eq!(
+        to_html("0 < x < 1 => x^2 < x; f: A -> B; x << y >> z"),
+        "<p>0 &lt; x &lt; 1 =&gt; x^2 &lt; x; f: A -&gt; B; x &lt;&lt; y &gt;&gt; z</p>\n"
+    );
+    // `>` only starts a blockquote at the beginning of a line
+    assert_eq!(
+        to_html("x\n> y"),
+        "<p>x</p>\n<blockquote>\n<p>y</p>\n</blockquote>\n"
+    );
+    assert_eq!(to_html("x >\ny"), "<p>x &gt;\ny</p>\n");
+}
+
+#[test]
+fn chemistry_and_arrows() {
+    assert_eq!(
+        to_html("2H<sub>2</sub> + O2 -> 2 H2O <-> H3O+ + OH-"),
+        "<p>2H&lt;sub&gt;2&lt;/sub&gt; + O2 -&gt; 2 H2O &lt;-&gt; H3O+ + OH-</p>\n"
+    );
+    assert_eq!(
+        to_html("A <=> B, pH < 7 & T > 300 K"),
+        "<p>A &lt;=&gt; B, pH &lt; 7 &amp; T &gt; 300 K</p>\n"
+    );
+}
+
+#[test]
+fn every_text_context_escapes_the_same() {
+    let options = Options {
+        heading_ids: false,
+        ..Options::default()
+    };
+    let cases = [
+        ("# a<b>&\"", "<h1>a&lt;b&gt;&amp;&quot;</h1>\n"),
+        ("`a<b>&\"`", "<p><code>a&lt;b&gt;&amp;&quot;</code></p>\n"),
+        (
+            "```\na<b>&\"\n```",
+            "<pre><code>a&lt;b&gt;&amp;&quot;\n</code></pre>\n",
+        ),
+        (
+            "    a<b>&\"",
+            "<pre><code>a&lt;b&gt;&amp;&quot;</code></pre>\n",
+        ),
+        (
+            "[a<b>&\"](/u)",
+            "<p><a href=\"/u\">a&lt;b&gt;&amp;&quot;</a></p>\n",
+        ),
+        (
+            "![a<b>&\"](/i)",
+            "<p><img src=\"/i\" alt=\"a&lt;b&gt;&amp;&quot;\" /></p>\n",
+        ),
+        ("- a<b>&\"", "<ul>\n<li>a&lt;b&gt;&amp;&quot;</li>\n</ul>\n"),
+        ("\\< \\> \\& \\\"", "<p>&lt; &gt; &amp; &quot;</p>\n"),
+        ("&lt; &gt; &amp; &quot;", "<p>&lt; &gt; &amp; &quot;</p>\n"),
+        ("it's", "<p>it's</p>\n"),
+    ];
+    for (markdown, html) in cases {
+        assert_eq!(to_html_with_options(markdown, &options), html, "{markdown}");
+    }
+    let table = to_html("| a<b | c>d |\n| - | - |\n| \"e\" | f&g |\n");
+    assert!(
+        table.contains("<th>a&lt;b</th>\n<th>c&gt;d</th>"),
+        "{table}"
+    );
+    assert!(
+        table.contains("<td>&quot;e&quot;</td>\n<td>f&amp;g</td>"),
+        "{table}"
+    );
+}
+
+#[test]
+fn trusted_rendering_escapes_text_around_raw_html() {
+    let trusted = Options {
+        render_policy: RenderPolicy::Trusted,
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("a < b <em>c</em> d > e", &trusted),
+        "<p>a &lt; b <em>c</em> d &gt; e</p>\n"
+    );
+}
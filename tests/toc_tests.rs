@@ -0,0 +1,103 @@
+use ferromark::{
+    Options, TocEntry, parse_full, table_of_contents, table_of_contents_flat, to_html_with_options,
+};
+
+const DOC: &str = "# Guide\n\n## Install `cargo`\n\n### From **source**\n\n## Usage\n\n## Usage\n\n# FAQ &amp; Help\n";
+
+fn ids(entries: &[TocEntry]) -> Vec<&str> {
+    entries.iter().map(|entry| entry.id.as_str()).collect()
+}
+
+#[test]
+fn flat_toc_lists_headings_in_document_order() {
+    let toc = table_of_contents_flat(DOC, &Options::default());
+
+    assert_eq!(
+        toc.iter().map(|entry| entry.level).collect::<Vec<_>>(),
+        vec![1, 2, 3, 2, 2, 1]
+    );
+    assert_eq!(
+        ids(&toc),
+        vec![
+            "guide",
+            "install-cargo",
+            "from-source",
+            "usage",
+            "usage-1",
+            "faq-amp-help"
+        ]
+    );
+    assert!(toc.iter().all(|entry| entry.children.is_empty()));
+}
+
+#[test]
+fn toc_ids_match_rendered_heading_anchors() {
+    let options = Options::default();
+    let html = to_html_with_options(DOC, &options);
+
+    for entry in table_of_contents_flat(DOC, &options) {
+        assert!(
+            html.contains(&format!("<h{} id=\"{}\">", entry.level, entry.id)),
+            "missing anchor {:?} in {html}",
+            entry.id
+        );
+    }
+}
+
+#[test]
+fn toc_text_strips_inline_markup() {
+    let toc = table_of_contents_flat(DOC, &Options::default());
+
+    assert_eq!(toc[1].text, "Install cargo");
+    assert_eq!(toc[2].text, "From source");
+    assert_eq!(toc[5].text, "FAQ & Help");
+}
+
+#[test]
+fn nested_toc_follows_heading_levels() {
+    let toc = table_of_contents(DOC, &Options::default());
+
+    assert_eq!(ids(&toc), vec!["guide", "faq-amp-help"]);
+    assert_eq!(
+        ids(&toc[0].children),
+        vec!["install-cargo", "usage", "usage-1"]
+    );
+    assert_eq!(ids(&toc[0].children[0].children), vec!["from-source"]);
+    assert!(toc[1].children.is_empty());
+}
+
+#[test]
+fn toc_includes_setext_headings_and_skips_front_matter() {
+    let options = Options {
+        front_matter: true,
+        ..Options::default()
+    };
+    let toc = table_of_contents_flat("---\ntitle: x\n---\nTitle\n=====\n\nBody", &options);
+
+    assert_eq!(ids(&toc), vec!["title"]);
+    assert_eq!(toc[0].level, 1);
+}
+
+#[test]
+fn empty_headings_get_numbered_fallback_ids() {
+    let markdown = "#\n\n## *\n\n# Text\n\n###\n";
+    let options = Options::default();
+    let toc = table_of_contents_flat(markdown, &options);
+
+    assert_eq!(ids(&toc), vec!["heading", "heading-1", "text", "heading-2"]);
+    assert_eq!(
+        toc.iter()
+            .map(|entry| entry.text.as_str())
+            .collect::<Vec<_>>(),
+        vec!["", "*", "Text", ""]
+    );
+    assert_eq!(
+        to_html_with_options(markdown, &options),
+        "<h1 id=\"heading\"></h1>\n<h2 id=\"heading-1\">*</h2>\n\
+         <h1 id=\"text\">Text</h1>\n<h3 id=\"heading-2\"></h3>\n"
+    );
+    assert_eq!(
+        parse_full(markdown, &options).headings,
+        table_of_contents(markdown, &options)
+    );
+}
@@ -72,3 +72,45 @@ fn trusted_policy_is_an_explicit_passthrough_boundary() {
     assert!(html.contains("<span onclick=\"run()\">"));
     assert!(html.contains("href=\"app:open\""));
 }
+
+#[test]
+fn untrusted_content_marks_links_and_autolinks_as_ugc() {
+    let options = Options {
+        untrusted_content: true,
+        autolink_literals: true,
+        ..Options::default()
+    };
+
+    let html = to_html_with_options("[site](https://example.com)", &options);
+    assert_eq!(
+        html,
+        "<p><a href=\"https://example.com\" rel=\"ugc nofollow\">site</a></p>\n"
+    );
+
+    let html = to_html_with_options("<https://example.com> and www.example.com", &options);
+    assert_eq!(html.matches(" rel=\"ugc nofollow\"").count(), 2, "{html}");
+}
+
+#[test]
+fn untrusted_content_strips_referrer_from_images() {
+    let html = to_html_with_options(
+        "![pixel](https://example.com/p.png \"Pixel\")",
+        &Options {
+            untrusted_content: true,
+            ..Options::default()
+        },
+    );
+
+    assert_eq!(
+        html,
+        "<p><img src=\"https://example.com/p.png\" alt=\"pixel\" title=\"Pixel\" referrerpolicy=\"no-referrer\" /></p>\n"
+    );
+}
+
+#[test]
+fn untrusted_content_is_off_by_default() {
+    let html = to_html("[site](https://example.com) ![p](/p.png)");
+
+    assert!(!html.contains("rel="));
+    assert!(!html.contains("referrerpolicy="));
+}
@@ -0,0 +1,197 @@
+use ferromark::{Options, WikiLinkUrl, to_html_with_options, wikilink_slug};
+
+fn wikilink_html(input: &str) -> String {
+    to_html_with_options(
+        input,
+        &Options {
+            wikilinks: true,
+            ..Options::default()
+        },
+    )
+}
+
+fn notes_url(target: &str, url: &mut String) {
+    url.push_str("/notes/");
+    url.push_str(&target.replace(' ', "_"));
+    url.push_str(".html");
+}
+
+#[test]
+fn basic_wikilink() {
+    assert_eq!(
+        wikilink_html("See [[Page Name]]."),
+        "<p>See <a href=\"Page%20Name\">Page Name</a>.</p>\n"
+    );
+}
+
+#[test]
+fn wikilink_with_label() {
+    assert_eq!(
+        wikilink_html("[[Page Name | the page]]"),
+        "<p><a href=\"Page%20Name\">the page</a></p>\n"
+    );
+}
+
+#[test]
+fn wikilink_empty_label_falls_back_to_target() {
+    assert_eq!(
+        wikilink_html("[[Page|]]"),
+        "<p><a href=\"Page\">Page</a></p>\n"
+    );
+}
+
+#[test]
+fn wikilink_section_becomes_fragment() {
+    assert_eq!(
+        wikilink_html("[[Setup Guide#First Steps]]"),
+        "<p><a href=\"Setup%20Guide#First%20Steps\">Setup Guide#First Steps</a></p>\n"
+    );
+}
+
+#[test]
+fn slug_urls() {
+    let options = Options {
+        wikilinks: true,
+        wikilink_url: Some(WikiLinkUrl(wikilink_slug)),
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("[[Page Name|the page]] [[javascript:alert(1)|x]]", &options),
+        "<p><a href=\"page-name\">the page</a> <a href=\"javascriptalert1\">x</a></p>\n"
+    );
+    assert_eq!(
+        to_html_with_options("[[Setup Guide#First Steps]]", &options),
+        "<p><a href=\"setup-guide#first-steps\">Setup Guide#First Steps</a></p>\n"
+    );
+}
+
+#[test]
+fn wikilink_custom_url_transform() {
+    let html = to_html_with_options(
+        "[[Daily Log|today]]",
+        &Options {
+            wikilinks: true,
+            wikilink_url: Some(WikiLinkUrl(notes_url)),
+            ..Options::default()
+        },
+    );
+    assert_eq!(html, "<p><a href=\"/notes/Daily_Log.html\">today</a></p>\n");
+}
+
+#[test]
+fn wikilink_label_is_plain_escaped_text() {
+    assert_eq!(
+        wikilink_html("[[a|*x* & y]]"),
+        "<p><a href=\"a\">*x* &amp; y</a></p>\n"
+    );
+}
+
+#[test]
+fn wikilink_not_split_into_bracket_links() {
+    assert_eq!(
+        wikilink_html("[[foo]]\n\n[foo]: /url"),
+        "<p><a href=\"foo\">foo</a></p>\n"
+    );
+}
+
+#[test]
+fn escaped_wikilink_opener() {
+    assert_eq!(wikilink_html("\\[[Page]]"), "<p>[[Page]]</p>\n");
+}
+
+#[test]
+fn wikilink_ignored_in_code_span() {
+    assert_eq!(
+        wikilink_html("`[[Page]]` and [[Other]]"),
+        "<p><code>[[Page]]</code> and <a href=\"Other\">Other</a></p>\n"
+    );
+    assert_eq!(
+        wikilink_html("`[[`Page]]"),
+        "<p><code>[[</code>Page]]</p>\n"
+    );
+}
+
+#[test]
+fn wikilink_requires_closing_pair_on_one_line() {
+    assert_eq!(wikilink_html("[[Page]"), "<p>[[Page]</p>\n");
+    assert_eq!(wikilink_html("[[Page\nName]]"), "<p>[[Page\nName]]</p>\n");
+    assert_eq!(wikilink_html("[[ ]]"), "<p>[[ ]]</p>\n");
+}
+
+#[test]
+fn emphasis_cannot_cross_wikilink() {
+    assert_eq!(
+        wikilink_html("*a [[b*]]"),
+        "<p>*a <a href=\"b%2A\">b*</a></p>\n"
+    );
+}
+
+#[test]
+fn wikilink_url_is_policy_filtered() {
+    assert_eq!(
+        wikilink_html("[[javascript:alert(1)|x]]"),
+        "<p><a href=\"javascript%3Aalert%281%29\">x</a></p>\n"
+    );
+    let html = to_html_with_options(
+        "[[javascript:alert(1)|x]]",
+        &Options {
+            wikilinks: true,
+            wikilink_url: Some(WikiLinkUrl(|target, url| url.push_str(target))),
+            ..Options::default()
+        },
+    );
+    assert_eq!(html, "<p><a href=\"\">x</a></p>\n");
+}
+
+#[test]
+fn percent_encoded_urls_keep_the_page_name() {
+    assert_eq!(
+        wikilink_html("[[Page Name]] [[a/b c|label]]"),
+        "<p><a href=\"Page%20Name\">Page Name</a> <a href=\"a/b%20c\">label</a></p>\n"
+    );
+    assert_eq!(
+        wikilink_html("[[100% \"Q&A\"#Part 2]] [[javascript:alert(1)]]"),
+        "<p><a href=\"100%25%20%22Q%26A%22#Part%202\">100% &quot;Q&amp;A&quot;#Part 2</a> \
+         <a href=\"javascript%3Aalert%281%29\">javascript:alert(1)</a></p>\n"
+    );
+}
+
+#[test]
+fn wikilinks_disabled_by_default() {
+    assert_eq!(
+        to_html_with_options("[[Page]]", &Options::default()),
+        "<p>[[Page]]</p>\n"
+    );
+}
+
+#[test]
+fn wikilink_rejects_raw_html_inside() {
+    assert_eq!(wikilink_html("[[a|<b>]]"), "<p>[[a|&lt;b&gt;]]</p>\n");
+}
+
+#[test]
+fn wikilink_splits_at_the_first_pipe() {
+    assert_eq!(
+        wikilink_html("[[Page|a|b]]"),
+        "<p><a href=\"Page\">a|b</a></p>\n"
+    );
+    assert_eq!(wikilink_html("[[|label]]"), "<p>[[|label]]</p>\n");
+}
+
+#[test]
+fn wikilink_nested_brackets_stay_literal() {
+    assert_eq!(wikilink_html("[[a [b] c]]"), "<p>[[a [b] c]]</p>\n");
+    assert_eq!(wikilink_html("[[a]b]]"), "<p>[[a]b]]</p>\n");
+}
+
+#[test]
+fn regular_links_next_to_wikilinks() {
+    assert_eq!(
+        wikilink_html("[text](/url) and [[Page]]"),
+        "<p><a href=\"/url\">text</a> and <a href=\"Page\">Page</a></p>\n"
+    );
+    assert_eq!(
+        wikilink_html("[ [Page]](/url)"),
+        "<p><a href=\"/url\"> [Page]</a></p>\n"
+    );
+}
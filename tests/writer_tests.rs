@@ -0,0 +1,160 @@
+use ferromark::{Options, PrettyConfig, to_html_with_options, to_html_writer};
+use serde::Deserialize;
+use std::fs;
+use std::io::{self, Write};
+
+#[derive(Deserialize)]
+struct SpecExample {
+    markdown: String,
+}
+
+/// A document far larger than the streaming chunk, mixing multi-byte text,
+/// entities and blocks whose output is patched after it is written.
+fn large_document() -> String {
+    let mut doc = String::new();
+    for i in 0..400 {
+        doc.push_str(&format!(
+            "## Section {i} — ünïcödé 😀\n\n\
+             Text with &amp; <b>, \"quotes\" and a note[^n{i}].\n\n\
+             > [cite:https://example.com/{i}]\n> Quoted ✓.\n\n\
+             - plain\n- [x] task {i}\n\n\
+             $$\nx^{i}\n$$\n\n\
+             ```rust\nlet s = \"é{i}\";\n```\n\n\
+             [^n{i}]: Note {i} → done.\n\n"
+        ));
+    }
+    doc
+}
+
+fn options() -> Options {
+    Options {
+        footnotes: true,
+        math: true,
+        blockquote_cite_url: true,
+        task_list_classes: true,
+        ..Options::gfm()
+    }
+}
+
+fn streamed(input: &str, options: &Options) -> String {
+    let mut out = Vec::new();
+    to_html_writer(input, &mut out, options).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+/// Accepts at most `max` bytes per write, or records whole chunks.
+struct SmallChunks {
+    max: Option<usize>,
+    out: Vec<u8>,
+    chunks: Vec<Vec<u8>>,
+}
+
+impl Write for SmallChunks {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(max) = self.max else {
+            self.chunks.push(buf.to_vec());
+            self.out.extend_from_slice(buf);
+            return Ok(buf.len());
+        };
+        let len = buf.len().min(max);
+        self.out.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn matches_to_html_on_spec_examples() {
+    let spec: Vec<SpecExample> =
+        serde_json::from_str(&fs::read_to_string("tests/spec.json").unwrap()).unwrap();
+    for options in [Options::default(), options()] {
+        for example in &spec {
+            assert_eq!(
+                streamed(&example.markdown, &options),
+                to_html_with_options(&example.markdown, &options),
+                "{:?}",
+                example.markdown
+            );
+        }
+    }
+}
+
+#[test]
+fn matches_to_html_on_large_documents() {
+    let input = large_document();
+    let pretty = Options {
+        pretty: Some(PrettyConfig {
+            wrap_block_tags: true,
+            ..PrettyConfig::default()
+        }),
+        compact_list_items: true,
+        ..options()
+    };
+    for options in [options(), pretty] {
+        assert_eq!(
+            streamed(&input, &options),
+            to_html_with_options(&input, &options)
+        );
+    }
+}
+
+#[test]
+fn small_chunk_writer_gets_all_output() {
+    let input = large_document();
+    let mut sink = SmallChunks {
+        max: Some(7),
+        out: Vec::new(),
+        chunks: Vec::new(),
+    };
+    to_html_writer(&input, &mut sink, &options()).unwrap();
+    assert_eq!(
+        String::from_utf8(sink.out).unwrap(),
+        to_html_with_options(&input, &options())
+    );
+}
+
+#[test]
+fn chunks_hold_whole_characters_and_entities() {
+    let mut sink = SmallChunks {
+        max: None,
+        out: Vec::new(),
+        chunks: Vec::new(),
+    };
+    to_html_writer(&large_document(), &mut sink, &options()).unwrap();
+    assert!(sink.chunks.len() > 1, "output was not streamed");
+    for chunk in &sink.chunks {
+        assert!(std::str::from_utf8(chunk).is_ok());
+        if let Some(amp) = chunk.iter().rposition(|&b| b == b'&') {
+            assert!(chunk[amp..].contains(&b';'));
+        }
+    }
+}
+
+#[test]
+fn write_errors_are_returned() {
+    struct Failing {
+        written: usize,
+    }
+
+    impl Write for Failing {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.written > 20_000 {
+                return Err(io::Error::other("disk full"));
+            }
+            self.written += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut sink = Failing { written: 0 };
+    let error = to_html_writer(&large_document(), &mut sink, &options()).unwrap_err();
+    assert_eq!(error.to_string(), "disk full");
+    assert!(sink.written < 40_000);
+}
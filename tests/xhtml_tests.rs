@@ -0,0 +1,71 @@
+use ferromark::{HtmlWriter, Options, render_with, to_html_with_options};
+
+const INPUT: &str = "a  \nb\n\n---\n\n![alt](/i.png \"T\")\n\n- [ ] todo\n- [x] done\n";
+
+fn html(xhtml: bool) -> String {
+    to_html_with_options(
+        INPUT,
+        &Options {
+            xhtml,
+            ..Options::gfm()
+        },
+    )
+}
+
+#[test]
+fn xhtml_void_elements_self_close() {
+    assert_eq!(
+        html(true),
+        "<p>a<br />\nb</p>\n<hr />\n<p><img src=\"/i.png\" alt=\"alt\" title=\"T\" /></p>\n\
+         <ul>\n<li><input type=\"checkbox\" disabled=\"\" /> todo</li>\n\
+         <li><input type=\"checkbox\" checked=\"\" disabled=\"\" /> done</li>\n</ul>\n"
+    );
+}
+
+#[test]
+fn html5_void_elements_have_no_slash() {
+    assert_eq!(
+        html(false),
+        "<p>a<br>\nb</p>\n<hr>\n<p><img src=\"/i.png\" alt=\"alt\" title=\"T\"></p>\n\
+         <ul>\n<li><input type=\"checkbox\" disabled=\"\"> todo</li>\n\
+         <li><input type=\"checkbox\" checked=\"\" disabled=\"\"> done</li>\n</ul>\n"
+    );
+}
+
+#[test]
+fn html5_applies_to_hard_wrap_and_untrusted_images() {
+    let options = Options {
+        xhtml: false,
+        hard_wrap: true,
+        untrusted_content: true,
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("a\nb ![i](/i.png)", &options),
+        "<p>a<br>\nb <img src=\"/i.png\" alt=\"i\" referrerpolicy=\"no-referrer\"></p>\n"
+    );
+}
+
+#[test]
+fn raw_html_keeps_its_own_style() {
+    let options = Options {
+        xhtml: false,
+        render_policy: ferromark::RenderPolicy::Trusted,
+        ..Options::default()
+    };
+    assert_eq!(
+        to_html_with_options("a <br/> b\n\n<hr />\n", &options),
+        "<p>a <br/> b</p>\n<hr />\n"
+    );
+}
+
+#[test]
+fn html_writer_renderer_follows_the_xhtml_option() {
+    let mut writer = HtmlWriter::new();
+    let options = Options {
+        xhtml: false,
+        ..Options::gfm()
+    };
+    render_with(INPUT, &options, &mut writer);
+    assert_eq!(writer.into_string().unwrap(), html(false));
+}